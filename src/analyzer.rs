@@ -1,11 +1,23 @@
+pub mod aliases;
+mod artifacts;
 pub mod config;
 pub mod fix;
+pub mod golden;
 pub mod ignore;
+mod messages;
 mod parser;
 pub mod phpdoc;
 mod project;
 mod rules;
+pub mod scaffold;
+pub mod schema;
+mod scope;
+pub mod score;
+pub mod self_update;
+pub mod targets;
 pub mod test_config;
+pub mod types;
+pub mod version;
 
 use std::{
     collections::BTreeMap,
@@ -14,22 +26,23 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use config::AnalyzerConfig;
+use config::{AnalysisScope, AnalyzerConfig};
 use ignore::IgnoreState;
+use messages::MessageCatalog;
 use parser::PhpParser;
 use rayon::prelude::*;
 use rules::psr4;
 use serde::Serialize;
 use test_config::TestConfig;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use project::{ProjectContext, collect_file_metadata};
 use tree_sitter::Point;
 use walkdir::WalkDir;
 
 /// Represents the severity of a diagnostic.
 #[allow(dead_code)]
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum Severity {
     Info,
@@ -47,6 +60,26 @@ impl fmt::Display for Severity {
     }
 }
 
+/// How likely a heuristic diagnostic is to be a real problem, for rules that
+/// guess from names and values (such as hard-coded secrets).
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl fmt::Display for Confidence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Confidence::Low => write!(f, "low"),
+            Confidence::Medium => write!(f, "medium"),
+            Confidence::High => write!(f, "high"),
+        }
+    }
+}
+
 /// A diagnostic that can be emitted during analysis.
 #[derive(Debug, Clone)]
 pub struct Span {
@@ -54,6 +87,44 @@ pub struct Span {
     pub end: Point,
 }
 
+/// Span columns measured in Unicode scalar values and UTF-16 code units.
+///
+/// `Span` keeps tree-sitter's byte columns (which fixes rely on); these are
+/// what editors and terminals expect on lines containing multibyte text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpanColumns {
+    pub start_char: usize,
+    pub end_char: usize,
+    pub start_utf16: usize,
+    pub end_utf16: usize,
+}
+
+impl SpanColumns {
+    pub fn new(source: &str, span: &Span) -> Self {
+        let (start_char, start_utf16) = encoded_columns(source, span.start);
+        let (end_char, end_utf16) = encoded_columns(source, span.end);
+        Self {
+            start_char,
+            end_char,
+            start_utf16,
+            end_utf16,
+        }
+    }
+}
+
+/// Converts a byte column into `(characters, utf16 units)` for the point's line.
+fn encoded_columns(source: &str, point: Point) -> (usize, usize) {
+    let Some(line) = source.split('\n').nth(point.row) else {
+        return (point.column, point.column);
+    };
+
+    line.char_indices()
+        .take_while(|(offset, _)| *offset < point.column)
+        .fold((0, 0), |(chars, units), (_, ch)| {
+            (chars + 1, units + ch.len_utf16())
+        })
+}
+
 #[derive(Debug, Clone)]
 pub struct Diagnostic {
     pub file: PathBuf,
@@ -66,6 +137,16 @@ pub struct Diagnostic {
     pub snippet_after: Option<String>,
     pub caret_col: Option<usize>,
     pub caret_len: usize,
+    pub columns: Option<SpanColumns>,
+    /// Rule-specific structured fields (e.g. a TODO's owner), emitted in JSON output.
+    pub metadata: BTreeMap<String, String>,
+    /// Rule whose `--fix` edits resolve this diagnostic, set by [`fix::FixSet::link`].
+    pub fix_rule: Option<String>,
+    /// Set by heuristic rules; `None` for diagnostics that are certain.
+    pub confidence: Option<Confidence>,
+    /// The confidence as worded by the message catalog, e.g. "hohe Konfidenz";
+    /// `None` keeps the English "high confidence".
+    pub confidence_label: Option<String>,
 }
 
 impl Diagnostic {
@@ -82,6 +163,11 @@ impl Diagnostic {
             snippet_after: None,
             caret_col: None,
             caret_len: 1,
+            columns: None,
+            metadata: BTreeMap::new(),
+            fix_rule: None,
+            confidence: None,
+            confidence_label: None,
         }
     }
 
@@ -107,6 +193,11 @@ impl Diagnostic {
             caret_col,
             caret_len: caret_len.max(1),
             rule_name: None,
+            columns: None,
+            metadata: BTreeMap::new(),
+            fix_rule: None,
+            confidence: None,
+            confidence_label: None,
         }
     }
 
@@ -116,16 +207,119 @@ impl Diagnostic {
             severity: self.severity.clone(),
             message: self.message.clone(),
             rule_name: self.rule_name.clone(),
-            span: self.span.as_ref().map(|span| span.into()),
+            span: self
+                .span
+                .as_ref()
+                .map(|span| SpanJson::new(span, self.columns.as_ref())),
             snippet_before: self.snippet_before.clone(),
             snippet_line: self.snippet_line.clone(),
             snippet_after: self.snippet_after.clone(),
             caret_col: self.caret_col,
             caret_len: self.caret_len,
+            metadata: self.metadata.clone(),
+            fixable: self.fix_rule.is_some(),
+            fix_rule: self.fix_rule.clone(),
+            confidence: self.confidence,
+            fingerprint: self.fingerprint(),
+        }
+    }
+
+    /// A stable identifier for tracking this issue across runs: a hash of the
+    /// rule, the `/`-separated path relative to the working directory and the
+    /// flagged line with its whitespace collapsed. It survives lines shifting
+    /// up or down, but not edits to the flagged line itself.
+    pub fn fingerprint(&self) -> String {
+        let line = self
+            .snippet_line
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let key = format!(
+            "{}\0{}\0{line}",
+            self.rule_name.as_deref().unwrap_or_default(),
+            normalized_path(&self.file)
+        );
+
+        // FNV-1a, which unlike std's hashers is guaranteed not to change
+        // between Rust releases
+        let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        format!("{hash:016x}")
+    }
+
+    /// One `file:line:col: severity: message [rule]` line, as matched by Vim's
+    /// default `errorformat` and Emacs' compilation mode. Line and character
+    /// column are 1-based; diagnostics without a span point at `1:1`.
+    pub fn to_compact(&self) -> String {
+        let (line, column) = self.span.as_ref().map_or((1, 1), |span| {
+            let column = self
+                .columns
+                .map(|columns| columns.start_char)
+                .unwrap_or(span.start.column);
+            (span.start.row + 1, column + 1)
+        });
+        let message = self.labelled_message().replace('\n', " ");
+
+        let mut compact = format!(
+            "{}:{line}:{column}: {}: {message}",
+            self.file.display(),
+            self.severity
+        );
+        if let Some(rule) = &self.rule_name {
+            compact.push_str(&format!(" [{rule}]"));
+        }
+        compact
+    }
+
+    /// A TeamCity `inspection` service message; its `typeId` is the rule name,
+    /// registered beforehand with [`teamcity_inspection_type`].
+    pub fn to_teamcity(&self) -> String {
+        let severity = match self.severity {
+            Severity::Info => "INFO",
+            Severity::Warning => "WARNING",
+            Severity::Error => "ERROR",
+        };
+        let line = self.span.as_ref().map_or(1, |span| span.start.row + 1);
+
+        format!(
+            "##teamcity[inspection typeId='{}' message='{}' file='{}' line='{line}' SEVERITY='{severity}']",
+            teamcity_escape(self.rule_name.as_deref().unwrap_or("php-checker")),
+            teamcity_escape(&self.labelled_message()),
+            teamcity_escape(&self.file.display().to_string()),
+        )
+    }
+
+    /// The message with the confidence of heuristic diagnostics appended, as
+    /// shown by the line-based formats.
+    fn labelled_message(&self) -> String {
+        match (&self.confidence_label, self.confidence) {
+            (Some(label), Some(_)) => format!("{} ({label})", self.message),
+            (None, Some(confidence)) => format!("{} ({confidence} confidence)", self.message),
+            (_, None) => self.message.clone(),
         }
     }
 }
 
+/// `path` relative to the working directory when it lies inside it, with `.`
+/// segments dropped and `/` separators on every platform.
+fn normalized_path(path: &Path) -> String {
+    let relative = std::env::current_dir()
+        .ok()
+        .filter(|_| path.is_absolute())
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf());
+
+    relative
+        .components()
+        .filter(|component| !matches!(component, std::path::Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
 impl fmt::Display for Diagnostic {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         const RESET: &str = "\x1b[0m";
@@ -146,15 +340,19 @@ impl fmt::Display for Diagnostic {
             header.push(']');
         }
 
-        writeln!(f, "{}: {}", header, self.message)?;
+        writeln!(f, "{}: {}", header, self.labelled_message())?;
 
         if let Some(span) = &self.span {
+            let column = self
+                .columns
+                .map(|columns| columns.start_char)
+                .unwrap_or(span.start.column);
             writeln!(
                 f,
                 " --> {}:{}:{}",
                 self.file.display(),
                 span.start.row + 1,
-                span.start.column + 1
+                column + 1
             )?;
             writeln!(f, "{BLUE}    |{RESET}")?;
             let prefix_line =
@@ -222,6 +420,24 @@ pub struct DiagnosticJson {
     snippet_after: Option<String>,
     caret_col: Option<usize>,
     caret_len: usize,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+    fixable: bool,
+    fix_rule: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    confidence: Option<Confidence>,
+    fingerprint: String,
+}
+
+/// A suppression directive in `--format json` output, so review tools can
+/// spot new suppressions without parsing PHP.
+#[derive(Serialize)]
+pub struct SuppressionJson {
+    file: String,
+    directive: &'static str,
+    rules: Vec<String>,
+    span: SpanJson,
+    end_row: Option<usize>,
 }
 
 #[derive(Serialize)]
@@ -230,26 +446,33 @@ pub struct SpanJson {
     end: PointJson,
 }
 
+/// `column` is the byte offset reported by tree-sitter; `character` and `utf16`
+/// are included when the diagnostic was built from source text.
 #[derive(Serialize)]
 pub struct PointJson {
     row: usize,
     column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    character: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    utf16: Option<usize>,
 }
 
-impl From<&Span> for SpanJson {
-    fn from(span: &Span) -> Self {
-        Self {
-            start: span.start.into(),
-            end: span.end.into(),
-        }
-    }
-}
-
-impl From<Point> for PointJson {
-    fn from(point: Point) -> Self {
+impl SpanJson {
+    fn new(span: &Span, columns: Option<&SpanColumns>) -> Self {
         Self {
-            row: point.row,
-            column: point.column,
+            start: PointJson {
+                row: span.start.row,
+                column: span.start.column,
+                character: columns.map(|columns| columns.start_char),
+                utf16: columns.map(|columns| columns.start_utf16),
+            },
+            end: PointJson {
+                row: span.end.row,
+                column: span.end.column,
+                character: columns.map(|columns| columns.end_char),
+                utf16: columns.map(|columns| columns.end_utf16),
+            },
         }
     }
 }
@@ -292,6 +515,266 @@ mod tests {
         assert_eq!(json.snippet_after.as_deref(), Some("after"));
         assert_eq!(json.caret_col, Some(4));
         assert_eq!(json.caret_len, 3);
+        assert_eq!(span_json.start.character, None);
+    }
+
+    #[test]
+    fn fingerprint_ignores_line_numbers_and_indentation() {
+        let diagnostic = |file: &str, row: usize, line: &str, rule: &str| {
+            let mut diag = Diagnostic::with_span(
+                PathBuf::from(file),
+                Severity::Warning,
+                "undefined variable $name",
+                Span {
+                    start: Point { row, column: 4 },
+                    end: Point { row, column: 9 },
+                },
+                None,
+                Some(line.into()),
+                None,
+                Some(4),
+                5,
+            );
+            diag.rule_name = Some(rule.into());
+            diag
+        };
+        let rule = "sanity/undefined_variable";
+        let other_rule = "cleanup/unused_variable";
+        let original = diagnostic("src/User.php", 3, "    echo $name;", rule);
+
+        let fingerprint = original.fingerprint();
+        assert_eq!(fingerprint.len(), 16);
+        assert_eq!(original.to_json().fingerprint, fingerprint);
+        assert_eq!(
+            diagnostic("./src/User.php", 12, "\techo  $name;", rule).fingerprint(),
+            fingerprint
+        );
+        for different in [
+            diagnostic("src/Admin.php", 3, "    echo $name;", rule),
+            diagnostic("src/User.php", 3, "    echo $title;", rule),
+            diagnostic("src/User.php", 3, "    echo $name;", other_rule),
+        ] {
+            assert_ne!(different.fingerprint(), fingerprint);
+        }
+    }
+
+    #[test]
+    fn diagnostic_to_compact_is_one_plain_line() {
+        let span = Span {
+            start: Point { row: 4, column: 8 },
+            end: Point { row: 4, column: 12 },
+        };
+        let mut diag = Diagnostic::with_span(
+            PathBuf::from("src/User.php"),
+            Severity::Error,
+            "unused variable $name\nremove it",
+            span,
+            None,
+            Some("line".into()),
+            None,
+            Some(8),
+            4,
+        );
+        diag.rule_name = Some("cleanup/unused_variable".into());
+
+        assert_eq!(
+            diag.to_compact(),
+            "src/User.php:5:9: error: unused variable $name remove it [cleanup/unused_variable]"
+        );
+
+        let diag = Diagnostic::new(PathBuf::from("src/User.php"), Severity::Info, "note");
+        assert_eq!(diag.to_compact(), "src/User.php:1:1: info: note");
+    }
+
+    #[test]
+    fn heuristic_diagnostics_carry_their_confidence() {
+        let mut diag = Diagnostic::new(
+            PathBuf::from("src/Db.php"),
+            Severity::Warning,
+            "hard-coded credential or token detected",
+        );
+        diag.rule_name = Some("security/hard_coded_credentials".into());
+        let json = serde_json::to_value(diag.to_json()).unwrap();
+        assert_eq!(json.get("confidence"), None);
+
+        diag.confidence = Some(Confidence::Medium);
+        assert_eq!(
+            diag.to_compact(),
+            "src/Db.php:1:1: warning: hard-coded credential or token detected (medium confidence) [security/hard_coded_credentials]"
+        );
+        assert!(
+            diag.to_teamcity()
+                .contains("message='hard-coded credential or token detected (medium confidence)'")
+        );
+        assert_eq!(
+            serde_json::to_value(diag.to_json()).unwrap()["confidence"],
+            "medium"
+        );
+        assert!(Confidence::Low < Confidence::Medium && Confidence::Medium < Confidence::High);
+    }
+
+    #[test]
+    fn teamcity_messages_escape_attribute_values() {
+        let span = Span {
+            start: Point { row: 2, column: 0 },
+            end: Point { row: 2, column: 4 },
+        };
+        let mut diag = Diagnostic::with_span(
+            PathBuf::from("src/a.php"),
+            Severity::Warning,
+            "undefined array key 'id' [café]|x",
+            span,
+            None,
+            None,
+            None,
+            Some(0),
+            4,
+        );
+        diag.rule_name = Some("sanity/array_key_not_defined".into());
+
+        assert_eq!(
+            diag.to_teamcity(),
+            "##teamcity[inspection typeId='sanity/array_key_not_defined' message='undefined array key |'id|' |[caf|0x00e9|]||x' file='src/a.php' line='3' SEVERITY='WARNING']"
+        );
+        assert_eq!(
+            teamcity_inspection_type(
+                "cleanup/unused_use",
+                "use imports that are never referenced"
+            ),
+            "##teamcity[inspectionType id='cleanup/unused_use' name='cleanup/unused_use' description='use imports that are never referenced' category='cleanup']"
+        );
+    }
+
+    #[test]
+    fn span_columns_count_characters_and_utf16_units() {
+        // "é" is 2 bytes / 1 UTF-16 unit, "😀" is 4 bytes / 2 UTF-16 units.
+        let source = "<?php\n$s = 'é😀'; $x;\n";
+        let start = source.lines().nth(1).unwrap().find("$x").unwrap();
+        let span = Span {
+            start: Point {
+                row: 1,
+                column: start,
+            },
+            end: Point {
+                row: 1,
+                column: start + 2,
+            },
+        };
+
+        let columns = SpanColumns::new(source, &span);
+
+        assert_eq!(start, 15);
+        assert_eq!(columns.start_char, 11);
+        assert_eq!(columns.end_char, 13);
+        assert_eq!(columns.start_utf16, 12);
+        assert_eq!(columns.end_utf16, 14);
+
+        let mut diag = Diagnostic::with_span(
+            PathBuf::from("example.php"),
+            Severity::Warning,
+            "example message",
+            span,
+            None,
+            None,
+            None,
+            Some(columns.start_char),
+            2,
+        );
+        diag.columns = Some(columns);
+
+        let json = diag.to_json();
+        let span_json = json.span.as_ref().expect("span should be set");
+        assert_eq!(span_json.start.column, 15);
+        assert_eq!(span_json.start.character, Some(11));
+        assert_eq!(span_json.start.utf16, Some(12));
+        assert!(diag.to_string().contains("example.php:2:12"));
+    }
+
+    #[test]
+    fn fix_set_links_diagnostics_on_edited_rows() {
+        let source = "<?php\nuse Foo\\Bar;\n$a = 1;\n";
+        let use_line = source.find("use").unwrap();
+        let mut fixes = fix::FixSet::default();
+        fixes.push(
+            Path::new("example.php"),
+            source,
+            "cleanup/unused_use",
+            vec![fix::TextEdit::new(use_line, source.find("$a").unwrap(), "")],
+        );
+
+        let diagnostic = |row: usize, rule: &str| {
+            let point = Point { row, column: 0 };
+            let mut diag = Diagnostic::with_span(
+                PathBuf::from("example.php"),
+                Severity::Warning,
+                "example message",
+                Span {
+                    start: point,
+                    end: point,
+                },
+                None,
+                None,
+                None,
+                None,
+                1,
+            );
+            diag.rule_name = Some(rule.to_string());
+            diag
+        };
+
+        let mut fixable = diagnostic(1, "cleanup/unused_use");
+        let mut other_row = diagnostic(2, "cleanup/unused_use");
+        let mut other_rule = diagnostic(1, "cleanup/unused_variable");
+        for diag in [&mut fixable, &mut other_row, &mut other_rule] {
+            fixes.link(diag);
+        }
+
+        let json = fixable.to_json();
+        assert!(json.fixable);
+        assert_eq!(json.fix_rule.as_deref(), Some("cleanup/unused_use"));
+        assert!(!other_row.to_json().fixable);
+        assert!(!other_rule.to_json().fixable);
+        assert_eq!(fixes.edit_count(), 1);
+    }
+
+    #[test]
+    fn fix_set_skips_edits_for_ignored_diagnostics() {
+        let dir =
+            std::env::temp_dir().join(format!("php-checker-fix-ignore-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let regions = dir.join("regions.php");
+        let ignored = dir.join("ignored.php");
+        std::fs::write(
+            &regions,
+            "<?php\n// php-checker-disable cleanup/unused_use\nuse Foo\\Kept;\n// php-checker-enable cleanup/unused_use\nuse Foo\\Removed;\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &ignored,
+            "<?php\n// php-checker-ignore-file\nuse Foo\\Kept;\n",
+        )
+        .unwrap();
+
+        let mut analyzer = Analyzer::new(None).unwrap();
+        let fixes = analyzer.fix_set(&[regions.clone(), ignored]).unwrap();
+        let diagnostics = analyzer.analyse_root(&dir).unwrap();
+
+        let fixed = fix::apply_text_edits(
+            &std::fs::read_to_string(&regions).unwrap(),
+            &fixes.edits()[&regions],
+        );
+        assert!(fixed.contains("use Foo\\Kept;"), "{fixed}");
+        assert!(!fixed.contains("Removed"), "{fixed}");
+        assert_eq!(fixes.edit_count(), 1);
+        assert_eq!(
+            diagnostics
+                .iter()
+                .filter(|diag| fixes.fix_for(diag).is_some())
+                .count(),
+            1
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
 
@@ -299,53 +782,92 @@ mod tests {
 pub struct Analyzer {
     parser: Box<dyn parser::PhpParser>,
     rules: Vec<Arc<dyn rules::DiagnosticRule>>,
+    messages: Arc<MessageCatalog>,
     config: AnalyzerConfig,
 }
 
 impl Analyzer {
     pub fn new(config: Option<AnalyzerConfig>) -> Result<Self> {
+        let config = config.unwrap_or_default();
+        if let Some(required) = &config.required_version {
+            version::ensure_satisfies(required)?;
+        }
         let parser = Box::new(parser::TreeSitterPhpParser::new()?);
+        let php_version = config.target_php_version();
         let mut rules: Vec<Arc<dyn rules::DiagnosticRule>> = vec![
-            Arc::new(rules::UndefinedVariableRule::new()),
+            Arc::new(rules::UndefinedVariableRule::new(config.templates.clone())),
             Arc::new(rules::ArrayKeyNotDefinedRule::new()),
             Arc::new(rules::MissingReturnRule::new()),
             Arc::new(rules::MissingArgumentRule::new()),
             Arc::new(rules::TypeMismatchRule::new()),
+            Arc::new(rules::CoalesceTypeMismatchRule::new()),
             Arc::new(rules::ConsistentReturnRule::new()),
             Arc::new(rules::ForceReturnTypeRule::new()),
             Arc::new(rules::DuplicateDeclarationRule::new()),
+            Arc::new(rules::ConstantCollisionRule::new()),
+            Arc::new(rules::ModifyDuringIterationRule::new()),
+            Arc::new(rules::DanglingReferenceRule::new()),
+            Arc::new(rules::FloatEqualityRule::new(config.float_equality.clone())),
+            Arc::new(rules::SuspiciousNumericLiteralRule::new(php_version)),
+            Arc::new(rules::LocaleDependentRule::new(php_version)),
             Arc::new(rules::ImpossibleComparisonRule::new()),
             Arc::new(rules::RedundantConditionRule::new()),
             Arc::new(rules::DuplicateSwitchCaseRule::new()),
             Arc::new(rules::FallthroughRule::new()),
             Arc::new(rules::UnreachableCodeRule::new()),
             Arc::new(rules::UnreachableStatementRule::new()),
-            Arc::new(rules::UnusedVariableRule::new()),
+            Arc::new(rules::UnusedVariableRule::new(config.templates.clone())),
             Arc::new(rules::UnusedUseRule::new()),
+            Arc::new(rules::EnvironmentSpecificValuesRule::new(
+                config.environment_values.clone(),
+            )),
+            Arc::new(rules::TodoCommentRule::new()),
             Arc::new(rules::InvalidThisRule::new()),
-            Arc::new(rules::DeprecatedApiRule::new()),
-            Arc::new(rules::MutatingLiteralRule::new()),
-            Arc::new(rules::StrictTypesRule::new()),
+            Arc::new(rules::DeprecatedApiRule::new(config.deprecated_api.clone())),
+            Arc::new(rules::MutatingLiteralRule::new(config.security.clone())),
+            Arc::new(rules::StrictTypesRule::new(config.strict_types.clone())),
             Arc::new(rules::IncludeUserInputRule::new()),
-            Arc::new(rules::HardCodedCredentialsRule::new()),
-            Arc::new(rules::WeakHashingRule::new()),
-            Arc::new(rules::HardCodedKeysRule::new()),
+            Arc::new(rules::HardCodedCredentialsRule::new(
+                config.security.clone(),
+            )),
+            Arc::new(rules::WeakHashingRule::new(config.security.clone())),
+            Arc::new(rules::HardCodedKeysRule::new(config.security.clone())),
             Arc::new(rules::PhpDocVarCheckRule::new()),
             Arc::new(rules::PhpDocParamCheckRule::new()),
             Arc::new(rules::PhpDocReturnCheckRule::new()),
             Arc::new(rules::PhpDocReturnValueCheckRule::new()),
+            Arc::new(rules::MalformedTagRule::new()),
+            Arc::new(rules::ImplicitNullableRule::new(php_version)),
+            Arc::new(rules::DynamicPropertiesRule::new(php_version)),
+            Arc::new(rules::FileHeaderRule::new(config.file_header.clone())),
         ];
 
-        let config = config.unwrap_or_default();
         rules.retain(|rule| config.enabled(rule.name()));
 
+        let messages =
+            MessageCatalog::load(config.locale.as_deref(), config.message_catalog.as_deref())?;
+
         Ok(Self {
             parser,
             rules,
+            messages: Arc::new(messages),
             config,
         })
     }
 
+    /// Names and descriptions of the rules this analyzer will run, in registration order.
+    pub fn rule_descriptions(&self) -> Vec<(&str, &str)> {
+        let mut descriptions: Vec<(&str, &str)> = self
+            .rules
+            .iter()
+            .map(|rule| (rule.name(), rule.description()))
+            .collect();
+        if self.config.psr4.enabled && self.config.enabled(psr4::namespace::RULE_NAME) {
+            descriptions.push((psr4::namespace::RULE_NAME, psr4::namespace::DESCRIPTION));
+        }
+        descriptions
+    }
+
     pub fn analyse_file(&mut self, path: &Path) -> Result<Vec<Diagnostic>> {
         let parsed = self.parser.parse_file(path)?;
         let mut context = ProjectContext::new();
@@ -377,6 +899,19 @@ impl Analyzer {
         paths: &[PathBuf],
         root: &Path,
         progress: Option<&indicatif::ProgressBar>,
+    ) -> Result<Vec<Diagnostic>> {
+        self.analyse_files_streaming(paths, root, progress, None)
+    }
+
+    /// Like [`Self::analyse_files_with_progress`], additionally handing each
+    /// diagnostic to `on_diagnostic` as soon as it is found. The callback runs on
+    /// the worker threads, so diagnostics arrive in no particular order.
+    pub fn analyse_files_streaming(
+        &mut self,
+        paths: &[PathBuf],
+        root: &Path,
+        progress: Option<&indicatif::ProgressBar>,
+        on_diagnostic: Option<&(dyn Fn(&Diagnostic) + Sync)>,
     ) -> Result<Vec<Diagnostic>> {
         if paths.is_empty() {
             return Ok(Vec::new());
@@ -387,7 +922,41 @@ impl Analyzer {
             pb.set_message("Parsing files");
         }
 
-        let context = parse_files(paths, progress)?;
+        let context = parse_files(paths, &[], progress)?;
+        self.analyse_context(context, root, progress, on_diagnostic)
+    }
+
+    /// Analyses `paths` as they would read once `patched` is written, so the
+    /// diagnostics a fix leaves behind are known before (or without) writing it.
+    pub fn analyse_patched(
+        &mut self,
+        paths: &[PathBuf],
+        root: &Path,
+        patched: &[fix::StagedFile],
+    ) -> Result<Vec<Diagnostic>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let context = parse_files(paths, patched, None)?;
+        self.analyse_context(context, root, None, None)
+    }
+
+    /// Analyses `paths` and measures them for `php-checker score`.
+    pub fn score(&mut self, paths: &[PathBuf], root: &Path) -> Result<score::ScoreReport> {
+        let context = parse_files(paths, &[], None)?;
+        let metrics: Vec<_> = context.iter().map(score::FileMetrics::measure).collect();
+        let diagnostics = self.analyse_context(context, root, None, None)?;
+        Ok(score::ScoreReport::new(&metrics, &diagnostics))
+    }
+
+    fn analyse_context(
+        &mut self,
+        context: ProjectContext,
+        root: &Path,
+        progress: Option<&indicatif::ProgressBar>,
+        on_diagnostic: Option<&(dyn Fn(&Diagnostic) + Sync)>,
+    ) -> Result<Vec<Diagnostic>> {
         let file_count = context.len();
 
         if let Some(pb) = progress {
@@ -401,6 +970,8 @@ impl Analyzer {
         let rules = self.rules.clone();
         let pb_for_diag = progress.map(|p| p.clone());
         let context_for_diag = context.clone();
+        let scope = self.config.scope;
+        let messages = self.messages.clone();
 
         let diagnostics: Vec<_> = parsed_files
             .par_iter()
@@ -408,12 +979,15 @@ impl Analyzer {
                 if let Some(ref pb) = pb_for_diag {
                     pb.inc(1);
                 }
-                let mut diags =
-                    collect_diagnostics_with_rules(&rules, parsed, context_for_diag.as_ref());
-                if let Some(ref pb) = pb_for_diag {
-                    for diag in &diags {
-                        pb.println(format!("{diag}"));
-                    }
+                let diags = collect_diagnostics_with_rules(
+                    &rules,
+                    parsed,
+                    context_for_diag.as_ref(),
+                    scope,
+                    &messages,
+                );
+                if let Some(on_diagnostic) = on_diagnostic {
+                    diags.iter().for_each(on_diagnostic);
                 }
                 diags
             })
@@ -422,11 +996,15 @@ impl Analyzer {
         let mut all_diagnostics = diagnostics;
 
         if self.config.psr4.enabled {
-            all_diagnostics.extend(psr4::run_namespace_checks(
-                root,
-                context.as_ref(),
-                &self.config,
-            ));
+            let mut psr4_diagnostics =
+                psr4::run_namespace_checks(root, context.as_ref(), &self.config);
+            for diagnostic in psr4_diagnostics.iter_mut() {
+                self.messages.translate(diagnostic);
+            }
+            if let Some(on_diagnostic) = on_diagnostic {
+                psr4_diagnostics.iter().for_each(on_diagnostic);
+            }
+            all_diagnostics.extend(psr4_diagnostics);
         }
 
         Ok(all_diagnostics)
@@ -442,27 +1020,52 @@ impl Analyzer {
         &mut self,
         paths: &[PathBuf],
     ) -> Result<BTreeMap<PathBuf, Vec<fix::TextEdit>>> {
+        Ok(self.fix_set(paths)?.edits())
+    }
+
+    /// Collects fixes per rule so diagnostics can be linked to the edits that resolve them.
+    pub fn fix_set(&mut self, paths: &[PathBuf]) -> Result<fix::FixSet> {
+        let mut fixes = fix::FixSet::default();
         if paths.is_empty() {
-            return Ok(BTreeMap::new());
+            return Ok(fixes);
         }
 
-        let context = parse_files(paths, None)?;
-        let mut edits: BTreeMap<PathBuf, Vec<fix::TextEdit>> = BTreeMap::new();
-
+        let context = parse_files(paths, &[], None)?;
         for parsed in context.iter() {
+            // Fix only what analysis would report: the same ignore comments,
+            // test-file rule selection and scope apply to edits.
+            let ignore_state = IgnoreState::from_source(parsed.source.as_str());
+            if ignore_state.ignores_everything() {
+                continue;
+            }
+            let test_config = TestConfig::from_source(parsed.source.as_str());
+
             for rule in &self.rules {
-                let mut rule_edits = rule.fix(parsed, &context);
-                if rule_edits.is_empty() {
+                if test_config.is_test_file() && !test_config.should_run_rule(rule.name()) {
                     continue;
                 }
-                edits
-                    .entry(parsed.path.clone())
-                    .or_default()
-                    .append(&mut rule_edits);
+                for artifact in rule.requires() {
+                    parsed.prepare(*artifact);
+                }
+                let edits = rule
+                    .fix(parsed, &context)
+                    .into_iter()
+                    .filter(|edit| {
+                        let start = rules::helpers::point_at(parsed.source.as_str(), edit.start);
+                        !is_suppressed(
+                            &ignore_state,
+                            self.config.scope,
+                            parsed,
+                            rule.name(),
+                            Some(start),
+                        )
+                    })
+                    .collect();
+                fixes.push(&parsed.path, parsed.source.as_str(), rule.name(), edits);
             }
         }
 
-        Ok(edits)
+        Ok(fixes)
     }
 
     fn collect_diagnostics(
@@ -470,7 +1073,13 @@ impl Analyzer {
         parsed: &parser::ParsedSource,
         context: &ProjectContext,
     ) -> Vec<Diagnostic> {
-        collect_diagnostics_with_rules(&self.rules, parsed, context)
+        collect_diagnostics_with_rules(
+            &self.rules,
+            parsed,
+            context,
+            self.config.scope,
+            &self.messages,
+        )
     }
 
     // run_psr4_checks moved to `rules::psr4`.
@@ -480,6 +1089,8 @@ fn collect_diagnostics_with_rules(
     rules: &[Arc<dyn rules::DiagnosticRule>],
     parsed: &parser::ParsedSource,
     context: &ProjectContext,
+    scope: AnalysisScope,
+    messages: &MessageCatalog,
 ) -> Vec<Diagnostic> {
     let ignore_state = IgnoreState::from_source(parsed.source.as_str());
     if ignore_state.ignores_everything() {
@@ -496,23 +1107,113 @@ fn collect_diagnostics_with_rules(
             continue;
         }
 
+        for artifact in rule.requires() {
+            parsed.prepare(*artifact);
+        }
         let mut rule_diagnostics = rule.run(parsed, context);
         for diag in rule_diagnostics.iter_mut() {
             diag.rule_name = Some(rule_name.clone());
+            messages.translate(diag);
         }
         diagnostics.extend(rule_diagnostics);
     }
 
+    let deprecated = ignore_state.deprecated_names().iter().map(|name| {
+        let span = Span {
+            start: Point::new(name.row, name.column),
+            end: Point::new(name.row, name.column + name.alias.old.len()),
+        };
+        rules::helpers::diagnostic_for_span(parsed, span, Severity::Warning, name.alias.notice())
+    });
+
     diagnostics
         .into_iter()
         .filter(|diag| {
-            diag.rule_name
-                .as_deref()
-                .map_or(true, |name| !ignore_state.should_ignore(name))
+            diag.rule_name.as_deref().is_none_or(|name| {
+                let start = diag.span.as_ref().map(|span| span.start);
+                !is_suppressed(&ignore_state, scope, parsed, name, start)
+            })
         })
+        .chain(deprecated)
         .collect()
 }
 
+/// Whether ignore comments or the analysis scope hide `rule_name`'s findings
+/// starting at `start`, or anywhere in the file when there's no position.
+fn is_suppressed(
+    ignore_state: &IgnoreState,
+    scope: AnalysisScope,
+    parsed: &parser::ParsedSource,
+    rule_name: &str,
+    start: Option<Point>,
+) -> bool {
+    let ignored = match start {
+        Some(start) => ignore_state.should_ignore_at(rule_name, start.row),
+        None => ignore_state.should_ignore(rule_name),
+    };
+    ignored
+        || (scope.limits(rule_name)
+            && (is_test_file(&parsed.path)
+                || start.is_some_and(|start| !scope::in_public_api(parsed, start))))
+}
+
+/// The `php-checker-ignore`, `-ignore-file` and `-disable` directives in `path`.
+pub fn suppressions(path: &Path) -> Result<Vec<SuppressionJson>> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    Ok(IgnoreState::from_source(&source)
+        .directives()
+        .iter()
+        .map(|directive| {
+            let span = Span {
+                start: Point::new(directive.row, directive.start_column),
+                end: Point::new(directive.row, directive.end_column),
+            };
+            let columns = SpanColumns::new(&source, &span);
+            SuppressionJson {
+                file: path.display().to_string(),
+                directive: directive.kind.as_str(),
+                rules: directive.rules.clone(),
+                span: SpanJson::new(&span, Some(&columns)),
+                end_row: directive.end_row,
+            }
+        })
+        .collect())
+}
+
+/// A TeamCity `inspectionType` service message registering a rule; the
+/// category is the rule's group (`cleanup` for `cleanup/unused_variable`).
+pub fn teamcity_inspection_type(rule_name: &str, description: &str) -> String {
+    let category = rule_name
+        .split_once('/')
+        .map_or(rule_name, |(group, _)| group);
+    format!(
+        "##teamcity[inspectionType id='{name}' name='{name}' description='{}' category='{}']",
+        teamcity_escape(description),
+        teamcity_escape(category),
+        name = teamcity_escape(rule_name),
+    )
+}
+
+/// Escapes a service message attribute value with TeamCity's `|` escapes.
+fn teamcity_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '|' => escaped.push_str("||"),
+            '\'' => escaped.push_str("|'"),
+            '\n' => escaped.push_str("|n"),
+            '\r' => escaped.push_str("|r"),
+            '[' => escaped.push_str("|["),
+            ']' => escaped.push_str("|]"),
+            c if !c.is_ascii() => escaped.push_str(&format!("|0x{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 pub fn collect_php_files(root: &Path) -> Result<Vec<PathBuf>> {
     if root.is_file() {
         return Ok(if is_php_file(root) {
@@ -545,8 +1246,11 @@ pub fn collect_php_files_from_roots(roots: &[PathBuf]) -> Result<Vec<PathBuf>> {
     Ok(php_files)
 }
 
+/// Parses `paths` into a project context, reading staged contents from
+/// `patched` instead of disk where a file has them.
 fn parse_files(
     paths: &[PathBuf],
+    patched: &[fix::StagedFile],
     progress: Option<&indicatif::ProgressBar>,
 ) -> Result<ProjectContext> {
     let context = Arc::new(Mutex::new(ProjectContext::new()));
@@ -556,7 +1260,10 @@ fn parse_files(
         .par_iter()
         .map(|path| {
             let mut parser = Box::new(parser::TreeSitterPhpParser::new()?);
-            let parsed = parser.parse_file(path)?;
+            let parsed = match patched.iter().find(|file| &file.path == path) {
+                Some(file) => parser.parse_source(path, file.contents.clone())?,
+                None => parser.parse_file(path)?,
+            };
             let metadata = collect_file_metadata(&parsed);
             context
                 .lock()
@@ -589,4 +1296,31 @@ pub fn is_php_file(path: &Path) -> bool {
     path.extension()
         .and_then(|ext| ext.to_str())
         .map_or(false, |ext| ext.eq_ignore_ascii_case("php"))
+        || is_template_file(path)
+}
+
+/// `.phtml` templates mix HTML with PHP blocks. tree-sitter-php parses the HTML
+/// as `text` nodes, so diagnostics already point at the original template
+/// lines and columns; rules use this to relax checks that assume a pure PHP file.
+pub fn is_template_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("phtml"))
+}
+
+/// Test code lives under a `test`/`tests` directory or in a `*Test.php` file
+/// (PHPUnit convention).
+pub fn is_test_file(path: &Path) -> bool {
+    let in_test_dir = path.parent().is_some_and(|dir| {
+        dir.components().any(|component| {
+            let name = component.as_os_str().to_string_lossy();
+            name.eq_ignore_ascii_case("test") || name.eq_ignore_ascii_case("tests")
+        })
+    });
+
+    in_test_dir
+        || path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.ends_with("Test"))
 }
@@ -0,0 +1,199 @@
+//! Former rule names that configs and ignore comments may still use.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use super::fix::{self, StagedFile};
+
+/// A retired rule name and the name that replaced it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuleAlias {
+    pub old: &'static str,
+    pub new: &'static str,
+}
+
+const fn alias(old: &'static str, new: &'static str) -> RuleAlias {
+    RuleAlias { old, new }
+}
+
+/// Flat names used before rules were grouped by category. Keep entries here
+/// when renaming a rule so existing configs and comments keep working.
+pub const RULE_ALIASES: &[RuleAlias] = &[
+    alias("undefined-variable", "sanity/undefined_variable"),
+    alias("array-key-not-defined", "sanity/array_key_not_defined"),
+    alias("duplicate-declaration", "sanity/duplicate_declaration"),
+    alias("unused-variable", "cleanup/unused_variable"),
+    alias("unused-use", "cleanup/unused_use"),
+    alias("deprecated-api", "api/deprecated_api"),
+    alias("invalid-this", "api/invalid_this"),
+    alias(
+        "duplicate-switch-case",
+        "control_flow/duplicate_switch_case",
+    ),
+    alias("fallthrough", "control_flow/fallthrough"),
+    alias(
+        "impossible-comparison",
+        "control_flow/impossible_comparison",
+    ),
+    alias("redundant-condition", "control_flow/redundant_condition"),
+    alias("unreachable-code", "control_flow/unreachable"),
+    alias(
+        "unreachable-statement",
+        "control_flow/unreachable_statement",
+    ),
+    alias("psr4-namespace", "psr4/namespace"),
+    alias("hard-coded-credentials", "security/hard_coded_credentials"),
+    alias("hard-coded-keys", "security/hard_coded_keys"),
+    alias("include-user-input", "security/include_user_input"),
+    alias("mutating-literal", "security/mutating_literal"),
+    alias("weak-hashing", "security/weak_hashing"),
+    alias("consistent-return", "strict_typing/consistent_return"),
+    alias("force-return-type", "strict_typing/force_return_type"),
+    alias("missing-argument", "strict_typing/missing_argument"),
+    alias("missing-return", "strict_typing/missing_return"),
+    alias("phpdoc-param-check", "strict_typing/phpdoc_param_check"),
+    alias("phpdoc-return-check", "strict_typing/phpdoc_return_check"),
+    alias(
+        "phpdoc-return-value-check",
+        "strict_typing/phpdoc_return_value_check",
+    ),
+    alias("phpdoc-var-check", "strict_typing/phpdoc_var_check"),
+    alias("strict-types", "strict_typing/strict_types"),
+    alias("type-mismatch", "strict_typing/type_mismatch"),
+];
+
+/// The alias `name` refers to, if it is a retired rule name (case-insensitive).
+pub fn find(name: &str) -> Option<RuleAlias> {
+    RULE_ALIASES
+        .iter()
+        .find(|alias| alias.old.eq_ignore_ascii_case(name))
+        .copied()
+}
+
+impl RuleAlias {
+    pub fn notice(&self) -> String {
+        format!(
+            "rule name `{}` is deprecated; use `{}` instead (`php-checker config migrate` updates it)",
+            self.old, self.new
+        )
+    }
+}
+
+/// Replaces every retired rule name standing on its own in `text` (not as part
+/// of a longer name), returning the new text and the aliases it replaced. With
+/// `keys_only`, only names used as YAML keys (`name:`) are replaced.
+pub fn rewrite(text: &str, keys_only: bool) -> (String, Vec<RuleAlias>) {
+    let mut rewritten = String::with_capacity(text.len());
+    let mut replaced = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(is_name_char) {
+        let len = rest[start..]
+            .find(|c: char| !is_name_char(c))
+            .unwrap_or(rest.len() - start);
+        let token = &rest[start..start + len];
+        rewritten.push_str(&rest[..start]);
+        let after = rest[start + len..].trim_start_matches(['"', '\'']);
+        match find(token).filter(|_| !keys_only || after.starts_with(':')) {
+            Some(alias) => {
+                rewritten.push_str(alias.new);
+                replaced.push(alias);
+            }
+            None => rewritten.push_str(token),
+        }
+        rest = &rest[start + len..];
+    }
+    rewritten.push_str(rest);
+
+    (rewritten, replaced)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// Rewrites retired rule names in the config file and in the ignore comments
+/// of `php_files`. Returns the files that change; nothing is written with
+/// `dry_run`.
+pub fn migrate(
+    config: Option<&Path>,
+    php_files: &[PathBuf],
+    dry_run: bool,
+) -> Result<Vec<StagedFile>> {
+    let mut staged = Vec::new();
+
+    if let Some(path) = config {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config {}", path.display()))?;
+        let (contents, replaced) = rewrite(&source, true);
+        if !replaced.is_empty() {
+            staged.push(staged_file(path, contents, replaced.len()));
+        }
+    }
+
+    for path in php_files {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut count = 0;
+        let contents: String = source
+            .split_inclusive('\n')
+            .map(|line| match line.find("php-checker-") {
+                Some(idx) => {
+                    let (tail, replaced) = rewrite(&line[idx..], false);
+                    count += replaced.len();
+                    format!("{}{tail}", &line[..idx])
+                }
+                None => line.to_string(),
+            })
+            .collect();
+        if count > 0 {
+            staged.push(staged_file(path, contents, count));
+        }
+    }
+
+    if !dry_run {
+        fix::write_staged(&staged)?;
+    }
+    Ok(staged)
+}
+
+fn staged_file(path: &Path, contents: String, applied: usize) -> StagedFile {
+    StagedFile {
+        path: path.to_path_buf(),
+        contents,
+        applied,
+        skipped: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_standalone_names_only() {
+        let config = "# fallthrough is fine\nrules:\n  undefined-variable: false\n  \"Unused-Use\": true\n  my-undefined-variable-check: true\n";
+        let (rewritten, replaced) = rewrite(config, true);
+
+        assert_eq!(
+            rewritten,
+            "# fallthrough is fine\nrules:\n  sanity/undefined_variable: false\n  \"cleanup/unused_use\": true\n  my-undefined-variable-check: true\n"
+        );
+        assert_eq!(replaced.len(), 2);
+
+        let (rewritten, _) = rewrite("php-checker-ignore: fallthrough, unused-use", false);
+        assert_eq!(
+            rewritten,
+            "php-checker-ignore: control_flow/fallthrough, cleanup/unused_use"
+        );
+        assert_eq!(
+            find("missing-return").unwrap().new,
+            "strict_typing/missing_return"
+        );
+        assert!(find("sanity/undefined_variable").is_none());
+    }
+}
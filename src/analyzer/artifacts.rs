@@ -0,0 +1,243 @@
+mod control_flow;
+mod scopes;
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use tree_sitter::Node;
+
+use crate::analyzer::parser::ParsedSource;
+use crate::analyzer::phpdoc::{VarAnnotation, collect_var_annotations};
+use crate::analyzer::rules::helpers::{
+    FunctionSignature, collect_function_signatures, node_text, type_hint_from_parameter, walk_node,
+};
+use crate::analyzer::types::TypeHint;
+
+pub use control_flow::{BlockKind, ControlFlow, Jump};
+pub use scopes::{ScopeKind, ScopeTable, VariableRole};
+
+/// Per-file data that several rules build on. A rule lists the artifacts it reads
+/// in `DiagnosticRule::requires`, and the analyzer computes each one once per
+/// file, before the first rule that needs it, instead of every rule walking the
+/// tree for it again. Artifacts no enabled rule asks for are never built, and in
+/// debug builds reading one that wasn't prepared panics, so a missing entry in
+/// `requires` shows up in the rule's tests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Artifact {
+    /// Every `@var` annotation in the file ([`ParsedSource::var_annotations`]).
+    VarAnnotations,
+    /// Parameter types of the file's functions by name
+    /// ([`ParsedSource::function_signatures`]).
+    FunctionSignatures,
+    /// Declared parameter types per function, method and closure
+    /// ([`ParsedSource::parameter_type`]).
+    ParameterTypes,
+    /// Every `$variable` with its scope and role ([`ParsedSource::scopes`]).
+    Scopes,
+    /// Statement sequences, jumps, returns and branches
+    /// ([`ParsedSource::control_flow`]).
+    ControlFlow,
+}
+
+/// The artifacts of one file, each filled in when first prepared.
+#[derive(Default)]
+pub struct FileArtifacts {
+    var_annotations: OnceLock<Vec<VarAnnotation>>,
+    function_signatures: OnceLock<HashMap<String, FunctionSignature>>,
+    /// Keyed by the id of the function-like node, then by `$name`.
+    parameter_types: OnceLock<HashMap<usize, HashMap<String, TypeHint>>>,
+    scopes: OnceLock<ScopeTable>,
+    control_flow: OnceLock<ControlFlow>,
+}
+
+/// A node of the file's tree, kept by position so that artifacts can be stored
+/// next to the tree they describe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeRef {
+    pub start: usize,
+    pub end: usize,
+    pub kind: &'static str,
+}
+
+impl NodeRef {
+    pub fn new(node: Node) -> Self {
+        Self {
+            start: node.start_byte(),
+            end: node.end_byte(),
+            kind: node.kind(),
+        }
+    }
+
+    /// The node in `parsed`'s tree.
+    pub fn node<'t>(&self, parsed: &'t ParsedSource) -> Node<'t> {
+        let root = parsed.tree.root_node();
+        let smallest = root
+            .descendant_for_byte_range(self.start, self.end)
+            .unwrap_or(root);
+        // Nodes sharing this range are ancestors of the smallest one.
+        let mut node = smallest;
+        loop {
+            if node.kind() == self.kind
+                && node.start_byte() == self.start
+                && node.end_byte() == self.end
+            {
+                return node;
+            }
+            match node.parent() {
+                Some(parent) => node = parent,
+                None => return smallest,
+            }
+        }
+    }
+
+    /// Whether `other` lies within this node.
+    pub fn contains(&self, other: &NodeRef) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+}
+
+impl ParsedSource {
+    /// Builds `artifact` unless an earlier rule already needed it.
+    pub fn prepare(&self, artifact: Artifact) {
+        let artifacts = &self.artifacts;
+        match artifact {
+            Artifact::VarAnnotations => {
+                artifacts
+                    .var_annotations
+                    .get_or_init(|| collect_var_annotations(self));
+            }
+            Artifact::FunctionSignatures => {
+                artifacts
+                    .function_signatures
+                    .get_or_init(|| collect_function_signatures(self));
+            }
+            Artifact::ParameterTypes => {
+                artifacts
+                    .parameter_types
+                    .get_or_init(|| collect_parameter_types(self));
+            }
+            Artifact::Scopes => {
+                artifacts.scopes.get_or_init(|| ScopeTable::build(self));
+            }
+            Artifact::ControlFlow => {
+                artifacts
+                    .control_flow
+                    .get_or_init(|| ControlFlow::build(self));
+            }
+        }
+    }
+
+    pub fn var_annotations(&self) -> &[VarAnnotation] {
+        self.prepared(&self.artifacts.var_annotations, Artifact::VarAnnotations)
+            .as_slice()
+    }
+
+    pub fn function_signatures(&self) -> &HashMap<String, FunctionSignature> {
+        self.prepared(
+            &self.artifacts.function_signatures,
+            Artifact::FunctionSignatures,
+        )
+    }
+
+    /// The declared type of parameter `name` (with `$`) of a function-like node.
+    pub fn parameter_type(&self, function: Node, name: &str) -> Option<&TypeHint> {
+        self.prepared(&self.artifacts.parameter_types, Artifact::ParameterTypes)
+            .get(&function.id())?
+            .get(name)
+    }
+
+    pub fn scopes(&self) -> &ScopeTable {
+        self.prepared(&self.artifacts.scopes, Artifact::Scopes)
+    }
+
+    pub fn control_flow(&self) -> &ControlFlow {
+        self.prepared(&self.artifacts.control_flow, Artifact::ControlFlow)
+    }
+
+    /// The contents of `cell`, which [`ParsedSource::prepare`] fills in. Release
+    /// builds prepare an artifact a rule forgot to declare rather than fail.
+    fn prepared<'a, T>(&'a self, cell: &'a OnceLock<T>, artifact: Artifact) -> &'a T {
+        debug_assert!(
+            cell.get().is_some(),
+            "{artifact:?} was read by a rule that doesn't list it in `requires`"
+        );
+        if cell.get().is_none() {
+            self.prepare(artifact);
+        }
+        cell.get().expect("prepare fills in the artifact")
+    }
+}
+
+fn collect_parameter_types(parsed: &ParsedSource) -> HashMap<usize, HashMap<String, TypeHint>> {
+    let mut functions = HashMap::new();
+    walk_node(parsed.tree.root_node(), &mut |node| {
+        if !matches!(
+            node.kind(),
+            "function_definition"
+                | "method_declaration"
+                | "anonymous_function_creation_expression"
+                | "arrow_function"
+        ) {
+            return;
+        }
+        let Some(parameters) = node.child_by_field_name("parameters") else {
+            return;
+        };
+
+        let types = (0..parameters.named_child_count())
+            .filter_map(|idx| parameters.named_child(idx))
+            .filter_map(|parameter| {
+                let name = node_text(parameter.child_by_field_name("name")?, parsed)?;
+                Some((name, type_hint_from_parameter(parameter, parsed)))
+            })
+            .collect();
+        functions.insert(node.id(), types);
+    });
+    functions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Artifact;
+    use crate::analyzer::rules::test_utils::parse_php;
+    use crate::analyzer::types::TypeHint;
+
+    #[test]
+    fn artifacts_are_built_once_and_shared() {
+        let parsed = parse_php(
+            "<?php\n/** @var int $count */\n$count = f(1.5);\nfunction f(float $ratio, $any) {}\n",
+        );
+        for artifact in [
+            Artifact::VarAnnotations,
+            Artifact::FunctionSignatures,
+            Artifact::ParameterTypes,
+        ] {
+            parsed.prepare(artifact);
+        }
+
+        let annotations = parsed.var_annotations();
+        assert_eq!(annotations.len(), 1);
+        assert!(std::ptr::eq(annotations, parsed.var_annotations()));
+
+        assert_eq!(parsed.function_signatures()["f"].params.len(), 2);
+
+        let function = parsed.tree.root_node().named_child(3).unwrap();
+        assert_eq!(
+            parsed.parameter_type(function, "$ratio"),
+            Some(&TypeHint::Float)
+        );
+        assert_eq!(
+            parsed.parameter_type(function, "$any"),
+            Some(&TypeHint::Unknown)
+        );
+        assert_eq!(parsed.parameter_type(function, "$other"), None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "doesn't list it in `requires`")]
+    fn reading_an_undeclared_artifact_panics_in_debug_builds() {
+        let parsed = parse_php("<?php\n$count = 1;\n");
+        parsed.scopes();
+    }
+}
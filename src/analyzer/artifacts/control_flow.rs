@@ -0,0 +1,272 @@
+//! How control moves through a file at the statement level: the sequences
+//! statements run in, the jumps that leave them, the returns of each function
+//! and the `if` branches.
+
+use tree_sitter::Node;
+
+use super::NodeRef;
+use crate::analyzer::parser::ParsedSource;
+
+/// A statement that leaves the normal flow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Jump {
+    Return,
+    Throw,
+    Break,
+    Continue,
+    Goto,
+}
+
+impl Jump {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Jump::Return => "return",
+            Jump::Throw => "throw",
+            Jump::Break => "break",
+            Jump::Continue => "continue",
+            Jump::Goto => "goto",
+        }
+    }
+}
+
+/// Where a sequence of statements comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockKind {
+    /// The top level of the file.
+    File,
+    /// `{ ... }`
+    Compound,
+    /// The body of `if (...): ... endif;` and the other alternative syntaxes.
+    Colon,
+    /// The statements under a `case` label.
+    Case,
+    /// The statements under `default:`.
+    Default,
+}
+
+pub struct Statement {
+    pub node: NodeRef,
+    pub jump: Option<Jump>,
+}
+
+/// Statements that run one after the other, comments included.
+pub struct Block {
+    pub kind: BlockKind,
+    pub node: NodeRef,
+    pub statements: Vec<Statement>,
+}
+
+pub struct Switch {
+    pub node: NodeRef,
+    /// The `case` and `default` blocks, in order.
+    pub cases: Vec<usize>,
+}
+
+/// A function, method, closure or arrow function.
+pub struct Function {
+    pub node: NodeRef,
+    pub body: Option<NodeRef>,
+    /// The function's own `return` statements, not those of functions nested in it.
+    pub returns: Vec<NodeRef>,
+}
+
+/// An `if` or `elseif`.
+pub struct Branch {
+    pub node: NodeRef,
+    /// The block an `if` sits in directly; `None` for `elseif` and for an `if`
+    /// that is the whole body of another statement.
+    pub block: Option<usize>,
+}
+
+/// The statement-level control flow of a file; every list is in source order.
+#[derive(Default)]
+pub struct ControlFlow {
+    pub blocks: Vec<Block>,
+    pub switches: Vec<Switch>,
+    pub functions: Vec<Function>,
+    pub branches: Vec<Branch>,
+}
+
+/// What the statements being visited are inside of.
+#[derive(Clone, Copy, Default)]
+struct Enclosing {
+    /// The block whose statement is being visited, for its direct children only.
+    block: Option<usize>,
+    switch: Option<usize>,
+    function: Option<usize>,
+}
+
+impl ControlFlow {
+    pub(super) fn build(parsed: &ParsedSource) -> Self {
+        let mut flow = Self::default();
+        flow.visit(parsed.tree.root_node(), Enclosing::default());
+        flow
+    }
+
+    fn visit(&mut self, node: Node, enclosing: Enclosing) {
+        let mut inner = Enclosing {
+            block: None,
+            ..enclosing
+        };
+
+        match node.kind() {
+            "program" => inner.block = Some(self.push_block(node, BlockKind::File)),
+            "compound_statement" => inner.block = Some(self.push_block(node, BlockKind::Compound)),
+            "colon_block" => inner.block = Some(self.push_block(node, BlockKind::Colon)),
+            "case_statement" | "default_statement" => {
+                let kind = if node.kind() == "case_statement" {
+                    BlockKind::Case
+                } else {
+                    BlockKind::Default
+                };
+                let block = self.push_block(node, kind);
+                if let Some(switch) = enclosing.switch {
+                    self.switches[switch].cases.push(block);
+                }
+                inner.block = Some(block);
+            }
+            "switch_statement" => {
+                self.switches.push(Switch {
+                    node: NodeRef::new(node),
+                    cases: Vec::new(),
+                });
+                inner.switch = Some(self.switches.len() - 1);
+            }
+            "function_definition"
+            | "method_declaration"
+            | "anonymous_function_creation_expression"
+            | "arrow_function" => {
+                self.functions.push(Function {
+                    node: NodeRef::new(node),
+                    body: node.child_by_field_name("body").map(NodeRef::new),
+                    returns: Vec::new(),
+                });
+                inner.function = Some(self.functions.len() - 1);
+            }
+            "return_statement" => {
+                if let Some(function) = enclosing.function {
+                    self.functions[function].returns.push(NodeRef::new(node));
+                }
+            }
+            _ => {}
+        }
+
+        if matches!(node.kind(), "if_statement" | "else_if_clause") {
+            self.branches.push(Branch {
+                node: NodeRef::new(node),
+                block: enclosing.block.filter(|_| node.kind() == "if_statement"),
+            });
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(child, inner);
+        }
+    }
+
+    fn push_block(&mut self, node: Node, kind: BlockKind) -> usize {
+        // A case's value is the label, not one of its statements.
+        let value = node.child_by_field_name("value").map(|value| value.id());
+        let mut cursor = node.walk();
+        let statements = node
+            .named_children(&mut cursor)
+            .filter(|child| Some(child.id()) != value)
+            .map(|child| Statement {
+                node: NodeRef::new(child),
+                jump: jump(child),
+            })
+            .collect();
+
+        self.blocks.push(Block {
+            kind,
+            node: NodeRef::new(node),
+            statements,
+        });
+        self.blocks.len() - 1
+    }
+}
+
+fn jump(statement: Node) -> Option<Jump> {
+    match statement.kind() {
+        "return_statement" => Some(Jump::Return),
+        "break_statement" => Some(Jump::Break),
+        "continue_statement" => Some(Jump::Continue),
+        "goto_statement" => Some(Jump::Goto),
+        // `throw` is an expression, so a throwing statement wraps one.
+        "expression_statement"
+            if statement
+                .named_child(0)
+                .is_some_and(|expression| expression.kind() == "throw_expression") =>
+        {
+            Some(Jump::Throw)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::artifacts::Artifact;
+    use crate::analyzer::rules::test_utils::parse_php;
+
+    #[test]
+    fn records_blocks_jumps_returns_and_branches() {
+        let parsed = parse_php(
+            r#"<?php
+function pick(int $value) {
+    switch ($value) {
+        case 1:
+            return 'one';
+        default:
+            throw new Exception();
+    }
+    $format = function () {
+        return 'nested';
+    };
+    if ($value) {
+    } elseif ($format) {
+    }
+    return 'none';
+}
+"#,
+        );
+        parsed.prepare(Artifact::ControlFlow);
+        let flow = parsed.control_flow();
+
+        let kinds: Vec<_> = flow.blocks.iter().map(|block| block.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                BlockKind::File,
+                BlockKind::Compound,
+                BlockKind::Case,
+                BlockKind::Default,
+                BlockKind::Compound,
+                BlockKind::Compound,
+                BlockKind::Compound,
+            ]
+        );
+        let case_jumps: Vec<_> = flow.switches[0]
+            .cases
+            .iter()
+            .map(|&case| flow.blocks[case].statements[0].jump)
+            .collect();
+        assert_eq!(case_jumps, [Some(Jump::Return), Some(Jump::Throw)]);
+
+        let pick = &flow.functions[0];
+        assert_eq!(pick.node.kind, "function_definition");
+        assert_eq!(pick.returns.len(), 2);
+        assert_eq!(flow.functions[1].returns.len(), 1);
+
+        let branches: Vec<_> = flow
+            .branches
+            .iter()
+            .map(|branch| (branch.node.kind, branch.block))
+            .collect();
+        assert_eq!(
+            branches,
+            [("if_statement", Some(1)), ("else_if_clause", None)]
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Every `$variable` of a file, with the scope it is in and what it does there.
+
+use tree_sitter::Node;
+
+use super::NodeRef;
+use crate::analyzer::parser::ParsedSource;
+use crate::analyzer::rules::helpers::variable_name_text;
+
+/// Code that opens a scope of its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScopeKind {
+    File,
+    Function,
+    Method,
+    Closure,
+    ArrowFunction,
+}
+
+pub struct Scope {
+    pub kind: ScopeKind,
+    /// Index of the enclosing scope; `None` for the file.
+    pub parent: Option<usize>,
+}
+
+/// What an occurrence of a variable does with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VariableRole {
+    /// Every other use, including `$x[] = ...` and `$x += 1`.
+    Read,
+    /// The left-hand side of a plain `$x = ...`.
+    Assigned,
+    /// A parameter of a function, method or closure.
+    Parameter,
+    /// A constructor parameter promoted to a property.
+    PromotedParameter,
+    /// Bound by `global`, `catch`, `foreach` or a property declaration.
+    Bound,
+}
+
+pub struct VariableSite {
+    /// The name without its `$`.
+    pub name: String,
+    pub role: VariableRole,
+    /// Index of the innermost scope around the variable.
+    pub scope: usize,
+    pub node: NodeRef,
+}
+
+/// The scopes of a file, the file scope first, and its variables in source order.
+pub struct ScopeTable {
+    pub scopes: Vec<Scope>,
+    pub variables: Vec<VariableSite>,
+}
+
+impl ScopeTable {
+    pub(super) fn build(parsed: &ParsedSource) -> Self {
+        let mut table = Self {
+            scopes: vec![Scope {
+                kind: ScopeKind::File,
+                parent: None,
+            }],
+            variables: Vec::new(),
+        };
+        table.visit(parsed.tree.root_node(), 0, parsed);
+        table
+    }
+
+    /// `scope` or the closest scope around it whose kind is in `kinds`.
+    pub fn enclosing(&self, mut scope: usize, kinds: &[ScopeKind]) -> usize {
+        while !kinds.contains(&self.scopes[scope].kind)
+            && let Some(parent) = self.scopes[scope].parent
+        {
+            scope = parent;
+        }
+        scope
+    }
+
+    fn visit(&mut self, node: Node, scope: usize, parsed: &ParsedSource) {
+        let scope = match scope_kind(node) {
+            Some(kind) => {
+                self.scopes.push(Scope {
+                    kind,
+                    parent: Some(scope),
+                });
+                self.scopes.len() - 1
+            }
+            None => scope,
+        };
+
+        if node.kind() == "variable_name"
+            && let Some(name) = variable_name_text(node, parsed)
+        {
+            self.variables.push(VariableSite {
+                name,
+                role: variable_role(node),
+                scope,
+                node: NodeRef::new(node),
+            });
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(child, scope, parsed);
+        }
+    }
+}
+
+fn scope_kind(node: Node) -> Option<ScopeKind> {
+    match node.kind() {
+        "function_definition" => Some(ScopeKind::Function),
+        "method_declaration" => Some(ScopeKind::Method),
+        "anonymous_function_creation_expression" => Some(ScopeKind::Closure),
+        "arrow_function" => Some(ScopeKind::ArrowFunction),
+        _ => None,
+    }
+}
+
+fn variable_role(variable: Node) -> VariableRole {
+    let Some(parent) = variable.parent() else {
+        return VariableRole::Read;
+    };
+    match parent.kind() {
+        "assignment_expression" if parent.named_child(0) == Some(variable) => {
+            VariableRole::Assigned
+        }
+        "simple_parameter" | "variadic_parameter" => VariableRole::Parameter,
+        "property_promotion_parameter" => VariableRole::PromotedParameter,
+        "global_declaration" | "property_element" | "catch_clause" => VariableRole::Bound,
+        // The collection is read; the loop variables after it are bound
+        "foreach_statement" if parent.named_child(0) != Some(variable) => VariableRole::Bound,
+        // foreach ($rows as $key => $row)
+        "pair"
+            if parent
+                .parent()
+                .is_some_and(|grandparent| grandparent.kind() == "foreach_statement") =>
+        {
+            VariableRole::Bound
+        }
+        _ => VariableRole::Read,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::artifacts::Artifact;
+    use crate::analyzer::rules::test_utils::parse_php;
+
+    #[test]
+    fn variables_carry_their_scope_and_role() {
+        let parsed = parse_php(
+            r#"<?php
+$total = 0;
+function add(int $amount) {
+    global $total;
+    $total += $amount;
+    return fn($x) => $x + $amount;
+}
+foreach ($items as $key => $item) {}
+"#,
+        );
+        parsed.prepare(Artifact::Scopes);
+        let table = parsed.scopes();
+
+        let sites: Vec<_> = table
+            .variables
+            .iter()
+            .map(|site| (site.name.as_str(), site.role, table.scopes[site.scope].kind))
+            .collect();
+        assert_eq!(
+            sites,
+            [
+                ("total", VariableRole::Assigned, ScopeKind::File),
+                ("amount", VariableRole::Parameter, ScopeKind::Function),
+                ("total", VariableRole::Bound, ScopeKind::Function),
+                ("total", VariableRole::Read, ScopeKind::Function),
+                ("amount", VariableRole::Read, ScopeKind::Function),
+                ("x", VariableRole::Parameter, ScopeKind::ArrowFunction),
+                ("x", VariableRole::Read, ScopeKind::ArrowFunction),
+                ("amount", VariableRole::Read, ScopeKind::ArrowFunction),
+                ("items", VariableRole::Read, ScopeKind::File),
+                ("key", VariableRole::Bound, ScopeKind::File),
+                ("item", VariableRole::Bound, ScopeKind::File),
+            ]
+        );
+
+        let arrow = table.variables[5].scope;
+        assert_eq!(table.enclosing(arrow, &[ScopeKind::Function]), 1);
+        assert_eq!(
+            table.variables[5].node.node(&parsed).kind(),
+            "variable_name"
+        );
+    }
+}
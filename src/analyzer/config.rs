@@ -2,10 +2,16 @@ use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::{
     collections::HashMap,
-    fs,
+    fmt, fs,
     path::{Path, PathBuf},
 };
 
+use super::aliases;
+use super::version::VersionRequirement;
+
+/// Rules that stay disabled unless `rules.<name>: true` is set.
+pub const OPT_IN_RULES: &[&str] = &["cleanup/todo_comment", "sanity/locale_dependent"];
+
 /// Configuration for enabling/disabling individual rules plus general analyzer settings.
 #[derive(Clone, Debug, Deserialize, Default)]
 #[serde(default)]
@@ -14,6 +20,50 @@ pub struct AnalyzerConfig {
     pub rules: HashMap<String, bool>,
     #[serde(default)]
     pub psr4: Psr4Config,
+    /// PHP version the analysed code targets; compatibility rules only report
+    /// deprecations that apply to this version. Defaults to the latest release.
+    #[serde(default)]
+    pub php_version: Option<PhpVersion>,
+    #[serde(default)]
+    pub templates: TemplateConfig,
+    #[serde(default)]
+    pub environment_values: EnvironmentValuesConfig,
+    #[serde(default)]
+    pub file_header: FileHeaderConfig,
+    #[serde(default)]
+    pub strict_types: StrictTypesConfig,
+    #[serde(default)]
+    pub deprecated_api: DeprecatedApiConfig,
+    #[serde(default)]
+    pub security: SecurityConfig,
+    #[serde(default)]
+    pub float_equality: FloatEqualityConfig,
+    /// Which code the documentation and strict typing rules check (`--scope`).
+    #[serde(default)]
+    pub scope: AnalysisScope,
+    /// Print diagnostics as soon as they are found instead of sorted and
+    /// grouped by file once analysis finishes (`--stream`).
+    #[serde(default)]
+    pub stream: bool,
+    /// php-checker releases the project accepts, e.g. `^0.5`; other versions refuse
+    /// to run so a team never mixes diagnostics from different releases.
+    #[serde(default)]
+    pub required_version: Option<VersionRequirement>,
+    /// Language diagnostic messages are reported in (`de`, or `en`, the default).
+    /// Rule names are never translated, so ignore comments and tooling keep matching.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// YAML message catalog adding or overriding translations for `locale`;
+    /// relative paths are resolved against the config file's directory.
+    #[serde(default)]
+    pub message_catalog: Option<PathBuf>,
+    /// Rules or rule groups picked with `--rule`; when non-empty, every other
+    /// rule is skipped.
+    #[serde(skip)]
+    pub only_rules: Vec<String>,
+    /// Deprecation notices about the loaded config, such as retired rule names.
+    #[serde(skip)]
+    pub notices: Vec<String>,
 }
 
 impl AnalyzerConfig {
@@ -21,12 +71,53 @@ impl AnalyzerConfig {
         let path = path.as_ref();
         let content = fs::read_to_string(path)
             .with_context(|| format!("failed to read config {}", path.display()))?;
-        let config = serde_yaml::from_str(&content)
+        let mut config: Self = serde_yaml::from_str(&content)
             .with_context(|| format!("failed to parse {}", path.display()))?;
+        if let (Some(catalog), Some(dir)) = (&config.message_catalog, path.parent()) {
+            config.message_catalog = Some(dir.join(catalog));
+        }
+        config.resolve_rule_aliases();
+        config
+            .strict_types
+            .compile()
+            .with_context(|| format!("invalid strict_types.paths in {}", path.display()))?;
         Ok(config)
     }
 
+    /// Moves `rules` entries keyed by a retired rule name to the current name,
+    /// noting each one. An entry already using the current name wins.
+    pub fn resolve_rule_aliases(&mut self) {
+        let mut aliased: Vec<_> = self
+            .rules
+            .keys()
+            .filter_map(|key| aliases::find(key).map(|alias| (key.clone(), alias)))
+            .collect();
+        aliased.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (key, alias) in aliased {
+            if let Some(enabled) = self.rules.remove(&key) {
+                self.rules.entry(alias.new.to_string()).or_insert(enabled);
+            }
+            self.notices.push(alias.notice());
+        }
+    }
+
     pub fn enabled(&self, rule_name: &str) -> bool {
+        if !self.only_rules.is_empty() {
+            if !self.only_rules.iter().any(|only| selects(only, rule_name)) {
+                return false;
+            }
+            // Naming a rule outright runs it even if it is opt-in or switched off.
+            if self.only_rules.iter().any(|only| only == rule_name) {
+                return true;
+            }
+        }
+
+        // Opt-in rules only run when named explicitly; group toggles don't reach them.
+        if OPT_IN_RULES.contains(&rule_name) {
+            return self.rules.get(rule_name).copied().unwrap_or(false);
+        }
+
         let mut candidate = rule_name;
         loop {
             if let Some(enabled) = self.rules.get(candidate) {
@@ -44,6 +135,11 @@ impl AnalyzerConfig {
         true
     }
 
+    /// Returns the configured target PHP version, falling back to the latest supported release.
+    pub fn target_php_version(&self) -> PhpVersion {
+        self.php_version.unwrap_or(PhpVersion::LATEST)
+    }
+
     pub fn find_config(path: Option<PathBuf>, root: &Path) -> Option<PathBuf> {
         if let Some(path) = path {
             return Some(path);
@@ -78,9 +174,310 @@ impl Default for Psr4Config {
     }
 }
 
+/// Settings applied to `.phtml` templates (HTML with embedded PHP).
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct TemplateConfig {
+    /// Variables the rendering layer injects into every template, with or without `$`.
+    pub view_variables: Vec<String>,
+}
+
+impl TemplateConfig {
+    pub fn is_view_variable(&self, name: &str) -> bool {
+        let name = name.trim_start_matches('$');
+        self.view_variables
+            .iter()
+            .any(|variable| variable.trim_start_matches('$') == name)
+    }
+}
+
+/// Literal values that tie code to one machine or environment.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct EnvironmentValuesConfig {
+    /// Case-insensitive `*` wildcard patterns. Patterns containing `/` or `\` are
+    /// matched against the whole string (paths); the rest are matched against each
+    /// host-like token in it, so `localhost` also catches `mysql:host=localhost;...`.
+    /// Setting this replaces the built-in list.
+    pub patterns: Vec<String>,
+}
+
+impl Default for EnvironmentValuesConfig {
+    fn default() -> Self {
+        Self {
+            patterns: [
+                "localhost",
+                "127.0.0.1",
+                "*.test",
+                "*.local",
+                "/Users/*",
+                "/home/*",
+                "C:\\Users\\*",
+            ]
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .collect(),
+        }
+    }
+}
+
+/// The comment every PHP file should open with (license, copyright notice, ...).
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FileHeaderConfig {
+    /// The header comment exactly as it should appear, delimiters included.
+    /// `{year}` matches any four-digit year (or `2019-2024` range). Unset disables
+    /// `style/file_header`.
+    pub template: Option<String>,
+}
+
+/// Which files `strict_typing/strict_types` expects to declare `strict_types=1`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct StrictTypesConfig {
+    /// Require the declaration in every analysed file.
+    pub all_files: bool,
+    /// Glob patterns such as `src/**/*.php`; relative patterns match at any depth.
+    pub paths: Vec<String>,
+    /// `paths` compiled by [`Self::compile`].
+    #[serde(skip)]
+    patterns: Vec<glob::Pattern>,
+}
+
+impl StrictTypesConfig {
+    /// Compiles `paths`, failing on the first pattern that isn't a valid glob.
+    pub fn compile(&mut self) -> Result<()> {
+        self.patterns = self
+            .paths
+            .iter()
+            .map(|pattern| {
+                let glob = if Path::new(pattern).is_absolute() {
+                    pattern.clone()
+                } else {
+                    format!("**/{}", pattern.trim_start_matches("./"))
+                };
+                glob::Pattern::new(&glob)
+                    .with_context(|| format!("invalid strict_types pattern \"{pattern}\""))
+            })
+            .collect::<Result<_>>()?;
+        Ok(())
+    }
+
+    pub fn applies_to(&self, path: &Path) -> bool {
+        if self.all_files {
+            return true;
+        }
+
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..glob::MatchOptions::new()
+        };
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches_path_with(path, options))
+    }
+}
+
+/// Project-specific deprecations reported by `api/deprecated_api` on top of PHP's own.
+///
+/// Each entry maps the deprecated name to its replacement, or to `~` when there is
+/// none. Functions, methods (`Class::method`) and classes use fully qualified names.
+/// Functions may also give a `{ replacement, message }` map to explain the deprecation.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct DeprecatedApiConfig {
+    pub functions: HashMap<String, Deprecation>,
+    pub methods: HashMap<String, Option<String>>,
+    pub classes: HashMap<String, Option<String>>,
+    pub ini: HashMap<String, Option<String>>,
+}
+
+/// A configured deprecated function: what to call instead and why it was retired.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(from = "RawDeprecation")]
+pub struct Deprecation {
+    pub replacement: Option<String>,
+    /// Shown in place of the generic "use ... instead" advice.
+    pub message: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawDeprecation {
+    Replacement(Option<String>),
+    Detailed {
+        #[serde(default)]
+        replacement: Option<String>,
+        #[serde(default)]
+        message: Option<String>,
+    },
+}
+
+impl From<RawDeprecation> for Deprecation {
+    fn from(raw: RawDeprecation) -> Self {
+        match raw {
+            RawDeprecation::Replacement(replacement) => Self {
+                replacement,
+                message: None,
+            },
+            RawDeprecation::Detailed {
+                replacement,
+                message,
+            } => Self {
+                replacement,
+                message,
+            },
+        }
+    }
+}
+
+/// Project-specific words and functions added to the `security` rules' built-in lists.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// Functions that hash their first argument weakly, like `md5` (`security/weak_hashing`).
+    pub weak_hash_functions: Vec<String>,
+    /// Name fragments that mark a password (`security/weak_hashing`,
+    /// `security/hard_coded_credentials`).
+    pub password_indicators: Vec<String>,
+    /// Identifier words that mark key material (`security/hard_coded_keys`).
+    pub key_indicators: Vec<String>,
+    /// Functions that modify an array argument in place (`security/mutating_literal`).
+    pub mutating_functions: Vec<String>,
+    /// Name of the variables the `security/mutating_literal` fix moves literals
+    /// into, numbered per statement; `_php_checker_literal` when unset.
+    pub literal_placeholder_prefix: Option<String>,
+}
+
+impl SecurityConfig {
+    /// The built-in entries followed by the configured ones, lowercased.
+    pub fn extend(builtin: &[&str], extra: &[String]) -> Vec<String> {
+        builtin
+            .iter()
+            .map(|entry| entry.to_string())
+            .chain(
+                extra
+                    .iter()
+                    .map(|entry| entry.trim_start_matches('\\').to_lowercase()),
+            )
+            .collect()
+    }
+}
+
+/// Which exact float comparisons `sanity/float_equality` lets through.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct FloatEqualityConfig {
+    /// Accept comparisons with whole-number literals such as `0.0` or `1`, which
+    /// floats represent exactly. On by default.
+    pub allow_whole_literals: bool,
+}
+
+impl Default for FloatEqualityConfig {
+    fn default() -> Self {
+        Self {
+            allow_whole_literals: true,
+        }
+    }
+}
+
+/// A `major.minor` PHP release used to gate version-specific diagnostics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PhpVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+impl PhpVersion {
+    pub const LATEST: PhpVersion = PhpVersion::new(8, 4);
+
+    pub const fn new(major: u32, minor: u32) -> Self {
+        Self { major, minor }
+    }
+
+    /// Parses versions such as `8`, `8.2`, or `8.2.12` (the patch level is ignored).
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().split('.');
+        let major = parts.next()?.trim().parse().ok()?;
+        let minor = match parts.next() {
+            Some(minor) => minor.trim().parse().ok()?,
+            None => 0,
+        };
+        Some(Self::new(major, minor))
+    }
+}
+
+impl fmt::Display for PhpVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl<'de> Deserialize<'de> for PhpVersion {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // YAML reads `php_version: 8.2` as a float, so accept numbers as well as strings.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawVersion {
+            Text(String),
+            Number(f64),
+        }
+
+        let text = match RawVersion::deserialize(deserializer)? {
+            RawVersion::Text(text) => text,
+            RawVersion::Number(number) => number.to_string(),
+        };
+
+        PhpVersion::parse(&text)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid PHP version \"{text}\"")))
+    }
+}
+
+/// Rule groups that `AnalysisScope::PublicApi` restricts to the exported surface.
+const PUBLIC_API_GROUPS: &[&str] = &["phpdoc", "strict_typing"];
+
+/// How much of the code base the documentation and strict typing rules hold to
+/// their standards.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnalysisScope {
+    /// Every file and declaration.
+    #[default]
+    All,
+    /// Public classes, methods, properties and functions of non-test code;
+    /// private and protected members, anonymous classes and `@internal`
+    /// declarations are skipped.
+    PublicApi,
+}
+
+impl AnalysisScope {
+    /// Whether this scope narrows where `rule_name` reports.
+    pub fn limits(self, rule_name: &str) -> bool {
+        self == AnalysisScope::PublicApi
+            && PUBLIC_API_GROUPS
+                .iter()
+                .any(|group| selects(group, rule_name))
+    }
+}
+
+/// Whether `--rule <only>` covers `rule_name`: the rule itself, a rule inside
+/// the `only` group, or a group that contains the `only` rule.
+fn selects(only: &str, rule_name: &str) -> bool {
+    let nested = |outer: &str, inner: &str| {
+        inner
+            .strip_prefix(outer)
+            .is_some_and(|rest| rest.starts_with('/'))
+    };
+    only == rule_name || nested(only, rule_name) || nested(rule_name, only)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analyzer::version::Version;
 
     #[test]
     fn psr4_config_deserializes_values() {
@@ -107,4 +504,158 @@ mod tests {
         assert!(!config.enabled("psr4/namespace"));
         assert!(config.enabled("psr4/anything"));
     }
+
+    #[test]
+    fn opt_in_rules_require_explicit_toggle() {
+        let mut config = AnalyzerConfig::default();
+        assert!(!config.enabled("cleanup/todo_comment"));
+        assert!(!config.enabled("sanity/locale_dependent"));
+
+        config.rules.insert("cleanup".to_string(), true);
+        assert!(!config.enabled("cleanup/todo_comment"));
+
+        config
+            .rules
+            .insert("cleanup/todo_comment".to_string(), true);
+        assert!(config.enabled("cleanup/todo_comment"));
+    }
+
+    #[test]
+    fn only_rules_restrict_to_selected_rules_and_groups() {
+        let mut config = AnalyzerConfig::default();
+        config.rules.insert("security".to_string(), false);
+        config.only_rules = vec![
+            "cleanup".to_string(),
+            "security/weak_hashing".to_string(),
+            "cleanup/todo_comment".to_string(),
+        ];
+
+        assert!(config.enabled("cleanup/unused_variable"));
+        assert!(config.enabled("cleanup/todo_comment"));
+        assert!(config.enabled("security/weak_hashing"));
+        assert!(!config.enabled("security/hard_coded_keys"));
+        assert!(!config.enabled("sanity/undefined_variable"));
+    }
+
+    #[test]
+    fn strict_types_paths_match_at_any_depth() {
+        let mut config: AnalyzerConfig =
+            serde_yaml::from_str("strict_types:\n  paths: [\"src/**/*.php\"]").unwrap();
+        config.strict_types.compile().unwrap();
+
+        let strict_types = &config.strict_types;
+        assert!(strict_types.applies_to(Path::new("/repo/src/Http/Kernel.php")));
+        assert!(!strict_types.applies_to(Path::new("/repo/legacy/src.php")));
+        assert!(!StrictTypesConfig::default().applies_to(Path::new("/repo/src/Kernel.php")));
+
+        let mut invalid: StrictTypesConfig =
+            serde_yaml::from_str("paths: [\"src/**\", \"src/[a-\"]").unwrap();
+        let error = invalid.compile().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid strict_types pattern \"src/[a-\""
+        );
+    }
+
+    #[test]
+    fn php_version_accepts_strings_and_numbers() {
+        let config: AnalyzerConfig = serde_yaml::from_str("php_version: \"8.1\"").unwrap();
+        assert_eq!(config.php_version, Some(PhpVersion::new(8, 1)));
+
+        let config: AnalyzerConfig = serde_yaml::from_str("php_version: 8.2").unwrap();
+        assert_eq!(config.target_php_version(), PhpVersion::new(8, 2));
+
+        let config = AnalyzerConfig::default();
+        assert_eq!(config.target_php_version(), PhpVersion::LATEST);
+    }
+
+    #[test]
+    fn deprecated_functions_accept_replacements_and_messages() {
+        let config: AnalyzerConfig = serde_yaml::from_str(
+            "deprecated_api:\n  functions:\n    legacy_escape: htmlspecialchars\n    old_log: ~\n    fetch_all:\n      message: rows are streamed now\n",
+        )
+        .unwrap();
+
+        let functions = &config.deprecated_api.functions;
+        assert_eq!(
+            functions["legacy_escape"].replacement.as_deref(),
+            Some("htmlspecialchars")
+        );
+        assert_eq!(functions["old_log"], Deprecation::default());
+        assert_eq!(
+            functions["fetch_all"].message.as_deref(),
+            Some("rows are streamed now")
+        );
+        assert_eq!(functions["fetch_all"].replacement, None);
+    }
+
+    #[test]
+    fn security_dictionaries_extend_builtins() {
+        let config: AnalyzerConfig =
+            serde_yaml::from_str("security:\n  weak_hash_functions: [crc32, '\\Legacy_Hash']")
+                .unwrap();
+
+        assert_eq!(
+            SecurityConfig::extend(&["md5"], &config.security.weak_hash_functions),
+            ["md5", "crc32", "legacy_hash"]
+        );
+        assert!(config.security.mutating_functions.is_empty());
+    }
+
+    #[test]
+    fn float_equality_allows_whole_literals_unless_disabled() {
+        assert!(FloatEqualityConfig::default().allow_whole_literals);
+
+        let config: AnalyzerConfig =
+            serde_yaml::from_str("float_equality:\n  allow_whole_literals: false").unwrap();
+        assert!(!config.float_equality.allow_whole_literals);
+    }
+
+    #[test]
+    fn retired_rule_names_map_to_current_names() {
+        let mut config: AnalyzerConfig = serde_yaml::from_str(
+            "rules:\n  undefined-variable: false\n  unused-use: false\n  cleanup/unused_use: true",
+        )
+        .unwrap();
+        config.resolve_rule_aliases();
+
+        assert!(!config.enabled("sanity/undefined_variable"));
+        assert!(config.enabled("cleanup/unused_use"));
+        assert_eq!(config.notices.len(), 2);
+    }
+
+    #[test]
+    fn required_version_accepts_ranges_and_rejects_numbers() {
+        let config: AnalyzerConfig =
+            serde_yaml::from_str("required_version: \">=0.4, <0.6\"").unwrap();
+        let required = config.required_version.unwrap();
+        assert!(required.matches(Version::new(0, 5, 3)));
+        assert!(!required.matches(Version::new(0, 6, 0)));
+
+        let config: AnalyzerConfig = serde_yaml::from_str("required_version: \"0.10\"").unwrap();
+        assert_eq!(config.required_version.unwrap().to_string(), "0.10");
+        let error = serde_yaml::from_str::<AnalyzerConfig>("required_version: 0.10").unwrap_err();
+        assert!(error.to_string().contains("must be quoted"), "{error}");
+        assert!(serde_yaml::from_str::<AnalyzerConfig>("required_version: soon").is_err());
+    }
+
+    #[test]
+    fn public_api_scope_limits_documentation_and_typing_rules() {
+        let config: AnalyzerConfig = serde_yaml::from_str("scope: public-api").unwrap();
+
+        assert!(config.scope.limits("strict_typing/force_return_type"));
+        assert!(config.scope.limits("phpdoc/malformed_tag"));
+        assert!(!config.scope.limits("cleanup/unused_variable"));
+        assert!(!AnalysisScope::All.limits("strict_typing/force_return_type"));
+    }
+
+    #[test]
+    fn template_view_variables_ignore_dollar_prefix() {
+        let config: AnalyzerConfig =
+            serde_yaml::from_str("templates:\n  view_variables: [title, $items]").unwrap();
+
+        assert!(config.templates.is_view_variable("$title"));
+        assert!(config.templates.is_view_variable("items"));
+        assert!(!config.templates.is_view_variable("user"));
+    }
 }
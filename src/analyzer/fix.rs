@@ -1,7 +1,15 @@
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use super::Diagnostic;
+use super::parser::TreeSitterPhpParser;
 
 /// Represents a single in-file edit returned by a fixable rule.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TextEdit {
     pub start: usize,
     pub end: usize,
@@ -19,6 +27,92 @@ impl TextEdit {
     }
 }
 
+/// Edits one rule proposes for a file, with the rows they touch.
+#[derive(Clone, Debug)]
+pub struct RuleFix {
+    pub rule_name: String,
+    pub edits: Vec<TextEdit>,
+    /// Zero-based `(first, last)` rows of each edit.
+    rows: Vec<(usize, usize)>,
+}
+
+impl RuleFix {
+    fn covers(&self, diagnostic: &Diagnostic) -> bool {
+        let Some(span) = &diagnostic.span else {
+            return true;
+        };
+        self.rows
+            .iter()
+            .any(|&(first, last)| first <= span.end.row && span.start.row <= last)
+    }
+}
+
+/// Fixes proposed for a set of files, grouped by file and rule.
+#[derive(Clone, Debug, Default)]
+pub struct FixSet {
+    files: BTreeMap<PathBuf, Vec<RuleFix>>,
+}
+
+impl FixSet {
+    pub fn push(&mut self, path: &Path, source: &str, rule_name: &str, edits: Vec<TextEdit>) {
+        if edits.is_empty() {
+            return;
+        }
+
+        let row = |offset: usize| source[..offset.min(source.len())].matches('\n').count();
+        // An edit ending right after a newline (a removed line) stays on that line.
+        let rows = edits
+            .iter()
+            .map(|edit| {
+                let last = edit.end.saturating_sub(1).max(edit.start);
+                (row(edit.start), row(last))
+            })
+            .collect();
+
+        self.files
+            .entry(path.to_path_buf())
+            .or_default()
+            .push(RuleFix {
+                rule_name: rule_name.to_string(),
+                edits,
+                rows,
+            });
+    }
+
+    /// All edits per file, in rule order.
+    pub fn edits(&self) -> BTreeMap<PathBuf, Vec<TextEdit>> {
+        self.files
+            .iter()
+            .map(|(path, fixes)| {
+                let edits = fixes.iter().flat_map(|fix| fix.edits.clone()).collect();
+                (path.clone(), edits)
+            })
+            .collect()
+    }
+
+    pub fn edit_count(&self) -> usize {
+        self.files
+            .values()
+            .flatten()
+            .map(|fix| fix.edits.len())
+            .sum()
+    }
+
+    /// The fix from the diagnostic's own rule with an edit on one of its rows.
+    pub fn fix_for(&self, diagnostic: &Diagnostic) -> Option<&RuleFix> {
+        let rule_name = diagnostic.rule_name.as_deref()?;
+        self.files
+            .get(&diagnostic.file)?
+            .iter()
+            .find(|fix| fix.rule_name == rule_name && fix.covers(diagnostic))
+    }
+
+    /// Records on `diagnostic` which rule's fix resolves it, if any.
+    pub fn link(&self, diagnostic: &mut Diagnostic) {
+        diagnostic.fix_rule = self.fix_for(diagnostic).map(|fix| fix.rule_name.clone());
+    }
+}
+
 /// Applies a sequence of edits to `source` and returns the updated text.
 pub fn apply_text_edits(source: &str, edits: &[TextEdit]) -> String {
     let mut sorted = edits.to_vec();
@@ -43,6 +137,134 @@ pub fn apply_text_edits(source: &str, edits: &[TextEdit]) -> String {
     result
 }
 
+/// Picks the edits that can be applied together, in order: an edit overlapping one
+/// already picked is skipped (so earlier rules win), and exact repeats are dropped.
+/// Returns the picked edits and the number skipped.
+pub fn select_edits(edits: &[TextEdit]) -> (Vec<TextEdit>, usize) {
+    let mut selected: Vec<TextEdit> = Vec::new();
+    let mut skipped = 0;
+
+    for edit in edits {
+        if selected.contains(edit) {
+            continue;
+        }
+        // Insertions at the same offset both apply, in rule order.
+        let overlaps = selected
+            .iter()
+            .any(|other| edit.start < other.end && other.start < edit.end);
+        if overlaps {
+            skipped += 1;
+        } else {
+            selected.push(edit.clone());
+        }
+    }
+
+    (selected, skipped)
+}
+
+/// A file's patched contents, held in memory until every file has been checked.
+#[derive(Clone, Debug)]
+pub struct StagedFile {
+    pub path: PathBuf,
+    pub contents: String,
+    /// Edits applied to the file.
+    pub applied: usize,
+    /// Edits left out because they overlapped another rule's edit.
+    pub skipped: usize,
+}
+
+/// Applies every file's edits in memory. Fails without touching any file when one
+/// of them would no longer parse, so `--fix` never leaves the tree half-fixed.
+pub fn stage(fixes: &BTreeMap<PathBuf, Vec<TextEdit>>) -> Result<Vec<StagedFile>> {
+    let mut parser = TreeSitterPhpParser::new()?;
+    let mut staged = Vec::new();
+    let mut broken = Vec::new();
+
+    for (path, edits) in fixes {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let file = stage_file(path, &source, edits);
+        if introduces_syntax_errors(&mut parser, path, &source, &file.contents)? {
+            broken.push(path.display().to_string());
+        }
+        staged.push(file);
+    }
+
+    if !broken.is_empty() {
+        bail!(
+            "fixes would introduce syntax errors in {}; no files were changed",
+            broken.join(", ")
+        );
+    }
+
+    Ok(staged)
+}
+
+/// Applies the non-conflicting subset of `edits` to `source`.
+pub fn stage_file(path: &Path, source: &str, edits: &[TextEdit]) -> StagedFile {
+    let (selected, skipped) = select_edits(edits);
+    StagedFile {
+        path: path.to_path_buf(),
+        contents: apply_text_edits(source, &selected),
+        applied: selected.len(),
+        skipped,
+    }
+}
+
+/// Files that already had syntax errors are judged by whether patching added any.
+fn introduces_syntax_errors(
+    parser: &mut TreeSitterPhpParser,
+    path: &Path,
+    source: &str,
+    patched: &str,
+) -> Result<bool> {
+    let has_errors = |parser: &mut TreeSitterPhpParser, text: &str| -> Result<bool> {
+        let parsed = parser.parse_source(path, text.to_string())?;
+        Ok(parsed.tree.root_node().has_error())
+    };
+    Ok(has_errors(parser, patched)? && !has_errors(parser, source)?)
+}
+
+/// Writes the staged files. Every file is first written to a temporary sibling and
+/// only then renamed into place, so a failed write leaves the originals untouched.
+pub fn write_staged(staged: &[StagedFile]) -> Result<()> {
+    let mut temporaries = Vec::new();
+    for file in staged {
+        let temporary = temporary_path(&file.path);
+        let written = fs::write(&temporary, &file.contents)
+            .and_then(|()| fs::metadata(&file.path))
+            .and_then(|metadata| fs::set_permissions(&temporary, metadata.permissions()));
+        if let Err(error) = written {
+            let _ = fs::remove_file(&temporary);
+            for (written, _) in &temporaries {
+                let _ = fs::remove_file(written);
+            }
+            return Err(error).with_context(|| {
+                format!(
+                    "failed to write {}; no files were changed",
+                    file.path.display()
+                )
+            });
+        }
+        temporaries.push((temporary, &file.path));
+    }
+
+    for (temporary, path) in temporaries {
+        fs::rename(&temporary, path)
+            .with_context(|| format!("failed to replace {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.php-checker-fix"))
+}
+
 /// Expands the range defined by `start`/`end` to cover the entire line it sits on.
 pub fn covering_line_range(source: &str, start: usize, end: usize) -> (usize, usize) {
     let start = line_start(source, start);
@@ -69,3 +291,57 @@ fn line_end(source: &str, idx: usize) -> usize {
     }
     pos
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn select_edits_skips_overlaps_and_repeats() {
+        let edits = [
+            TextEdit::new(6, 10, "first"),
+            TextEdit::new(8, 12, "overlapping"),
+            TextEdit::new(6, 10, "first"),
+            TextEdit::new(12, 12, "insert"),
+            TextEdit::new(12, 12, "other insert"),
+            TextEdit::new(10, 12, "adjacent"),
+            TextEdit::new(11, 11, "inside"),
+        ];
+
+        let (selected, skipped) = select_edits(&edits);
+        assert_eq!(
+            selected,
+            [
+                TextEdit::new(6, 10, "first"),
+                TextEdit::new(12, 12, "insert"),
+                TextEdit::new(12, 12, "other insert"),
+                TextEdit::new(10, 12, "adjacent"),
+            ]
+        );
+        assert_eq!(skipped, 2);
+    }
+
+    #[test]
+    fn stage_rejects_fixes_that_break_the_syntax() {
+        let dir = std::env::temp_dir().join(format!("php-checker-stage-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("good.php");
+        let bad = dir.join("bad.php");
+        fs::write(&good, "<?php\n$a = 1;\n").unwrap();
+        fs::write(&bad, "<?php\n$b = 2;\n").unwrap();
+
+        let mut fixes = BTreeMap::new();
+        fixes.insert(good.clone(), vec![TextEdit::new(6, 8, "$c")]);
+        fixes.insert(bad.clone(), vec![TextEdit::new(12, 13, "")]);
+        let error = stage(&fixes).unwrap_err().to_string();
+        assert!(error.contains("bad.php"), "{error}");
+
+        fixes.remove(&bad);
+        let staged = stage(&fixes).unwrap();
+        write_staged(&staged).unwrap();
+        assert_eq!(fs::read_to_string(&good).unwrap(), "<?php\n$c = 1;\n");
+        assert_eq!(fs::read_to_string(&bad).unwrap(), "<?php\n$b = 2;\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
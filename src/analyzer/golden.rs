@@ -0,0 +1,281 @@
+//! Golden-output runner for the fixture corpora under `tests/`.
+//!
+//! Every PHP file in a corpus is analysed together with its siblings by the
+//! full `Analyzer` (using the corpus' `php_checker.yaml`, if any) and compared
+//! with the `.expect` file next to it. A missing `.expect` means the file must
+//! stay clean. Expectation lines look like
+//!
+//! ```text
+//! 4:1: error[strict_typing/missing_argument]: missing required argument 2 for Svc\takesTwo
+//! ```
+//!
+//! Older `severity: message` lines are still accepted and only compare the
+//! severity and message; re-bless a corpus to upgrade them.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::config::AnalyzerConfig;
+use super::{Analyzer, Diagnostic, collect_php_files};
+
+/// Outcome of comparing one or more corpora with their expectations.
+#[derive(Debug, Default)]
+pub struct GoldenReport {
+    pub passed: usize,
+    pub failures: Vec<GoldenFailure>,
+    /// Expectation files written when running with `bless`.
+    pub blessed: Vec<PathBuf>,
+}
+
+/// A fixture whose diagnostics differ from its `.expect` file.
+#[derive(Debug)]
+pub struct GoldenFailure {
+    pub file: PathBuf,
+    /// Expected lines with no matching diagnostic.
+    pub missing: Vec<String>,
+    /// Diagnostics with no matching expected line.
+    pub unexpected: Vec<String>,
+}
+
+impl GoldenReport {
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Human-readable summary with a diff per failing fixture.
+    pub fn format(&self) -> String {
+        let rule = "━".repeat(52);
+        let mut output = String::new();
+
+        for failure in &self.failures {
+            let _ = writeln!(
+                output,
+                "\n{rule}\nFAILED: {}\n{rule}",
+                failure.file.display()
+            );
+            if !failure.missing.is_empty() {
+                output.push_str("  Missing (expected but not found):\n");
+                for line in &failure.missing {
+                    let _ = writeln!(output, "    - {line}");
+                }
+            }
+            if !failure.unexpected.is_empty() {
+                output.push_str("  Unexpected (found but not expected):\n");
+                for line in &failure.unexpected {
+                    let _ = writeln!(output, "    + {line}");
+                }
+            }
+        }
+
+        for path in &self.blessed {
+            let _ = writeln!(output, "Updated {}", path.display());
+        }
+
+        let _ = writeln!(
+            output,
+            "\nGolden summary: {} failed, {} passed",
+            self.failures.len(),
+            self.passed
+        );
+        output
+    }
+}
+
+/// Compares every fixture under `roots` with its `.expect` file. With `bless`,
+/// mismatching expectation files are rewritten from the current output instead
+/// of being reported.
+pub fn run(roots: &[PathBuf], bless: bool) -> Result<GoldenReport> {
+    let mut report = GoldenReport::default();
+    for root in roots {
+        run_corpus(root, bless, &mut report)?;
+    }
+    Ok(report)
+}
+
+fn run_corpus(root: &Path, bless: bool, report: &mut GoldenReport) -> Result<()> {
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("failed to resolve corpus {}", root.display()))?;
+    let config = AnalyzerConfig::find_config(None, &root)
+        .map(AnalyzerConfig::load)
+        .transpose()?;
+
+    let mut analyzer = Analyzer::new(config)?;
+    let mut actual: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+    for diagnostic in analyzer.analyse_root(&root)? {
+        actual
+            .entry(diagnostic.file.clone())
+            .or_default()
+            .push(golden_line(&diagnostic));
+    }
+
+    let mut php_files = collect_php_files(&root)?;
+    php_files.sort();
+
+    for php_file in php_files {
+        let mut lines = actual.remove(&php_file).unwrap_or_default();
+        lines.sort_by_cached_key(|line| sort_key(line));
+
+        let expect_path = php_file.with_extension("expect");
+        let expected = read_expectations(&expect_path)?;
+        if bless {
+            // Blessing also upgrades legacy lines that only match loosely.
+            if expected != lines {
+                bless_expectations(&expect_path, &lines)?;
+                report.blessed.push(expect_path);
+            }
+            report.passed += 1;
+            continue;
+        }
+
+        let (missing, unexpected) = compare(&expected, &lines);
+        if missing.is_empty() && unexpected.is_empty() {
+            report.passed += 1;
+        } else {
+            report.failures.push(GoldenFailure {
+                file: php_file,
+                missing,
+                unexpected,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// `line:column: severity[rule]: message`, with 1-based positions.
+pub fn golden_line(diagnostic: &Diagnostic) -> String {
+    let mut line = String::new();
+    if let Some(span) = &diagnostic.span {
+        let column = diagnostic
+            .columns
+            .map_or(span.start.column, |columns| columns.start_char);
+        let _ = write!(line, "{}:{}: ", span.start.row + 1, column + 1);
+    }
+    line.push_str(&diagnostic.severity.to_string());
+    if let Some(rule) = &diagnostic.rule_name {
+        let _ = write!(line, "[{rule}]");
+    }
+    let _ = write!(line, ": {}", diagnostic.message);
+    line
+}
+
+fn read_expectations(path: &Path) -> Result<Vec<String>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("failed to read expectation file {}", path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+fn bless_expectations(path: &Path, lines: &[String]) -> Result<()> {
+    if lines.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)
+                .with_context(|| format!("failed to remove {}", path.display()))?;
+        }
+        return Ok(());
+    }
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    fs::write(path, content).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Matches expected lines against actual ones, ignoring order. Returns the
+/// unmatched expected lines and the unmatched actual lines.
+fn compare(expected: &[String], actual: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut unmatched: Vec<&String> = actual.iter().collect();
+    let mut missing = Vec::new();
+
+    for line in expected {
+        match unmatched
+            .iter()
+            .position(|candidate| lines_match(line, candidate))
+        {
+            Some(idx) => {
+                unmatched.remove(idx);
+            }
+            None => missing.push(line.clone()),
+        }
+    }
+
+    (missing, unmatched.into_iter().cloned().collect())
+}
+
+fn lines_match(expected: &str, actual: &str) -> bool {
+    if expected == actual {
+        return true;
+    }
+
+    // Legacy `severity: message` expectations ignore position and rule.
+    match expected.split_once(": ") {
+        Some((severity, message)) if is_severity(severity) => {
+            let actual = strip_position(actual);
+            actual
+                .split_once(": ")
+                .is_some_and(|(header, actual_message)| {
+                    header.split('[').next() == Some(severity) && actual_message == message
+                })
+        }
+        _ => false,
+    }
+}
+
+fn is_severity(text: &str) -> bool {
+    matches!(text, "error" | "warning" | "info")
+}
+
+fn strip_position(line: &str) -> &str {
+    match line.split_once(": ") {
+        Some((position, rest))
+            if position
+                .split(':')
+                .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit())) =>
+        {
+            rest
+        }
+        _ => line,
+    }
+}
+
+/// Orders lines by position so blessed files read top to bottom.
+fn sort_key(line: &str) -> (usize, usize, String) {
+    let position = line.split_once(": ").map_or("", |(position, _)| position);
+    let mut parts = position.split(':').map(|part| part.parse().unwrap_or(0));
+    let row = parts.next().unwrap_or(0);
+    let column = parts.next().unwrap_or(0);
+    (row, column, line.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_accepts_legacy_lines_and_reports_differences() {
+        let actual = vec![
+            "3:5: warning[cleanup/unused_variable]: variable $a is never used".to_string(),
+            "7:1: error[strict_typing/missing_argument]: missing required argument 1".to_string(),
+        ];
+        let expected = vec![
+            "warning: variable $a is never used".to_string(),
+            "8:1: error[strict_typing/missing_argument]: missing required argument 1".to_string(),
+        ];
+
+        let (missing, unexpected) = compare(&expected, &actual);
+        assert_eq!(missing, vec![expected[1].clone()]);
+        assert_eq!(unexpected, vec![actual[1].clone()]);
+    }
+}
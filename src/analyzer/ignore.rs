@@ -1,13 +1,88 @@
 //! Utilities to honor in-source ignore directives for diagnostics.
 
+use super::aliases::{self, RuleAlias};
+
 const DIRECTIVE: &str = "php-checker-ignore";
 const FILE_DIRECTIVE: &str = "php-checker-ignore-file";
+const DISABLE_DIRECTIVE: &str = "php-checker-disable";
+const ENABLE_DIRECTIVE: &str = "php-checker-enable";
 
 /// Tracks the ignore directives declared in a file.
 #[derive(Clone, Debug, Default)]
 pub struct IgnoreState {
     ignore_all: bool,
     patterns: Vec<String>,
+    regions: Vec<DisabledRegion>,
+    deprecated: Vec<DeprecatedName>,
+    directives: Vec<Directive>,
+}
+
+/// A suppression directive where it is written, for reporting.
+#[derive(Clone, Debug)]
+pub struct Directive {
+    pub kind: DirectiveKind,
+    /// Rules and groups it names; empty means every rule.
+    pub rules: Vec<String>,
+    /// Zero-based row and byte columns of the directive and its arguments.
+    pub row: usize,
+    pub start_column: usize,
+    pub end_column: usize,
+    /// For `disable`, the row of the `php-checker-enable` that closes it, or
+    /// `None` when it runs to the end of the file.
+    pub end_row: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DirectiveKind {
+    /// `php-checker-ignore`, for the named rules in the whole file.
+    Ignore,
+    /// `php-checker-ignore-file`, for every rule in the file.
+    IgnoreFile,
+    /// `php-checker-disable`, until the matching `php-checker-enable`.
+    Disable,
+}
+
+impl DirectiveKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DirectiveKind::Ignore => DIRECTIVE,
+            DirectiveKind::IgnoreFile => FILE_DIRECTIVE,
+            DirectiveKind::Disable => DISABLE_DIRECTIVE,
+        }
+    }
+}
+
+/// A retired rule name used in a directive, still honoured under its new name.
+#[derive(Clone, Debug)]
+pub struct DeprecatedName {
+    pub alias: RuleAlias,
+    /// Zero-based row and byte column of the name.
+    pub row: usize,
+    pub column: usize,
+}
+
+/// Rows between a `php-checker-disable` comment and the `php-checker-enable`
+/// that closes it, or the end of the file when nothing does.
+#[derive(Clone, Debug)]
+struct DisabledRegion {
+    /// Rules and groups switched off; empty switches off every rule.
+    patterns: Vec<String>,
+    start: usize,
+    end: Option<usize>,
+    /// Index of the `disable` directive that opened it.
+    directive: usize,
+}
+
+impl DisabledRegion {
+    fn covers(&self, rule_lower: &str, row: usize) -> bool {
+        row >= self.start
+            && self.end.is_none_or(|end| row <= end)
+            && (self.patterns.is_empty()
+                || self
+                    .patterns
+                    .iter()
+                    .any(|pattern| matches_pattern(rule_lower, pattern)))
+    }
 }
 
 impl IgnoreState {
@@ -15,65 +90,152 @@ impl IgnoreState {
     pub fn from_source(source: &str) -> Self {
         let mut state = Self::default();
 
-        for line in source.lines() {
+        for (row, line) in source.lines().enumerate() {
             if state.ignore_all {
                 break;
             }
 
-            state.collect_from_line(line);
+            state.collect_from_line(row, line);
+            state.collect_region(row, line);
+            state.collect_deprecated(row, line);
+        }
+
+        for (idx, directive) in state.directives.iter_mut().enumerate() {
+            let mut regions = state
+                .regions
+                .iter()
+                .filter(|region| region.directive == idx);
+            if directive.kind == DirectiveKind::Disable {
+                directive.end_row =
+                    regions.try_fold(0, |last, region| region.end.map(|end| last.max(end)));
+            }
         }
 
         state
     }
 
-    fn collect_from_line(&mut self, line: &str) {
+    fn collect_from_line(&mut self, row: usize, line: &str) {
         if let Some(idx) = line.find(FILE_DIRECTIVE) {
+            self.push_directive(DirectiveKind::IgnoreFile, row, idx, line);
             self.ignore_all = true;
             self.apply_args(&line[idx + FILE_DIRECTIVE.len()..]);
             return;
         }
 
         if let Some(idx) = line.find(DIRECTIVE) {
+            self.push_directive(DirectiveKind::Ignore, row, idx, line);
             self.apply_args(&line[idx + DIRECTIVE.len()..]);
         }
     }
 
-    fn apply_args(&mut self, tail: &str) {
-        if self.ignore_all {
+    /// Records the directive of `kind` starting at byte `column` of `line`.
+    fn push_directive(&mut self, kind: DirectiveKind, row: usize, column: usize, line: &str) {
+        let name_end = column + kind.as_str().len();
+        let tail = trim_comment_tail(&line[name_end..]);
+        self.directives.push(Directive {
+            kind,
+            rules: rule_patterns(tail).unwrap_or_default(),
+            row,
+            start_column: column,
+            end_column: name_end + tail.len(),
+            end_row: None,
+        });
+    }
+
+    /// Opens a region at `php-checker-disable` and closes regions at `php-checker-enable`.
+    ///
+    /// A bare `enable` closes every open region. Naming rules only re-enables those;
+    /// other rules listed by the same `disable` stay off, and a bare `disable` can
+    /// only be closed by a bare `enable`.
+    fn collect_region(&mut self, row: usize, line: &str) {
+        if let Some(idx) = line.find(DISABLE_DIRECTIVE) {
+            let patterns =
+                rule_patterns(&line[idx + DISABLE_DIRECTIVE.len()..]).unwrap_or_default();
+            self.push_directive(DirectiveKind::Disable, row, idx, line);
+            self.regions.push(DisabledRegion {
+                patterns,
+                start: row,
+                end: None,
+                directive: self.directives.len() - 1,
+            });
             return;
         }
 
-        let mut args = trim_comment_tail(tail).trim_start();
-        if args.is_empty() {
-            self.ignore_all = true;
+        let Some(idx) = line.find(ENABLE_DIRECTIVE) else {
             return;
-        }
+        };
+        let enabled = rule_patterns(&line[idx + ENABLE_DIRECTIVE.len()..]);
 
-        if let Some(stripped) = args.strip_prefix(':') {
-            args = stripped.trim_start();
+        let mut reopened = Vec::new();
+        for region in self
+            .regions
+            .iter_mut()
+            .filter(|region| region.end.is_none())
+        {
+            let Some(enabled) = &enabled else {
+                region.end = Some(row);
+                continue;
+            };
+            let named = |pattern: &String| enabled.contains(pattern);
+            if !region.patterns.iter().any(named) {
+                continue;
+            }
+
+            region.end = Some(row);
+            let remaining: Vec<String> = region
+                .patterns
+                .iter()
+                .filter(|pattern| !enabled.contains(pattern))
+                .cloned()
+                .collect();
+            if !remaining.is_empty() {
+                reopened.push(DisabledRegion {
+                    patterns: remaining,
+                    start: row,
+                    end: None,
+                    directive: region.directive,
+                });
+            }
         }
+        self.regions.extend(reopened);
+    }
 
-        if args.is_empty() {
-            self.ignore_all = true;
+    fn collect_deprecated(&mut self, row: usize, line: &str) {
+        let Some(column) = [DIRECTIVE, DISABLE_DIRECTIVE, ENABLE_DIRECTIVE]
+            .iter()
+            .filter_map(|directive| line.find(directive))
+            .min()
+        else {
             return;
+        };
+        let lower = line.to_ascii_lowercase();
+        let (_, replaced) = aliases::rewrite(trim_comment_tail(&lower[column..]), false);
+        for alias in replaced {
+            let column = lower[column..]
+                .find(alias.old)
+                .map_or(column, |offset| column + offset);
+            self.deprecated.push(DeprecatedName { alias, row, column });
         }
+    }
 
-        for token in args.split(|c: char| c == ',' || c.is_whitespace()) {
-            let mut trimmed = token
-                .trim()
-                .trim_matches(|c| c == '"' || c == '\'' || c == '`');
-            trimmed = trimmed.trim_end_matches('/');
-            if trimmed.is_empty() {
-                continue;
-            }
+    /// The `ignore`, `ignore-file` and `disable` directives in source order.
+    pub fn directives(&self) -> &[Directive] {
+        &self.directives
+    }
 
-            let normalized = trimmed.to_ascii_lowercase();
-            if ["*", "all", "file"].contains(&normalized.as_str()) {
-                self.ignore_all = true;
-                break;
-            }
+    /// Retired rule names the directives use.
+    pub fn deprecated_names(&self) -> &[DeprecatedName] {
+        &self.deprecated
+    }
 
-            self.patterns.push(normalized);
+    fn apply_args(&mut self, tail: &str) {
+        if self.ignore_all {
+            return;
+        }
+
+        match rule_patterns(tail) {
+            Some(patterns) => self.patterns.extend(patterns),
+            None => self.ignore_all = true,
         }
     }
 
@@ -84,21 +246,22 @@ impl IgnoreState {
         }
 
         let rule_lower = rule_name.to_ascii_lowercase();
-        let rule_bytes = rule_lower.as_bytes();
-
-        for pattern in &self.patterns {
-            if rule_lower == *pattern {
-                return true;
-            }
+        self.patterns
+            .iter()
+            .any(|pattern| matches_pattern(&rule_lower, pattern))
+    }
 
-            if rule_lower.starts_with(pattern) {
-                if rule_bytes.len() > pattern.len() && rule_bytes[pattern.len()] == b'/' {
-                    return true;
-                }
-            }
+    /// Like [`Self::should_ignore`], but also honours disabled regions covering
+    /// the zero-based `row` the diagnostic starts on.
+    pub fn should_ignore_at(&self, rule_name: &str, row: usize) -> bool {
+        if self.should_ignore(rule_name) {
+            return true;
         }
 
-        false
+        let rule_lower = rule_name.to_ascii_lowercase();
+        self.regions
+            .iter()
+            .any(|region| region.covers(&rule_lower, row))
     }
 
     /// Returns `true` if a file-level ignore directive was encountered.
@@ -107,6 +270,44 @@ impl IgnoreState {
     }
 }
 
+/// Parses the rule and group names after a directive; `None` means every rule
+/// (no names, or `*`, `all` or `file`).
+fn rule_patterns(tail: &str) -> Option<Vec<String>> {
+    let mut args = trim_comment_tail(tail).trim_start();
+    if let Some(stripped) = args.strip_prefix(':') {
+        args = stripped.trim_start();
+    }
+
+    let mut patterns = Vec::new();
+    for token in args.split(|c: char| c == ',' || c.is_whitespace()) {
+        let mut trimmed = token
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'' || c == '`');
+        trimmed = trimmed.trim_end_matches('/');
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let normalized = trimmed.to_ascii_lowercase();
+        if ["*", "all", "file"].contains(&normalized.as_str()) {
+            return None;
+        }
+        let normalized =
+            aliases::find(&normalized).map_or(normalized, |alias| alias.new.to_string());
+
+        patterns.push(normalized);
+    }
+
+    (!patterns.is_empty()).then_some(patterns)
+}
+
+/// A rule matches its own name and any group it is nested in.
+fn matches_pattern(rule_lower: &str, pattern: &str) -> bool {
+    rule_lower
+        .strip_prefix(pattern)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
 fn trim_comment_tail(value: &str) -> &str {
     let mut limit = value.len();
 
@@ -167,4 +368,96 @@ mod tests {
         assert!(state.should_ignore("cleanup/unused_use"));
         assert!(!state.should_ignore("strict_typing/missing_argument"));
     }
+
+    #[test]
+    fn disabled_regions_cover_rows_until_enabled() {
+        let source = "<?php
+// php-checker-disable cleanup, security/weak_hashing
+$generated = 1;
+// php-checker-enable cleanup
+$partly = 2;
+// php-checker-enable
+$checked = 3;
+// php-checker-disable
+$tail = 4;
+";
+
+        let state = IgnoreState::from_source(source);
+        assert!(!state.ignores_everything());
+        assert!(!state.should_ignore_at("security/weak_hashing", 0));
+        assert!(state.should_ignore_at("cleanup/unused_variable", 2));
+        assert!(state.should_ignore_at("security/weak_hashing", 2));
+        assert!(!state.should_ignore_at("sanity/undefined_variable", 2));
+
+        assert!(!state.should_ignore_at("cleanup/unused_variable", 4));
+        assert!(state.should_ignore_at("security/weak_hashing", 4));
+
+        assert!(!state.should_ignore_at("security/weak_hashing", 6));
+        assert!(state.should_ignore_at("sanity/undefined_variable", 8));
+    }
+
+    #[test]
+    fn directives_are_reported_with_their_positions() {
+        let source = "<?php
+$a = 1; // php-checker-ignore: cleanup // legacy
+// php-checker-disable cleanup, security/weak_hashing
+$b = 2;
+// php-checker-enable cleanup
+/* php-checker-disable */
+// php-checker-enable security/weak_hashing
+";
+
+        let state = IgnoreState::from_source(source);
+        let directives: Vec<_> = state
+            .directives()
+            .iter()
+            .map(|directive| {
+                (
+                    directive.kind,
+                    directive.rules.clone(),
+                    directive.row,
+                    directive.start_column,
+                    directive.end_column,
+                    directive.end_row,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            directives,
+            [
+                (
+                    DirectiveKind::Ignore,
+                    vec!["cleanup".to_string()],
+                    1,
+                    11,
+                    38,
+                    None
+                ),
+                (
+                    DirectiveKind::Disable,
+                    vec!["cleanup".to_string(), "security/weak_hashing".to_string()],
+                    2,
+                    3,
+                    53,
+                    Some(6),
+                ),
+                (DirectiveKind::Disable, vec![], 5, 3, 22, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn retired_rule_names_are_honoured_and_reported() {
+        let source = "<?php\n$a = 1; // php-checker-ignore: unused-variable, cleanup/unused_use\n";
+
+        let state = IgnoreState::from_source(source);
+        assert!(state.should_ignore("cleanup/unused_variable"));
+        assert!(state.should_ignore("cleanup/unused_use"));
+
+        let deprecated = state.deprecated_names();
+        assert_eq!(deprecated.len(), 1);
+        assert_eq!(deprecated[0].alias.new, "cleanup/unused_variable");
+        assert_eq!((deprecated[0].row, deprecated[0].column), (1, 31));
+    }
 }
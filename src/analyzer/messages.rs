@@ -0,0 +1,415 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+
+use super::Diagnostic;
+
+/// Catalogs shipped with the analyzer, by locale.
+const BUILTIN_CATALOGS: &[(&str, &str)] = &[("de", include_str!("messages/de.yaml"))];
+
+/// Catalog section translating confidence labels rather than a rule's messages.
+const CONFIDENCE_SECTION: &str = "confidence";
+
+/// Translations of diagnostic messages, keyed by rule name. Each entry maps an
+/// English message template to its translation, with `{placeholder}`s standing
+/// for the parts a rule fills in:
+///
+/// ```yaml
+/// cleanup/unused_variable:
+///   "unused variable ${name}": "ungenutzte Variable ${name}"
+/// ```
+///
+/// Messages without a matching template stay in English. The reserved
+/// `confidence` section words the confidence appended to heuristic diagnostics:
+///
+/// ```yaml
+/// confidence:
+///   "high confidence": "hohe Konfidenz"
+/// ```
+#[derive(Debug, Default)]
+pub struct MessageCatalog {
+    rules: HashMap<String, Vec<Translation>>,
+}
+
+#[derive(Debug)]
+struct Translation {
+    source: Vec<Segment>,
+    target: Vec<Segment>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Text(String),
+    Placeholder(String),
+}
+
+impl MessageCatalog {
+    /// The catalog for `locale` (none means English), extended by the entries
+    /// of the `overrides` file, which take precedence over built-in ones.
+    pub fn load(locale: Option<&str>, overrides: Option<&Path>) -> Result<Self> {
+        let mut catalog = Self::default();
+
+        if let Some(locale) = locale {
+            match builtin_catalog(locale) {
+                Some(source) => catalog
+                    .extend(source)
+                    .with_context(|| format!("invalid built-in catalog for locale `{locale}`"))?,
+                None if overrides.is_none() && !is_english(locale) => {
+                    let known: Vec<&str> = BUILTIN_CATALOGS.iter().map(|(name, _)| *name).collect();
+                    bail!(
+                        "unknown locale `{locale}`; built-in locales are en, {} (set message_catalog to provide your own)",
+                        known.join(", ")
+                    );
+                }
+                None => {}
+            }
+        }
+
+        if let Some(path) = overrides {
+            let source = fs::read_to_string(path)
+                .with_context(|| format!("failed to read message catalog {}", path.display()))?;
+            catalog
+                .extend(&source)
+                .with_context(|| format!("failed to parse message catalog {}", path.display()))?;
+        }
+
+        Ok(catalog)
+    }
+
+    /// Adds the entries of a YAML catalog, ahead of those already loaded.
+    fn extend(&mut self, source: &str) -> Result<()> {
+        let raw: HashMap<String, HashMap<String, String>> = serde_yaml::from_str(source)?;
+        for (rule, entries) in raw {
+            let mut translations = Vec::with_capacity(entries.len());
+            for (source, target) in entries {
+                let source = parse_template(&source);
+                let target = parse_template(&target);
+                for segment in &target {
+                    if let Segment::Placeholder(name) = segment
+                        && !source.contains(segment)
+                    {
+                        bail!(
+                            "{rule}: translation uses {{{name}}}, which the English message lacks"
+                        );
+                    }
+                }
+                translations.push(Translation { source, target });
+            }
+            // Longer templates are more specific; try them first.
+            translations
+                .sort_by_key(|translation| std::cmp::Reverse(text_len(&translation.source)));
+            let existing = self.rules.entry(rule).or_default();
+            translations.append(existing);
+            *existing = translations;
+        }
+        Ok(())
+    }
+
+    /// Replaces the message of a diagnostic with its translation, if the catalog
+    /// has one for the diagnostic's rule.
+    pub fn translate(&self, diagnostic: &mut Diagnostic) {
+        let Some(rule) = diagnostic.rule_name.as_deref() else {
+            return;
+        };
+        if let Some(message) = self.lookup(rule, &diagnostic.message) {
+            diagnostic.message = message;
+        }
+        if let Some(confidence) = diagnostic.confidence {
+            diagnostic.confidence_label =
+                self.lookup(CONFIDENCE_SECTION, &format!("{confidence} confidence"));
+        }
+    }
+
+    fn lookup(&self, rule: &str, message: &str) -> Option<String> {
+        self.rules.get(rule)?.iter().find_map(|translation| {
+            let mut values = HashMap::new();
+            match_segments(&translation.source, message, &mut values).then(|| {
+                translation
+                    .target
+                    .iter()
+                    .map(|segment| match segment {
+                        Segment::Text(text) => text.as_str(),
+                        Segment::Placeholder(name) => values[name.as_str()],
+                    })
+                    .collect()
+            })
+        })
+    }
+}
+
+fn builtin_catalog(locale: &str) -> Option<&'static str> {
+    let locale = locale.to_ascii_lowercase();
+    // `de_DE` and `de-AT` fall back to `de`.
+    let language = locale.split(['_', '-']).next().unwrap_or_default();
+    [locale.as_str(), language].into_iter().find_map(|name| {
+        BUILTIN_CATALOGS
+            .iter()
+            .find(|(builtin, _)| *builtin == name)
+            .map(|(_, source)| *source)
+    })
+}
+
+fn is_english(locale: &str) -> bool {
+    locale
+        .split(['_', '-'])
+        .next()
+        .is_some_and(|language| language.eq_ignore_ascii_case("en"))
+}
+
+/// Splits `"unused variable ${name}"` into text and `{name}` placeholder segments.
+fn parse_template(template: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let placeholder = rest[start + 1..].find('}').and_then(|end| {
+            let name = &rest[start + 1..start + 1 + end];
+            let valid =
+                !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            valid.then_some(name)
+        });
+        match placeholder {
+            Some(name) => {
+                text.push_str(&rest[..start]);
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+                segments.push(Segment::Placeholder(name.to_string()));
+                rest = &rest[start + name.len() + 2..];
+            }
+            None => {
+                text.push_str(&rest[..=start]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+
+    segments
+}
+
+fn text_len(segments: &[Segment]) -> usize {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => text.len(),
+            Segment::Placeholder(_) => 0,
+        })
+        .sum()
+}
+
+/// Matches `message` against a template, capturing each placeholder's (non-empty)
+/// value. Placeholders are tried shortest first, backtracking on a mismatch.
+fn match_segments<'t, 'm>(
+    segments: &'t [Segment],
+    message: &'m str,
+    values: &mut HashMap<&'t str, &'m str>,
+) -> bool {
+    let Some((first, rest)) = segments.split_first() else {
+        return message.is_empty();
+    };
+
+    match first {
+        Segment::Text(text) => message
+            .strip_prefix(text.as_str())
+            .is_some_and(|remaining| match_segments(rest, remaining, values)),
+        Segment::Placeholder(name) => {
+            let ends = message
+                .char_indices()
+                .map(|(idx, _)| idx)
+                .skip(1)
+                .chain([message.len()]);
+            for end in ends {
+                if match_segments(rest, &message[end..], values) {
+                    values.insert(name.as_str(), &message[..end]);
+                    return true;
+                }
+            }
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::config::AnalyzerConfig;
+    use crate::analyzer::{Analyzer, Confidence, Severity};
+    use std::path::PathBuf;
+
+    fn translated(catalog: &MessageCatalog, rule: &str, message: &str) -> String {
+        let mut diagnostic = Diagnostic::new("a.php".into(), Severity::Error, message);
+        diagnostic.rule_name = Some(rule.to_string());
+        catalog.translate(&mut diagnostic);
+        diagnostic.message
+    }
+
+    #[test]
+    fn builtin_catalog_translates_with_placeholders() {
+        let catalog = MessageCatalog::load(Some("de_DE"), None).unwrap();
+
+        assert_eq!(
+            translated(
+                &catalog,
+                "sanity/undefined_variable",
+                "undefined variable $total at 4:10"
+            ),
+            "undefinierte Variable $total bei 4:10"
+        );
+        assert_eq!(
+            translated(
+                &catalog,
+                "api/deprecated_api",
+                "each is deprecated; use foreach instead"
+            ),
+            "each ist veraltet; stattdessen foreach verwenden"
+        );
+        // Unknown messages and rules stay in English.
+        assert_eq!(
+            translated(&catalog, "sanity/undefined_variable", "something else"),
+            "something else"
+        );
+        assert_eq!(
+            translated(&catalog, "custom/rule", "unused variable $x"),
+            "unused variable $x"
+        );
+    }
+
+    #[test]
+    fn builtin_catalog_translates_rule_specific_fragments() {
+        let catalog = MessageCatalog::load(Some("de"), None).unwrap();
+
+        assert_eq!(
+            translated(
+                &catalog,
+                "phpdoc/malformed_tag",
+                "malformed @param tag: is missing a `$name`"
+            ),
+            "fehlerhaftes @param-Tag: `$name` fehlt"
+        );
+        assert_eq!(
+            translated(
+                &catalog,
+                "sanity/modify_during_iteration",
+                "$items is appended to inside a foreach over it; the loop keeps iterating the original array"
+            ),
+            "an $items wird in einem foreach darüber angehängt; die Schleife durchläuft weiterhin das ursprüngliche Array"
+        );
+
+        let mut diagnostic = Diagnostic::new(
+            "a.php".into(),
+            Severity::Error,
+            "hard-coded credential or token detected",
+        );
+        diagnostic.rule_name = Some("security/hard_coded_credentials".to_string());
+        diagnostic.confidence = Some(Confidence::High);
+        catalog.translate(&mut diagnostic);
+        assert_eq!(
+            diagnostic.labelled_message(),
+            "fest kodierte Zugangsdaten oder Token gefunden (hohe Konfidenz)"
+        );
+    }
+
+    /// Copies the projects under `tests/messages` out of the `tests` directory,
+    /// so that rules skipping test files report on them too.
+    fn message_projects() -> Vec<PathBuf> {
+        fn copy_dir(from: &Path, to: &Path) {
+            fs::create_dir_all(to).unwrap();
+            for entry in fs::read_dir(from).unwrap() {
+                let path = entry.unwrap().path();
+                let target = to.join(path.file_name().unwrap());
+                if path.is_dir() {
+                    copy_dir(&path, &target);
+                } else {
+                    fs::copy(&path, &target).unwrap();
+                }
+            }
+        }
+
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/messages");
+        let root =
+            std::env::temp_dir().join(format!("php-checker-messages-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        copy_dir(&corpus, &root);
+
+        let mut projects: Vec<PathBuf> = fs::read_dir(root.canonicalize().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        projects.sort();
+        projects
+    }
+
+    #[test]
+    fn builtin_catalogs_match_real_rule_output() {
+        let mut reported: HashMap<String, Vec<String>> = HashMap::new();
+        for project in message_projects() {
+            let config = AnalyzerConfig::find_config(None, &project)
+                .map(AnalyzerConfig::load)
+                .transpose()
+                .unwrap();
+            let mut analyzer = Analyzer::new(config).unwrap();
+            for diagnostic in analyzer.analyse_root(&project).unwrap() {
+                if let Some(confidence) = diagnostic.confidence {
+                    reported
+                        .entry(CONFIDENCE_SECTION.to_string())
+                        .or_default()
+                        .push(format!("{confidence} confidence"));
+                }
+                let rule = diagnostic.rule_name.unwrap_or_default();
+                reported.entry(rule).or_default().push(diagnostic.message);
+            }
+        }
+
+        let mut unmatched = Vec::new();
+        for (locale, source) in BUILTIN_CATALOGS {
+            let mut catalog = MessageCatalog::default();
+            catalog.extend(source).unwrap();
+            for (rule, translations) in &catalog.rules {
+                let messages = reported.get(rule).map(Vec::as_slice).unwrap_or_default();
+                for translation in translations {
+                    let matches = messages.iter().any(|message| {
+                        match_segments(&translation.source, message, &mut HashMap::new())
+                    });
+                    if !matches {
+                        let template: String = translation
+                            .source
+                            .iter()
+                            .map(|segment| match segment {
+                                Segment::Text(text) => text.clone(),
+                                Segment::Placeholder(name) => format!("{{{name}}}"),
+                            })
+                            .collect();
+                        unmatched.push(format!("{locale}: {rule}: {template}"));
+                    }
+                }
+            }
+        }
+        unmatched.sort();
+        assert!(
+            unmatched.is_empty(),
+            "catalog entries no diagnostic in tests/messages reads like:\n{}",
+            unmatched.join("\n")
+        );
+    }
+
+    #[test]
+    fn overrides_take_precedence_and_unknown_locales_fail() {
+        let mut catalog = MessageCatalog::load(Some("de"), None).unwrap();
+        catalog
+            .extend("cleanup/unused_variable:\n  \"unused variable ${name}\": \"Variable ${name} wird nie gelesen\"\n")
+            .unwrap();
+        assert_eq!(
+            translated(&catalog, "cleanup/unused_variable", "unused variable $x"),
+            "Variable $x wird nie gelesen"
+        );
+
+        assert!(catalog.extend("a/b:\n  \"text\": \"{missing}\"\n").is_err());
+        assert!(MessageCatalog::load(Some("fr"), None).is_err());
+        assert!(MessageCatalog::load(Some("en"), None).is_ok());
+    }
+}
@@ -7,12 +7,27 @@ use std::{
 use anyhow::{Context, Result};
 use tree_sitter::Parser;
 
+use super::artifacts::FileArtifacts;
+
 /// Parsed contents of a source file.
 #[allow(dead_code)]
 pub struct ParsedSource {
     pub path: PathBuf,
     pub source: Arc<String>,
     pub tree: tree_sitter::Tree,
+    /// Shared per-file data, built on demand; see [`super::artifacts::Artifact`].
+    pub(crate) artifacts: FileArtifacts,
+}
+
+impl ParsedSource {
+    pub fn new(path: impl Into<PathBuf>, source: String, tree: tree_sitter::Tree) -> Self {
+        Self {
+            path: path.into(),
+            source: Arc::new(source),
+            tree,
+            artifacts: FileArtifacts::default(),
+        }
+    }
 }
 
 /// Trait that abstracts PHP parsing implementations.
@@ -35,23 +50,22 @@ impl TreeSitterPhpParser {
 
         Ok(Self { parser })
     }
-}
-
-impl PhpParser for TreeSitterPhpParser {
-    fn parse_file(&mut self, path: &Path) -> Result<ParsedSource> {
-        let source = fs::read_to_string(path)
-            .with_context(|| format!("failed to read {}", path.display()))?;
-        let source = Arc::new(source);
 
+    /// Parses source text that has not been written to `path` (yet).
+    pub fn parse_source(&mut self, path: &Path, source: String) -> Result<ParsedSource> {
         let tree = self
             .parser
             .parse(source.as_str(), None)
             .context("tree-sitter failed to parse PHP source")?;
 
-        Ok(ParsedSource {
-            path: path.to_path_buf(),
-            source,
-            tree,
-        })
+        Ok(ParsedSource::new(path, source, tree))
+    }
+}
+
+impl PhpParser for TreeSitterPhpParser {
+    fn parse_file(&mut self, path: &Path) -> Result<ParsedSource> {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        self.parse_source(path, source)
     }
 }
@@ -1,8 +1,18 @@
 use super::parser::{PhpDocComment, PhpDocParser};
+use super::types::TypeExpression;
 use crate::analyzer::parser::ParsedSource;
 use tree_sitter::Node;
 
-/// Extract PHPDoc comment that precedes a node
+/// A `@var` annotation tied to a variable name
+#[derive(Debug, Clone)]
+pub struct VarAnnotation {
+    pub name: String,
+    pub type_expr: TypeExpression,
+    /// Start of the docblock; the annotation applies to uses after this point
+    pub offset: usize,
+}
+
+/// Extract PHPDoc comment that precedes a node, with tag ranges pointing into the file
 pub fn extract_phpdoc_for_node<'a>(
     node: Node<'a>,
     parsed: &'a ParsedSource,
@@ -17,7 +27,7 @@ pub fn extract_phpdoc_for_node<'a>(
         if let Some(prev) = parent.named_child(node_index - 1) {
             if prev.kind() == "comment" {
                 let comment_text = prev.utf8_text(parsed.source.as_bytes()).ok()?;
-                return PhpDocParser::parse(comment_text);
+                return PhpDocParser::parse_at(comment_text, prev.start_byte());
             }
         }
     }
@@ -32,7 +42,7 @@ pub fn extract_phpdoc_for_node<'a>(
             // Check if this is a comment
             if child.kind() == "comment" {
                 let comment_text = child.utf8_text(parsed.source.as_bytes()).ok()?;
-                if let Some(parsed_doc) = PhpDocParser::parse(comment_text) {
+                if let Some(parsed_doc) = PhpDocParser::parse_at(comment_text, child.start_byte()) {
                     // Return the last comment found before our node
                     return Some(parsed_doc);
                 }
@@ -43,6 +53,83 @@ pub fn extract_phpdoc_for_node<'a>(
     None
 }
 
+/// Collect every `@var` annotation in a file, wherever the docblock stands.
+///
+/// Named tags (`/** @var Foo $x */`) apply on their own, even when nothing follows
+/// them; unnamed ones take the variable declared by the next statement: an
+/// assignment, `static $x` or `global $x`.
+pub fn collect_var_annotations(parsed: &ParsedSource) -> Vec<VarAnnotation> {
+    let source = parsed.source.as_bytes();
+    let mut annotations = Vec::new();
+    let mut stack = vec![parsed.tree.root_node()];
+
+    while let Some(node) = stack.pop() {
+        if node.kind() == "comment" {
+            let Some(doc) = node
+                .utf8_text(source)
+                .ok()
+                .and_then(|text| PhpDocParser::parse_at(text, node.start_byte()))
+            else {
+                continue;
+            };
+
+            for var_tag in doc.var_tags {
+                let name = var_tag
+                    .name
+                    .or_else(|| declared_variable(node.next_named_sibling()?, source));
+                if let Some(name) = name {
+                    annotations.push(VarAnnotation {
+                        name,
+                        type_expr: var_tag.type_expr,
+                        offset: node.start_byte(),
+                    });
+                }
+            }
+            continue;
+        }
+
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+
+    annotations.sort_by_key(|annotation| annotation.offset);
+    annotations
+}
+
+/// The single variable a statement declares or assigns, without the `$`
+fn declared_variable(statement: Node, source: &[u8]) -> Option<String> {
+    let target = match statement.kind() {
+        "expression_statement" => statement
+            .named_child(0)
+            .filter(|expr| expr.kind() == "assignment_expression")?
+            .child_by_field_name("left")?,
+        "function_static_declaration" => {
+            let mut cursor = statement.walk();
+            let mut declarations = statement
+                .named_children(&mut cursor)
+                .filter(|child| child.kind() == "static_variable_declaration");
+            let declaration = declarations
+                .next()
+                .filter(|_| declarations.next().is_none())?;
+            declaration.named_child(0)?
+        }
+        "global_declaration" => {
+            let mut cursor = statement.walk();
+            let mut variables = statement
+                .named_children(&mut cursor)
+                .filter(|child| child.kind() == "variable_name");
+            variables.next().filter(|_| variables.next().is_none())?
+        }
+        _ => return None,
+    };
+
+    if target.kind() != "variable_name" {
+        return None;
+    }
+    let text = target.utf8_text(source).ok()?;
+    Some(text.trim_start_matches('$').to_string())
+}
+
 /// Find the comment node immediately preceding a given node
 pub fn find_preceding_comment<'a>(node: Node<'a>) -> Option<Node<'a>> {
     // Check parent's children for a comment before this node
@@ -69,7 +156,6 @@ mod tests {
     use super::*;
     use crate::analyzer::parser::PhpParser;
     use crate::analyzer::parser::TreeSitterPhpParser;
-    use std::sync::Arc;
 
     #[test]
     fn test_extract_phpdoc_from_function() {
@@ -87,11 +173,8 @@ function test($value) {
         ts_parser.set_language(tree_sitter_php::language()).unwrap();
         let tree = ts_parser.parse(php_code, None).unwrap();
 
-        let parsed = crate::analyzer::parser::ParsedSource {
-            path: std::path::PathBuf::from("test.php"),
-            source: Arc::new(php_code.to_string()),
-            tree,
-        };
+        let parsed =
+            crate::analyzer::parser::ParsedSource::new("test.php", php_code.to_string(), tree);
 
         // Find the function_definition node
         let root = parsed.tree.root_node();
@@ -114,5 +197,64 @@ function test($value) {
         let doc = phpdoc.unwrap();
         assert_eq!(doc.params.len(), 1);
         assert!(doc.return_tag.is_some());
+
+        // Tag ranges point into the file, not the comment
+        assert_eq!(&php_code[doc.params[0].range.clone()], "@param int $value");
+        let return_range = doc.return_tag.unwrap().range;
+        assert_eq!(&php_code[return_range], "@return string");
+    }
+
+    #[test]
+    fn test_collect_var_annotations() {
+        let php_code = r#"<?php
+/** @var Config $config */
+
+function run() {
+    /** @var int */
+    static $count = 0;
+    /** @var Logger */
+    global $logger;
+    /**
+     * @var int $a
+     * @var string $b
+     */
+    [$a, $b] = pair();
+    /** @var User */
+    $user = find();
+    /** @var int */
+    static $x, $y;
+}
+"#;
+
+        let mut ts_parser = tree_sitter::Parser::new();
+        ts_parser.set_language(tree_sitter_php::language()).unwrap();
+        let parsed = crate::analyzer::parser::ParsedSource::new(
+            "test.php",
+            php_code.to_string(),
+            ts_parser.parse(php_code, None).unwrap(),
+        );
+
+        let annotations: Vec<_> = collect_var_annotations(&parsed)
+            .into_iter()
+            .map(|annotation| {
+                let TypeExpression::Simple(type_name) = annotation.type_expr else {
+                    panic!("expected a simple type for ${}", annotation.name);
+                };
+                (annotation.name, type_name)
+            })
+            .collect();
+
+        let expected = [
+            ("config", "Config"),
+            ("count", "int"),
+            ("logger", "Logger"),
+            ("a", "int"),
+            ("b", "string"),
+            ("user", "User"),
+        ];
+        assert_eq!(
+            annotations,
+            expected.map(|(name, type_name)| (name.to_string(), type_name.to_string()))
+        );
     }
 }
@@ -2,6 +2,10 @@ pub mod extractor;
 pub mod parser;
 pub mod types;
 
-pub use extractor::{extract_phpdoc_for_node, find_preceding_comment};
+pub use extractor::{
+    VarAnnotation, collect_var_annotations, extract_phpdoc_for_node, find_preceding_comment,
+};
 pub use parser::{PhpDocComment, PhpDocParser};
-pub use types::{ParamTag, ReturnTag, ThrowsTag, TypeExpression, VarTag};
+pub use types::{
+    AssertKind, AssertTag, MalformedTag, ParamTag, ReturnTag, ThrowsTag, TypeExpression, VarTag,
+};
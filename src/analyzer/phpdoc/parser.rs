@@ -1,21 +1,40 @@
 use super::types::*;
+use std::ops::Range;
 
 /// Represents a parsed PHPDoc comment
 #[derive(Debug, Clone, Default)]
 pub struct PhpDocComment {
     pub params: Vec<ParamTag>,
     pub return_tag: Option<ReturnTag>,
-    pub var_tag: Option<VarTag>,
+    /// Every @var tag, in order; a docblock may annotate several variables
+    pub var_tags: Vec<VarTag>,
     pub throws: Vec<ThrowsTag>,
     pub properties: Vec<PropertyTag>,
     pub methods: Vec<MethodTag>,
+    /// @phpstan-assert tags, including the -if-true/-if-false variants
+    pub asserts: Vec<AssertTag>,
+    /// Tags that were recognised but could not be parsed; they are left out above
+    pub malformed: Vec<MalformedTag>,
+}
+
+impl PhpDocComment {
+    /// The first @var tag, which is the one that applies to a property or constant
+    pub fn var_tag(&self) -> Option<&VarTag> {
+        self.var_tags.first()
+    }
 }
 
 pub struct PhpDocParser;
 
 impl PhpDocParser {
-    /// Parse a PHPDoc comment string
+    /// Parse a PHPDoc comment string; tag ranges are relative to the comment
     pub fn parse(comment: &str) -> Option<PhpDocComment> {
+        Self::parse_at(comment, 0)
+    }
+
+    /// Parse a PHPDoc comment that starts at byte `offset` of the file, so tag
+    /// ranges point into the file's source
+    pub fn parse_at(comment: &str, offset: usize) -> Option<PhpDocComment> {
         // Check if this is a valid PHPDoc comment
         if !comment.trim_start().starts_with("/**") {
             return None;
@@ -23,44 +42,35 @@ impl PhpDocParser {
 
         let mut doc = PhpDocComment::default();
 
-        // Extract lines from the comment
-        let lines = Self::extract_lines(comment);
+        let mut offset = offset;
+        for raw_line in comment.split_inclusive('\n') {
+            let line_start = offset;
+            offset += raw_line.len();
 
-        for line in lines {
-            let line = line.trim();
-
-            // Skip empty lines
-            if line.is_empty() {
+            let line = Self::clean_line(raw_line);
+            let Some(tag_content) = line.strip_prefix('@') else {
                 continue;
-            }
+            };
 
-            // Parse tags
-            if let Some(tag_content) = line.strip_prefix('@') {
-                Self::parse_tag(tag_content, &mut doc);
-            }
+            // `line` is a subslice of `raw_line`, so its offset locates the tag
+            let start = line_start + (line.as_ptr() as usize - raw_line.as_ptr() as usize);
+            Self::parse_tag(tag_content, start..start + line.len(), &mut doc);
         }
 
         Some(doc)
     }
 
-    /// Extract clean lines from PHPDoc comment
-    fn extract_lines(comment: &str) -> Vec<String> {
-        comment
-            .lines()
-            .map(|line| {
-                line.trim()
-                    .trim_start_matches("/**")
-                    .trim_start_matches('*')
-                    .trim_end_matches("*/")
-                    .trim()
-                    .to_string()
-            })
-            .filter(|line| !line.is_empty())
-            .collect()
-    }
-
-    /// Parse a single tag line
-    fn parse_tag(tag_content: &str, doc: &mut PhpDocComment) {
+    /// Strip the comment delimiters and leading `*` from a single line
+    fn clean_line(line: &str) -> &str {
+        line.trim()
+            .trim_start_matches("/**")
+            .trim_start_matches('*')
+            .trim_end_matches("*/")
+            .trim()
+    }
+
+    /// Parse a single tag line, recording it as malformed when its value can't be read
+    fn parse_tag(tag_content: &str, range: Range<usize>, doc: &mut PhpDocComment) {
         let parts: Vec<&str> = tag_content.splitn(2, char::is_whitespace).collect();
         if parts.is_empty() {
             return;
@@ -69,117 +79,289 @@ impl PhpDocParser {
         let tag_name = parts[0];
         let tag_value = parts.get(1).unwrap_or(&"").trim();
 
-        match tag_name {
-            "param" | "phpstan-param" => {
-                if let Some(param) = Self::parse_param_tag(tag_value) {
-                    doc.params.push(param);
-                }
-            }
-            "return" | "phpstan-return" => {
-                if let Some(return_tag) = Self::parse_return_tag(tag_value) {
-                    doc.return_tag = Some(return_tag);
-                }
-            }
-            "var" | "phpstan-var" => {
-                if let Some(var_tag) = Self::parse_var_tag(tag_value) {
-                    doc.var_tag = Some(var_tag);
-                }
-            }
-            "throws" => {
-                if let Some(throws_tag) = Self::parse_throws_tag(tag_value) {
-                    doc.throws.push(throws_tag);
-                }
+        let result = match tag_name {
+            "param" | "phpstan-param" => Self::parse_param_tag(tag_value).map(|param| {
+                doc.params.push(ParamTag {
+                    range: range.clone(),
+                    ..param
+                })
+            }),
+            "return" | "phpstan-return" => Self::parse_return_tag(tag_value).map(|return_tag| {
+                doc.return_tag = Some(ReturnTag {
+                    range: range.clone(),
+                    ..return_tag
+                })
+            }),
+            "var" | "phpstan-var" => Self::parse_var_tag(tag_value).map(|var_tag| {
+                doc.var_tags.push(VarTag {
+                    range: range.clone(),
+                    ..var_tag
+                })
+            }),
+            "phpstan-assert"
+            | "phpstan-assert-if-true"
+            | "phpstan-assert-if-false"
+            | "psalm-assert"
+            | "psalm-assert-if-true"
+            | "psalm-assert-if-false" => {
+                Self::parse_assert_tag(tag_name, tag_value).map(|assert| {
+                    doc.asserts.push(AssertTag {
+                        range: range.clone(),
+                        ..assert
+                    })
+                })
             }
+            "throws" => Self::parse_throws_tag(tag_value).map(|throws_tag| {
+                doc.throws.push(ThrowsTag {
+                    range: range.clone(),
+                    ..throws_tag
+                })
+            }),
             _ => {
                 // Ignore other tags for now
+                Ok(())
             }
+        };
+
+        if let Err(message) = result {
+            doc.malformed.push(MalformedTag {
+                tag: tag_name.to_string(),
+                message,
+                range,
+            });
         }
     }
 
     /// Parse @param tag
     /// Format: @param Type $name [description]
-    fn parse_param_tag(value: &str) -> Option<ParamTag> {
+    fn parse_param_tag(value: &str) -> Result<ParamTag, String> {
         let value = value.trim();
+        if value.is_empty() {
+            return Err("is missing a type and `$name`".to_string());
+        }
 
-        // Find where the variable name starts (marked by $)
-        let dollar_pos = value.find('$')?;
-
-        // Type is everything before the $, trimmed
-        let type_str = value[..dollar_pos].trim();
-        let var_part = &value[dollar_pos..];
+        let Some(dollar_pos) = Self::find_variable(value) else {
+            // An unclosed bracket swallows the name, so report the type first
+            Self::parse_checked_type(Self::split_type(value).0)?;
+            return Err("is missing a `$name`".to_string());
+        };
 
-        let type_expr = Self::parse_type_expression(type_str)?;
+        // Type is everything before the $, without by-reference or variadic markers
+        let type_str = value[..dollar_pos]
+            .trim_end_matches("...")
+            .trim_end_matches('&')
+            .trim();
+        let type_expr = Self::parse_checked_type(type_str)?;
 
         // Extract variable name (first token after $)
+        let var_part = &value[dollar_pos..];
         let parts: Vec<&str> = var_part.splitn(2, char::is_whitespace).collect();
         let var_name = parts[0].trim_start_matches('$');
+        if var_name.is_empty() {
+            return Err("is missing a `$name`".to_string());
+        }
 
-        Some(ParamTag {
+        Ok(ParamTag {
             name: var_name.to_string(),
             type_expr,
+            range: Range::default(),
         })
     }
 
     /// Parse @return tag
     /// Format: @return Type [description]
-    fn parse_return_tag(value: &str) -> Option<ReturnTag> {
-        let parts: Vec<&str> = value.splitn(2, char::is_whitespace).collect();
-        if parts.is_empty() {
-            return None;
-        }
-
-        let type_str = parts[0];
-        let type_expr = Self::parse_type_expression(type_str)?;
+    fn parse_return_tag(value: &str) -> Result<ReturnTag, String> {
+        let (type_str, _) = Self::split_type(value);
+        let type_expr = Self::parse_checked_type(type_str)?;
 
-        Some(ReturnTag { type_expr })
+        Ok(ReturnTag {
+            type_expr,
+            range: Range::default(),
+        })
     }
 
     /// Parse @var tag
-    /// Format: @var Type [$name] [description]
-    fn parse_var_tag(value: &str) -> Option<VarTag> {
+    /// Format: @var Type [$name] [description], or @var $name Type
+    fn parse_var_tag(value: &str) -> Result<VarTag, String> {
         let value = value.trim();
 
-        // Find where the variable name starts (marked by $)
-        // The type is everything before that, or the whole string if no variable name
-        let (type_str, rest) = if let Some(dollar_pos) = value.find('$') {
-            // Type is everything before the $, trimmed
-            let type_part = value[..dollar_pos].trim();
-            let var_part = &value[dollar_pos..];
-            (type_part, Some(var_part))
+        let (type_str, name) = match Self::find_variable(value) {
+            Some(dollar_pos) => {
+                let var_part = &value[dollar_pos..];
+                let (var_name, after) = var_part
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((var_part, ""));
+                let name = Some(var_name.trim_start_matches('$').to_string());
+
+                match value[..dollar_pos].trim() {
+                    "" => (Self::split_type(after).0, name),
+                    type_str => (type_str, name),
+                }
+            }
+            // No variable name, the type is the first token
+            None => (Self::split_type(value).0, None),
+        };
+
+        let type_expr = Self::parse_checked_type(type_str)?;
+
+        Ok(VarTag {
+            name,
+            type_expr,
+            range: Range::default(),
+        })
+    }
+
+    /// Parse @throws tag
+    /// Format: @throws ExceptionType [description]
+    fn parse_throws_tag(value: &str) -> Result<ThrowsTag, String> {
+        let (exception_type, description) = Self::split_type(value);
+        Self::parse_checked_type(exception_type)?;
+
+        Ok(ThrowsTag {
+            exception_type: exception_type.to_string(),
+            description: Some(description)
+                .filter(|description| !description.is_empty())
+                .map(str::to_string),
+            range: Range::default(),
+        })
+    }
+
+    /// Parse @phpstan-assert tags
+    /// Format: @phpstan-assert[-if-true|-if-false] [!]Type $name [description]
+    fn parse_assert_tag(tag_name: &str, value: &str) -> Result<AssertTag, String> {
+        let kind = if tag_name.ends_with("-if-true") {
+            AssertKind::IfTrue
+        } else if tag_name.ends_with("-if-false") {
+            AssertKind::IfFalse
         } else {
-            // No variable name, entire string is the type
-            (value, None)
+            AssertKind::Always
         };
 
-        let type_expr = Self::parse_type_expression(type_str)?;
+        let value = value.trim();
+        let Some(dollar_pos) = Self::find_variable(value) else {
+            return Err("is missing a `$name`".to_string());
+        };
 
-        let name = rest.and_then(|s| {
-            let parts: Vec<&str> = s.splitn(2, char::is_whitespace).collect();
-            if !parts.is_empty() && parts[0].starts_with('$') {
-                Some(parts[0].trim_start_matches('$').to_string())
-            } else {
-                None
+        let type_str = value[..dollar_pos].trim();
+        let (negated, type_str) = match type_str.strip_prefix('!') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, type_str),
+        };
+        let type_expr = Self::parse_checked_type(type_str)?;
+
+        let var_part = &value[dollar_pos + 1..];
+        let parameter = var_part.split(char::is_whitespace).next().unwrap_or("");
+        if parameter.is_empty() {
+            return Err("is missing a `$name`".to_string());
+        }
+
+        Ok(AssertTag {
+            kind,
+            parameter: parameter.to_string(),
+            negated,
+            type_expr,
+            range: Range::default(),
+        })
+    }
+
+    /// Split a tag value into its leading type and the rest, keeping
+    /// whitespace inside brackets and around `|` as part of the type
+    fn split_type(value: &str) -> (&str, &str) {
+        let value = value.trim();
+        let mut depth = 0usize;
+
+        for (idx, ch) in value.char_indices() {
+            match ch {
+                '{' | '<' | '(' | '[' => depth += 1,
+                '}' | '>' | ')' | ']' => depth = depth.saturating_sub(1),
+                c if c.is_whitespace() && depth == 0 => {
+                    let (head, tail) = value.split_at(idx);
+                    let tail = tail.trim_start();
+                    if head.ends_with('|') || tail.starts_with('|') {
+                        continue;
+                    }
+                    return (head, tail);
+                }
+                _ => {}
             }
-        });
+        }
 
-        Some(VarTag { name, type_expr })
+        (value, "")
     }
 
-    /// Parse @throws tag
-    /// Format: @throws ExceptionType [description]
-    fn parse_throws_tag(value: &str) -> Option<ThrowsTag> {
-        let parts: Vec<&str> = value.splitn(2, char::is_whitespace).collect();
-        if parts.is_empty() {
-            return None;
+    /// Position of the `$name` that follows the type, ignoring `$` inside brackets
+    /// such as `callable(int $x): void`
+    fn find_variable(value: &str) -> Option<usize> {
+        let mut depth = 0usize;
+        for (idx, ch) in value.char_indices() {
+            match ch {
+                '{' | '<' | '(' | '[' => depth += 1,
+                '}' | '>' | ')' | ']' => depth = depth.saturating_sub(1),
+                '$' if depth == 0 => return Some(idx),
+                _ => {}
+            }
         }
+        None
+    }
 
-        let exception_type = parts[0].to_string();
-        let description = parts.get(1).map(|s| s.to_string());
+    /// Parse a tag's type, explaining why it is unusable instead of guessing
+    fn parse_checked_type(type_str: &str) -> Result<TypeExpression, String> {
+        if type_str.is_empty() {
+            return Err("is missing a type".to_string());
+        }
 
-        Some(ThrowsTag {
-            exception_type,
-            description,
-        })
+        let mut open = Vec::new();
+        for ch in type_str.chars() {
+            match ch {
+                '{' | '<' | '(' | '[' => open.push(ch),
+                '}' | '>' | ')' | ']' => {
+                    let expected = match open.pop() {
+                        Some('{') => '}',
+                        Some('<') => '>',
+                        Some('(') => ')',
+                        Some(_) => ']',
+                        None => return Err(format!("has an unexpected `{ch}` in `{type_str}`")),
+                    };
+                    if ch != expected {
+                        return Err(format!("has an unexpected `{ch}` in `{type_str}`"));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(unclosed) = open.first() {
+            return Err(format!("has an unclosed `{unclosed}` in `{type_str}`"));
+        }
+
+        if Self::split_union(type_str)
+            .iter()
+            .any(|member| member.trim().is_empty())
+        {
+            return Err(format!("has an empty union member in `{type_str}`"));
+        }
+
+        Self::parse_type_expression(type_str)
+            .ok_or_else(|| format!("has an invalid type `{type_str}`"))
+    }
+
+    /// Split a type on top-level `|`, keeping empty members
+    fn split_union(type_str: &str) -> Vec<&str> {
+        let mut members = Vec::new();
+        let mut depth = 0usize;
+        let mut start = 0;
+        for (idx, ch) in type_str.char_indices() {
+            match ch {
+                '{' | '<' | '(' | '[' => depth += 1,
+                '}' | '>' | ')' | ']' => depth = depth.saturating_sub(1),
+                '|' if depth == 0 => {
+                    members.push(&type_str[start..idx]);
+                    start = idx + 1;
+                }
+                _ => {}
+            }
+        }
+        members.push(&type_str[start..]);
+        members
     }
 
     /// Parse a type expression
@@ -187,6 +369,11 @@ impl PhpDocParser {
     pub fn parse_type_expression(type_str: &str) -> Option<TypeExpression> {
         let type_str = type_str.trim();
 
+        // Handle parenthesized types: (int|string), ($flag is true ? string : null)
+        if let Some(inner) = Self::strip_parens(type_str) {
+            return Self::parse_conditional(inner).or_else(|| Self::parse_type_expression(inner));
+        }
+
         // Handle nullable types: ?Type
         if let Some(inner) = type_str.strip_prefix('?') {
             let inner_expr = Self::parse_type_expression(inner)?;
@@ -194,9 +381,10 @@ impl PhpDocParser {
         }
 
         // Handle union types: Type1|Type2|Type3
-        if type_str.contains('|') {
-            let types: Option<Vec<_>> = type_str
-                .split('|')
+        let members = Self::split_union(type_str);
+        if members.len() > 1 {
+            let types: Option<Vec<_>> = members
+                .iter()
                 .map(|t| Self::parse_type_expression(t.trim()))
                 .collect();
             return types.map(TypeExpression::Union);
@@ -224,7 +412,16 @@ impl PhpDocParser {
                 .map(|p| Self::parse_type_expression(p.trim()))
                 .collect();
 
-            if let Some(params) = params {
+            if let Some(mut params) = params {
+                match base {
+                    "key-of" if params.len() == 1 => {
+                        return Some(TypeExpression::KeyOf(Box::new(params.remove(0))));
+                    }
+                    "value-of" if params.len() == 1 => {
+                        return Some(TypeExpression::ValueOf(Box::new(params.remove(0))));
+                    }
+                    _ => {}
+                }
                 return Some(TypeExpression::Generic {
                     base: base.to_string(),
                     params,
@@ -241,6 +438,70 @@ impl PhpDocParser {
         }
     }
 
+    /// The contents of a type wrapped in one pair of parentheses
+    fn strip_parens(type_str: &str) -> Option<&str> {
+        let inner = type_str.strip_prefix('(')?.strip_suffix(')')?;
+        // `(A)|(B)` starts and ends with parens without being wrapped in them
+        let mut depth = 0usize;
+        for ch in inner.chars() {
+            match ch {
+                '{' | '<' | '(' | '[' => depth += 1,
+                '}' | '>' | ')' | ']' => depth = depth.checked_sub(1)?,
+                _ => {}
+            }
+        }
+        Some(inner)
+    }
+
+    /// Parse the inside of a conditional type: `$flag is [not] true ? string : null`
+    fn parse_conditional(inner: &str) -> Option<TypeExpression> {
+        let inner = inner.trim();
+        let (subject, rest) = inner.split_once(char::is_whitespace)?;
+        let rest = rest.trim_start().strip_prefix("is")?;
+        if !rest.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let rest = rest.trim_start();
+        let (negated, rest) = match rest.strip_prefix("not") {
+            Some(after) if after.starts_with(char::is_whitespace) => (true, after.trim_start()),
+            _ => (false, rest),
+        };
+
+        // The branches are separated by a top-level ` ? ` and ` : `
+        let question = Self::find_separator(rest, '?')?;
+        let colon = question + 1 + Self::find_separator(&rest[question + 1..], ':')?;
+
+        Some(TypeExpression::Conditional {
+            parameter: subject.trim_start_matches('$').to_string(),
+            negated,
+            condition: Box::new(Self::parse_type_expression(&rest[..question])?),
+            then: Box::new(Self::parse_type_expression(&rest[question + 1..colon])?),
+            otherwise: Box::new(Self::parse_type_expression(&rest[colon + 1..])?),
+        })
+    }
+
+    /// Position of a top-level `separator` surrounded by whitespace
+    fn find_separator(value: &str, separator: char) -> Option<usize> {
+        let mut depth = 0usize;
+        let mut previous = ' ';
+        for (idx, ch) in value.char_indices() {
+            match ch {
+                '{' | '<' | '(' | '[' => depth += 1,
+                '}' | '>' | ')' | ']' => depth = depth.saturating_sub(1),
+                c if c == separator
+                    && depth == 0
+                    && previous.is_whitespace()
+                    && value[idx + 1..].starts_with(char::is_whitespace) =>
+                {
+                    return Some(idx);
+                }
+                _ => {}
+            }
+            previous = ch;
+        }
+        None
+    }
+
     /// Split a generic type into base and parameters
     /// Example: "array<string, int>" -> ("array", "string, int")
     fn split_generic(type_str: &str) -> Option<(&str, &str)> {
@@ -258,20 +519,18 @@ impl PhpDocParser {
     }
 
     /// Parse shaped array fields: "name: string, age: int" -> [("name", Simple("string")), ("age", Simple("int"))]
+    /// Fields without a name are numbered by position: "int, string" -> [("0", ...), ("1", ...)]
     fn parse_shaped_array_fields(fields_str: &str) -> Option<Vec<(String, TypeExpression)>> {
         let field_strs = Self::split_params(fields_str);
         let mut fields = Vec::new();
 
-        for field_str in field_strs {
-            // Split on colon: "name: string" -> ["name", "string"]
-            let parts: Vec<&str> = field_str.splitn(2, ':').collect();
-            if parts.len() != 2 {
-                return None;
-            }
-
-            let field_name = parts[0].trim().to_string();
-            let type_str = parts[1].trim();
-            let type_expr = Self::parse_type_expression(type_str)?;
+        for (position, field_str) in field_strs.iter().enumerate() {
+            // Split on the first top-level colon: "name: string" -> ("name", "string")
+            let (field_name, type_str) = match Self::top_level_colon(field_str) {
+                Some(idx) => (field_str[..idx].trim().to_string(), &field_str[idx + 1..]),
+                None => (position.to_string(), field_str.as_str()),
+            };
+            let type_expr = Self::parse_type_expression(type_str.trim())?;
 
             fields.push((field_name, type_expr));
         }
@@ -279,6 +538,20 @@ impl PhpDocParser {
         Some(fields)
     }
 
+    /// The byte offset of the first `:` outside nested braces, brackets and parens
+    fn top_level_colon(field_str: &str) -> Option<usize> {
+        let mut depth = 0;
+        for (idx, ch) in field_str.char_indices() {
+            match ch {
+                '{' | '<' | '(' | '[' => depth += 1,
+                '}' | '>' | ')' | ']' => depth -= 1,
+                ':' if depth == 0 => return Some(idx),
+                _ => {}
+            }
+        }
+        None
+    }
+
     /// Split comma-separated parameters while respecting nesting
     /// Example: "int, array{name: string, age: int}" -> ["int", "array{name: string, age: int}"]
     fn split_params(params_str: &str) -> Vec<String> {
@@ -368,6 +641,108 @@ mod tests {
         assert!(matches!(param.type_expr, TypeExpression::Simple(s) if s == "int"));
     }
 
+    #[test]
+    fn test_parse_multiple_var_tags() {
+        let comment = r#"/**
+         * @var int $id
+         * @var string $name
+         */"#;
+
+        let doc = PhpDocParser::parse(comment).unwrap();
+        let names: Vec<_> = doc.var_tags.iter().map(|tag| tag.name.as_deref()).collect();
+        assert_eq!(names, vec![Some("id"), Some("name")]);
+        assert_eq!(doc.var_tag().unwrap().name.as_deref(), Some("id"));
+    }
+
+    #[test]
+    fn test_parse_conditional_return_type() {
+        let expr =
+            PhpDocParser::parse_type_expression("($flag is not true ? string|int : null)").unwrap();
+        let TypeExpression::Conditional {
+            parameter,
+            negated,
+            condition,
+            then,
+            otherwise,
+        } = expr
+        else {
+            panic!("Expected conditional type");
+        };
+        assert_eq!(parameter, "flag");
+        assert!(negated);
+        assert_eq!(*condition, TypeExpression::Simple("true".to_string()));
+        assert!(matches!(*then, TypeExpression::Union(ref types) if types.len() == 2));
+        assert_eq!(*otherwise, TypeExpression::Simple("null".to_string()));
+
+        // Plain parentheses only group
+        let grouped = PhpDocParser::parse_type_expression("(int|string)[]").unwrap();
+        assert!(
+            matches!(grouped, TypeExpression::Array(inner) if matches!(*inner, TypeExpression::Union(_)))
+        );
+    }
+
+    #[test]
+    fn test_parse_key_of_and_value_of() {
+        let simple = |name: &str| TypeExpression::Simple(name.to_string());
+
+        let key_of = PhpDocParser::parse_type_expression("key-of<array<string, int>>").unwrap();
+        let TypeExpression::KeyOf(inner) = key_of else {
+            panic!("Expected key-of type");
+        };
+        assert_eq!(inner.key_type(), simple("string"));
+
+        let value_of = PhpDocParser::parse_type_expression("value-of<list<User>>").unwrap();
+        let TypeExpression::ValueOf(inner) = value_of else {
+            panic!("Expected value-of type");
+        };
+        assert_eq!(inner.value_type(), Some(simple("User")));
+        assert_eq!(inner.key_type(), simple("int"));
+
+        // Unnamed shape fields are numbered by position
+        let pair = PhpDocParser::parse_type_expression("array{int, array{id: int}}").unwrap();
+        let TypeExpression::ShapedArray(fields) = pair else {
+            panic!("Expected shaped array");
+        };
+        let names: Vec<_> = fields.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["0", "1"]);
+
+        // Keys of a shape come from its field names; unknown keys are any array-key
+        let shape = PhpDocParser::parse_type_expression("array{0: int, name: string}").unwrap();
+        assert_eq!(
+            shape.key_type(),
+            TypeExpression::Union(vec![simple("int"), simple("string")])
+        );
+        assert_eq!(simple("Traversable").key_type(), simple("array-key"));
+        assert_eq!(simple("Traversable").value_type(), None);
+    }
+
+    #[test]
+    fn test_parse_assert_tags() {
+        let comment = r#"/**
+         * @phpstan-assert !null $value
+         * @phpstan-assert-if-true User $user
+         * @psalm-assert-if-false string $name
+         * @phpstan-assert int
+         */"#;
+
+        let doc = PhpDocParser::parse(comment).unwrap();
+        let asserts: Vec<_> = doc
+            .asserts
+            .iter()
+            .map(|assert| (assert.kind, assert.parameter.as_str(), assert.negated))
+            .collect();
+        assert_eq!(
+            asserts,
+            [
+                (AssertKind::Always, "value", true),
+                (AssertKind::IfTrue, "user", false),
+                (AssertKind::IfFalse, "name", false),
+            ]
+        );
+        assert_eq!(doc.malformed.len(), 1);
+        assert_eq!(doc.malformed[0].message, "is missing a `$name`");
+    }
+
     #[test]
     fn test_parse_phpdoc_comment() {
         let comment = r#"/**
@@ -407,8 +782,8 @@ mod tests {
          */"#;
 
         let doc = PhpDocParser::parse(comment).unwrap();
-        assert!(doc.var_tag.is_some());
-        let var_tag = doc.var_tag.unwrap();
+        assert!(doc.var_tag().is_some());
+        let var_tag = doc.var_tag().unwrap().clone();
         eprintln!("DEBUG: Parsed type_expr = {:?}", var_tag.type_expr);
         match var_tag.type_expr {
             TypeExpression::Generic { base, params } => {
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 /// Represents a type expression from PHPDoc
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TypeExpression {
@@ -31,6 +33,23 @@ pub enum TypeExpression {
 
     /// Never type
     Never,
+
+    /// Key type of an array-like type: key-of<array<string, int>>
+    KeyOf(Box<TypeExpression>),
+
+    /// Value type of an array-like type: value-of<list<User>>
+    ValueOf(Box<TypeExpression>),
+
+    /// Conditional return type: ($flag is true ? string : null)
+    Conditional {
+        /// Parameter name without the `$`
+        parameter: String,
+        /// `is not` instead of `is`
+        negated: bool,
+        condition: Box<TypeExpression>,
+        then: Box<TypeExpression>,
+        otherwise: Box<TypeExpression>,
+    },
 }
 
 impl TypeExpression {
@@ -61,9 +80,90 @@ impl TypeExpression {
             TypeExpression::ShapedArray(fields) => {
                 fields.iter().any(|(_, t)| t.contains_type(type_name))
             }
+            TypeExpression::Conditional {
+                then, otherwise, ..
+            } => then.contains_type(type_name) || otherwise.contains_type(type_name),
             _ => false,
         }
     }
+
+    /// The key type `key-of<T>` stands for; `array-key` when T's keys are unknown
+    pub fn key_type(&self) -> TypeExpression {
+        let array_key = || TypeExpression::Simple("array-key".to_string());
+        match self {
+            TypeExpression::Generic { base, params } if is_list(base) && params.len() == 1 => {
+                TypeExpression::Simple("int".to_string())
+            }
+            TypeExpression::Generic { base, params } if is_array(base) && params.len() == 2 => {
+                params[0].clone()
+            }
+            TypeExpression::ShapedArray(fields) if !fields.is_empty() => {
+                let mut keys = Vec::new();
+                for (name, _) in fields {
+                    let key = if name.trim_end_matches('?').parse::<i64>().is_ok() {
+                        "int"
+                    } else {
+                        "string"
+                    };
+                    let key = TypeExpression::Simple(key.to_string());
+                    if !keys.contains(&key) {
+                        keys.push(key);
+                    }
+                }
+                match keys.len() {
+                    1 => keys.remove(0),
+                    _ => TypeExpression::Union(keys),
+                }
+            }
+            _ => array_key(),
+        }
+    }
+
+    /// The value type `value-of<T>` stands for, if T is array-like
+    pub fn value_type(&self) -> Option<TypeExpression> {
+        match self {
+            TypeExpression::Array(inner) => Some(inner.as_ref().clone()),
+            TypeExpression::Generic { base, params } if is_list(base) || is_array(base) => {
+                params.last().cloned()
+            }
+            TypeExpression::ShapedArray(fields) if !fields.is_empty() => {
+                let mut values: Vec<TypeExpression> = Vec::new();
+                for (_, value) in fields {
+                    if !values.contains(value) {
+                        values.push(value.clone());
+                    }
+                }
+                Some(match values.len() {
+                    1 => values.remove(0),
+                    _ => TypeExpression::Union(values),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Either branch of a conditional type, for checks that can't evaluate the condition
+    pub fn branch_union(&self) -> Option<TypeExpression> {
+        match self {
+            TypeExpression::Conditional {
+                then, otherwise, ..
+            } => Some(TypeExpression::Union(vec![
+                then.as_ref().clone(),
+                otherwise.as_ref().clone(),
+            ])),
+            _ => None,
+        }
+    }
+}
+
+/// `array<...>` and its non-empty variant
+fn is_array(base: &str) -> bool {
+    matches!(base, "array" | "non-empty-array")
+}
+
+/// `list<...>` and its non-empty variant, arrays with sequential int keys
+fn is_list(base: &str) -> bool {
+    matches!(base, "list" | "non-empty-list")
 }
 
 /// @param tag
@@ -71,12 +171,16 @@ impl TypeExpression {
 pub struct ParamTag {
     pub name: String,
     pub type_expr: TypeExpression,
+    /// Byte range of the tag line, from `@` to the end of the line
+    pub range: Range<usize>,
 }
 
 /// @return tag
 #[derive(Debug, Clone)]
 pub struct ReturnTag {
     pub type_expr: TypeExpression,
+    /// Byte range of the tag line, from `@` to the end of the line
+    pub range: Range<usize>,
 }
 
 /// @var tag
@@ -84,6 +188,43 @@ pub struct ReturnTag {
 pub struct VarTag {
     pub name: Option<String>,
     pub type_expr: TypeExpression,
+    /// Byte range of the tag line, from `@` to the end of the line
+    pub range: Range<usize>,
+}
+
+/// A tag whose contents could not be parsed, e.g. `@param` without a `$name`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MalformedTag {
+    /// Tag name without the `@`
+    pub tag: String,
+    /// What is wrong, phrased to follow the tag: "is missing a `$name`"
+    pub message: String,
+    /// Byte range of the tag line, from `@` to the end of the line
+    pub range: Range<usize>,
+}
+
+/// When an assertion tag applies to its parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertKind {
+    /// @phpstan-assert: whenever the call returns
+    Always,
+    /// @phpstan-assert-if-true: when the call returns true
+    IfTrue,
+    /// @phpstan-assert-if-false: when the call returns false
+    IfFalse,
+}
+
+/// @phpstan-assert tag and its -if-true/-if-false variants
+#[derive(Debug, Clone)]
+pub struct AssertTag {
+    pub kind: AssertKind,
+    /// Parameter name without the `$`
+    pub parameter: String,
+    /// `!Type`: the parameter is asserted not to be the type
+    pub negated: bool,
+    pub type_expr: TypeExpression,
+    /// Byte range of the tag line, from `@` to the end of the line
+    pub range: Range<usize>,
 }
 
 /// @throws tag
@@ -91,6 +232,8 @@ pub struct VarTag {
 pub struct ThrowsTag {
     pub exception_type: String,
     pub description: Option<String>,
+    /// Byte range of the tag line, from `@` to the end of the line
+    pub range: Range<usize>,
 }
 
 /// @property tag
@@ -1,19 +1,44 @@
+use crate::analyzer::phpdoc::{AssertTag, TypeExpression, extract_phpdoc_for_node};
+use crate::analyzer::types::{TypeArena, TypeHint};
 use crate::analyzer::{Span, parser};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use tree_sitter::Node;
 
+mod references;
+
+pub use references::SymbolReference;
+use references::{ReferenceTarget, collect_references, short_name_key};
+
 /// Stores parsed PHP sources and derived symbol data for the whole workspace.
+///
+/// Function and class maps are keyed by lowercased names because PHP resolves
+/// those names case-insensitively; constants keep their declared case.
 pub struct ProjectContext {
     sources: HashMap<PathBuf, parser::ParsedSource>,
     file_scopes: HashMap<PathBuf, FileScope>,
     function_symbols: HashMap<String, Vec<FunctionSymbol>>,
+    class_symbols: HashMap<String, Vec<ClassSymbol>>,
+    enum_symbols: HashMap<String, Vec<EnumSymbol>>,
+    global_sites: HashMap<String, Vec<GlobalSite>>,
+    global_reads: HashSet<String>,
+    /// Reference sites keyed by the lowercased short name of the symbol, built
+    /// from every source on the first reference query.
+    references: OnceLock<HashMap<String, Vec<SymbolReference>>>,
+    /// Types interned by the rules during this run.
+    types: TypeArena,
 }
 
 pub(crate) struct FileMetadata {
     pub namespace: Option<String>,
-    pub uses: HashMap<String, UseInfo>,
+    pub uses: Vec<UseInfo>,
     pub symbols: Vec<FunctionSymbol>,
+    pub classes: Vec<ClassSymbol>,
+    pub enums: Vec<EnumSymbol>,
+    pub globals: Vec<GlobalSite>,
+    pub global_reads: HashSet<String>,
 }
 
 /// Namespace and symbol information for a single file.
@@ -21,15 +46,51 @@ pub(crate) struct FileMetadata {
 pub struct FileScope {
     pub namespace: Option<String>,
     pub functions: Vec<FunctionSymbol>,
-    pub uses: HashMap<String, UseInfo>,
+    pub classes: Vec<ClassSymbol>,
+    pub enums: Vec<EnumSymbol>,
+    pub uses: Vec<UseInfo>,
+    pub globals: Vec<GlobalSite>,
+}
+
+impl FileScope {
+    /// Resolves a class reference written in this file to its fully qualified name.
+    pub fn resolve_class(&self, name: &str) -> String {
+        resolve_class_name(name, self.namespace.as_deref(), &self.uses)
+    }
+}
+
+/// Which symbol table a `use` clause imports into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UseKind {
+    Class,
+    Function,
+    Constant,
+}
+
+impl UseKind {
+    /// Constants are the only imported names PHP matches case-sensitively.
+    pub fn matches(self, alias: &str, name: &str) -> bool {
+        match self {
+            UseKind::Constant => alias == name,
+            UseKind::Class | UseKind::Function => alias.eq_ignore_ascii_case(name),
+        }
+    }
 }
 
+/// A single imported name. Group members (`use App\{Foo, Bar}`) each get their
+/// own entry whose clause range covers just that member.
 #[derive(Clone)]
 pub struct UseInfo {
+    pub alias: String,
+    pub kind: UseKind,
+    /// Fully qualified target without a leading backslash.
     pub target: String,
+    /// Span of the alias name (the last segment when no `as` is given).
     pub span: Span,
     pub clause_start: usize,
     pub clause_end: usize,
+    pub declaration_start: usize,
+    pub declaration_end: usize,
     pub declaration_has_multiple_clauses: bool,
 }
 
@@ -41,6 +102,160 @@ pub struct FunctionSymbol {
     pub file: PathBuf,
     pub span: Span,
     pub required_params: usize,
+    /// Parameter names without the `$`, in declaration order
+    pub params: Vec<String>,
+    /// @phpstan-assert tags from the function's docblock
+    pub asserts: Vec<AssertTag>,
+    /// The docblock's @return type, which may be conditional on a parameter
+    pub return_type: Option<TypeExpression>,
+    /// The value when every `return` yields the same literal or class constant
+    pub constant_return: Option<ConstantReturn>,
+}
+
+/// A constant expression a function always returns, as written in its body.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstantReturn {
+    /// Normalised like [`ClassSymbol::constant_values`], e.g. `false` or `'on'`.
+    Literal(String),
+    /// `Class::NAME`, with the class fully qualified.
+    ClassConstant { class: String, name: String },
+}
+
+/// A value known at analysis time: a literal, or an enum case.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstantValue {
+    /// Normalised like [`ClassSymbol::constant_values`].
+    Literal(String),
+    EnumCase {
+        enum_name: String,
+        case: String,
+    },
+}
+
+impl ConstantValue {
+    /// Whether the value is truthy in a condition, when that's certain.
+    pub fn is_truthy(&self) -> Option<bool> {
+        let ConstantValue::Literal(literal) = self else {
+            // Enum cases are objects, which are always truthy
+            return Some(true);
+        };
+        match literal.as_str() {
+            "true" => return Some(true),
+            "false" | "null" => return Some(false),
+            _ => {}
+        }
+        if let Some(quote) = literal.chars().next().filter(|c| matches!(c, '\'' | '"')) {
+            let inner = literal.trim_matches(quote);
+            return Some(!matches!(inner, "" | "0"));
+        }
+
+        let digits = literal.trim_start_matches(['-', '+']);
+        let digits = ["0x", "0b", "0o"]
+            .iter()
+            .find_map(|prefix| digits.strip_prefix(prefix))
+            .unwrap_or(digits);
+        if digits.chars().all(|c| c == '0') {
+            return Some(false);
+        }
+        digits.parse::<f64>().ok().map(|value| value != 0.0)
+    }
+}
+
+impl fmt::Display for ConstantValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstantValue::Literal(literal) => f.write_str(literal),
+            ConstantValue::EnumCase { enum_name, case } => {
+                let short = enum_name.rsplit('\\').next().unwrap_or(enum_name);
+                write!(f, "{short}::{case}")
+            }
+        }
+    }
+}
+
+/// Declared members of a class, interface or trait. Names referenced from the
+/// declaration (`extends`, `implements`, trait `use`, attributes) are stored
+/// fully qualified.
+///
+/// Anonymous classes are included under [`anonymous_class_key`], with PHP's
+/// display name (`Foo@anonymous`) as their `name`.
+#[derive(Clone)]
+#[allow(dead_code)]
+pub struct ClassSymbol {
+    pub name: String,
+    pub fq_name: String,
+    pub file: PathBuf,
+    pub span: Span,
+    pub parent: Option<String>,
+    /// Implemented interfaces, or the interfaces an interface extends.
+    pub interfaces: Vec<String>,
+    pub traits: Vec<String>,
+    pub attributes: Vec<String>,
+    pub properties: Vec<String>,
+    pub methods: Vec<String>,
+    /// Class constant names; unlike methods these are case-sensitive.
+    pub constants: Vec<String>,
+    /// Types of the constants initialised with a literal (`const LIMIT = 10;`).
+    pub constant_types: HashMap<String, TypeHint>,
+    /// Literal initialisers as written, normalised so `'a'` and `"a"` match.
+    pub constant_values: HashMap<String, String>,
+}
+
+/// A declared enum. Its cases are instances of the enum itself; a backed
+/// enum's `->value` has the backing type.
+#[derive(Clone)]
+#[allow(dead_code)]
+pub struct EnumSymbol {
+    pub name: String,
+    pub fq_name: String,
+    pub file: PathBuf,
+    pub span: Span,
+    /// `int` or `string` for backed enums.
+    pub backing: Option<TypeHint>,
+    /// Case names, which are case-sensitive like constants.
+    pub cases: Vec<String>,
+    /// Types of the constants initialised with a literal.
+    pub constant_types: HashMap<String, TypeHint>,
+}
+
+/// How a global variable is touched at a [`GlobalSite`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GlobalSiteKind {
+    /// `$name = ...` at file scope.
+    Assignment,
+    /// `$GLOBALS['name'] = ...` anywhere.
+    SuperglobalWrite,
+    /// `$name = ...` inside a function that declared `global $name`.
+    DeclaredWrite,
+    /// `global $name;`
+    Declaration,
+}
+
+/// A place where a global variable is defined, written, or imported with `global`.
+#[derive(Clone)]
+pub struct GlobalSite {
+    pub name: String,
+    pub kind: GlobalSiteKind,
+    pub span: Span,
+}
+
+impl ClassSymbol {
+    /// Method names are case-insensitive in PHP.
+    pub fn has_method(&self, name: &str) -> bool {
+        self.methods
+            .iter()
+            .any(|method| method.eq_ignore_ascii_case(name))
+    }
+
+    /// Matches attributes by their short name so `#[Foo]` and `#[\Foo]` are treated alike.
+    pub fn has_attribute(&self, name: &str) -> bool {
+        self.attributes.iter().any(|attribute| {
+            attribute
+                .rsplit('\\')
+                .next()
+                .is_some_and(|short| short.eq_ignore_ascii_case(name))
+        })
+    }
 }
 
 impl ProjectContext {
@@ -49,6 +264,12 @@ impl ProjectContext {
             sources: HashMap::new(),
             file_scopes: HashMap::new(),
             function_symbols: HashMap::new(),
+            class_symbols: HashMap::new(),
+            enum_symbols: HashMap::new(),
+            global_sites: HashMap::new(),
+            global_reads: HashSet::new(),
+            references: OnceLock::new(),
+            types: TypeArena::new(),
         }
     }
 
@@ -67,21 +288,52 @@ impl ProjectContext {
             namespace,
             uses,
             symbols,
+            classes,
+            enums,
+            globals,
+            global_reads,
         } = metadata;
 
         for symbol in &symbols {
             self.function_symbols
-                .entry(symbol.fq_name.clone())
+                .entry(symbol_key(&symbol.fq_name))
                 .or_default()
                 .push(symbol.clone());
         }
 
+        for class in &classes {
+            self.class_symbols
+                .entry(symbol_key(&class.fq_name))
+                .or_default()
+                .push(class.clone());
+        }
+
+        for symbol in &enums {
+            self.enum_symbols
+                .entry(symbol_key(&symbol.fq_name))
+                .or_default()
+                .push(symbol.clone());
+        }
+
+        for site in &globals {
+            self.global_sites
+                .entry(site.name.clone())
+                .or_default()
+                .push(site.clone());
+        }
+        self.global_reads.extend(global_reads);
+
+        self.references = OnceLock::new();
+
         self.file_scopes.insert(
             path.clone(),
             FileScope {
                 namespace,
                 functions: symbols.clone(),
+                classes,
+                enums,
                 uses,
+                globals,
             },
         );
 
@@ -100,6 +352,11 @@ impl ProjectContext {
         self.sources.len()
     }
 
+    /// The arena the rules intern types into for this run.
+    pub fn types(&self) -> &TypeArena {
+        &self.types
+    }
+
     pub fn scope_for(&self, path: &Path) -> Option<&FileScope> {
         self.file_scopes.get(path)
     }
@@ -110,16 +367,208 @@ impl ProjectContext {
         parsed: &parser::ParsedSource,
     ) -> Option<&'a FunctionSymbol> {
         let scope = self.scope_for(&parsed.path)?;
-        for candidate in candidate_function_names(name, scope) {
-            if let Some(symbols) = self.function_symbols.get(&candidate) {
-                if let Some(symbol) = symbols.first() {
-                    return Some(symbol);
-                }
+        candidate_function_names(name, scope.namespace.as_deref(), &scope.uses)
+            .into_iter()
+            .find_map(|candidate| self.function_symbols.get(&symbol_key(&candidate)))
+            .and_then(|symbols| symbols.first())
+    }
+
+    /// Looks up a class or trait by its fully qualified name (case-insensitively).
+    pub fn class_symbol(&self, fq_name: &str) -> Option<&ClassSymbol> {
+        self.class_symbols
+            .get(&symbol_key(fq_name))
+            .and_then(|classes| classes.first())
+    }
+
+    /// Looks up an enum by its fully qualified name (case-insensitively).
+    pub fn enum_symbol(&self, fq_name: &str) -> Option<&EnumSymbol> {
+        self.enum_symbols
+            .get(&symbol_key(fq_name))
+            .and_then(|enums| enums.first())
+    }
+
+    /// The type of `class::constant`, looking through parent classes. Only
+    /// constants initialised with a literal have a known type.
+    pub fn class_constant_type(&self, class: &str, constant: &str) -> Option<TypeHint> {
+        if let Some(symbol) = self.enum_symbol(class) {
+            return symbol.constant_types.get(constant).cloned();
+        }
+
+        let mut current = class.to_owned();
+        let mut seen = HashSet::new();
+        while seen.insert(symbol_key(&current)) {
+            let symbol = self.class_symbol(&current)?;
+            if let Some(hint) = symbol.constant_types.get(constant) {
+                return Some(hint.clone());
+            }
+            current = symbol.parent.clone()?;
+        }
+        None
+    }
+
+    /// The enum case `class::name`, or the literal value of the class constant,
+    /// looking through parent classes.
+    pub fn class_constant_value(&self, class: &str, name: &str) -> Option<ConstantValue> {
+        if let Some(symbol) = self.enum_symbol(class) {
+            return symbol
+                .cases
+                .iter()
+                .any(|case| case == name)
+                .then(|| ConstantValue::EnumCase {
+                    enum_name: symbol.fq_name.clone(),
+                    case: name.to_owned(),
+                });
+        }
+
+        let mut current = class.to_owned();
+        let mut seen = HashSet::new();
+        while seen.insert(symbol_key(&current)) {
+            let symbol = self.class_symbol(&current)?;
+            if let Some(value) = symbol.constant_values.get(name) {
+                return Some(ConstantValue::Literal(value.clone()));
             }
+            current = symbol.parent.clone()?;
         }
         None
     }
 
+    /// What a call to `symbol` always evaluates to, if its body decides it.
+    /// Functions with more than one definition, such as polyfills declared in
+    /// both branches of a version check, have no constant result.
+    pub fn constant_return(&self, symbol: &FunctionSymbol) -> Option<ConstantValue> {
+        if self
+            .function_symbols
+            .get(&symbol_key(&symbol.fq_name))
+            .is_some_and(|definitions| definitions.len() > 1)
+        {
+            return None;
+        }
+        match symbol.constant_return.as_ref()? {
+            ConstantReturn::Literal(literal) => Some(ConstantValue::Literal(literal.clone())),
+            ConstantReturn::ClassConstant { class, name } => self.class_constant_value(class, name),
+        }
+    }
+
+    /// Whether any analysed file assigns the global `name` (at file scope, via
+    /// `$GLOBALS`, or through a `global` declaration).
+    pub fn global_is_defined(&self, name: &str) -> bool {
+        self.global_sites.get(name).is_some_and(|sites| {
+            sites
+                .iter()
+                .any(|site| site.kind != GlobalSiteKind::Declaration)
+        })
+    }
+
+    /// Whether any analysed file reads the global `name`.
+    pub fn global_is_read(&self, name: &str) -> bool {
+        self.global_reads.contains(name)
+    }
+
+    /// Every call, import and declaration of the function `fq_name`. Unqualified
+    /// calls count when they resolve to it, including PHP's global fallback.
+    pub fn function_references(&self, fq_name: &str) -> Vec<&SymbolReference> {
+        let key = symbol_key(fq_name);
+        self.references_named(fq_name)
+            .filter(|reference| match &reference.target {
+                ReferenceTarget::Function(candidates) => {
+                    self.resolve_function_candidates(candidates).as_ref() == Some(&key)
+                }
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Every place the class, interface or trait `fq_name` is named: declarations,
+    /// imports, instantiations, type hints, `extends`/`implements` and static access.
+    #[allow(dead_code)]
+    pub fn class_references(&self, fq_name: &str) -> Vec<&SymbolReference> {
+        let key = symbol_key(fq_name);
+        self.references_named(fq_name)
+            .filter(|reference| match &reference.target {
+                ReferenceTarget::Class(class) => symbol_key(class) == key,
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Declarations and calls of `class::method`. Calls through a subclass count
+    /// when the method is inherited; calls on receivers of unknown type are
+    /// included by name and marked with [`SymbolReference::is_exact`] = false.
+    #[allow(dead_code)]
+    pub fn method_references(&self, class: &str, method: &str) -> Vec<&SymbolReference> {
+        let key = symbol_key(class);
+        self.references_named(method)
+            .filter(|reference| match &reference.target {
+                ReferenceTarget::Method {
+                    class: Some(called_on),
+                    name,
+                } => {
+                    name.eq_ignore_ascii_case(method)
+                        && symbol_key(&self.declaring_class(called_on, method)) == key
+                }
+                ReferenceTarget::Method { class: None, name } => name.eq_ignore_ascii_case(method),
+                _ => false,
+            })
+            .collect()
+    }
+
+    fn references_named(&self, name: &str) -> impl Iterator<Item = &SymbolReference> {
+        self.references
+            .get_or_init(|| self.index_references())
+            .get(&short_name_key(name))
+            .into_iter()
+            .flatten()
+    }
+
+    fn index_references(&self) -> HashMap<String, Vec<SymbolReference>> {
+        let mut paths: Vec<&PathBuf> = self.sources.keys().collect();
+        paths.sort();
+
+        let mut index: HashMap<String, Vec<SymbolReference>> = HashMap::new();
+        for path in paths {
+            let (Some(parsed), Some(scope)) = (self.sources.get(path), self.file_scopes.get(path))
+            else {
+                continue;
+            };
+            for reference in collect_references(parsed, scope.namespace.as_deref(), &scope.uses) {
+                index
+                    .entry(reference.index_key())
+                    .or_default()
+                    .push(reference);
+            }
+        }
+        index
+    }
+
+    /// The first candidate defined in the project, else PHP's global fallback.
+    fn resolve_function_candidates(&self, candidates: &[String]) -> Option<String> {
+        candidates
+            .iter()
+            .map(|candidate| symbol_key(candidate))
+            .find(|candidate| self.function_symbols.contains_key(candidate))
+            .or_else(|| candidates.last().map(|candidate| symbol_key(candidate)))
+    }
+
+    /// Walks up from `class` to the nearest ancestor declaring `method`.
+    fn declaring_class(&self, class: &str, method: &str) -> String {
+        let mut current = class.to_owned();
+        let mut seen = HashSet::new();
+        while seen.insert(symbol_key(&current)) {
+            let Some(symbol) = self.class_symbol(&current) else {
+                break;
+            };
+            if symbol.has_method(method) {
+                return current;
+            }
+            match &symbol.parent {
+                Some(parent) => current = parent.clone(),
+                None => break,
+            }
+        }
+        class.to_owned()
+    }
+
+    /// Function symbols keyed by their lowercased fully qualified name.
     #[allow(dead_code)]
     pub fn function_symbols(&self) -> &HashMap<String, Vec<FunctionSymbol>> {
         &self.function_symbols
@@ -146,72 +595,122 @@ fn collect_namespace(parsed: &parser::ParsedSource) -> Option<String> {
     namespace
 }
 
-fn collect_use_aliases(parsed: &parser::ParsedSource) -> HashMap<String, UseInfo> {
-    let mut uses = HashMap::new();
+fn collect_use_aliases(parsed: &parser::ParsedSource) -> Vec<UseInfo> {
+    let mut uses = Vec::new();
 
     walk_node(parsed.tree.root_node(), &mut |node| {
         if node.kind() != "namespace_use_declaration" {
             return;
         }
 
-        let clause_count = (0..node.named_child_count())
-            .filter_map(|idx| node.named_child(idx))
-            .filter(|child| child.kind() == "namespace_use_clause")
-            .count();
-        let declaration_has_multiple_clauses = clause_count > 1;
+        let declaration_kind = use_kind_keyword(node).unwrap_or(UseKind::Class);
+        let mut clauses = Vec::new();
+
+        if let Some(group) = child_by_kind(node, "namespace_use_group") {
+            let prefix = child_by_kind(node, "namespace_name")
+                .and_then(|prefix| node_text(prefix, parsed))
+                .unwrap_or_default();
+            let prefix = prefix.trim_start_matches('\\');
 
-        for idx in 0..node.named_child_count() {
-            if let Some(child) = node.named_child(idx) {
-                if child.kind() != "namespace_use_clause" {
+            for idx in 0..group.named_child_count() {
+                let Some(clause) = group.named_child(idx) else {
+                    continue;
+                };
+                if clause.kind() != "namespace_use_group_clause" {
                     continue;
                 }
+                let Some(name) = child_by_kind(clause, "namespace_name")
+                    .and_then(|name| node_text(name, parsed))
+                else {
+                    continue;
+                };
 
-                if let Some(qualified) = child_by_kind(child, "qualified_name") {
-                    if let Some(fq_name) = node_text(qualified, parsed) {
-                        if let Some(alias_node) = alias_node_from_clause(child) {
-                            if let Some(alias) = node_text(alias_node, parsed) {
-                                uses.insert(
-                                    alias.clone(),
-                                    UseInfo {
-                                        target: fq_name,
-                                        span: span_from_node(alias_node),
-                                        clause_start: child.start_byte(),
-                                        clause_end: child.end_byte(),
-                                        declaration_has_multiple_clauses,
-                                    },
-                                );
-                            }
-                        }
-                    }
+                let target = if prefix.is_empty() {
+                    name
+                } else {
+                    format!("{prefix}\\{name}")
+                };
+                let kind = use_kind_keyword(clause).unwrap_or(declaration_kind);
+                clauses.push((clause, target, kind));
+            }
+        } else {
+            for idx in 0..node.named_child_count() {
+                let Some(clause) = node.named_child(idx) else {
+                    continue;
+                };
+                if clause.kind() != "namespace_use_clause" {
+                    continue;
                 }
+                let Some(target) = child_by_kind(clause, "qualified_name")
+                    .or_else(|| child_by_kind(clause, "name"))
+                    .and_then(|name| node_text(name, parsed))
+                else {
+                    continue;
+                };
+                clauses.push((clause, target, declaration_kind));
             }
         }
+
+        let declaration_has_multiple_clauses = clauses.len() > 1;
+        for (clause, target, kind) in clauses {
+            let Some(alias_node) = alias_node_from_clause(clause) else {
+                continue;
+            };
+            let Some(alias) = node_text(alias_node, parsed) else {
+                continue;
+            };
+
+            uses.push(UseInfo {
+                alias,
+                kind,
+                target: target.trim_start_matches('\\').to_owned(),
+                span: span_from_node(alias_node),
+                clause_start: clause.start_byte(),
+                clause_end: clause.end_byte(),
+                declaration_start: node.start_byte(),
+                declaration_end: node.end_byte(),
+                declaration_has_multiple_clauses,
+            });
+        }
     });
 
     uses
 }
 
+/// Reads the `function`/`const` keyword of a use declaration or group member.
+fn use_kind_keyword(node: Node) -> Option<UseKind> {
+    (0..node.child_count())
+        .filter_map(|idx| node.child(idx))
+        .find_map(|child| match child.kind() {
+            "function" => Some(UseKind::Function),
+            "const" => Some(UseKind::Constant),
+            _ => None,
+        })
+}
+
 fn alias_node_from_clause<'a>(clause: Node<'a>) -> Option<Node<'a>> {
-    if let Some(alias_clause) = child_by_kind(clause, "namespace_aliasing_clause") {
-        if let Some(alias_name) = child_by_kind(alias_clause, "name") {
-            return Some(alias_name);
-        }
+    if let Some(alias_name) = child_by_kind(clause, "namespace_aliasing_clause")
+        .and_then(|alias| child_by_kind(alias, "name"))
+    {
+        return Some(alias_name);
     }
 
-    if let Some(qualified) = child_by_kind(clause, "qualified_name") {
-        return last_name_in_node(qualified);
+    if let Some(name) = child_by_kind(clause, "name") {
+        return Some(name);
     }
 
-    None
+    child_by_kind(clause, "qualified_name")
+        .or_else(|| child_by_kind(clause, "namespace_name"))
+        .and_then(last_name_in_node)
 }
 
 fn last_name_in_node<'a>(node: Node<'a>) -> Option<Node<'a>> {
     let mut last = None;
     for idx in 0..node.named_child_count() {
-        if let Some(child) = node.named_child(idx) {
-            if child.kind() == "name" {
-                last = Some(child);
-            }
+        if let Some(child) = node.named_child(idx)
+            && child.kind() == "name"
+        {
+            last = Some(child);
         }
     }
     last
@@ -227,6 +726,7 @@ fn span_from_node(node: Node) -> Span {
 fn collect_function_symbols(
     parsed: &parser::ParsedSource,
     namespace: Option<&str>,
+    uses: &[UseInfo],
 ) -> Vec<FunctionSymbol> {
     let mut symbols = Vec::new();
 
@@ -238,6 +738,7 @@ fn collect_function_symbols(
         if let Some(name_node) = child_by_kind(node, "name") {
             if let Some(name) = node_text(name_node, parsed) {
                 let fq = qualify_name(namespace, &name);
+                let phpdoc = extract_phpdoc_for_node(node, parsed).unwrap_or_default();
                 symbols.push(FunctionSymbol {
                     name,
                     fq_name: fq,
@@ -249,6 +750,12 @@ fn collect_function_symbols(
                     required_params: child_by_kind(node, "formal_parameters")
                         .map(count_required_parameters)
                         .unwrap_or(0),
+                    params: child_by_kind(node, "formal_parameters")
+                        .map(|formal| parameter_names(formal, parsed))
+                        .unwrap_or_default(),
+                    asserts: phpdoc.asserts,
+                    return_type: phpdoc.return_tag.map(|return_tag| return_tag.type_expr),
+                    constant_return: constant_return(node, parsed, namespace, uses),
                 });
             }
         }
@@ -257,18 +764,597 @@ fn collect_function_symbols(
     symbols
 }
 
+fn collect_class_symbols(
+    parsed: &parser::ParsedSource,
+    namespace: Option<&str>,
+    uses: &[UseInfo],
+) -> Vec<ClassSymbol> {
+    let mut classes = Vec::new();
+
+    walk_node(parsed.tree.root_node(), &mut |node| {
+        let anonymous = node.kind() == "object_creation_expression"
+            && child_by_kind(node, "declaration_list").is_some();
+        if !anonymous
+            && !matches!(
+                node.kind(),
+                "class_declaration" | "interface_declaration" | "trait_declaration"
+            )
+        {
+            return;
+        }
+
+        let resolve = |name_node: Node| {
+            node_text(name_node, parsed).map(|text| resolve_class_name(&text, namespace, uses))
+        };
+
+        let named = |clause: Option<Node>| -> Vec<String> {
+            clause
+                .map(|clause| {
+                    let mut cursor = clause.walk();
+                    clause
+                        .named_children(&mut cursor)
+                        .filter_map(resolve)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let extends = named(child_by_kind(node, "base_clause"));
+        let (parent, interfaces) = if node.kind() == "interface_declaration" {
+            (None, extends)
+        } else {
+            let implements = named(child_by_kind(node, "class_interface_clause"));
+            (extends.last().cloned(), implements)
+        };
+
+        let (name, fq_name, body) = if anonymous {
+            // `new class extends Foo { ... }`, named like PHP does: after the
+            // parent class or first interface, e.g. `Foo@anonymous`.
+            let named_after = parent.as_ref().or(interfaces.first());
+            let name = format!("{}@anonymous", named_after.map_or("class", String::as_str));
+            let body = child_by_kind(node, "declaration_list");
+            (name, anonymous_class_key(parsed, node), body)
+        } else {
+            let Some(name) = node
+                .child_by_field_name("name")
+                .and_then(|name_node| node_text(name_node, parsed))
+            else {
+                return;
+            };
+            let fq_name = qualify_name(namespace, &name);
+            (name, fq_name, node.child_by_field_name("body"))
+        };
+
+        let mut attributes = Vec::new();
+        if let Some(attribute_list) = child_by_kind(node, "attribute_list") {
+            walk_node(attribute_list, &mut |child| {
+                if child.kind() != "attribute" {
+                    return;
+                }
+                if let Some(attribute) = child.named_child(0).and_then(resolve) {
+                    attributes.push(attribute);
+                }
+            });
+        }
+
+        let mut traits = Vec::new();
+        let mut properties = Vec::new();
+        let mut methods = Vec::new();
+        let mut constants = Vec::new();
+        let mut constant_types = HashMap::new();
+        let mut constant_values = HashMap::new();
+        if let Some(body) = body {
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor) {
+                match member.kind() {
+                    "use_declaration" => {
+                        let mut names = member.walk();
+                        traits.extend(
+                            member
+                                .named_children(&mut names)
+                                .filter(|child| matches!(child.kind(), "name" | "qualified_name"))
+                                .filter_map(resolve),
+                        );
+                    }
+                    "property_declaration" => {
+                        let mut elements = member.walk();
+                        properties.extend(
+                            member
+                                .named_children(&mut elements)
+                                .filter(|child| child.kind() == "property_element")
+                                .filter_map(|element| child_by_kind(element, "variable_name"))
+                                .filter_map(|variable| node_text(variable, parsed))
+                                .map(|text| text.trim_start_matches('$').to_owned()),
+                        );
+                    }
+                    "const_declaration" => {
+                        for (name, value) in constant_declarations(member, parsed) {
+                            if let Some(value) = value
+                                && let Some(hint) = literal_constant_type(value)
+                            {
+                                constant_types.insert(name.clone(), hint);
+                                if let Some(text) = literal_constant_value(value, parsed) {
+                                    constant_values.insert(name.clone(), text);
+                                }
+                            }
+                            constants.push(name);
+                        }
+                    }
+                    "method_declaration" => {
+                        let Some(method) = child_by_kind(member, "name")
+                            .and_then(|name_node| node_text(name_node, parsed))
+                        else {
+                            continue;
+                        };
+                        if method.eq_ignore_ascii_case("__construct")
+                            && let Some(formal) = child_by_kind(member, "formal_parameters")
+                        {
+                            properties.extend(promoted_properties(formal, parsed));
+                        }
+                        methods.push(method);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        classes.push(ClassSymbol {
+            fq_name,
+            name,
+            file: parsed.path.clone(),
+            span: span_from_node(node),
+            parent,
+            interfaces,
+            traits,
+            attributes,
+            properties,
+            methods,
+            constants,
+            constant_types,
+            constant_values,
+        });
+    });
+
+    classes
+}
+
+/// The name an anonymous class (`new class { ... }`) is indexed under. Like
+/// PHP's internal name, it is made unique by the file and position.
+pub(crate) fn anonymous_class_key(parsed: &parser::ParsedSource, node: Node) -> String {
+    let start = node.start_position();
+    format!(
+        "class@anonymous{}:{}:{}",
+        parsed.path.display(),
+        start.row + 1,
+        start.column + 1
+    )
+}
+
+/// The names declared by a `const_declaration`, with their initialisers.
+fn constant_declarations<'a>(
+    declaration: Node<'a>,
+    parsed: &parser::ParsedSource,
+) -> Vec<(String, Option<Node<'a>>)> {
+    let mut cursor = declaration.walk();
+    declaration
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() == "const_element")
+        .filter_map(|element| {
+            let name = child_by_kind(element, "name").and_then(|name| node_text(name, parsed))?;
+            let value = element
+                .named_child(element.named_child_count().checked_sub(1)?)
+                .filter(|value| value.kind() != "name");
+            Some((name, value))
+        })
+        .collect()
+}
+
+/// The text of a literal initialiser, with quotes, case and digit separators
+/// normalised: `"a"` becomes `'a'`, `TRUE` becomes `true` and `1_000` `1000`.
+pub(crate) fn literal_constant_value(node: Node, parsed: &parser::ParsedSource) -> Option<String> {
+    literal_constant_type(node)?;
+    let text = node_text(node, parsed)?;
+    let value = match node.kind() {
+        "string" | "encapsed_string" => {
+            let inner = text.get(1..text.len().checked_sub(1)?)?;
+            if inner.contains(['\\', '$', '\'']) {
+                text
+            } else {
+                format!("'{inner}'")
+            }
+        }
+        _ => text
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '_')
+            .collect::<String>()
+            .to_ascii_lowercase(),
+    };
+    Some(value)
+}
+
+/// The constant every `return` of `function` yields. Functions that can end
+/// without a `return`, that are generators, or that are only declared when an
+/// enclosing block runs have none.
+fn constant_return(
+    function: Node,
+    parsed: &parser::ParsedSource,
+    namespace: Option<&str>,
+    uses: &[UseInfo],
+) -> Option<ConstantReturn> {
+    let mut ancestor = function.parent();
+    while let Some(node) = ancestor {
+        let top_level = match node.kind() {
+            "program" | "namespace_definition" => true,
+            "compound_statement" => node
+                .parent()
+                .is_some_and(|parent| parent.kind() == "namespace_definition"),
+            _ => false,
+        };
+        if !top_level {
+            return None;
+        }
+        ancestor = node.parent();
+    }
+
+    let body = function.child_by_field_name("body")?;
+    let mut cursor = body.walk();
+    let last = body.named_children(&mut cursor).last()?;
+    if last.kind() != "return_statement" {
+        return None;
+    }
+
+    let mut values = Vec::new();
+    let mut generator = false;
+    collect_returns(body, &mut values, &mut generator);
+    if generator {
+        return None;
+    }
+
+    let mut constant = None;
+    for value in values {
+        let value = value?;
+        let current = if value.kind() == "null" {
+            ConstantReturn::Literal("null".to_owned())
+        } else if value.kind() == "class_constant_access_expression" {
+            let class = node_text(value.named_child(0)?, parsed)?;
+            if matches!(
+                class.to_ascii_lowercase().as_str(),
+                "self" | "static" | "parent"
+            ) {
+                return None;
+            }
+            ConstantReturn::ClassConstant {
+                class: resolve_class_name(&class, namespace, uses),
+                name: node_text(value.named_child(1)?, parsed)?,
+            }
+        } else {
+            ConstantReturn::Literal(literal_constant_value(value, parsed)?)
+        };
+        if constant
+            .as_ref()
+            .is_some_and(|constant| *constant != current)
+        {
+            return None;
+        }
+        constant = Some(current);
+    }
+    constant
+}
+
+/// The returned expressions in `node`, outside nested functions and classes;
+/// `None` for a bare `return;`.
+fn collect_returns<'a>(node: Node<'a>, values: &mut Vec<Option<Node<'a>>>, generator: &mut bool) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        match child.kind() {
+            "function_definition"
+            | "anonymous_function_creation_expression"
+            | "arrow_function"
+            | "class_declaration"
+            | "object_creation_expression" => continue,
+            "yield_expression" => *generator = true,
+            "return_statement" => values.push(child.named_child(0)),
+            _ => {}
+        }
+        collect_returns(child, values, generator);
+    }
+}
+
+/// The type of a literal constant initialiser, including negated numbers.
+fn literal_constant_type(node: Node) -> Option<TypeHint> {
+    match node.kind() {
+        "string" | "encapsed_string" => Some(TypeHint::String),
+        "integer" => Some(TypeHint::Int),
+        "float" => Some(TypeHint::Float),
+        "boolean" => Some(TypeHint::Bool),
+        "unary_op_expression" => {
+            let sign = node.child(0)?;
+            let operand = node.named_child(0)?;
+            let numeric =
+                matches!(sign.kind(), "-" | "+") && matches!(operand.kind(), "integer" | "float");
+            numeric.then(|| literal_constant_type(operand)).flatten()
+        }
+        _ => None,
+    }
+}
+
+fn collect_enum_symbols(parsed: &parser::ParsedSource, namespace: Option<&str>) -> Vec<EnumSymbol> {
+    let mut enums = Vec::new();
+
+    walk_node(parsed.tree.root_node(), &mut |node| {
+        if node.kind() != "enum_declaration" {
+            return;
+        }
+        let Some(name) = node
+            .child_by_field_name("name")
+            .and_then(|name_node| node_text(name_node, parsed))
+        else {
+            return;
+        };
+
+        let backing = child_by_kind(node, "primitive_type")
+            .and_then(|backing| node_text(backing, parsed))
+            .and_then(|backing| match backing.to_ascii_lowercase().as_str() {
+                "int" => Some(TypeHint::Int),
+                "string" => Some(TypeHint::String),
+                _ => None,
+            });
+
+        let mut cases = Vec::new();
+        let mut constant_types = HashMap::new();
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor) {
+                match member.kind() {
+                    "enum_case" => cases.extend(
+                        member
+                            .child_by_field_name("name")
+                            .and_then(|name_node| node_text(name_node, parsed)),
+                    ),
+                    "const_declaration" => constant_types.extend(
+                        constant_declarations(member, parsed)
+                            .into_iter()
+                            .filter_map(|(name, value)| {
+                                Some((name, literal_constant_type(value?)?))
+                            }),
+                    ),
+                    _ => {}
+                }
+            }
+        }
+
+        enums.push(EnumSymbol {
+            fq_name: qualify_name(namespace, &name),
+            name,
+            file: parsed.path.clone(),
+            span: span_from_node(node),
+            backing,
+            cases,
+            constant_types,
+        });
+    });
+
+    enums
+}
+
+fn promoted_properties(formal: Node, parsed: &parser::ParsedSource) -> Vec<String> {
+    let mut cursor = formal.walk();
+    formal
+        .named_children(&mut cursor)
+        .filter(|param| param.kind() == "property_promotion_parameter")
+        .filter_map(|param| child_by_kind(param, "variable_name"))
+        .filter_map(|variable| node_text(variable, parsed))
+        .map(|text| text.trim_start_matches('$').to_owned())
+        .collect()
+}
+
+/// Resolves a class reference against the file's namespace and `use` imports.
+fn resolve_class_name(name: &str, namespace: Option<&str>, uses: &[UseInfo]) -> String {
+    if let Some(absolute) = name.strip_prefix('\\') {
+        return absolute.to_owned();
+    }
+
+    let (first, remainder) = match name.split_once('\\') {
+        Some((first, remainder)) => (first, Some(remainder)),
+        None => (name, None),
+    };
+
+    if let Some(use_info) = find_use(uses, UseKind::Class, first) {
+        let target = use_info.target.as_str();
+        return match remainder {
+            Some(remainder) => format!("{target}\\{remainder}"),
+            None => target.to_owned(),
+        };
+    }
+
+    qualify_name(namespace, name)
+}
+
 pub(crate) fn collect_file_metadata(parsed: &parser::ParsedSource) -> FileMetadata {
     let namespace = collect_namespace(parsed);
     let uses = collect_use_aliases(parsed);
-    let symbols = collect_function_symbols(parsed, namespace.as_deref());
+    let symbols = collect_function_symbols(parsed, namespace.as_deref(), &uses);
+    let classes = collect_class_symbols(parsed, namespace.as_deref(), &uses);
+    let enums = collect_enum_symbols(parsed, namespace.as_deref());
+    let mut globals = GlobalCollector::new(parsed);
+    globals.visit(parsed.tree.root_node());
 
     FileMetadata {
         namespace,
         uses,
         symbols,
+        classes,
+        enums,
+        globals: globals.sites,
+        global_reads: globals.reads,
     }
 }
 
+/// Records global variable activity: file-scope assignments and reads,
+/// `$GLOBALS['name']` accesses, and variables imported into functions with `global`.
+struct GlobalCollector<'a> {
+    parsed: &'a parser::ParsedSource,
+    sites: Vec<GlobalSite>,
+    reads: HashSet<String>,
+    /// `None` at file scope, otherwise the names the current function imported.
+    function_globals: Option<HashSet<String>>,
+}
+
+impl<'a> GlobalCollector<'a> {
+    fn new(parsed: &'a parser::ParsedSource) -> Self {
+        Self {
+            parsed,
+            sites: Vec::new(),
+            reads: HashSet::new(),
+            function_globals: None,
+        }
+    }
+
+    fn visit(&mut self, node: Node) {
+        match node.kind() {
+            "function_definition"
+            | "method_declaration"
+            | "anonymous_function_creation_expression" => {
+                // Closure `use ($x)` captures read from the enclosing scope.
+                if let Some(use_clause) = child_by_kind(node, "anonymous_function_use_clause") {
+                    self.visit_children(use_clause);
+                }
+
+                let outer = self.function_globals.replace(HashSet::new());
+                if let Some(body) = child_by_kind(node, "compound_statement") {
+                    self.visit(body);
+                }
+                self.function_globals = outer;
+                return;
+            }
+            "property_element" | "property_promotion_parameter" => return,
+            "global_declaration" => {
+                for idx in 0..node.named_child_count() {
+                    let Some(variable) = node.named_child(idx) else {
+                        continue;
+                    };
+                    let Some(name) = self.variable_name(variable) else {
+                        continue;
+                    };
+                    if let Some(imported) = self.function_globals.as_mut() {
+                        imported.insert(name.clone());
+                    }
+                    self.push_site(name, GlobalSiteKind::Declaration, variable);
+                }
+                return;
+            }
+            "subscript_expression" => {
+                if let Some(name) = self.superglobal_key(node) {
+                    if is_assignment_target(node) {
+                        self.push_site(name, GlobalSiteKind::SuperglobalWrite, node);
+                    } else {
+                        self.reads.insert(name);
+                    }
+                    return;
+                }
+            }
+            "variable_name" => {
+                if let Some(name) = self.variable_name(node) {
+                    self.record_variable(name, node);
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        self.visit_children(node);
+    }
+
+    fn visit_children(&mut self, node: Node) {
+        for idx in 0..node.child_count() {
+            if let Some(child) = node.child(idx) {
+                self.visit(child);
+            }
+        }
+    }
+
+    fn record_variable(&mut self, name: String, node: Node) {
+        if name == "this" {
+            return;
+        }
+
+        let kind = match &self.function_globals {
+            None => GlobalSiteKind::Assignment,
+            Some(imported) if imported.contains(&name) => GlobalSiteKind::DeclaredWrite,
+            Some(_) => return,
+        };
+
+        if is_assignment_target(node) {
+            self.push_site(name, kind, node);
+        } else {
+            self.reads.insert(name);
+        }
+    }
+
+    /// Returns `name` for `$GLOBALS['name']` with a literal key.
+    fn superglobal_key(&self, node: Node) -> Option<String> {
+        let base = node.named_child(0)?;
+        if base.kind() != "variable_name" || self.variable_name(base)? != "GLOBALS" {
+            return None;
+        }
+
+        let key = node.named_child(1)?;
+        if !matches!(key.kind(), "string" | "encapsed_string") {
+            return None;
+        }
+        let value = child_by_kind(key, "string_value")?;
+        node_text(value, self.parsed)
+    }
+
+    fn variable_name(&self, node: Node) -> Option<String> {
+        if node.kind() != "variable_name" {
+            return None;
+        }
+        node_text(node, self.parsed)
+            .map(|text| text.trim_start_matches('$').to_owned())
+            .filter(|name| !name.is_empty())
+    }
+
+    fn push_site(&mut self, name: String, kind: GlobalSiteKind, node: Node) {
+        self.sites.push(GlobalSite {
+            name,
+            kind,
+            span: span_from_node(node),
+        });
+    }
+}
+
+/// Whether `node` is the left-hand side of a plain assignment or a `foreach` target.
+fn is_assignment_target(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+
+    match parent.kind() {
+        "assignment_expression" => parent.child_by_field_name("left") == Some(node),
+        "foreach_statement" => parent.named_child(0) != Some(node),
+        "pair" => parent
+            .parent()
+            .is_some_and(|grandparent| grandparent.kind() == "foreach_statement"),
+        _ => false,
+    }
+}
+
+/// Normalizes a fully qualified function or class name into its index key.
+fn symbol_key(fq_name: &str) -> String {
+    fq_name.trim_start_matches('\\').to_ascii_lowercase()
+}
+
+/// Finds the import for `alias`; aliases match case-insensitively like the
+/// names they stand for, except constants.
+fn find_use<'a>(uses: &'a [UseInfo], kind: UseKind, alias: &str) -> Option<&'a UseInfo> {
+    uses.iter()
+        .find(|use_info| use_info.kind == kind && use_info.alias == alias)
+        .or_else(|| {
+            uses.iter()
+                .find(|use_info| use_info.kind == kind && kind.matches(&use_info.alias, alias))
+        })
+}
+
 fn qualify_name(namespace: Option<&str>, name: &str) -> String {
     match namespace {
         Some(ns) => format!("{ns}\\{name}"),
@@ -284,6 +1370,16 @@ fn count_required_parameters<'a>(formal: Node<'a>) -> usize {
         .count()
 }
 
+fn parameter_names(formal: Node, parsed: &parser::ParsedSource) -> Vec<String> {
+    (0..formal.named_child_count())
+        .filter_map(|idx| formal.named_child(idx))
+        .filter(|param| param.kind().ends_with("_parameter"))
+        .filter_map(|param| child_by_kind(param, "variable_name"))
+        .filter_map(|variable| node_text(variable, parsed))
+        .map(|name| name.trim_start_matches('$').to_string())
+        .collect()
+}
+
 fn parameter_has_default<'a>(param: Node<'a>) -> bool {
     for idx in 0..param.named_child_count() {
         if let Some(child) = param.named_child(idx) {
@@ -296,7 +1392,7 @@ fn parameter_has_default<'a>(param: Node<'a>) -> bool {
     false
 }
 
-fn candidate_function_names(name: &str, scope: &FileScope) -> Vec<String> {
+fn candidate_function_names(name: &str, namespace: Option<&str>, uses: &[UseInfo]) -> Vec<String> {
     let mut candidates = Vec::new();
     let normalized = name.trim_start_matches('\\');
     let segments: Vec<&str> = normalized.split('\\').collect();
@@ -310,15 +1406,17 @@ fn candidate_function_names(name: &str, scope: &FileScope) -> Vec<String> {
     if name.starts_with('\\') {
         candidates.push(normalized.to_owned());
     } else {
-        if let Some(use_info) = scope.uses.get(first) {
-            if remainder.is_empty() {
+        // Unqualified calls consult `use function` imports; qualified ones
+        // resolve their first segment through class/namespace imports.
+        if remainder.is_empty() {
+            if let Some(use_info) = find_use(uses, UseKind::Function, first) {
                 candidates.push(use_info.target.clone());
-            } else {
-                candidates.push(format!("{}\\{}", use_info.target, remainder));
             }
+        } else if let Some(use_info) = find_use(uses, UseKind::Class, first) {
+            candidates.push(format!("{}\\{}", use_info.target, remainder));
         }
 
-        if let Some(ns) = &scope.namespace {
+        if let Some(ns) = namespace {
             candidates.push(format!("{ns}\\{normalized}"));
         }
     }
@@ -0,0 +1,476 @@
+use super::{
+    UseInfo, UseKind, anonymous_class_key, candidate_function_names, child_by_kind,
+    last_name_in_node, node_text, qualify_name, resolve_class_name, span_from_node,
+    use_kind_keyword,
+};
+use crate::analyzer::{Span, parser};
+use std::path::PathBuf;
+use tree_sitter::Node;
+
+/// What a [`SymbolReference`] points at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferenceKind {
+    Function,
+    Class,
+    Method,
+}
+
+/// A place where a function, class or method name is written, declarations included.
+///
+/// The span covers only the identifier (the last segment of a qualified name), so
+/// renaming a symbol means rewriting exactly these ranges.
+#[derive(Clone)]
+#[allow(dead_code)]
+pub struct SymbolReference {
+    pub kind: ReferenceKind,
+    pub file: PathBuf,
+    pub span: Span,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// Whether this site declares the symbol rather than using it.
+    pub declaration: bool,
+    pub(super) target: ReferenceTarget,
+}
+
+#[derive(Clone)]
+pub(super) enum ReferenceTarget {
+    /// Candidate fully qualified names in PHP's lookup order (namespace, then global).
+    Function(Vec<String>),
+    Class(String),
+    /// `class` is `None` when the receiver's type is unknown, e.g. `$service->run()`.
+    Method {
+        class: Option<String>,
+        name: String,
+    },
+}
+
+#[allow(dead_code)]
+impl SymbolReference {
+    /// False for method calls on receivers whose class could not be determined;
+    /// those are matched by method name alone.
+    pub fn is_exact(&self) -> bool {
+        !matches!(self.target, ReferenceTarget::Method { class: None, .. })
+    }
+
+    /// Lowercased short name the reference is indexed under.
+    pub(super) fn index_key(&self) -> String {
+        let name = match &self.target {
+            ReferenceTarget::Function(candidates) => {
+                candidates.last().map(String::as_str).unwrap_or_default()
+            }
+            ReferenceTarget::Class(class) => class.as_str(),
+            ReferenceTarget::Method { name, .. } => name.as_str(),
+        };
+        short_name_key(name)
+    }
+}
+
+pub(super) fn short_name_key(name: &str) -> String {
+    name.rsplit('\\')
+        .next()
+        .unwrap_or(name)
+        .to_ascii_lowercase()
+}
+
+/// Collects every function, class and method reference in a file.
+pub(super) fn collect_references(
+    parsed: &parser::ParsedSource,
+    namespace: Option<&str>,
+    uses: &[UseInfo],
+) -> Vec<SymbolReference> {
+    let mut collector = ReferenceCollector {
+        parsed,
+        namespace,
+        uses,
+        classes: Vec::new(),
+        references: Vec::new(),
+    };
+    collector.visit(parsed.tree.root_node());
+    collector.references
+}
+
+/// The class being walked, used to resolve `self`, `static`, `parent` and `$this`.
+struct ClassContext {
+    fq_name: Option<String>,
+    parent: Option<String>,
+}
+
+struct ReferenceCollector<'a> {
+    parsed: &'a parser::ParsedSource,
+    namespace: Option<&'a str>,
+    uses: &'a [UseInfo],
+    classes: Vec<ClassContext>,
+    references: Vec<SymbolReference>,
+}
+
+impl ReferenceCollector<'_> {
+    fn visit(&mut self, node: Node) {
+        match node.kind() {
+            "class_declaration"
+            | "interface_declaration"
+            | "trait_declaration"
+            | "enum_declaration" => {
+                let name = node.child_by_field_name("name");
+                let fq_name = name
+                    .and_then(|name| node_text(name, self.parsed))
+                    .map(|name| qualify_name(self.namespace, &name));
+                if let (Some(name), Some(fq_name)) = (name, &fq_name) {
+                    self.push(name, ReferenceTarget::Class(fq_name.clone()), true);
+                }
+                let parent = child_by_kind(node, "base_clause")
+                    .and_then(|clause| clause.named_children(&mut clause.walk()).last())
+                    .and_then(|parent| self.resolve_class(parent));
+
+                self.classes.push(ClassContext { fq_name, parent });
+                self.visit_children(node);
+                self.classes.pop();
+                return;
+            }
+            "object_creation_expression" if child_by_kind(node, "declaration_list").is_some() => {
+                // Anonymous classes: `self` inside them is not the enclosing class.
+                let parent = child_by_kind(node, "base_clause")
+                    .and_then(|clause| clause.named_children(&mut clause.walk()).last())
+                    .and_then(|parent| self.resolve_class(parent));
+                self.classes.push(ClassContext {
+                    fq_name: Some(anonymous_class_key(self.parsed, node)),
+                    parent,
+                });
+                self.visit_children(node);
+                self.classes.pop();
+                return;
+            }
+            "function_definition" => {
+                if let Some(name) = node.child_by_field_name("name")
+                    && let Some(text) = node_text(name, self.parsed)
+                {
+                    let fq_name = qualify_name(self.namespace, &text);
+                    self.push(name, ReferenceTarget::Function(vec![fq_name]), true);
+                }
+            }
+            "method_declaration" => {
+                if let Some(name) = node.child_by_field_name("name")
+                    && let Some(text) = node_text(name, self.parsed)
+                {
+                    let class = self.classes.last().and_then(|class| class.fq_name.clone());
+                    self.push(name, ReferenceTarget::Method { class, name: text }, true);
+                }
+            }
+            "function_call_expression" => {
+                if let Some(function) = node
+                    .child_by_field_name("function")
+                    .filter(|function| matches!(function.kind(), "name" | "qualified_name"))
+                    && let Some(text) = node_text(function, self.parsed)
+                {
+                    let candidates = candidate_function_names(&text, self.namespace, self.uses);
+                    self.push(function, ReferenceTarget::Function(candidates), false);
+                }
+            }
+            "scoped_call_expression" => {
+                if let Some(scope) = node.child_by_field_name("scope")
+                    && let Some(name) = node.child_by_field_name("name")
+                    && name.kind() == "name"
+                    && let Some(text) = node_text(name, self.parsed)
+                {
+                    let class = self.resolve_scope(scope);
+                    self.push(name, ReferenceTarget::Method { class, name: text }, false);
+                }
+            }
+            "member_call_expression" | "nullsafe_member_call_expression" => {
+                if let Some(object) = node.child_by_field_name("object")
+                    && let Some(name) = node.child_by_field_name("name")
+                    && name.kind() == "name"
+                    && let Some(text) = node_text(name, self.parsed)
+                {
+                    let class = (node_text(object, self.parsed).as_deref() == Some("$this"))
+                        .then(|| self.classes.last().and_then(|class| class.fq_name.clone()))
+                        .flatten();
+                    self.push(name, ReferenceTarget::Method { class, name: text }, false);
+                }
+            }
+            "namespace_use_declaration" => {
+                self.visit_use_declaration(node);
+                return;
+            }
+            "name" | "qualified_name" if is_class_position(node) => {
+                if let Some(class) = self.resolve_class(node) {
+                    self.push(node, ReferenceTarget::Class(class), false);
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        self.visit_children(node);
+    }
+
+    fn visit_children(&mut self, node: Node) {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(child);
+        }
+    }
+
+    /// `use Foo\Bar;` and `use function Foo\bar;` refer to the imported symbol.
+    fn visit_use_declaration(&mut self, node: Node) {
+        let declaration_kind = use_kind_keyword(node).unwrap_or(UseKind::Class);
+        let prefix = child_by_kind(node, "namespace_name")
+            .and_then(|prefix| node_text(prefix, self.parsed))
+            .map(|prefix| format!("{}\\", prefix.trim_start_matches('\\')))
+            .unwrap_or_default();
+        let clauses = child_by_kind(node, "namespace_use_group").unwrap_or(node);
+
+        let mut cursor = clauses.walk();
+        for clause in clauses.named_children(&mut cursor) {
+            if !matches!(
+                clause.kind(),
+                "namespace_use_clause" | "namespace_use_group_clause"
+            ) {
+                continue;
+            }
+            let Some(name) = child_by_kind(clause, "qualified_name")
+                .or_else(|| child_by_kind(clause, "namespace_name"))
+                .or_else(|| child_by_kind(clause, "name"))
+            else {
+                continue;
+            };
+            let Some(text) = node_text(name, self.parsed) else {
+                continue;
+            };
+
+            let target = format!("{prefix}{}", text.trim_start_matches('\\'));
+            let target = match use_kind_keyword(clause).unwrap_or(declaration_kind) {
+                UseKind::Class => ReferenceTarget::Class(target),
+                UseKind::Function => ReferenceTarget::Function(vec![target]),
+                UseKind::Constant => continue,
+            };
+            self.push(name, target, false);
+        }
+    }
+
+    fn push(&mut self, node: Node, target: ReferenceTarget, declaration: bool) {
+        let identifier = match node.kind() {
+            "qualified_name" | "namespace_name" => last_name_in_node(node).unwrap_or(node),
+            _ => node,
+        };
+        let kind = match target {
+            ReferenceTarget::Function(_) => ReferenceKind::Function,
+            ReferenceTarget::Class(_) => ReferenceKind::Class,
+            ReferenceTarget::Method { .. } => ReferenceKind::Method,
+        };
+
+        self.references.push(SymbolReference {
+            kind,
+            file: self.parsed.path.clone(),
+            span: span_from_node(identifier),
+            start_byte: identifier.start_byte(),
+            end_byte: identifier.end_byte(),
+            declaration,
+            target,
+        });
+    }
+
+    fn resolve_class(&self, node: Node) -> Option<String> {
+        let text = node_text(node, self.parsed)?;
+        Some(resolve_class_name(&text, self.namespace, self.uses))
+    }
+
+    /// Resolves the class on the left of `::`, following `self`/`static`/`parent`.
+    fn resolve_scope(&self, scope: Node) -> Option<String> {
+        let current = self.classes.last();
+        match scope.kind() {
+            "relative_scope" => {
+                match node_text(scope, self.parsed)?.to_ascii_lowercase().as_str() {
+                    "parent" => current?.parent.clone(),
+                    _ => current?.fq_name.clone(),
+                }
+            }
+            "name" | "qualified_name" => self.resolve_class(scope),
+            _ => None,
+        }
+    }
+}
+
+/// Positions where a bare name refers to a class: `new Foo`, type hints,
+/// `extends`/`implements`, trait `use`, attributes, `instanceof` and `Foo::`.
+fn is_class_position(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+
+    match parent.kind() {
+        "named_type" | "base_clause" | "class_interface_clause" | "use_declaration" => true,
+        "object_creation_expression"
+        | "attribute"
+        | "scoped_call_expression"
+        | "class_constant_access_expression"
+        | "scoped_property_access_expression" => parent.named_child(0) == Some(node),
+        "binary_expression" => {
+            parent.child_by_field_name("right") == Some(node)
+                && parent
+                    .child_by_field_name("operator")
+                    .is_some_and(|operator| operator.kind() == "instanceof")
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::project::ProjectContext;
+    use crate::analyzer::rules::test_utils::parse_php_with_path;
+
+    fn project() -> ProjectContext {
+        let mut context = ProjectContext::new();
+        context.insert(parse_php_with_path(
+            r#"<?php
+namespace Lib;
+
+function helper(): void {}
+
+class Base
+{
+    public function boot(): void {}
+}
+"#,
+            "src/Lib.php",
+        ));
+        context.insert(parse_php_with_path(
+            r#"<?php
+namespace App;
+
+use Lib\Base;
+use function Lib\helper;
+
+class Service extends Base
+{
+    public function run(Base $base): void
+    {
+        helper();
+        \Lib\helper();
+        strlen('x');
+        $this->boot();
+        parent::boot();
+        Service::boot();
+        $base->boot();
+        if ($base instanceof Base) {}
+    }
+}
+"#,
+            "src/Service.php",
+        ));
+        context
+    }
+
+    fn locations(references: Vec<&SymbolReference>) -> Vec<(String, usize, usize, bool)> {
+        let mut locations: Vec<_> = references
+            .into_iter()
+            .map(|reference| {
+                (
+                    reference.file.display().to_string(),
+                    reference.span.start.row,
+                    reference.span.start.column,
+                    reference.declaration,
+                )
+            })
+            .collect();
+        locations.sort();
+        locations
+    }
+
+    #[test]
+    fn test_function_references() {
+        let context = project();
+        let references = context.function_references("\\Lib\\helper");
+
+        assert!(references.iter().all(|r| r.kind == ReferenceKind::Function));
+        assert_eq!(
+            locations(references),
+            vec![
+                ("src/Lib.php".into(), 3, 9, true),
+                ("src/Service.php".into(), 4, 17, false),
+                ("src/Service.php".into(), 10, 8, false),
+                ("src/Service.php".into(), 11, 13, false),
+            ]
+        );
+        assert_eq!(context.function_references("strlen").len(), 1);
+    }
+
+    #[test]
+    fn test_references_are_indexed_on_first_query() {
+        let mut context = project();
+        assert!(context.references.get().is_none());
+
+        assert_eq!(context.function_references("\\Lib\\helper").len(), 4);
+        assert!(context.references.get().is_some());
+
+        context.insert(parse_php_with_path(
+            "<?php\n\\Lib\\helper();\n",
+            "src/bootstrap.php",
+        ));
+        assert!(context.references.get().is_none());
+        assert_eq!(context.function_references("\\Lib\\helper").len(), 5);
+    }
+
+    #[test]
+    fn test_class_references_cover_identifier_only() {
+        let context = project();
+        let references = context.class_references("Lib\\Base");
+
+        assert_eq!(references.len(), 5);
+        let import = references
+            .iter()
+            .find(|reference| reference.span.start.row == 3)
+            .expect("use clause should be a reference");
+        let source = context.get(&import.file).unwrap().source.clone();
+        assert_eq!(&source[import.start_byte..import.end_byte], "Base");
+    }
+
+    #[test]
+    fn test_method_references_follow_inheritance() {
+        let context = project();
+        let references = context.method_references("Lib\\Base", "boot");
+
+        assert_eq!(
+            locations(references.clone()),
+            vec![
+                ("src/Lib.php".into(), 7, 20, true),
+                ("src/Service.php".into(), 13, 15, false),
+                ("src/Service.php".into(), 14, 16, false),
+                ("src/Service.php".into(), 15, 17, false),
+                ("src/Service.php".into(), 16, 15, false),
+            ]
+        );
+        let inexact: Vec<_> = references.iter().filter(|r| !r.is_exact()).collect();
+        assert_eq!(inexact.len(), 1);
+        assert_eq!(context.method_references("App\\Service", "boot").len(), 1);
+    }
+
+    #[test]
+    fn test_method_references_inside_anonymous_classes() {
+        let mut context = project();
+        context.insert(parse_php_with_path(
+            r#"<?php
+use Lib\Base;
+
+$job = new class extends Base {
+    public function handle(): void
+    {
+        $this->boot();
+        parent::boot();
+    }
+};
+"#,
+            "src/job.php",
+        ));
+
+        let references = context.method_references("Lib\\Base", "boot");
+        let calls: Vec<_> = references
+            .iter()
+            .filter(|reference| reference.file.ends_with("job.php"))
+            .collect();
+        assert_eq!(calls.len(), 2);
+        assert!(calls.iter().all(|reference| reference.is_exact()));
+        assert!(context.method_references("Lib\\Base", "handle").is_empty());
+    }
+}
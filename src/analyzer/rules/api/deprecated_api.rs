@@ -1,22 +1,320 @@
 use super::DiagnosticRule;
 use super::helpers::{child_by_kind, diagnostic_for_node, node_text, walk_node};
+use crate::analyzer::config::DeprecatedApiConfig;
+use crate::analyzer::fix;
 use crate::analyzer::project::ProjectContext;
 use crate::analyzer::{Severity, parser};
+use tree_sitter::Node;
 
-const DEPRECATED_APIS: &[&str] = &[
-    "mysql_query",
-    "mysql_connect",
-    "mysql_pconnect",
-    "each",
-    "create_function",
+/// How `--fix` can rewrite a call to a deprecated function.
+#[derive(Clone, Copy)]
+enum FunctionFix {
+    None,
+    /// Same arguments, new name.
+    Rename,
+    /// Rename only when the first argument is a literal without regex syntax
+    /// (`split('-', $s)` → `explode('-', $s)`).
+    LiteralPattern,
+    /// Single-argument call rewritten to the replacement with extra trailing arguments.
+    AppendArguments(&'static str),
+    /// Argument-less call replaced by a constant expression.
+    Constant(&'static str),
+    /// `while (list($k, $v) = each($a))` becomes `foreach ($a as $k => $v)`.
+    ForeachLoop,
+}
+
+struct DeprecatedFunction {
+    name: String,
+    replacement: Option<String>,
+    message: Option<String>,
+    fix: FunctionFix,
+}
+
+const DEPRECATED_FUNCTIONS: &[(&str, Option<&str>, FunctionFix)] = &[
+    ("mysql_query", None, FunctionFix::None),
+    ("mysql_connect", None, FunctionFix::None),
+    ("mysql_pconnect", None, FunctionFix::None),
+    ("each", Some("foreach"), FunctionFix::ForeachLoop),
+    ("create_function", None, FunctionFix::None),
+    ("split", Some("explode"), FunctionFix::LiteralPattern),
+    (
+        "utf8_encode",
+        Some("mb_convert_encoding"),
+        FunctionFix::AppendArguments("'UTF-8', 'ISO-8859-1'"),
+    ),
+    (
+        "utf8_decode",
+        Some("mb_convert_encoding"),
+        FunctionFix::AppendArguments("'ISO-8859-1', 'UTF-8'"),
+    ),
+    (
+        "get_magic_quotes_gpc",
+        Some("false"),
+        FunctionFix::Constant("false"),
+    ),
+    (
+        "get_magic_quotes_runtime",
+        Some("false"),
+        FunctionFix::Constant("false"),
+    ),
+    (
+        "strftime",
+        Some("date() or IntlDateFormatter"),
+        FunctionFix::None,
+    ),
+    (
+        "gmstrftime",
+        Some("gmdate() or IntlDateFormatter"),
+        FunctionFix::None,
+    ),
+    ("money_format", Some("NumberFormatter"), FunctionFix::None),
 ];
 
-pub struct DeprecatedApiRule;
+const DEPRECATED_INI_SETTINGS: &[&str] = &[
+    "allow_url_include",
+    "assert.active",
+    "assert.bail",
+    "assert.callback",
+    "assert.exception",
+    "assert.warning",
+    "mbstring.func_overload",
+    "session.referer_check",
+    "session.sid_bits_per_character",
+    "session.sid_length",
+    "session.trans_sid_hosts",
+    "session.trans_sid_tags",
+    "session.use_only_cookies",
+    "session.use_trans_sid",
+    "track_errors",
+];
+
+const INI_FUNCTIONS: &[&str] = &["ini_set", "ini_get", "ini_alter", "ini_restore"];
+
+/// Reports deprecated functions, methods, classes and ini settings, and rewrites
+/// the ones with a mechanical replacement.
+pub struct DeprecatedApiRule {
+    functions: Vec<DeprecatedFunction>,
+    config: DeprecatedApiConfig,
+}
 
 impl DeprecatedApiRule {
-    pub fn new() -> Self {
-        Self
+    pub fn new(config: DeprecatedApiConfig) -> Self {
+        let mut functions: Vec<DeprecatedFunction> = DEPRECATED_FUNCTIONS
+            .iter()
+            .map(|(name, replacement, fix)| DeprecatedFunction {
+                name: name.to_string(),
+                replacement: replacement.map(str::to_string),
+                message: None,
+                fix: *fix,
+            })
+            .collect();
+        functions.extend(
+            config
+                .functions
+                .iter()
+                .map(|(name, deprecation)| DeprecatedFunction {
+                    name: name.trim_start_matches('\\').to_lowercase(),
+                    replacement: deprecation.replacement.clone(),
+                    message: deprecation.message.clone(),
+                    fix: if deprecation.replacement.is_some() {
+                        FunctionFix::Rename
+                    } else {
+                        FunctionFix::None
+                    },
+                }),
+        );
+
+        Self { functions, config }
+    }
+
+    fn findings<'a>(
+        &self,
+        parsed: &'a parser::ParsedSource,
+        context: &ProjectContext,
+    ) -> Vec<Finding<'a>> {
+        let scope = context.scope_for(&parsed.path);
+        let resolve = |name: &str| match scope {
+            Some(scope) => scope.resolve_class(name),
+            None => name.trim_start_matches('\\').to_string(),
+        };
+
+        let mut findings = Vec::new();
+        walk_node(parsed.tree.root_node(), &mut |node| match node.kind() {
+            "function_call_expression" => {
+                findings.extend(self.function_finding(node, parsed));
+                findings.extend(self.ini_finding(node, parsed));
+            }
+            "scoped_call_expression" => {
+                if let Some(finding) = self.method_finding(node, parsed, &resolve) {
+                    findings.push(finding);
+                } else if let Some(scope) = node.named_child(0) {
+                    findings.extend(self.class_finding(scope, parsed, &resolve));
+                }
+            }
+            "name" | "qualified_name" if is_class_reference(node) => {
+                findings.extend(self.class_finding(node, parsed, &resolve));
+            }
+            _ => {}
+        });
+        findings
+    }
+
+    fn function_finding<'a>(
+        &self,
+        call: Node<'a>,
+        parsed: &parser::ParsedSource,
+    ) -> Option<Finding<'a>> {
+        let name_node = call
+            .child_by_field_name("function")
+            .filter(|node| matches!(node.kind(), "name" | "qualified_name"))?;
+        let name = node_text(name_node, parsed)?;
+        let key = name.trim_start_matches('\\').to_lowercase();
+        let deprecated = self.functions.iter().find(|entry| entry.name == key)?;
+
+        let message = match (&deprecated.message, &deprecated.replacement) {
+            (Some(message), _) => format!("{name} is deprecated; {message}"),
+            (None, Some(replacement)) => format!("{name} is deprecated; use {replacement} instead"),
+            (None, None) => format!("{name} is deprecated; use modern alternatives"),
+        };
+        let edit = function_fix(call, name_node, deprecated, parsed);
+
+        Some(Finding {
+            node: name_node,
+            message,
+            edit,
+        })
+    }
+
+    fn ini_finding<'a>(
+        &self,
+        call: Node<'a>,
+        parsed: &parser::ParsedSource,
+    ) -> Option<Finding<'a>> {
+        let function = call
+            .child_by_field_name("function")
+            .and_then(|node| node_text(node, parsed))?;
+        let function = function.trim_start_matches('\\').to_lowercase();
+        if !INI_FUNCTIONS.contains(&function.as_str()) {
+            return None;
+        }
+
+        let setting_node = call_arguments(call)
+            .first()
+            .and_then(|argument| argument.named_child(0))
+            .filter(|value| matches!(value.kind(), "string" | "encapsed_string"))?;
+        let setting = string_value(setting_node, parsed)?;
+
+        let message = match self.config.ini.get(&setting) {
+            Some(Some(replacement)) => {
+                format!("ini setting '{setting}' is deprecated; use {replacement} instead")
+            }
+            Some(None) => format!("ini setting '{setting}' is deprecated"),
+            None if DEPRECATED_INI_SETTINGS.contains(&setting.as_str()) => {
+                format!("ini setting '{setting}' is deprecated")
+            }
+            None => return None,
+        };
+
+        Some(Finding {
+            node: setting_node,
+            message,
+            edit: None,
+        })
     }
+
+    fn method_finding<'a>(
+        &self,
+        call: Node<'a>,
+        parsed: &parser::ParsedSource,
+        resolve: &dyn Fn(&str) -> String,
+    ) -> Option<Finding<'a>> {
+        let scope = call.child_by_field_name("scope")?;
+        let method = call.child_by_field_name("name")?;
+        let class_name = node_text(scope, parsed)?;
+        if is_relative_class(&class_name) {
+            return None;
+        }
+        let method_name = node_text(method, parsed)?;
+        let class = resolve(&class_name);
+
+        let replacement = lookup(&self.config.methods, |key| {
+            key.split_once("::").is_some_and(|(key_class, key_method)| {
+                same_name(key_class, &class) && key_method.eq_ignore_ascii_case(&method_name)
+            })
+        })?;
+
+        let edit = replacement.as_deref().and_then(|replacement| {
+            let (new_class, new_method) = replacement.split_once("::")?;
+            let new_class = if same_name(new_class, &class) {
+                class_name.clone()
+            } else {
+                format!("\\{}", new_class.trim_start_matches('\\'))
+            };
+            Some(fix::TextEdit::new(
+                scope.start_byte(),
+                method.end_byte(),
+                format!("{new_class}::{new_method}"),
+            ))
+        });
+
+        let message = match &replacement {
+            Some(replacement) => format!(
+                "{class}::{method_name} is deprecated; use {} instead",
+                replacement.trim_start_matches('\\')
+            ),
+            None => format!("{class}::{method_name} is deprecated"),
+        };
+
+        Some(Finding {
+            node: method,
+            message,
+            edit,
+        })
+    }
+
+    fn class_finding<'a>(
+        &self,
+        node: Node<'a>,
+        parsed: &parser::ParsedSource,
+        resolve: &dyn Fn(&str) -> String,
+    ) -> Option<Finding<'a>> {
+        if !matches!(node.kind(), "name" | "qualified_name") {
+            return None;
+        }
+        let written = node_text(node, parsed)?;
+        if is_relative_class(&written) {
+            return None;
+        }
+        let class = resolve(&written);
+
+        let replacement = lookup(&self.config.classes, |key| same_name(key, &class))?;
+        let message = match &replacement {
+            Some(replacement) => format!(
+                "class {class} is deprecated; use {} instead",
+                replacement.trim_start_matches('\\')
+            ),
+            None => format!("class {class} is deprecated"),
+        };
+        let edit = replacement.map(|replacement| {
+            fix::TextEdit::new(
+                node.start_byte(),
+                node.end_byte(),
+                format!("\\{}", replacement.trim_start_matches('\\')),
+            )
+        });
+
+        Some(Finding {
+            node,
+            message,
+            edit,
+        })
+    }
+}
+
+struct Finding<'a> {
+    node: Node<'a>,
+    message: String,
+    edit: Option<fix::TextEdit>,
 }
 
 impl DiagnosticRule for DeprecatedApiRule {
@@ -24,40 +322,222 @@ impl DiagnosticRule for DeprecatedApiRule {
         "api/deprecated_api"
     }
 
+    fn description(&self) -> &str {
+        "Calls to deprecated or removed functions, methods, classes and ini settings"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
-        _context: &ProjectContext,
+        context: &ProjectContext,
     ) -> Vec<crate::analyzer::Diagnostic> {
-        let mut diagnostics = Vec::new();
+        self.findings(parsed, context)
+            .into_iter()
+            .map(|finding| {
+                diagnostic_for_node(parsed, finding.node, Severity::Warning, finding.message)
+            })
+            .collect()
+    }
 
-        walk_node(parsed.tree.root_node(), &mut |node| {
-            if node.kind() != "function_call_expression" {
-                return;
-            }
+    fn fix(&self, parsed: &parser::ParsedSource, context: &ProjectContext) -> Vec<fix::TextEdit> {
+        self.findings(parsed, context)
+            .into_iter()
+            .filter_map(|finding| finding.edit)
+            .collect()
+    }
+}
 
-            if let Some(name_node) = child_by_kind(node, "name") {
-                if let Some(name) = node_text(name_node, parsed) {
-                    if DEPRECATED_APIS.contains(&name.as_str()) {
-                        diagnostics.push(diagnostic_for_node(
-                            parsed,
-                            name_node,
-                            Severity::Warning,
-                            format!("{} is deprecated; use modern alternatives", name),
-                        ));
-                    }
-                }
+fn function_fix(
+    call: Node,
+    name_node: Node,
+    deprecated: &DeprecatedFunction,
+    parsed: &parser::ParsedSource,
+) -> Option<fix::TextEdit> {
+    let replacement = deprecated.replacement.as_deref()?;
+    let arguments = call_arguments(call);
+
+    match deprecated.fix {
+        FunctionFix::None => None,
+        FunctionFix::Rename => Some(rename(name_node, replacement)),
+        FunctionFix::LiteralPattern => {
+            let pattern = arguments
+                .first()
+                .and_then(|argument| argument.named_child(0))
+                .filter(|value| value.kind() == "string")
+                .and_then(|value| string_value(value, parsed))?;
+            let is_plain =
+                !pattern.is_empty() && !pattern.contains(|c: char| ".[](){}*+?|^$\\".contains(c));
+            is_plain.then(|| rename(name_node, replacement))
+        }
+        FunctionFix::AppendArguments(extra) => {
+            let [argument] = arguments.as_slice() else {
+                return None;
+            };
+            let argument = node_text(*argument, parsed)?;
+            Some(fix::TextEdit::new(
+                call.start_byte(),
+                call.end_byte(),
+                format!("{replacement}({argument}, {extra})"),
+            ))
+        }
+        FunctionFix::Constant(value) => arguments
+            .is_empty()
+            .then(|| fix::TextEdit::new(call.start_byte(), call.end_byte(), value.to_string())),
+        FunctionFix::ForeachLoop => foreach_fix(call, parsed),
+    }
+}
+
+fn rename(name_node: Node, replacement: &str) -> fix::TextEdit {
+    fix::TextEdit::new(
+        name_node.start_byte(),
+        name_node.end_byte(),
+        replacement.to_string(),
+    )
+}
+
+/// Rewrites `while (list($key, $value) = each($array))` into a `foreach` header.
+fn foreach_fix(each_call: Node, parsed: &parser::ParsedSource) -> Option<fix::TextEdit> {
+    let assignment = each_call
+        .parent()
+        .filter(|parent| parent.kind() == "assignment_expression")?;
+    if assignment.child_by_field_name("right") != Some(each_call) {
+        return None;
+    }
+    let condition = assignment
+        .parent()
+        .filter(|parent| parent.kind() == "parenthesized_expression")?;
+    let loop_node = condition
+        .parent()
+        .filter(|parent| parent.kind() == "while_statement")?;
+
+    let [array] = call_arguments(each_call)[..] else {
+        return None;
+    };
+    let array = node_text(array, parsed)?;
+    let list = assignment.child_by_field_name("left")?;
+    let (key, value) = list_slots(list, parsed)?;
+
+    let header = match (key, value) {
+        (Some(key), Some(value)) => format!("foreach ({array} as {key} => {value})"),
+        (None, Some(value)) => format!("foreach ({array} as {value})"),
+        (Some(key), None) => format!("foreach (array_keys({array}) as {key})"),
+        (None, None) => return None,
+    };
+
+    Some(fix::TextEdit::new(
+        loop_node.start_byte(),
+        condition.end_byte(),
+        header,
+    ))
+}
+
+/// The first two slots of `list($a, $b)` / `[$a, $b]`; skipped slots are `None`.
+fn list_slots(
+    list: Node,
+    parsed: &parser::ParsedSource,
+) -> Option<(Option<String>, Option<String>)> {
+    if !matches!(list.kind(), "list_literal" | "array_creation_expression") {
+        return None;
+    }
+
+    let mut slots: Vec<Option<String>> = vec![None];
+    for idx in 0..list.child_count() {
+        let child = list.child(idx)?;
+        match child.kind() {
+            "," => slots.push(None),
+            "variable_name" => *slots.last_mut()? = node_text(child, parsed),
+            "array_element_initializer" => {
+                let variable = child
+                    .named_child(0)
+                    .filter(|variable| variable.kind() == "variable_name")?;
+                *slots.last_mut()? = node_text(variable, parsed);
             }
-        });
+            "list" | "(" | ")" | "[" | "]" => {}
+            _ => return None,
+        }
+    }
+
+    if slots.len() > 2 {
+        return None;
+    }
+    let key = slots.first().cloned().flatten();
+    let value = slots.get(1).cloned().flatten();
+    Some((key, value))
+}
+
+fn call_arguments(call: Node) -> Vec<Node> {
+    let Some(arguments) = child_by_kind(call, "arguments") else {
+        return Vec::new();
+    };
+
+    (0..arguments.named_child_count())
+        .filter_map(|idx| arguments.named_child(idx))
+        .filter(|argument| argument.kind() == "argument")
+        .collect()
+}
 
-        diagnostics
+fn string_value(node: Node, parsed: &parser::ParsedSource) -> Option<String> {
+    node_text(node, parsed).map(|text| text.trim_matches(|c| c == '\'' || c == '"').to_string())
+}
+
+/// Positions where a bare name refers to a class: `new Foo`, type hints,
+/// `extends`/`implements`, `instanceof` and `Foo::CONST`.
+fn is_class_reference(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+
+    match parent.kind() {
+        "object_creation_expression"
+        | "named_type"
+        | "base_clause"
+        | "class_interface_clause"
+        | "class_constant_access_expression"
+        | "scoped_property_access_expression" => {
+            parent.named_child(0) == Some(node)
+                || matches!(
+                    parent.kind(),
+                    "named_type" | "base_clause" | "class_interface_clause"
+                )
+        }
+        "binary_expression" => {
+            parent.child_by_field_name("right") == Some(node)
+                && parent
+                    .child_by_field_name("operator")
+                    .is_some_and(|operator| operator.kind() == "instanceof")
+        }
+        _ => false,
     }
 }
 
+fn is_relative_class(name: &str) -> bool {
+    ["self", "static", "parent"]
+        .iter()
+        .any(|keyword| name.eq_ignore_ascii_case(keyword))
+}
+
+fn same_name(left: &str, right: &str) -> bool {
+    left.trim_start_matches('\\')
+        .eq_ignore_ascii_case(right.trim_start_matches('\\'))
+}
+
+fn lookup(
+    entries: &std::collections::HashMap<String, Option<String>>,
+    matches: impl Fn(&str) -> bool,
+) -> Option<Option<String>> {
+    entries
+        .iter()
+        .find(|(key, _)| matches(key))
+        .map(|(_, replacement)| replacement.clone())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule};
+    use crate::analyzer::rules::test_utils::{
+        assert_diagnostics_exact, assert_fix, assert_fix_with_context, assert_no_diagnostics,
+        parse_php, run_rule, run_rule_with_context,
+    };
 
     #[test]
     fn test_deprecated_api() {
@@ -69,13 +549,16 @@ create_function('$a', 'return $a;');
 "#;
 
         let parsed = parse_php(source);
-        let rule = DeprecatedApiRule::new();
+        let rule = DeprecatedApiRule::new(DeprecatedApiConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
-        assert_diagnostics_exact(&diagnostics, &[
-            "warning: mysql_connect is deprecated; use modern alternatives",
-            "warning: create_function is deprecated; use modern alternatives",
-        ]);
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "warning: mysql_connect is deprecated; use modern alternatives",
+                "warning: create_function is deprecated; use modern alternatives",
+            ],
+        );
     }
 
     #[test]
@@ -83,12 +566,133 @@ create_function('$a', 'return $a;');
         let source = r#"<?php
 mysqli_connect('localhost', 'user', 'pass');
 $func = function($a) { return $a; };
+ini_set('display_errors', '1');
 "#;
 
         let parsed = parse_php(source);
-        let rule = DeprecatedApiRule::new();
+        let rule = DeprecatedApiRule::new(DeprecatedApiConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         assert_no_diagnostics(&diagnostics);
     }
+
+    #[test]
+    fn test_deprecated_function_fixes() {
+        let input = r#"<?php
+while (list($key, $value) = each($items)) {
+    echo $key, $value;
+}
+while (list(, $row) = each($rows)) {}
+$parts = split(',', $csv);
+$words = split('[ ,]', $text);
+$latin = utf8_decode($name);
+if (get_magic_quotes_gpc()) {}
+"#;
+        let expected = r#"<?php
+foreach ($items as $key => $value) {
+    echo $key, $value;
+}
+foreach ($rows as $row) {}
+$parts = explode(',', $csv);
+$words = split('[ ,]', $text);
+$latin = mb_convert_encoding($name, 'ISO-8859-1', 'UTF-8');
+if (false) {}
+"#;
+
+        let parsed = parse_php(input);
+        let rule = DeprecatedApiRule::new(DeprecatedApiConfig::default());
+        assert_diagnostics_exact(
+            &run_rule(&rule, &parsed),
+            &[
+                "warning: each is deprecated; use foreach instead",
+                "warning: each is deprecated; use foreach instead",
+                "warning: split is deprecated; use explode instead",
+                "warning: split is deprecated; use explode instead",
+                "warning: utf8_decode is deprecated; use mb_convert_encoding instead",
+                "warning: get_magic_quotes_gpc is deprecated; use false instead",
+            ],
+        );
+        assert_fix(&rule, &parsed, input, expected);
+    }
+
+    #[test]
+    fn test_deprecated_methods_classes_and_ini() {
+        let input = r#"<?php
+namespace App;
+
+use Legacy\Mailer;
+
+$mailer = new Mailer();
+Mailer::sendNow($message);
+ini_set('assert.active', '1');
+ini_get('app.legacy_mode');
+"#;
+        let expected = r#"<?php
+namespace App;
+
+use Legacy\Mailer;
+
+$mailer = new \App\Mail\Mailer();
+\App\Mail\Mailer::send($message);
+ini_set('assert.active', '1');
+ini_get('app.legacy_mode');
+"#;
+
+        let config: DeprecatedApiConfig = serde_yaml::from_str(
+            r#"
+methods:
+  "Legacy\\Mailer::sendNow": "App\\Mail\\Mailer::send"
+classes:
+  "Legacy\\Mailer": "App\\Mail\\Mailer"
+ini:
+  app.legacy_mode: ~
+"#,
+        )
+        .unwrap();
+        let rule = DeprecatedApiRule::new(config);
+
+        assert_diagnostics_exact(
+            &run_rule_with_context(&rule, input),
+            &[
+                "warning: class Legacy\\Mailer is deprecated; use App\\Mail\\Mailer instead",
+                "warning: Legacy\\Mailer::sendNow is deprecated; use App\\Mail\\Mailer::send instead",
+                "warning: ini setting 'assert.active' is deprecated",
+                "warning: ini setting 'app.legacy_mode' is deprecated",
+            ],
+        );
+        assert_fix_with_context(&rule, input, expected);
+    }
+
+    #[test]
+    fn test_configured_functions_with_messages() {
+        let input = r#"<?php
+$safe = legacy_escape($name);
+$rows = \Db\fetch_all($query);
+"#;
+        let expected = r#"<?php
+$safe = htmlspecialchars($name);
+$rows = \Db\fetch_all($query);
+"#;
+
+        let config: DeprecatedApiConfig = serde_yaml::from_str(
+            r#"
+functions:
+  legacy_escape: htmlspecialchars
+  Db\fetch_all:
+    message: iterate Db\cursor() instead
+"#,
+        )
+        .unwrap();
+        let rule = DeprecatedApiRule::new(config);
+        let parsed = parse_php(input);
+
+        assert_diagnostics_exact(
+            &run_rule(&rule, &parsed),
+            &[
+                "warning: legacy_escape is deprecated; use htmlspecialchars instead",
+                "warning: \\Db\\fetch_all is deprecated; iterate Db\\cursor() instead",
+            ],
+        );
+        assert_fix(&rule, &parsed, input, expected);
+    }
 }
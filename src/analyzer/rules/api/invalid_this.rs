@@ -1,5 +1,7 @@
 use super::DiagnosticRule;
-use super::helpers::{child_by_kind, diagnostic_for_node, node_text, walk_node};
+use super::helpers::{
+    child_by_kind, diagnostic_for_node, is_anonymous_class, node_text, walk_node,
+};
 use crate::analyzer::project::ProjectContext;
 use crate::analyzer::{Severity, parser};
 
@@ -16,6 +18,10 @@ impl DiagnosticRule for InvalidThisRule {
         "api/invalid_this"
     }
 
+    fn description(&self) -> &str {
+        "$this used outside an instance method or closure bound to one"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
@@ -53,6 +59,10 @@ impl DiagnosticRule for InvalidThisRule {
                         found_class = true;
                         break;
                     }
+                    _ if is_anonymous_class(p) => {
+                        found_class = true;
+                        break;
+                    }
                     _ => parent = p,
                 }
             }
@@ -84,11 +94,12 @@ impl DiagnosticRule for InvalidThisRule {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule};
+    use crate::analyzer::rules::test_utils::rule_test;
 
-    #[test]
-    fn test_invalid_this() {
-        let source = r#"<?php
+    rule_test! {
+        test_invalid_this,
+        rule: InvalidThisRule::new(),
+        source: r#"<?php
 
 function global_this() {
     return $this;
@@ -100,32 +111,29 @@ class Example {
     }
 }
 
-"#;
-
-        let parsed = parse_php(source);
-        let rule = InvalidThisRule::new();
-        let diagnostics = run_rule(&rule, &parsed);
-
-        assert_diagnostics_exact(&diagnostics, &[
+"#,
+        expect: [
             "error: $this is not allowed outside of class scope",
             "error: $this cannot be used in static context",
-        ]);
+        ],
     }
 
-    #[test]
-    fn test_invalid_this_valid() {
-        let source = r#"<?php
+    rule_test! {
+        test_invalid_this_valid,
+        rule: InvalidThisRule::new(),
+        source: r#"<?php
 class Example {
     public function instanceMethod() {
         return $this;
     }
 }
-"#;
-
-        let parsed = parse_php(source);
-        let rule = InvalidThisRule::new();
-        let diagnostics = run_rule(&rule, &parsed);
 
-        assert_no_diagnostics(&diagnostics);
+$handler = new class {
+    public function handle() {
+        return $this;
+    }
+};
+"#,
+        expect: [],
     }
 }
@@ -0,0 +1,207 @@
+use super::DiagnosticRule;
+use super::helpers::{child_by_kind, diagnostic_for_node, node_text, walk_node};
+use crate::analyzer::config::EnvironmentValuesConfig;
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::{Severity, is_test_file, parser};
+use tree_sitter::Node;
+
+/// Flags string literals that only make sense on one machine: loopback hosts,
+/// `.test`/`.local` domains and developer home directories.
+pub struct EnvironmentSpecificValuesRule {
+    config: EnvironmentValuesConfig,
+}
+
+impl EnvironmentSpecificValuesRule {
+    pub fn new(config: EnvironmentValuesConfig) -> Self {
+        Self { config }
+    }
+
+    fn matching_value(&self, value: &str) -> Option<String> {
+        // Double-quoted sources spell Windows separators as `\\`.
+        let value = value.replace("\\\\", "\\");
+
+        for pattern in &self.config.patterns {
+            if pattern.contains(['/', '\\']) {
+                if wildcard_match(pattern, &value) {
+                    return Some(value);
+                }
+                continue;
+            }
+
+            if let Some(token) = host_tokens(&value).find(|token| wildcard_match(pattern, token)) {
+                return Some(token.to_string());
+            }
+        }
+
+        None
+    }
+}
+
+impl DiagnosticRule for EnvironmentSpecificValuesRule {
+    fn name(&self) -> &str {
+        "cleanup/environment_specific_values"
+    }
+
+    fn description(&self) -> &str {
+        "Hosts and paths that only exist on one machine"
+    }
+
+    fn run(
+        &self,
+        parsed: &parser::ParsedSource,
+        _context: &ProjectContext,
+    ) -> Vec<crate::analyzer::Diagnostic> {
+        let mut diagnostics = Vec::new();
+        if is_test_file(&parsed.path) {
+            return diagnostics;
+        }
+
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            if !matches!(node.kind(), "string" | "encapsed_string") {
+                return;
+            }
+
+            let Some(value) = string_contents(node, parsed) else {
+                return;
+            };
+
+            if let Some(matched) = self.matching_value(&value) {
+                diagnostics.push(diagnostic_for_node(
+                    parsed,
+                    node,
+                    Severity::Warning,
+                    format!(
+                        "environment-specific value \"{matched}\" is hard-coded; read it from configuration or an environment variable"
+                    ),
+                ));
+            }
+        });
+
+        diagnostics
+    }
+}
+
+/// Literal text of a string, ignoring interpolated expressions.
+fn string_contents(node: Node, parsed: &parser::ParsedSource) -> Option<String> {
+    if child_by_kind(node, "string_value").is_none() {
+        return node_text(node, parsed)
+            .map(|text| text.trim_matches(|c| c == '\'' || c == '"').to_string());
+    }
+
+    let mut contents = String::new();
+    for idx in 0..node.named_child_count() {
+        let Some(child) = node.named_child(idx) else {
+            continue;
+        };
+        if !matches!(child.kind(), "string_value" | "escape_sequence") {
+            continue;
+        }
+        if let Some(text) = node_text(child, parsed) {
+            contents.push_str(&text);
+        }
+    }
+    Some(contents)
+}
+
+/// Splits a value into host-like tokens, e.g. `mysql:host=db.local;port=3306`
+/// yields `mysql`, `host`, `db.local`, `port`, `3306`.
+fn host_tokens(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(|c: char| !(c.is_alphanumeric() || matches!(c, '.' | '-' | '_')))
+        .filter(|token| !token.is_empty())
+}
+
+/// Case-insensitive match where `*` stands for any run of characters.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::{
+        assert_diagnostics_exact, assert_no_diagnostics, parse_php, rule_test, run_rule,
+    };
+
+    #[test]
+    fn test_environment_specific_values() {
+        let source = r#"<?php
+$pdo = new PDO('mysql:host=localhost;dbname=app');
+$cache = "redis://127.0.0.1:6379";
+$api = 'https://shop.test/api';
+$log = '/Users/alice/project/var/app.log';
+$tmp = "C:\\Users\\alice\\tmp";
+"#;
+
+        let parsed = parse_php(source);
+        let rule = EnvironmentSpecificValuesRule::new(EnvironmentValuesConfig::default());
+        let diagnostics = run_rule(&rule, &parsed);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "warning: environment-specific value \"localhost\" is hard-coded",
+                "warning: environment-specific value \"127.0.0.1\" is hard-coded",
+                "warning: environment-specific value \"shop.test\" is hard-coded",
+                "warning: environment-specific value \"/Users/alice/project/var/app.log\" is hard-coded",
+                "warning: environment-specific value \"C:\\Users\\alice\\tmp\" is hard-coded",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_environment_specific_values_valid() {
+        let source = r#"<?php
+$host = getenv('DB_HOST') ?: 'db';
+$config = require __DIR__ . '/config.local.php';
+$url = "https://example.com/{$path}";
+"#;
+
+        let parsed = parse_php(source);
+        let rule = EnvironmentSpecificValuesRule::new(EnvironmentValuesConfig::default());
+        assert_no_diagnostics(&run_rule(&rule, &parsed));
+    }
+
+    rule_test! {
+        test_environment_specific_values_skip_tests,
+        rule: EnvironmentSpecificValuesRule::new(EnvironmentValuesConfig::default()),
+        source: "<?php\n$url = 'http://localhost:8080';\n",
+        path: "tests/ClientTest.php",
+        expect: [],
+    }
+
+    #[test]
+    fn test_environment_specific_values_use_custom_patterns() {
+        let source = "<?php\n$url = 'http://localhost:8080';\n$host = 'build-01.internal';\n";
+        let rule = EnvironmentSpecificValuesRule::new(EnvironmentValuesConfig {
+            patterns: vec!["*.internal".into()],
+        });
+
+        let parsed = parse_php(source);
+        assert_diagnostics_exact(
+            &run_rule(&rule, &parsed),
+            &["warning: environment-specific value \"build-01.internal\" is hard-coded"],
+        );
+    }
+}
@@ -1,7 +1,11 @@
 pub use crate::analyzer::rules::{DiagnosticRule, helpers};
 
+pub mod environment_specific_values;
+pub mod todo_comment;
 pub mod unused_use;
 pub mod unused_variable;
 
+pub use environment_specific_values::EnvironmentSpecificValuesRule;
+pub use todo_comment::TodoCommentRule;
 pub use unused_use::UnusedUseRule;
 pub use unused_variable::UnusedVariableRule;
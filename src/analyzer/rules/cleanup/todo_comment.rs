@@ -0,0 +1,236 @@
+use super::DiagnosticRule;
+use super::helpers::{diagnostic_for_span, node_text, walk_node};
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::{Severity, Span, parser};
+use tree_sitter::Point;
+
+const MARKERS: &[&str] = &["TODO", "FIXME", "HACK"];
+
+/// Lists TODO/FIXME/HACK comments so outstanding cleanup work can be tracked.
+///
+/// Recognised shapes are `TODO: text`, `TODO(owner): text`,
+/// `TODO(owner, 2025-01-31): text` and `TODO @owner text`; owner, due date and
+/// text are attached to the diagnostic's metadata for JSON consumers.
+pub struct TodoCommentRule;
+
+impl TodoCommentRule {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DiagnosticRule for TodoCommentRule {
+    fn name(&self) -> &str {
+        "cleanup/todo_comment"
+    }
+
+    fn description(&self) -> &str {
+        "TODO, FIXME and HACK comments"
+    }
+
+    fn run(
+        &self,
+        parsed: &parser::ParsedSource,
+        _context: &ProjectContext,
+    ) -> Vec<crate::analyzer::Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            if node.kind() != "comment" {
+                return;
+            }
+            let Some(text) = node_text(node, parsed) else {
+                return;
+            };
+
+            for (offset, line) in text.lines().enumerate() {
+                let Some((column, item)) = parse_todo(line) else {
+                    continue;
+                };
+
+                let row = node.start_position().row + offset;
+                let start_column = if offset == 0 {
+                    node.start_position().column + column
+                } else {
+                    column
+                };
+                let span = Span {
+                    start: Point::new(row, start_column),
+                    end: Point::new(row, start_column + item.marker.len()),
+                };
+
+                let mut diagnostic =
+                    diagnostic_for_span(parsed, span, Severity::Info, item.message());
+                diagnostic
+                    .metadata
+                    .insert("marker".into(), item.marker.to_string());
+                diagnostic.metadata.insert("text".into(), item.text.clone());
+                if let Some(owner) = &item.owner {
+                    diagnostic.metadata.insert("owner".into(), owner.clone());
+                }
+                if let Some(due) = &item.due {
+                    diagnostic.metadata.insert("due".into(), due.clone());
+                }
+                diagnostics.push(diagnostic);
+            }
+        });
+
+        diagnostics
+    }
+}
+
+struct TodoItem {
+    marker: &'static str,
+    owner: Option<String>,
+    due: Option<String>,
+    text: String,
+}
+
+impl TodoItem {
+    fn message(&self) -> String {
+        let mut message = if self.text.is_empty() {
+            self.marker.to_string()
+        } else {
+            format!("{}: {}", self.marker, self.text)
+        };
+
+        let details: Vec<String> = [("owner", &self.owner), ("due", &self.due)]
+            .into_iter()
+            .filter_map(|(label, value)| value.as_ref().map(|value| format!("{label}: {value}")))
+            .collect();
+        if !details.is_empty() {
+            message.push_str(&format!(" ({})", details.join(", ")));
+        }
+        message
+    }
+}
+
+/// Finds the first marker on a comment line, returning its byte column and the parsed item.
+fn parse_todo(line: &str) -> Option<(usize, TodoItem)> {
+    let (column, marker) = find_marker(line)?;
+    let mut rest = &line[column + marker.len()..];
+    let mut owner = None;
+    let mut due = None;
+
+    if let Some(inner) = rest.strip_prefix('(')
+        && let Some(close) = inner.find(')')
+    {
+        for part in inner[..close].split(',').map(str::trim) {
+            if is_iso_date(part) {
+                due = Some(part.to_string());
+            } else if !part.is_empty() {
+                owner = Some(part.trim_start_matches('@').to_string());
+            }
+        }
+        rest = &inner[close + 1..];
+    } else if let Some(mention) = rest.trim_start().strip_prefix('@') {
+        let end = mention
+            .find(|c: char| c.is_whitespace() || c == ':')
+            .unwrap_or(mention.len());
+        owner = Some(mention[..end].to_string());
+        rest = &mention[end..];
+    }
+
+    let text = rest
+        .trim_start()
+        .trim_start_matches([':', '-'])
+        .trim()
+        .trim_end_matches("*/")
+        .trim_end()
+        .to_string();
+
+    Some((
+        column,
+        TodoItem {
+            marker,
+            owner: owner.filter(|owner| !owner.is_empty()),
+            due,
+            text,
+        },
+    ))
+}
+
+/// Markers must be upper case and stand alone, so `todos` or `TODOLIST` don't count.
+fn find_marker(line: &str) -> Option<(usize, &'static str)> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    MARKERS
+        .iter()
+        .filter_map(|marker| {
+            line.match_indices(marker)
+                .find(|(idx, _)| {
+                    let before = line[..*idx].chars().next_back();
+                    let after = line[idx + marker.len()..].chars().next();
+                    !before.is_some_and(is_word) && !after.is_some_and(is_word)
+                })
+                .map(|(idx, _)| (idx, *marker))
+        })
+        .min_by_key(|(idx, _)| *idx)
+}
+
+fn is_iso_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(idx, byte)| match idx {
+            4 | 7 => *byte == b'-',
+            _ => byte.is_ascii_digit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::{
+        assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule,
+    };
+
+    #[test]
+    fn test_todo_comments() {
+        let source = r#"<?php
+// TODO: drop the legacy importer
+function import(): void
+{
+    # FIXME(alice, 2025-03-01): handle empty files
+    /*
+     * HACK @bob work around the vendor bug
+     */
+}
+"#;
+
+        let parsed = parse_php(source);
+        let diagnostics = run_rule(&TodoCommentRule::new(), &parsed);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "info: TODO: drop the legacy importer",
+                "info: FIXME: handle empty files (owner: alice, due: 2025-03-01)",
+                "info: HACK: work around the vendor bug (owner: bob)",
+            ],
+        );
+
+        let fixme = &diagnostics[1];
+        assert_eq!(
+            fixme.metadata.get("owner").map(String::as_str),
+            Some("alice")
+        );
+        assert_eq!(
+            fixme.metadata.get("due").map(String::as_str),
+            Some("2025-03-01")
+        );
+        let hack = diagnostics[2].span.as_ref().expect("span should be set");
+        assert_eq!((hack.start.row, hack.start.column), (6, 7));
+    }
+
+    #[test]
+    fn test_todo_comments_ignore_lowercase_and_strings() {
+        let source = r#"<?php
+// keep the todos list sorted
+$label = 'TODO: not a comment';
+/* TODOLIST is a constant name */
+"#;
+
+        let parsed = parse_php(source);
+        assert_no_diagnostics(&run_rule(&TodoCommentRule::new(), &parsed));
+    }
+}
@@ -3,7 +3,7 @@ use super::helpers::{diagnostic_for_span, node_text, walk_node};
 use crate::analyzer::fix;
 use crate::analyzer::project::{ProjectContext, UseInfo};
 use crate::analyzer::{Severity, parser};
-use std::collections::HashMap;
+use std::collections::HashSet;
 use tree_sitter::Node;
 
 pub struct UnusedUseRule;
@@ -19,6 +19,10 @@ impl DiagnosticRule for UnusedUseRule {
         "cleanup/unused_use"
     }
 
+    fn description(&self) -> &str {
+        "use imports that are never referenced"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
@@ -26,32 +30,120 @@ impl DiagnosticRule for UnusedUseRule {
     ) -> Vec<crate::analyzer::Diagnostic> {
         unused_aliases(parsed, context)
             .into_iter()
-            .map(|(alias, info)| {
+            .map(|info| {
                 diagnostic_for_span(
                     parsed,
                     info.span,
                     Severity::Warning,
-                    format!("unused import alias `{alias}`"),
+                    format!("unused import alias `{}`", info.alias),
                 )
             })
             .collect()
     }
 
     fn fix(&self, parsed: &parser::ParsedSource, context: &ProjectContext) -> Vec<fix::TextEdit> {
+        let Some(scope) = context.scope_for(&parsed.path) else {
+            return Vec::new();
+        };
         let source = parsed.source.as_str();
+        let unused: HashSet<usize> = unused_aliases(parsed, context)
+            .iter()
+            .map(|info| info.clause_start)
+            .collect();
+
+        let mut declarations: Vec<usize> = scope
+            .uses
+            .iter()
+            .filter(|info| unused.contains(&info.clause_start))
+            .map(|info| info.declaration_start)
+            .collect();
+        declarations.sort_unstable();
+        declarations.dedup();
+
+        let mut edits = Vec::new();
+        for declaration_start in declarations {
+            let mut clauses: Vec<&UseInfo> = scope
+                .uses
+                .iter()
+                .filter(|info| info.declaration_start == declaration_start)
+                .collect();
+            clauses.sort_by_key(|info| info.clause_start);
+
+            if clauses
+                .iter()
+                .all(|info| unused.contains(&info.clause_start))
+            {
+                let (start, end) = declaration_range(
+                    source,
+                    clauses[0].declaration_start,
+                    clauses[0].declaration_end,
+                );
+                edits.push(fix::TextEdit::new(start, end, ""));
+            } else if clauses[0].declaration_has_multiple_clauses {
+                edits.extend(clause_edits(&clauses, &unused));
+            }
+        }
 
-        unused_aliases(parsed, context)
-            .into_iter()
-            .filter(|(_, info)| !info.declaration_has_multiple_clauses)
-            .map(|(_, info)| {
-                let (start, end) =
-                    fix::covering_line_range(source, info.clause_start, info.clause_end);
-                fix::TextEdit::new(start, end, "")
-            })
-            .collect()
+        edits
+    }
+}
+
+/// The range removing a whole use declaration: its entire line, indentation and
+/// line break included, when nothing else is on it, otherwise just the
+/// declaration and the blanks separating it from the neighbouring code.
+fn declaration_range(source: &str, start: usize, end: usize) -> (usize, usize) {
+    let (line_start, line_end) = fix::covering_line_range(source, start, end);
+    let is_blank = |text: &str| text.trim().is_empty();
+    let before = &source[line_start..start];
+    let after = &source[end..line_end];
+
+    match (is_blank(before), is_blank(after)) {
+        (true, true) => (line_start, line_end),
+        // `use A; use B;`: take the blanks before the next statement
+        (_, false) => {
+            let gap = after.len() - after.trim_start_matches([' ', '\t']).len();
+            (start, end + gap)
+        }
+        // `<?php use A;` keeps the line break
+        (false, true) => (before.trim_end_matches([' ', '\t']).len() + line_start, end),
     }
 }
 
+/// Removes unused clauses from a declaration with several of them (`use A, B;`
+/// or `use App\{Foo, Bar};`) together with their separating commas. Consecutive
+/// unused clauses are removed as one run so edits never overlap; at least one
+/// clause is always kept.
+fn clause_edits(clauses: &[&UseInfo], unused: &HashSet<usize>) -> Vec<fix::TextEdit> {
+    let mut edits = Vec::new();
+    let mut idx = 0;
+
+    while idx < clauses.len() {
+        if !unused.contains(&clauses[idx].clause_start) {
+            idx += 1;
+            continue;
+        }
+
+        let run_start = idx;
+        while idx < clauses.len() && unused.contains(&clauses[idx].clause_start) {
+            idx += 1;
+        }
+
+        let edit = match clauses.get(idx) {
+            Some(next_kept) => {
+                fix::TextEdit::new(clauses[run_start].clause_start, next_kept.clause_start, "")
+            }
+            None => fix::TextEdit::new(
+                clauses[run_start - 1].clause_end,
+                clauses[idx - 1].clause_end,
+                "",
+            ),
+        };
+        edits.push(edit);
+    }
+
+    edits
+}
+
 fn is_use_clause(mut node: Node) -> bool {
     while let Some(parent) = node.parent() {
         match parent.kind() {
@@ -68,13 +160,13 @@ fn is_use_clause(mut node: Node) -> bool {
 fn unused_aliases<'a>(
     parsed: &'a parser::ParsedSource,
     context: &'a ProjectContext,
-) -> Vec<(String, UseInfo)> {
+) -> Vec<UseInfo> {
     let scope = match context.scope_for(&parsed.path) {
         Some(scope) if !scope.uses.is_empty() => scope,
         _ => return Vec::new(),
     };
 
-    let mut unused: HashMap<String, UseInfo> = scope.uses.clone();
+    let mut unused: Vec<UseInfo> = scope.uses.clone();
 
     walk_node(parsed.tree.root_node(), &mut |node| {
         if is_use_clause(node) {
@@ -84,16 +176,14 @@ fn unused_aliases<'a>(
         if matches!(node.kind(), "qualified_name" | "namespace_name" | "name") {
             if let Some(text) = node_text(node, parsed) {
                 if let Some(first) = text.split('\\').next() {
-                    unused.remove(first);
+                    unused.retain(|info| !info.kind.matches(&info.alias, first));
                 }
             }
         }
     });
 
+    unused.retain(|info| !info.alias.starts_with('_'));
     unused
-        .into_iter()
-        .filter(|(alias, _)| !alias.starts_with('_'))
-        .collect()
 }
 
 #[cfg(test)]
@@ -156,4 +246,119 @@ Svc\takesTwo(1);
 
         assert_no_diagnostics(&diagnostics);
     }
+
+    #[test]
+    fn test_unused_use_matches_alias_case_insensitively() {
+        let source = r#"<?php
+
+use Multi\Client;
+
+$client = new client();
+"#;
+
+        let rule = UnusedUseRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_no_diagnostics(&diagnostics);
+    }
+
+    #[test]
+    fn test_unused_group_use_members() {
+        let source = r#"<?php
+
+use App\{Foo, Sub\Bar as Baz, Qux};
+use \Vendor\Tool;
+use function App\helper;
+
+new Foo();
+new Qux();
+"#;
+
+        let rule = UnusedUseRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "warning: unused import alias `Baz`",
+                "warning: unused import alias `Tool`",
+                "warning: unused import alias `helper`",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_unused_group_use_fix() {
+        let input = r#"<?php
+
+use App\{Foo, Bar, Baz, Qux};
+use App\Models\{User, Post};
+use Vendor\{Alpha, Beta,};
+
+new Foo(new Qux(), new Alpha());
+"#;
+
+        let expected = r#"<?php
+
+use App\{Foo, Qux};
+use Vendor\{Alpha};
+
+new Foo(new Qux(), new Alpha());
+"#;
+
+        let rule = UnusedUseRule::new();
+        assert_fix_with_context(&rule, input, expected);
+    }
+
+    #[test]
+    fn test_unused_use_fix_removes_whole_lines() {
+        let input = "<?php\nnamespace App;\n\n    use Multi\\Client;\r\nuse Multi\\Service; use Multi\\Other;\nuse Multi\\Kept; // keeps the comment\n\nnew Kept(new Service());\n";
+        let expected = "<?php\nnamespace App;\n\nuse Multi\\Service;\nuse Multi\\Kept; // keeps the comment\n\nnew Kept(new Service());\n";
+
+        let rule = UnusedUseRule::new();
+        assert_fix_with_context(&rule, input, expected);
+    }
+
+    #[test]
+    fn test_unused_use_fix_multiple_clauses() {
+        let input = r#"<?php
+
+use Multi\Client, Multi\Service as Svc, Multi\Other;
+use function Multi\first, Multi\second;
+use Multi\Gone, Multi\Removed;
+
+new Svc(second());
+"#;
+
+        let expected = r#"<?php
+
+use Multi\Service as Svc;
+use function Multi\second;
+
+new Svc(second());
+"#;
+
+        let rule = UnusedUseRule::new();
+        assert_fix_with_context(&rule, input, expected);
+    }
+
+    #[test]
+    fn test_unused_group_use_fix_trailing_members() {
+        let input = r#"<?php
+
+use App\{Foo, Bar, Baz};
+
+new Foo();
+"#;
+
+        let expected = r#"<?php
+
+use App\{Foo};
+
+new Foo();
+"#;
+
+        let rule = UnusedUseRule::new();
+        assert_fix_with_context(&rule, input, expected);
+    }
 }
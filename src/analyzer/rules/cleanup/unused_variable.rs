@@ -1,16 +1,30 @@
 use super::DiagnosticRule;
-use super::helpers::{diagnostic_for_node, is_definition, variable_name_text};
+use super::helpers::diagnostic_for_node;
+use crate::analyzer::artifacts::{Artifact, VariableRole};
 use crate::analyzer::fix;
+use crate::analyzer::config::TemplateConfig;
 use crate::analyzer::project::ProjectContext;
-use crate::analyzer::{Severity, parser};
+use crate::analyzer::{Severity, is_template_file, parser};
 use std::collections::{HashMap, HashSet};
 use tree_sitter::Node;
 
-pub struct UnusedVariableRule;
+pub struct UnusedVariableRule {
+    templates: TemplateConfig,
+}
 
 impl UnusedVariableRule {
-    pub fn new() -> Self {
-        Self
+    pub fn new(templates: TemplateConfig) -> Self {
+        Self { templates }
+    }
+
+    /// Collects unused variables, skipping view variables assigned in templates
+    /// since the surrounding layout may read them.
+    fn unused_variables<'a>(&self, parsed: &'a parser::ParsedSource) -> Vec<UnusedVariable<'a>> {
+        let is_template = is_template_file(&parsed.path);
+        collect_unused_variables(parsed)
+            .into_iter()
+            .filter(|unused| !is_template || !self.templates.is_view_variable(&unused.name))
+            .collect()
     }
 }
 
@@ -19,12 +33,20 @@ impl DiagnosticRule for UnusedVariableRule {
         "cleanup/unused_variable"
     }
 
+    fn description(&self) -> &str {
+        "Variables that are assigned but never read"
+    }
+
+    fn requires(&self) -> &[Artifact] {
+        &[Artifact::Scopes]
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
         _context: &ProjectContext,
     ) -> Vec<crate::analyzer::Diagnostic> {
-        collect_unused_variables(parsed)
+        self.unused_variables(parsed)
             .into_iter()
             .map(|unused| {
                 diagnostic_for_node(
@@ -39,7 +61,7 @@ impl DiagnosticRule for UnusedVariableRule {
 
     fn fix(&self, parsed: &parser::ParsedSource, _context: &ProjectContext) -> Vec<fix::TextEdit> {
         let source = parsed.source.as_str();
-        collect_unused_variables(parsed)
+        self.unused_variables(parsed)
             .into_iter()
             .map(|unused| {
                 let (start, end) = fix::covering_line_range(
@@ -54,9 +76,35 @@ impl DiagnosticRule for UnusedVariableRule {
 }
 
 fn collect_unused_variables<'a>(parsed: &'a parser::ParsedSource) -> Vec<UnusedVariable<'a>> {
-    let mut visitor = UnusedVariableVisitor::new(parsed);
-    visitor.visit(parsed.tree.root_node());
-    visitor.collect_unused()
+    let mut defined: HashMap<&str, VariableDefinition<'a>> = HashMap::new();
+    let mut used: HashSet<&str> = HashSet::new();
+
+    for site in &parsed.scopes().variables {
+        match site.role {
+            VariableRole::Assigned => {
+                defined.entry(&site.name).or_insert_with(|| {
+                    let node = site.node.node(parsed);
+                    VariableDefinition {
+                        node,
+                        statement: enclosing_expression_statement(node),
+                    }
+                });
+            }
+            VariableRole::Parameter | VariableRole::PromotedParameter => {}
+            VariableRole::Read | VariableRole::Bound => {
+                used.insert(&site.name);
+            }
+        }
+    }
+
+    defined
+        .into_iter()
+        .filter(|(name, _)| !used.contains(name) && !name.starts_with('_'))
+        .map(|(name, definition)| UnusedVariable {
+            name: name.to_string(),
+            definition,
+        })
+        .collect()
 }
 
 struct UnusedVariable<'a> {
@@ -69,63 +117,6 @@ struct VariableDefinition<'a> {
     statement: Node<'a>,
 }
 
-struct UnusedVariableVisitor<'a> {
-    parsed: &'a parser::ParsedSource,
-    defined: HashMap<String, VariableDefinition<'a>>,
-    used: HashSet<String>,
-}
-
-impl<'a> UnusedVariableVisitor<'a> {
-    fn new(parsed: &'a parser::ParsedSource) -> Self {
-        Self {
-            parsed,
-            defined: HashMap::new(),
-            used: HashSet::new(),
-        }
-    }
-
-    fn visit(&mut self, node: Node<'a>) {
-        if node.kind() == "variable_name" {
-            if let Some(name) = variable_name_text(node, self.parsed) {
-                let is_definition = is_definition(node);
-                if is_definition {
-                    if !is_parameter_definition(node) {
-                        self.define_variable(name, node);
-                    }
-                } else {
-                    self.used.insert(name);
-                }
-            }
-        }
-
-        let mut cursor = node.walk();
-        if cursor.goto_first_child() {
-            loop {
-                self.visit(cursor.node());
-                if !cursor.goto_next_sibling() {
-                    break;
-                }
-            }
-        }
-    }
-
-    fn collect_unused(self) -> Vec<UnusedVariable<'a>> {
-        let UnusedVariableVisitor { defined, used, .. } = self;
-        defined
-            .into_iter()
-            .filter(|(name, _)| !used.contains(name) && !name.starts_with('_'))
-            .map(|(name, definition)| UnusedVariable { name, definition })
-            .collect()
-    }
-
-    fn define_variable(&mut self, name: String, node: Node<'a>) {
-        let statement = enclosing_expression_statement(node);
-        self.defined
-            .entry(name)
-            .or_insert(VariableDefinition { node, statement });
-    }
-}
-
 fn enclosing_expression_statement(mut node: Node) -> Node {
     while let Some(parent) = node.parent() {
         if parent.kind() == "expression_statement" {
@@ -137,21 +128,10 @@ fn enclosing_expression_statement(mut node: Node) -> Node {
     node
 }
 
-fn is_parameter_definition(node: Node) -> bool {
-    node.parent()
-        .map(|parent| {
-            matches!(
-                parent.kind(),
-                "simple_parameter" | "variadic_parameter" | "property_promotion_parameter"
-            )
-        })
-        .unwrap_or(false)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_fix, assert_no_diagnostics, parse_php, run_rule};
+    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_fix, assert_no_diagnostics, parse_php, parse_php_with_path, run_rule};
 
     #[test]
     fn test_unused_variable() {
@@ -164,7 +144,7 @@ echo "Done";
 "#;
 
         let parsed = parse_php(source);
-        let rule = UnusedVariableRule::new();
+        let rule = UnusedVariableRule::new(TemplateConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         assert_diagnostics_exact(&diagnostics, &["error: unused variable $unused"]);
@@ -188,7 +168,7 @@ echo "Done";
 "#;
 
         let parsed = parse_php(input);
-        let rule = UnusedVariableRule::new();
+        let rule = UnusedVariableRule::new(TemplateConfig::default());
         assert_fix(&rule, &parsed, input, expected);
     }
 
@@ -201,9 +181,31 @@ echo $used;
 "#;
 
         let parsed = parse_php(source);
-        let rule = UnusedVariableRule::new();
+        let rule = UnusedVariableRule::new(TemplateConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         assert_no_diagnostics(&diagnostics);
     }
+
+    #[test]
+    fn test_template_view_variables_not_reported() {
+        let source = r#"<?php $title = 'Dashboard'; $count = 3; ?>
+<h1>Welcome</h1>
+"#;
+
+        let templates = TemplateConfig {
+            view_variables: vec!["title".into()],
+        };
+
+        let parsed = parse_php_with_path(source, "layout.phtml");
+        let rule = UnusedVariableRule::new(templates.clone());
+        let diagnostics = run_rule(&rule, &parsed);
+        assert_diagnostics_exact(&diagnostics, &["error: unused variable $count"]);
+
+        // Outside templates the allow-list does not apply.
+        let parsed = parse_php_with_path(source, "layout.php");
+        let rule = UnusedVariableRule::new(templates);
+        let diagnostics = run_rule(&rule, &parsed);
+        assert_eq!(diagnostics.len(), 2);
+    }
 }
@@ -0,0 +1,321 @@
+use super::DiagnosticRule;
+use super::helpers::{
+    child_by_kind, diagnostic_for_node, is_anonymous_class, node_text, walk_node,
+};
+use crate::analyzer::config::PhpVersion;
+use crate::analyzer::project::{ClassSymbol, ProjectContext};
+use crate::analyzer::{Diagnostic, Severity, parser};
+use std::collections::HashSet;
+
+/// PHP release that deprecated creating properties that were never declared.
+const DEPRECATED_SINCE: PhpVersion = PhpVersion::new(8, 2);
+
+/// Flags `$this->name = ...` writes to properties the class never declares.
+///
+/// Classes marked `#[AllowDynamicProperties]`, classes implementing `__set`, and
+/// classes whose hierarchy is not fully known to the project are skipped.
+///
+/// ```php
+/// class User {
+///     public function __construct(string $name) {
+///         $this->name = $name; // ✗ Deprecated in PHP 8.2
+///     }
+/// }
+/// ```
+pub struct DynamicPropertiesRule {
+    php_version: PhpVersion,
+}
+
+impl DynamicPropertiesRule {
+    pub fn new(php_version: PhpVersion) -> Self {
+        Self { php_version }
+    }
+}
+
+impl DiagnosticRule for DynamicPropertiesRule {
+    fn name(&self) -> &str {
+        "compat/dynamic_properties"
+    }
+
+    fn description(&self) -> &str {
+        "Writes to undeclared properties, deprecated since PHP 8.2"
+    }
+
+    fn run(&self, parsed: &parser::ParsedSource, context: &ProjectContext) -> Vec<Diagnostic> {
+        if self.php_version < DEPRECATED_SINCE {
+            return Vec::new();
+        }
+
+        let Some(scope) = context.scope_for(&parsed.path) else {
+            return Vec::new();
+        };
+
+        let mut diagnostics = Vec::new();
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            let body = match node.kind() {
+                "class_declaration" => node.child_by_field_name("body"),
+                _ if is_anonymous_class(node) => child_by_kind(node, "declaration_list"),
+                _ => return,
+            };
+
+            let Some(class) = scope
+                .classes
+                .iter()
+                .find(|class| class.span.start == node.start_position())
+            else {
+                return;
+            };
+
+            let mut declared = HashSet::new();
+            if collect_declared_properties(context, class, &mut HashSet::new(), &mut declared)
+                .is_none()
+            {
+                return;
+            }
+
+            let Some(body) = body else {
+                return;
+            };
+
+            let mut reported = HashSet::new();
+            for (name_node, property) in this_property_writes(body, parsed) {
+                if declared.contains(&property) || !reported.insert(property.clone()) {
+                    continue;
+                }
+
+                diagnostics.push(diagnostic_for_node(
+                    parsed,
+                    name_node,
+                    Severity::Warning,
+                    format!(
+                        "creation of dynamic property {}::${} is deprecated since PHP {}",
+                        class.name, property, DEPRECATED_SINCE
+                    ),
+                ));
+            }
+        });
+
+        diagnostics
+    }
+}
+
+/// Gathers the properties declared by `class`, its traits and its ancestors.
+///
+/// Returns `None` when the hierarchy allows dynamic properties or references a
+/// class/trait that is not part of the project, since nothing can be proven then.
+fn collect_declared_properties(
+    context: &ProjectContext,
+    class: &ClassSymbol,
+    seen: &mut HashSet<String>,
+    declared: &mut HashSet<String>,
+) -> Option<()> {
+    if !seen.insert(class.fq_name.clone()) {
+        return Some(());
+    }
+
+    if class.has_attribute("AllowDynamicProperties") || class.has_method("__set") {
+        return None;
+    }
+
+    declared.extend(class.properties.iter().cloned());
+
+    for trait_name in &class.traits {
+        let used = context.class_symbol(trait_name)?;
+        collect_declared_properties(context, used, seen, declared)?;
+    }
+
+    if let Some(parent) = &class.parent {
+        let parent = context.class_symbol(parent)?;
+        collect_declared_properties(context, parent, seen, declared)?;
+    }
+
+    Some(())
+}
+
+/// Finds assignments to `$this->name` (including `$this->name[] = ...`) inside a
+/// class body, without descending into anonymous classes where `$this` differs.
+fn this_property_writes<'a>(
+    body: tree_sitter::Node<'a>,
+    parsed: &parser::ParsedSource,
+) -> Vec<(tree_sitter::Node<'a>, String)> {
+    let mut writes = Vec::new();
+    let mut stack = vec![body];
+
+    while let Some(node) = stack.pop() {
+        if matches!(
+            node.kind(),
+            "assignment_expression" | "augmented_assignment_expression"
+        ) && let Some(write) = node
+            .child_by_field_name("left")
+            .and_then(|left| this_property_target(left, parsed))
+        {
+            writes.push(write);
+        }
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() == "declaration_list" && child != body {
+                continue;
+            }
+            stack.push(child);
+        }
+    }
+
+    writes.sort_by_key(|(name_node, _)| name_node.start_byte());
+    writes
+}
+
+fn this_property_target<'a>(
+    mut target: tree_sitter::Node<'a>,
+    parsed: &parser::ParsedSource,
+) -> Option<(tree_sitter::Node<'a>, String)> {
+    while target.kind() == "subscript_expression" {
+        target = target.named_child(0)?;
+    }
+
+    if target.kind() != "member_access_expression" {
+        return None;
+    }
+
+    let object = target.child_by_field_name("object")?;
+    if object.kind() != "variable_name" || node_text(object, parsed)? != "$this" {
+        return None;
+    }
+
+    let name_node = target.child_by_field_name("name")?;
+    if name_node.kind() != "name" {
+        return None;
+    }
+
+    let name = node_text(name_node, parsed)?;
+    Some((name_node, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::{
+        assert_diagnostics_exact, assert_no_diagnostics, run_rule_with_context,
+    };
+
+    #[test]
+    fn test_dynamic_property_writes() {
+        let source = r#"<?php
+namespace App;
+
+trait HasId
+{
+    protected int $id;
+}
+
+class Model
+{
+    protected array $attributes = [];
+}
+
+class User extends Model
+{
+    use HasId;
+
+    public string $email;
+
+    public function __construct(private string $name)
+    {
+        $this->id = 1;
+        $this->email = 'a@example.com';
+        $this->attributes[] = $name;
+        $this->nickname = $name;
+        $this->tags[] = 'new';
+        $this->nickname = strtolower($name);
+    }
+}
+
+$guest = new class extends Model {
+    public function fill(): void
+    {
+        $this->attributes[] = 'guest';
+        $this->label = 'Guest';
+    }
+};
+"#;
+
+        let rule = DynamicPropertiesRule::new(PhpVersion::LATEST);
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "warning: creation of dynamic property User::$nickname is deprecated since PHP 8.2",
+                "warning: creation of dynamic property User::$tags is deprecated since PHP 8.2",
+                "warning: creation of dynamic property App\\Model@anonymous::$label is deprecated since PHP 8.2",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_dynamic_properties_allowed() {
+        let source = r#"<?php
+namespace App;
+
+use Vendor\BaseModel;
+
+#[\AllowDynamicProperties]
+class Attributed
+{
+    public function fill(): void
+    {
+        $this->anything = 1;
+    }
+}
+
+class Magic
+{
+    public function __set(string $name, mixed $value): void {}
+
+    public function fill(): void
+    {
+        $this->anything = 1;
+    }
+}
+
+class Vendored extends BaseModel
+{
+    public function fill(): void
+    {
+        $this->anything = 1;
+    }
+}
+
+class Inherits extends Magic
+{
+    public function fill(): void
+    {
+        $this->other = 2;
+    }
+}
+"#;
+
+        let rule = DynamicPropertiesRule::new(PhpVersion::LATEST);
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_no_diagnostics(&diagnostics);
+    }
+
+    #[test]
+    fn test_skipped_below_target_version() {
+        let source = r#"<?php
+class User
+{
+    public function __construct()
+    {
+        $this->name = 'x';
+    }
+}
+"#;
+
+        let rule = DynamicPropertiesRule::new(PhpVersion::new(8, 1));
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_no_diagnostics(&diagnostics);
+    }
+}
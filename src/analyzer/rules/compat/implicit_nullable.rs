@@ -0,0 +1,230 @@
+use super::DiagnosticRule;
+use super::helpers::{diagnostic_for_node, node_text, walk_node};
+use crate::analyzer::config::PhpVersion;
+use crate::analyzer::fix;
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::{Diagnostic, Severity, parser};
+
+/// PHP release that deprecated implicitly nullable parameter types.
+const DEPRECATED_SINCE: PhpVersion = PhpVersion::new(8, 4);
+
+/// Flags parameters whose type is only nullable because of a `null` default.
+///
+/// ```php
+/// // ✗ Deprecated in PHP 8.4
+/// function find(int $id = null) {}
+///
+/// // ✓ OK
+/// function find(?int $id = null) {}
+/// ```
+pub struct ImplicitNullableRule {
+    php_version: PhpVersion,
+}
+
+impl ImplicitNullableRule {
+    pub fn new(php_version: PhpVersion) -> Self {
+        Self { php_version }
+    }
+
+    fn active(&self) -> bool {
+        self.php_version >= DEPRECATED_SINCE
+    }
+}
+
+impl DiagnosticRule for ImplicitNullableRule {
+    fn name(&self) -> &str {
+        "compat/implicit_nullable"
+    }
+
+    fn description(&self) -> &str {
+        "Parameters made nullable only by a null default, deprecated since PHP 8.4"
+    }
+
+    fn run(&self, parsed: &parser::ParsedSource, _context: &ProjectContext) -> Vec<Diagnostic> {
+        if !self.active() {
+            return Vec::new();
+        }
+
+        implicit_nullable_types(parsed)
+            .into_iter()
+            .map(|(type_node, name)| {
+                diagnostic_for_node(
+                    parsed,
+                    type_node,
+                    Severity::Warning,
+                    format!(
+                        "parameter {} is implicitly nullable; declare the type as nullable explicitly (deprecated since PHP {})",
+                        name, DEPRECATED_SINCE
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    fn fix(&self, parsed: &parser::ParsedSource, _context: &ProjectContext) -> Vec<fix::TextEdit> {
+        if !self.active() {
+            return Vec::new();
+        }
+
+        implicit_nullable_types(parsed)
+            .into_iter()
+            .filter_map(|(type_node, _)| {
+                let replacement = explicit_nullable_type(type_node, parsed)?;
+                Some(fix::TextEdit::new(
+                    type_node.start_byte(),
+                    type_node.end_byte(),
+                    replacement,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Collects the type node and variable name of every parameter that relies on
+/// a `null` default to accept `null`.
+fn implicit_nullable_types<'a>(
+    parsed: &'a parser::ParsedSource,
+) -> Vec<(tree_sitter::Node<'a>, String)> {
+    let mut found = Vec::new();
+    walk_node(parsed.tree.root_node(), &mut |node| {
+        if node.kind() != "simple_parameter" {
+            return;
+        }
+
+        let Some(default) = node.child_by_field_name("default_value") else {
+            return;
+        };
+        if default.kind() != "null" {
+            return;
+        }
+
+        let Some(type_node) = node.child_by_field_name("type") else {
+            return;
+        };
+        if type_allows_null(type_node, parsed) {
+            return;
+        }
+
+        let name = node
+            .child_by_field_name("name")
+            .and_then(|name| node_text(name, parsed))
+            .unwrap_or_default();
+        found.push((type_node, name));
+    });
+    found
+}
+
+fn type_allows_null(type_node: tree_sitter::Node, parsed: &parser::ParsedSource) -> bool {
+    match type_node.kind() {
+        "optional_type" => true,
+        "primitive_type" => node_text(type_node, parsed)
+            .map(|text| matches!(text.to_ascii_lowercase().as_str(), "null" | "mixed"))
+            .unwrap_or(false),
+        "union_type" => {
+            let mut cursor = type_node.walk();
+            type_node
+                .named_children(&mut cursor)
+                .any(|child| type_allows_null(child, parsed))
+        }
+        _ => false,
+    }
+}
+
+/// Rewrites a type so it accepts `null` without relying on the default value:
+/// single types become `?T`, unions gain `|null`, and intersections are wrapped
+/// into a DNF type `(A&B)|null`.
+fn explicit_nullable_type(
+    type_node: tree_sitter::Node,
+    parsed: &parser::ParsedSource,
+) -> Option<String> {
+    let text = node_text(type_node, parsed)?;
+    if type_node.kind() == "intersection_type" {
+        return Some(format!("({})|null", text));
+    }
+
+    if type_node.kind() == "union_type" && type_node.named_child_count() == 1 {
+        let inner = type_node.named_child(0)?;
+        if inner.kind() == "intersection_type" {
+            return Some(format!("({})|null", text));
+        }
+        return Some(format!("?{}", text));
+    }
+
+    Some(format!("{}|null", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::{
+        assert_diagnostics_exact, assert_fix, assert_no_diagnostics, parse_php, run_rule,
+    };
+
+    #[test]
+    fn test_implicit_nullable_parameters() {
+        let source = r#"<?php
+function find(int $id = null, string|int $key = null, Countable&Traversable $items = null) {}
+"#;
+
+        let parsed = parse_php(source);
+        let rule = ImplicitNullableRule::new(PhpVersion::LATEST);
+        let diagnostics = run_rule(&rule, &parsed);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "warning: parameter $id is implicitly nullable; declare the type as nullable explicitly (deprecated since PHP 8.4)",
+                "warning: parameter $key is implicitly nullable; declare the type as nullable explicitly (deprecated since PHP 8.4)",
+                "warning: parameter $items is implicitly nullable; declare the type as nullable explicitly (deprecated since PHP 8.4)",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_explicit_nullable_parameters_valid() {
+        let source = r#"<?php
+function find(?int $id = null, int|null $key = null, mixed $value = null, $untyped = null, int $count = 0) {}
+$fn = fn(?string $name = null) => $name;
+"#;
+
+        let parsed = parse_php(source);
+        let rule = ImplicitNullableRule::new(PhpVersion::LATEST);
+        let diagnostics = run_rule(&rule, &parsed);
+
+        assert_no_diagnostics(&diagnostics);
+    }
+
+    #[test]
+    fn test_skipped_below_target_version() {
+        let source = r#"<?php
+function find(int $id = null) {}
+"#;
+
+        let parsed = parse_php(source);
+        let rule = ImplicitNullableRule::new(PhpVersion::new(8, 3));
+        let diagnostics = run_rule(&rule, &parsed);
+
+        assert_no_diagnostics(&diagnostics);
+    }
+
+    #[test]
+    fn test_implicit_nullable_fix() {
+        let input = r#"<?php
+class Repository
+{
+    public function find(int $id = null, string|int $key = null, Countable&Traversable $items = null) {}
+}
+"#;
+
+        let expected = r#"<?php
+class Repository
+{
+    public function find(?int $id = null, string|int|null $key = null, (Countable&Traversable)|null $items = null) {}
+}
+"#;
+
+        let rule = ImplicitNullableRule::new(PhpVersion::LATEST);
+        let parsed = parse_php(input);
+        assert_fix(&rule, &parsed, input, expected);
+    }
+}
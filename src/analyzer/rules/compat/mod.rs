@@ -0,0 +1,7 @@
+pub use crate::analyzer::rules::{DiagnosticRule, helpers};
+
+pub mod dynamic_properties;
+pub mod implicit_nullable;
+
+pub use dynamic_properties::DynamicPropertiesRule;
+pub use implicit_nullable::ImplicitNullableRule;
@@ -19,6 +19,10 @@ impl DiagnosticRule for DuplicateSwitchCaseRule {
         "control_flow/duplicate_switch_case"
     }
 
+    fn description(&self) -> &str {
+        "switch statements with the same case value twice"
+    }
+
     fn run(&self, parsed: &parser::ParsedSource, _context: &ProjectContext) -> Vec<Diagnostic> {
         let mut visitor = DuplicateSwitchVisitor::new(parsed);
         visitor.visit(parsed.tree.root_node());
@@ -1,8 +1,12 @@
 use super::DiagnosticRule;
-use super::helpers::{diagnostic_for_node, literal_type, node_text, variable_name_text, walk_node};
-use crate::analyzer::project::ProjectContext;
+use super::helpers::{
+    TypeHint, constant_value, diagnostic_for_node, node_text, value_type, variable_name_text,
+    walk_node,
+};
+use crate::analyzer::project::{ConstantValue, ProjectContext};
 use crate::analyzer::{Severity, parser};
 use std::collections::HashMap;
+use tree_sitter::Node;
 
 pub struct ImpossibleComparisonRule;
 
@@ -17,10 +21,14 @@ impl DiagnosticRule for ImpossibleComparisonRule {
         "control_flow/impossible_comparison"
     }
 
+    fn description(&self) -> &str {
+        "Strict comparisons that can never be true because the types differ"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
-        _context: &ProjectContext,
+        context: &ProjectContext,
     ) -> Vec<crate::analyzer::Diagnostic> {
         let mut diagnostics = Vec::new();
         let mut var_types = HashMap::new();
@@ -29,7 +37,7 @@ impl DiagnosticRule for ImpossibleComparisonRule {
             if node.kind() == "assignment_expression" {
                 if let (Some(var_node), Some(value_node)) = (node.child(0), node.child(2)) {
                     if let Some(name) = variable_name_text(var_node, parsed) {
-                        if let Some(ty) = literal_type(value_node) {
+                        if let Some(ty) = value_type(value_node, parsed, context) {
                             var_types.insert(name, ty);
                         }
                     }
@@ -53,6 +61,13 @@ impl DiagnosticRule for ImpossibleComparisonRule {
             let left = left.unwrap();
             let right = right.unwrap();
 
+            if let Some(diagnostic) = constant_call_comparison(node, left, right, parsed, context)
+                .or_else(|| constant_call_comparison(node, right, left, parsed, context))
+            {
+                diagnostics.push(diagnostic);
+                return;
+            }
+
             let var_name = match variable_name_text(left, parsed) {
                 Some(name) => name,
                 None => return,
@@ -63,12 +78,17 @@ impl DiagnosticRule for ImpossibleComparisonRule {
                 None => return,
             };
 
-            let right_type = match literal_type(right) {
+            let right_type = match value_type(right, parsed, context) {
                 Some(ty) => ty,
                 None => return,
             };
 
-            if left_type != right_type {
+            // Enum cases are named as written, so two names may be one enum
+            let both_objects = matches!(
+                (&left_type, &right_type),
+                (TypeHint::Object(_), TypeHint::Object(_))
+            );
+            if left_type != right_type && !both_objects {
                 let expression = node_text(node, parsed).unwrap_or_else(|| "expression".into());
                 diagnostics.push(diagnostic_for_node(
                     parsed,
@@ -83,10 +103,65 @@ impl DiagnosticRule for ImpossibleComparisonRule {
     }
 }
 
+/// Reports `call === value` when the called function always returns a
+/// different constant than `value`.
+fn constant_call_comparison(
+    comparison: Node,
+    call: Node,
+    other: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<crate::analyzer::Diagnostic> {
+    if call.kind() != "function_call_expression" {
+        return None;
+    }
+    let returned = constant_value(call, parsed, context)?;
+    let compared = constant_value(other, parsed, context)?;
+    if !certainly_different(&returned, &compared) {
+        return None;
+    }
+
+    let function = node_text(call.child_by_field_name("function")?, parsed)?;
+    let expression = node_text(comparison, parsed)?;
+    Some(diagnostic_for_node(
+        parsed,
+        comparison,
+        Severity::Error,
+        format!(
+            "comparison \"{expression}\" is always false: {function}() always returns {returned}"
+        ),
+    ))
+}
+
+/// Whether two constants are never identical. Strings with escapes and
+/// numbers written differently, like `0x10` and `16`, are compared only when
+/// their values are plain.
+fn certainly_different(a: &ConstantValue, b: &ConstantValue) -> bool {
+    let (ConstantValue::Literal(a), ConstantValue::Literal(b)) = (a, b) else {
+        return a != b;
+    };
+    if a == b || a.contains('\\') || b.contains('\\') {
+        return false;
+    }
+
+    let is_number = |literal: &str| literal.starts_with(|c: char| c.is_ascii_digit() || c == '-');
+    if !(is_number(a) && is_number(b)) {
+        return true;
+    }
+    let is_float = |literal: &str| !literal.contains('x') && literal.contains(['.', 'e']);
+    if is_float(a) != is_float(b) {
+        return true;
+    }
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a != b,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule};
+    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule, run_rule_with_context};
 
     #[test]
     fn test_impossible_comparison() {
@@ -123,4 +198,63 @@ if ($flag === true) {
 
         assert_no_diagnostics(&diagnostics);
     }
+
+    #[test]
+    fn test_impossible_comparison_with_constant_returns() {
+        let source = r#"<?php
+enum Status: string { case Active = 'active'; case Closed = 'closed'; }
+class Defaults { const MODE = "strict"; }
+
+function mode(): string { return Defaults::MODE; }
+function status(): Status { return Status::Active; }
+function limit(): int { return 1_000; }
+
+if (mode() === 'loose') {}
+if ('strict' === mode()) {}
+if (status() === Status::Closed) {}
+if (status() === Status::Active) {}
+if (limit() === 1000) {}
+if (limit() === 1000.0) {}
+if (limit() === 0x3E8) {}
+"#;
+
+        let rule = ImpossibleComparisonRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "error: comparison \"mode() === 'loose'\" is always false: mode() always returns 'strict'",
+                "error: comparison \"status() === Status::Closed\" is always false: status() always returns Status::Active",
+                "error: comparison \"limit() === 1000.0\" is always false: limit() always returns 1000",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_impossible_comparison_with_constants() {
+        let source = r#"<?php
+enum Status: string { case Active = 'active'; }
+class Limits { const MAX = 10; }
+
+$max = Limits::MAX;
+if ($max === 'ten') {}
+if ($max === Limits::MAX) {}
+$status = Status::Active;
+if ($status === Status::Active) {}
+if ($status === 'active') {}
+if ($status->value === 'active') {}
+"#;
+
+        let rule = ImpossibleComparisonRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "error: comparison \"$max === 'ten'\" is always false due to type difference",
+                "error: comparison \"$status === 'active'\" is always false due to type difference",
+            ],
+        );
+    }
 }
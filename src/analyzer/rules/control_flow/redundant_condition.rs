@@ -1,9 +1,14 @@
 use super::DiagnosticRule;
-use super::helpers::{child_by_kind, diagnostic_for_node, node_text, walk_node};
+use super::helpers::{child_by_kind, constant_value, diagnostic_for_node, node_text};
+use crate::analyzer::artifacts::Artifact;
 use crate::analyzer::project::ProjectContext;
 use crate::analyzer::{Severity, parser};
 use std::collections::{HashMap, HashSet};
+use tree_sitter::Node;
 
+/// Flags `if` conditions that repeat an earlier guard, and conditions that
+/// call a function whose body always returns the same constant, such as
+/// `if (featureEnabled())` when `featureEnabled()` is `return false;`.
 pub struct RedundantConditionRule;
 
 impl RedundantConditionRule {
@@ -17,36 +22,51 @@ impl DiagnosticRule for RedundantConditionRule {
         "control_flow/redundant_condition"
     }
 
+    fn description(&self) -> &str {
+        "Conditions that repeat an earlier guard or call a function with a constant result"
+    }
+
+    fn requires(&self) -> &[Artifact] {
+        &[Artifact::ControlFlow]
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
-        _context: &ProjectContext,
+        context: &ProjectContext,
     ) -> Vec<crate::analyzer::Diagnostic> {
         let mut diagnostics = Vec::new();
-        let mut seen_by_parent: HashMap<usize, HashSet<String>> = HashMap::new();
+        let mut seen_by_block: HashMap<usize, HashSet<String>> = HashMap::new();
 
-        walk_node(parsed.tree.root_node(), &mut |node| {
-            if node.kind() != "if_statement" {
-                return;
+        for branch in &parsed.control_flow().branches {
+            let node = branch.node.node(parsed);
+            if let Some(condition) = node
+                .child_by_field_name("condition")
+                .and_then(|condition| condition.named_child(0))
+                && let Some(diagnostic) = constant_condition(condition, parsed, context)
+            {
+                diagnostics.push(diagnostic);
             }
 
+            // Only `if`s that follow each other in the same block guard the same code.
+            let Some(block) = branch.block else {
+                continue;
+            };
+
             let parenthesized = match child_by_kind(node, "parenthesized_expression") {
                 Some(expr) => expr,
-                None => return,
+                None => continue,
             };
 
-            let condition = parenthesized.child(1);
-            if condition.is_none() {
-                return;
-            }
-            let condition = condition.unwrap();
+            let Some(condition) = parenthesized.child(1) else {
+                continue;
+            };
             let text = match node_text(condition, parsed) {
                 Some(text) => text,
-                None => return,
+                None => continue,
             };
 
-            let parent_id = node.parent().map(|parent| parent.id()).unwrap_or(0);
-            let seen = seen_by_parent.entry(parent_id).or_default();
+            let seen = seen_by_block.entry(block).or_default();
 
             if seen.contains(&text) {
                 diagnostics.push(diagnostic_for_node(
@@ -58,16 +78,54 @@ impl DiagnosticRule for RedundantConditionRule {
             } else {
                 seen.insert(text);
             }
-        });
+        }
 
         diagnostics
     }
 }
 
+/// Reports a condition that calls a function always returning the same
+/// constant, possibly negated.
+fn constant_condition(
+    condition: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<crate::analyzer::Diagnostic> {
+    let mut call = condition;
+    let mut negated = false;
+    loop {
+        match call.kind() {
+            "parenthesized_expression" => call = call.named_child(0)?,
+            "unary_op_expression" if call.child(0)?.kind() == "!" => {
+                negated = !negated;
+                call = call.named_child(0)?;
+            }
+            _ => break,
+        }
+    }
+    if call.kind() != "function_call_expression" {
+        return None;
+    }
+
+    let value = constant_value(call, parsed, context)?;
+    let truthy = value.is_truthy()? != negated;
+    let function = node_text(call.child_by_field_name("function")?, parsed)?;
+    let text = node_text(condition, parsed)?;
+    Some(diagnostic_for_node(
+        parsed,
+        condition,
+        Severity::Error,
+        format!("condition \"{text}\" is always {truthy}: {function}() always returns {value}"),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule};
+    use crate::analyzer::rules::test_utils::{
+        assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule,
+        run_rule_with_context,
+    };
 
     #[test]
     fn test_redundant_condition() {
@@ -92,6 +150,90 @@ if ($value > 5) {
         assert_diagnostics_exact(&diagnostics, &["error: redundant condition \"$value > 5\" repeats an earlier guard"]);
     }
 
+    #[test]
+    fn test_condition_on_constant_return() {
+        let source = r#"<?php
+namespace App;
+
+enum Mode { case Fast; }
+class Flags { const BETA = 0; }
+
+function featureEnabled(): bool
+{
+    return false;
+}
+
+function betaEnabled(): int
+{
+    if (PHP_OS === 'Linux') {
+        return Flags::BETA;
+    }
+    return Flags::BETA;
+}
+
+function mode(): Mode
+{
+    return Mode::Fast;
+}
+
+function varies(bool $flag): bool
+{
+    if ($flag) {
+        return true;
+    }
+    return false;
+}
+
+function maybe()
+{
+    if (rand()) {
+        return true;
+    }
+}
+
+if (featureEnabled()) {
+} elseif (!\App\betaEnabled()) {
+}
+if (mode()) {}
+if (varies(true) || maybe()) {}
+"#;
+
+        let rule = RedundantConditionRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "error: condition \"featureEnabled()\" is always false: featureEnabled() always returns false",
+                "error: condition \"!\\App\\betaEnabled()\" is always true: \\App\\betaEnabled() always returns 0",
+                "error: condition \"mode()\" is always true: mode() always returns Mode::Fast",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_condition_on_conditionally_defined_function() {
+        let source = r#"<?php
+if (PHP_VERSION_ID >= 80000) {
+    function has_feature() { return true; }
+} else {
+    function has_feature() { return false; }
+}
+
+if (!function_exists('polyfilled')) {
+    function polyfilled() { return false; }
+}
+
+if (has_feature()) {}
+if (polyfilled()) {}
+"#;
+
+        let rule = RedundantConditionRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_no_diagnostics(&diagnostics);
+    }
+
     #[test]
     fn test_redundant_condition_valid() {
         let source = r#"<?php
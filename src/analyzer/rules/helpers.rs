@@ -1,25 +1,14 @@
 use crate::analyzer::parser;
-use crate::analyzer::{Diagnostic, Severity, Span};
+use crate::analyzer::phpdoc::{AssertKind, AssertTag};
+use crate::analyzer::project::{
+    ConstantValue, FunctionSymbol, ProjectContext, anonymous_class_key, literal_constant_value,
+};
+pub use crate::analyzer::types::TypeHint;
+use crate::analyzer::types::type_expression_to_hint;
+use crate::analyzer::{Diagnostic, Severity, Span, SpanColumns};
 use std::collections::HashMap;
-use tree_sitter::Node;
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum TypeHint {
-    Int,
-    String,
-    Bool,
-    Float,
-    Object(String),          // Stores the class/interface name
-    Nullable(Box<TypeHint>), // Wraps another type to make it nullable
-    Union(Vec<TypeHint>),    // Union of multiple types (int|string)
-    Array(Box<TypeHint>),    // Array of a specific type (int[], User[])
-    GenericArray {           // Associative array with key/value types (array<string, int>)
-        key: Box<TypeHint>,
-        value: Box<TypeHint>,
-    },
-    ShapedArray(Vec<(String, TypeHint)>), // Shaped array with named fields (array{name: string, age: int})
-    Unknown,
-}
+use std::ops::Range;
+use tree_sitter::{Node, Point};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LiteralKind {
@@ -42,32 +31,23 @@ pub fn diagnostic_for_node(
         end: node.end_position(),
     };
 
-    let snippet_before = span
-        .start
-        .row
-        .checked_sub(1)
-        .and_then(|row| line_at(parsed.source.as_str(), row));
+    diagnostic_for_span(parsed, span, severity, message)
+}
 
-    let snippet_line = line_at(parsed.source.as_str(), span.start.row);
-    let snippet_after = line_at(parsed.source.as_str(), span.start.row + 1);
-    let caret_col = Some(span.start.column);
-    let caret_len = if span.start.row == span.end.row {
-        span.end.column.saturating_sub(span.start.column).max(1)
-    } else {
-        1
+/// Builds a diagnostic for a byte range, e.g. a PHPDoc tag inside a comment.
+pub fn diagnostic_for_range(
+    parsed: &parser::ParsedSource,
+    range: Range<usize>,
+    severity: Severity,
+    message: impl Into<String>,
+) -> Diagnostic {
+    let source = parsed.source.as_str();
+    let span = Span {
+        start: point_at(source, range.start),
+        end: point_at(source, range.end),
     };
 
-    Diagnostic::with_span(
-        parsed.path.clone(),
-        severity,
-        message,
-        span,
-        snippet_before,
-        snippet_line,
-        snippet_after,
-        caret_col,
-        caret_len,
-    )
+    diagnostic_for_span(parsed, span, severity, message)
 }
 
 pub fn diagnostic_for_span(
@@ -76,22 +56,27 @@ pub fn diagnostic_for_span(
     severity: Severity,
     message: impl Into<String>,
 ) -> Diagnostic {
+    let source = parsed.source.as_str();
     let snippet_before = span
         .start
         .row
         .checked_sub(1)
-        .and_then(|row| line_at(parsed.source.as_str(), row));
+        .and_then(|row| line_at(source, row));
 
-    let snippet_line = line_at(parsed.source.as_str(), span.start.row);
-    let snippet_after = line_at(parsed.source.as_str(), span.start.row + 1);
-    let caret_col = Some(span.start.column);
+    let snippet_line = line_at(source, span.start.row);
+    let snippet_after = line_at(source, span.start.row + 1);
+
+    // Tree-sitter columns are byte offsets; the caret is drawn in characters so
+    // it lines up under multibyte text.
+    let columns = SpanColumns::new(source, &span);
+    let caret_col = Some(columns.start_char);
     let caret_len = if span.start.row == span.end.row {
-        span.end.column.saturating_sub(span.start.column).max(1)
+        columns.end_char.saturating_sub(columns.start_char).max(1)
     } else {
         1
     };
 
-    Diagnostic::with_span(
+    let mut diagnostic = Diagnostic::with_span(
         parsed.path.clone(),
         severity,
         message,
@@ -101,7 +86,19 @@ pub fn diagnostic_for_span(
         snippet_after,
         caret_col,
         caret_len,
-    )
+    );
+    diagnostic.columns = Some(columns);
+    diagnostic
+}
+
+/// Converts a byte offset into a tree-sitter point (row and byte column).
+pub fn point_at(source: &str, offset: usize) -> Point {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+    Point {
+        row: before.matches('\n').count(),
+        column: before.len() - line_start,
+    }
 }
 
 pub fn line_at(source: &str, row: usize) -> Option<String> {
@@ -135,6 +132,11 @@ pub fn child_by_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
     None
 }
 
+/// Whether `node` is `new class { ... }`, which declares a class inline.
+pub fn is_anonymous_class(node: Node) -> bool {
+    node.kind() == "object_creation_expression" && child_by_kind(node, "declaration_list").is_some()
+}
+
 pub fn find_descendant_by_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
     let mut stack = vec![node];
     while let Some(current) = stack.pop() {
@@ -190,18 +192,6 @@ pub fn has_conditional_ancestor(node: Node, boundary: Node) -> bool {
     false
 }
 
-pub fn is_definition(node: Node) -> bool {
-    if let Some(parent) = node.parent() {
-        match parent.kind() {
-            "assignment_expression" => parent.named_child(0).map_or(false, |left| left == node),
-            "simple_parameter" | "variadic_parameter" | "property_promotion_parameter" => true,
-            _ => false,
-        }
-    } else {
-        false
-    }
-}
-
 pub fn collect_function_signatures(
     parsed: &parser::ParsedSource,
 ) -> HashMap<String, FunctionSignature> {
@@ -348,13 +338,28 @@ pub fn literal_type(node: Node) -> Option<TypeHint> {
 /// Infer the type of a node, including variables with known assignments
 /// Returns Some(TypeHint::Unknown) if the node is a variable but type cannot be determined
 /// Returns None if the node is not a value expression
-pub fn infer_type(node: Node, parsed: &parser::ParsedSource) -> Option<TypeHint> {
+pub fn infer_type(
+    node: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<TypeHint> {
     // First try to get literal type
     if let Some(lit_type) = literal_type(node) {
         return Some(lit_type);
     }
 
     // Check for object creation expression (new User())
+    if is_anonymous_class(node) {
+        // `new class extends Foo { ... }` is an instance of `Foo@anonymous`
+        let key = anonymous_class_key(parsed, node);
+        return Some(
+            context
+                .class_symbol(&key)
+                .map(|class| TypeHint::Object(class.name.clone()))
+                .unwrap_or(TypeHint::Unknown),
+        );
+    }
+
     if node.kind() == "object_creation_expression" {
         // Get the class name from the object creation
         if let Some(name_node) = child_by_kind(node, "name") {
@@ -371,13 +376,26 @@ pub fn infer_type(node: Node, parsed: &parser::ParsedSource) -> Option<TypeHint>
         return Some(TypeHint::Unknown);
     }
 
+    // Class constants and enum cases resolve through the project's declarations
+    if matches!(
+        node.kind(),
+        "class_constant_access_expression" | "member_access_expression"
+    ) {
+        let constant = class_constant_type(node, parsed, context);
+        if constant.is_some() || node.kind() == "class_constant_access_expression" {
+            return Some(constant.unwrap_or(TypeHint::Unknown));
+        }
+    }
+
     // If it's a variable, try to infer from context
     if node.kind() == "variable_name" {
         // For now, we'll collect variable assignments in the same scope
         // and try to infer the type
         if let Some(var_name) = variable_name_text(node, parsed) {
             // Look backwards in the tree to find assignments to this variable
-            if let Some(inferred) = infer_variable_type(&var_name, node, parsed) {
+            let declared = infer_variable_type(&var_name, node, parsed, context);
+            if let Some(inferred) = narrow_variable_type(&var_name, declared, node, parsed, context)
+            {
                 return Some(inferred);
             }
         }
@@ -388,46 +406,177 @@ pub fn infer_type(node: Node, parsed: &parser::ParsedSource) -> Option<TypeHint>
     None
 }
 
-/// Try to infer a variable's type by looking at @var declarations or assignments
-fn infer_variable_type(
-    var_name: &str,
-    _context_node: Node,
+/// The type of a literal, class constant or enum case; None when it isn't one
+/// of those or its type isn't known
+pub fn value_type(
+    node: Node,
     parsed: &parser::ParsedSource,
+    context: &ProjectContext,
 ) -> Option<TypeHint> {
-    use crate::analyzer::phpdoc::{extract_phpdoc_for_node, TypeExpression};
+    literal_type(node).or_else(|| class_constant_type(node, parsed, context))
+}
 
-    let root = parsed.tree.root_node();
-    let mut found_type = None;
+/// The type of a value being unpacked: a call's @return type, or what
+/// [`infer_type`] knows; None when the type isn't known
+pub fn source_type(
+    node: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<TypeHint> {
+    match node.kind() {
+        "function_call_expression" => call_return_type(node, parsed, context),
+        "parenthesized_expression" => source_type(node.named_child(0)?, parsed, context),
+        _ => infer_type(node, parsed, context).filter(|hint| *hint != TypeHint::Unknown),
+    }
+}
 
-    // First priority: Look for @var declarations
-    walk_node(root, &mut |node| {
-        if found_type.is_some() {
-            return; // Already found
+/// The type of `Foo::BAR` (from a literal initialiser), `Status::Active` (the
+/// enum itself), `Foo::class` (a string), and `->value`/`->name` on an enum case
+pub fn class_constant_type(
+    node: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<TypeHint> {
+    if node.kind() == "member_access_expression" {
+        let property = node_text(node.child_by_field_name("name")?, parsed)?;
+        let object = node.child_by_field_name("object")?;
+        let (class, constant) = class_constant_target(object, parsed, context)?;
+        let symbol = context.enum_symbol(&class)?;
+        if !symbol.cases.contains(&constant) {
+            return None;
         }
+        return match property.as_str() {
+            "name" => Some(TypeHint::String),
+            "value" => symbol.backing.clone(),
+            _ => None,
+        };
+    }
 
-        // Check for inline @var on expression_statement
-        if node.kind() == "expression_statement" {
-            if let Some(phpdoc) = extract_phpdoc_for_node(node, parsed) {
-                if let Some(var_tag) = phpdoc.var_tag {
-                    // Check if the @var is for our variable
-                    if let Some(declared_name) = &var_tag.name {
-                        if declared_name == var_name {
-                            // Found a @var declaration for this variable
-                            found_type = type_expression_to_hint(&var_tag.type_expr);
-                        }
-                    }
+    if node.kind() != "class_constant_access_expression" {
+        return None;
+    }
+    let (class, constant) = class_constant_target(node, parsed, context)?;
+    if constant.eq_ignore_ascii_case("class") {
+        return Some(TypeHint::String);
+    }
+    if let Some(symbol) = context.enum_symbol(&class)
+        && symbol.cases.contains(&constant)
+    {
+        // Named as written, like `new Foo()`; `self::Case` takes the enum's name
+        let written = node_text(node.named_child(0)?, parsed)?;
+        let name = if is_relative_scope(&written) {
+            symbol.name.clone()
+        } else {
+            written
+        };
+        return Some(TypeHint::Object(name));
+    }
+    context.class_constant_type(&class, &constant)
+}
+
+/// The value of a literal, a class constant or enum case, or a call to a
+/// function that always returns one of those; None when it isn't known
+pub fn constant_value(
+    node: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<ConstantValue> {
+    match node.kind() {
+        "function_call_expression" => {
+            context.constant_return(called_function(node, parsed, context)?)
+        }
+        "class_constant_access_expression" => {
+            let (class, constant) = class_constant_target(node, parsed, context)?;
+            context.class_constant_value(&class, &constant)
+        }
+        "parenthesized_expression" => constant_value(node.named_child(0)?, parsed, context),
+        "null" => Some(ConstantValue::Literal("null".into())),
+        _ => literal_constant_value(node, parsed).map(ConstantValue::Literal),
+    }
+}
+
+/// The fully qualified class and the constant name of `Foo::BAR`, with `self`,
+/// `static` and `parent` resolved against the enclosing class or enum
+fn class_constant_target(
+    node: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<(String, String)> {
+    if node.kind() != "class_constant_access_expression" {
+        return None;
+    }
+    let scope = node.named_child(0)?;
+    let constant = node_text(node.named_child(1)?, parsed)?;
+    let file_scope = context.scope_for(&parsed.path)?;
+    let class = match scope.kind() {
+        "name" | "qualified_name" => file_scope.resolve_class(&node_text(scope, parsed)?),
+        "relative_scope" => {
+            let declaration = enclosing_class_like(node)?;
+            let fq_name = if declaration.kind() == "object_creation_expression" {
+                anonymous_class_key(parsed, declaration)
+            } else {
+                let name = node_text(declaration.child_by_field_name("name")?, parsed)?;
+                match file_scope.namespace.as_deref() {
+                    Some(namespace) => format!("{namespace}\\{name}"),
+                    None => name,
                 }
+            };
+            if node_text(scope, parsed)?.eq_ignore_ascii_case("parent") {
+                context.class_symbol(&fq_name)?.parent.clone()?
+            } else {
+                fq_name
             }
         }
-    });
+        _ => return None,
+    };
+    Some((class, constant))
+}
+
+fn is_relative_scope(name: &str) -> bool {
+    ["self", "static", "parent"]
+        .iter()
+        .any(|scope| name.eq_ignore_ascii_case(scope))
+}
+
+/// The class, trait or enum declaration around `node`, or the anonymous class
+/// (`new class { ... }`) whose body contains it
+fn enclosing_class_like(node: Node) -> Option<Node> {
+    let mut current = node.parent();
+    while let Some(candidate) = current {
+        if matches!(
+            candidate.kind(),
+            "class_declaration" | "trait_declaration" | "enum_declaration"
+        ) || is_anonymous_class(candidate)
+        {
+            return Some(candidate);
+        }
+        current = candidate.parent();
+    }
+    None
+}
 
-    // If we found a @var declaration, use it
-    if found_type.is_some() {
-        return found_type;
+/// Try to infer a variable's type by looking at @var declarations or assignments
+fn infer_variable_type(
+    var_name: &str,
+    context_node: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<TypeHint> {
+    let mut found_type = None;
+
+    // First priority: the closest @var annotation for this variable before the use
+    let annotation = parsed
+        .var_annotations()
+        .iter()
+        .rfind(|annotation| {
+            annotation.name == var_name && annotation.offset < context_node.start_byte()
+        });
+    if let Some(annotation) = annotation {
+        return type_expression_to_hint(&annotation.type_expr);
     }
 
-    // Second priority: Infer from literal assignment
-    walk_node(root, &mut |node| {
+    // Second priority: Infer from an assignment in the same function
+    walk_node(enclosing_scope(context_node), &mut |node| {
         if found_type.is_some() {
             return; // Already found
         }
@@ -440,9 +589,8 @@ fn infer_variable_type(
                         if name == var_name {
                             // Found an assignment to our variable
                             if let Some(right) = node.child_by_field_name("right") {
-                                if let Some(typ) = literal_type(right) {
-                                    found_type = Some(typ);
-                                }
+                                found_type = value_type(right, parsed, context)
+                                    .or_else(|| call_return_type(right, parsed, context));
                             }
                         }
                     }
@@ -454,50 +602,269 @@ fn infer_variable_type(
     found_type
 }
 
-/// Helper to convert TypeExpression to TypeHint (reused from phpdoc rules)
-fn type_expression_to_hint(expr: &crate::analyzer::phpdoc::TypeExpression) -> Option<TypeHint> {
+/// The @return type of a call to a known function, with conditional return
+/// types resolved against the literal arguments of the call
+fn call_return_type(
+    call: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<TypeHint> {
+    let symbol = called_function(call, parsed, context)?;
+    let return_type = symbol.return_type.as_ref()?;
+    type_expression_to_hint(&resolve_conditional(return_type, call, symbol, parsed))
+}
+
+/// The function symbol a `function_call_expression` resolves to
+fn called_function<'a>(
+    call: Node,
+    parsed: &parser::ParsedSource,
+    context: &'a ProjectContext,
+) -> Option<&'a FunctionSymbol> {
+    if call.kind() != "function_call_expression" {
+        return None;
+    }
+    let name = node_text(call.child_by_field_name("function")?, parsed)?;
+    context.resolve_function_symbol(&name, parsed)
+}
+
+/// The positional argument passed for `parameter`, if any
+fn argument_for<'a>(call: Node<'a>, symbol: &FunctionSymbol, parameter: &str) -> Option<Node<'a>> {
+    let index = symbol.params.iter().position(|name| name == parameter)?;
+    let arguments = call.child_by_field_name("arguments")?;
+    let mut cursor = arguments.walk();
+    let argument = arguments
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() == "argument")
+        .nth(index)?;
+    argument.named_child(0)
+}
+
+/// Pick the branch of a conditional type that a call's literal argument
+/// selects, falling back to both branches when it can't be decided
+fn resolve_conditional(
+    expr: &crate::analyzer::phpdoc::TypeExpression,
+    call: Node,
+    symbol: &FunctionSymbol,
+    parsed: &parser::ParsedSource,
+) -> crate::analyzer::phpdoc::TypeExpression {
     use crate::analyzer::phpdoc::TypeExpression;
 
-    match expr {
-        TypeExpression::Simple(s) => match s.as_str() {
-            "int" | "integer" => Some(TypeHint::Int),
-            "string" => Some(TypeHint::String),
-            "bool" | "boolean" => Some(TypeHint::Bool),
-            "float" | "double" => Some(TypeHint::Float),
-            _ => Some(TypeHint::Object(s.clone())),
-        },
-        TypeExpression::Nullable(inner) => {
-            type_expression_to_hint(inner).map(|t| TypeHint::Nullable(Box::new(t)))
+    let TypeExpression::Conditional {
+        parameter,
+        negated,
+        condition,
+        then,
+        otherwise,
+    } = expr
+    else {
+        return expr.clone();
+    };
+
+    let matches = argument_for(call, symbol, parameter)
+        .and_then(|argument| literal_matches(argument, condition, parsed));
+    match matches {
+        Some(matches) if matches != *negated => resolve_conditional(then, call, symbol, parsed),
+        Some(_) => resolve_conditional(otherwise, call, symbol, parsed),
+        None => TypeExpression::Union(vec![
+            resolve_conditional(then, call, symbol, parsed),
+            resolve_conditional(otherwise, call, symbol, parsed),
+        ]),
+    }
+}
+
+/// Whether a literal argument is of the condition's type; None for non-literals
+fn literal_matches(
+    argument: Node,
+    condition: &crate::analyzer::phpdoc::TypeExpression,
+    parsed: &parser::ParsedSource,
+) -> Option<bool> {
+    use crate::analyzer::phpdoc::TypeExpression;
+
+    let literal = match argument.kind() {
+        "boolean" => node_text(argument, parsed)?.to_ascii_lowercase(),
+        "null" => "null".to_string(),
+        "integer" => "int".to_string(),
+        "float" => "float".to_string(),
+        "string" | "encapsed_string" => "string".to_string(),
+        _ => return None,
+    };
+
+    let matches_member = |member: &TypeExpression| match member {
+        TypeExpression::Simple(name) => {
+            let name = name.to_ascii_lowercase();
+            name == literal || (name == "bool" && matches!(literal.as_str(), "true" | "false"))
         }
-        TypeExpression::Union(types) => {
-            let hints: Vec<TypeHint> = types
-                .iter()
-                .filter_map(|t| type_expression_to_hint(t))
-                .collect();
-            if hints.is_empty() {
-                None
-            } else {
-                Some(TypeHint::Union(hints))
+        TypeExpression::Mixed => true,
+        _ => false,
+    };
+
+    Some(match condition {
+        TypeExpression::Union(members) => members.iter().any(matches_member),
+        TypeExpression::Nullable(inner) => literal == "null" || matches_member(inner),
+        other => matches_member(other),
+    })
+}
+
+/// Apply the @phpstan-assert tags of guard calls made on the variable before
+/// `context_node` in the same function
+fn narrow_variable_type(
+    var_name: &str,
+    declared: Option<TypeHint>,
+    context_node: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<TypeHint> {
+    let scope = enclosing_scope(context_node);
+    let mut narrowed = declared;
+
+    walk_node(scope, &mut |call| {
+        if call.kind() != "function_call_expression"
+            || call.end_byte() > context_node.start_byte()
+            || enclosing_scope(call).id() != scope.id()
+        {
+            return;
+        }
+        let Some(outcome) = call_outcome(call, context_node, parsed) else {
+            return;
+        };
+        let Some(symbol) = called_function(call, parsed, context) else {
+            return;
+        };
+
+        for assert in &symbol.asserts {
+            let applies = match assert.kind {
+                AssertKind::Always => true,
+                AssertKind::IfTrue => outcome == Some(true),
+                AssertKind::IfFalse => outcome == Some(false),
+            };
+            let asserts_variable = argument_for(call, symbol, &assert.parameter)
+                .filter(|argument| argument.kind() == "variable_name")
+                .and_then(|argument| variable_name_text(argument, parsed))
+                .is_some_and(|name| name == var_name);
+            if applies && asserts_variable {
+                narrowed = apply_assertion(narrowed.take(), assert);
+            }
+        }
+    });
+
+    narrowed
+}
+
+/// The function body, or the whole file, that a node belongs to
+fn enclosing_scope(node: Node) -> Node {
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        if matches!(
+            parent.kind(),
+            "function_definition"
+                | "method_declaration"
+                | "anonymous_function_creation_expression"
+                | "arrow_function"
+        ) {
+            return parent;
+        }
+        current = parent;
+    }
+    current
+}
+
+/// How a call that runs before `use_node` finished, as seen from `use_node`:
+/// `Some(None)` when it returned, `Some(Some(result))` when its boolean result
+/// is known from the guard around it, and `None` when `use_node` can be
+/// reached without the call having returned
+fn call_outcome(call: Node, use_node: Node, parsed: &parser::ParsedSource) -> Option<Option<bool>> {
+    // Look through `!` and parentheses to the statement or condition holding the call
+    let mut result = true;
+    let mut expression = call;
+    while let Some(parent) = expression.parent() {
+        match parent.kind() {
+            "unary_op_expression" if parent.child(0).is_some_and(|op| op.kind() == "!") => {
+                result = !result;
             }
+            "parenthesized_expression" => {}
+            _ => break,
         }
-        TypeExpression::Array(inner) => {
-            type_expression_to_hint(inner).map(|t| TypeHint::Array(Box::new(t)))
+        expression = parent;
+    }
+
+    let parent = expression.parent()?;
+    match parent.kind() {
+        "expression_statement" => Some(None),
+        // assert(guard($x)) only returns when the guard is true
+        "argument" => {
+            let assert_call = parent.parent()?.parent()?;
+            let is_assert = assert_call.kind() == "function_call_expression"
+                && assert_call.parent()?.kind() == "expression_statement"
+                && assert_call
+                    .child_by_field_name("function")
+                    .and_then(|function| node_text(function, parsed))
+                    .is_some_and(|name| {
+                        name.trim_start_matches('\\').eq_ignore_ascii_case("assert")
+                    });
+            is_assert.then_some(Some(result))
         }
-        TypeExpression::Generic { base, params } => {
-            if base == "array" && params.len() == 2 {
-                let key_hint = type_expression_to_hint(&params[0])?;
-                let value_hint = type_expression_to_hint(&params[1])?;
-                return Some(TypeHint::GenericArray {
-                    key: Box::new(key_hint),
-                    value: Box::new(value_hint),
-                });
+        "if_statement" => {
+            let body = parent.child_by_field_name("body")?;
+            if contains(body, use_node) {
+                return Some(Some(result));
+            }
+            let alternative = parent.child_by_field_name("alternative");
+            if let Some(alternative) = alternative {
+                return (alternative.kind() == "else_clause" && contains(alternative, use_node))
+                    .then_some(Some(!result));
             }
-            None
+            // `if (!guard($x)) { return; }` guards the code that follows it
+            (use_node.start_byte() >= parent.end_byte() && always_exits(body))
+                .then_some(Some(!result))
         }
         _ => None,
     }
 }
 
+fn contains(ancestor: Node, node: Node) -> bool {
+    ancestor.start_byte() <= node.start_byte() && node.end_byte() <= ancestor.end_byte()
+}
+
+/// Whether a statement always leaves the enclosing block
+fn always_exits(statement: Node) -> bool {
+    match statement.kind() {
+        "return_statement" | "throw_expression" | "break_statement" | "continue_statement" => true,
+        "expression_statement" => statement
+            .named_child(0)
+            .is_some_and(|expression| expression.kind() == "throw_expression"),
+        "compound_statement" => {
+            let count = statement.named_child_count();
+            count > 0 && statement.named_child(count - 1).is_some_and(always_exits)
+        }
+        _ => false,
+    }
+}
+
+/// Narrow a type with an assertion: `Type` replaces it, `!Type` removes `Type` from it
+fn apply_assertion(current: Option<TypeHint>, assert: &AssertTag) -> Option<TypeHint> {
+    let asserted = type_expression_to_hint(&assert.type_expr)?;
+    if !assert.negated {
+        return Some(asserted);
+    }
+
+    match current? {
+        TypeHint::Nullable(inner) if asserted == TypeHint::Object("null".to_string()) => {
+            Some(*inner)
+        }
+        TypeHint::Union(members) => {
+            let mut remaining: Vec<TypeHint> = members
+                .into_iter()
+                .filter(|member| *member != asserted)
+                .collect();
+            match remaining.len() {
+                1 => remaining.pop(),
+                _ => Some(TypeHint::Union(remaining)),
+            }
+        }
+        other => Some(other),
+    }
+}
+
 fn literal_kind(node: Node) -> Option<LiteralKind> {
     match node.kind() {
         "string" | "encapsed_string" => Some(LiteralKind::String),
@@ -519,6 +886,7 @@ pub fn newline_for_source(source: &str) -> &'static str {
 pub fn extract_array_elements<'a>(
     array_node: Node<'a>,
     parsed: &parser::ParsedSource,
+    context: &ProjectContext,
 ) -> Vec<(Node<'a>, Option<TypeHint>)> {
     let mut elements = Vec::new();
     let mut cursor = array_node.walk();
@@ -538,7 +906,7 @@ pub fn extract_array_elements<'a>(
                 };
 
                 if let Some(val_node) = value_node {
-                    let elem_type = infer_type(val_node, parsed);
+                    let elem_type = infer_type(val_node, parsed, context);
                     elements.push((val_node, elem_type));
                 }
             }
@@ -557,6 +925,7 @@ pub fn extract_array_elements<'a>(
 pub fn extract_array_key_value_pairs<'a>(
     array_node: Node<'a>,
     parsed: &parser::ParsedSource,
+    context: &ProjectContext,
 ) -> Vec<(Option<Node<'a>>, Option<TypeHint>, Node<'a>, Option<TypeHint>)> {
     let mut pairs = Vec::new();
     let mut cursor = array_node.walk();
@@ -573,14 +942,14 @@ pub fn extract_array_key_value_pairs<'a>(
                     let value_node = child.named_child(1);
 
                     if let (Some(k_node), Some(v_node)) = (key_node, value_node) {
-                        let key_type = infer_type(k_node, parsed);
-                        let value_type = infer_type(v_node, parsed);
+                        let key_type = infer_type(k_node, parsed, context);
+                        let value_type = infer_type(v_node, parsed, context);
                         pairs.push((Some(k_node), key_type, v_node, value_type));
                     }
                 } else if child.named_child_count() == 1 {
                     // Simple array [value] - implicit integer keys
                     if let Some(val_node) = child.named_child(0) {
-                        let value_type = infer_type(val_node, parsed);
+                        let value_type = infer_type(val_node, parsed, context);
                         // Implicit integer key
                         pairs.push((None, Some(TypeHint::Int), val_node, value_type));
                     }
@@ -602,13 +971,44 @@ pub fn extract_array_key_value_pairs<'a>(
 /// - int is compatible with int (exact match)
 /// - ?string is compatible with string|null (equivalent)
 /// - string is compatible with ?string (subset)
+/// - list<int> is compatible with int[] and array<int, int>
+/// - array-key is compatible with int|string
 pub fn is_type_compatible(actual: &TypeHint, expected: &TypeHint) -> bool {
     // Exact match
     if actual == expected {
         return true;
     }
 
+    match actual {
+        // array-key is shorthand for int|string
+        TypeHint::ArrayKey if *expected != TypeHint::ArrayKey => {
+            return is_type_compatible(
+                &TypeHint::Union(vec![TypeHint::Int, TypeHint::String]),
+                expected,
+            );
+        }
+        // Emptiness isn't tracked, so plain and non-empty strings are interchangeable
+        TypeHint::NonEmptyString if *expected == TypeHint::String => return true,
+        TypeHint::String if *expected == TypeHint::NonEmptyString => return true,
+        _ => {}
+    }
+
     match expected {
+        TypeHint::ArrayKey => match actual {
+            TypeHint::Int | TypeHint::String | TypeHint::NonEmptyString => true,
+            TypeHint::Union(actual_types) => actual_types
+                .iter()
+                .all(|member| is_type_compatible(member, expected)),
+            _ => false,
+        },
+
+        // A list is an array with int keys, but not every array is a list
+        TypeHint::List(expected_elem) => {
+            if let TypeHint::List(actual_elem) = actual {
+                return is_type_compatible(actual_elem, expected_elem);
+            }
+            false
+        }
         // If expected is a union, actual must be compatible with at least one member
         TypeHint::Union(expected_types) => {
             // Check if actual matches any of the union members
@@ -648,12 +1048,12 @@ pub fn is_type_compatible(actual: &TypeHint, expected: &TypeHint) -> bool {
         }
 
         // If expected is an array, actual must be an array with compatible element type
-        TypeHint::Array(expected_elem) => {
-            if let TypeHint::Array(actual_elem) = actual {
-                return is_type_compatible(actual_elem, expected_elem);
+        TypeHint::Array(expected_elem) => match actual {
+            TypeHint::Array(actual_elem) | TypeHint::List(actual_elem) => {
+                is_type_compatible(actual_elem, expected_elem)
             }
-            false
-        }
+            _ => false,
+        },
 
         // If expected is a generic array, actual must have compatible key/value types
         TypeHint::GenericArray {
@@ -668,6 +1068,10 @@ pub fn is_type_compatible(actual: &TypeHint, expected: &TypeHint) -> bool {
                 return is_type_compatible(actual_key, expected_key)
                     && is_type_compatible(actual_value, expected_value);
             }
+            if let TypeHint::List(actual_elem) = actual {
+                return is_type_compatible(&TypeHint::Int, expected_key)
+                    && is_type_compatible(actual_elem, expected_value);
+            }
             false
         }
 
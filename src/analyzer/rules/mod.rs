@@ -1,37 +1,57 @@
 use super::project::ProjectContext;
+use crate::analyzer::artifacts::Artifact;
 use crate::analyzer::fix;
 use crate::analyzer::parser;
 
 pub mod api;
 pub mod cleanup;
+pub mod compat;
 pub mod control_flow;
 pub mod helpers;
+pub mod phpdoc;
 pub mod psr4;
 pub mod sanity;
 pub mod security;
 pub mod strict_typing;
+pub mod style;
 #[cfg(test)]
 pub mod test_utils;
 
 pub use api::{DeprecatedApiRule, InvalidThisRule};
-pub use cleanup::{UnusedUseRule, UnusedVariableRule};
+pub use cleanup::{
+    EnvironmentSpecificValuesRule, TodoCommentRule, UnusedUseRule, UnusedVariableRule,
+};
+pub use compat::{DynamicPropertiesRule, ImplicitNullableRule};
 pub use control_flow::{
     DuplicateSwitchCaseRule, FallthroughRule, ImpossibleComparisonRule, RedundantConditionRule,
     UnreachableCodeRule, UnreachableStatementRule,
 };
-pub use sanity::{ArrayKeyNotDefinedRule, DuplicateDeclarationRule, UndefinedVariableRule};
+pub use phpdoc::MalformedTagRule;
+pub use sanity::{
+    ArrayKeyNotDefinedRule, ConstantCollisionRule, DanglingReferenceRule, DuplicateDeclarationRule,
+    FloatEqualityRule, LocaleDependentRule, ModifyDuringIterationRule,
+    SuspiciousNumericLiteralRule, UndefinedVariableRule,
+};
 pub use security::{
     HardCodedCredentialsRule, HardCodedKeysRule, IncludeUserInputRule, MutatingLiteralRule,
     WeakHashingRule,
 };
 pub use strict_typing::{
-    ConsistentReturnRule, ForceReturnTypeRule, MissingArgumentRule, MissingReturnRule,
-    PhpDocParamCheckRule, PhpDocReturnCheckRule, PhpDocReturnValueCheckRule, PhpDocVarCheckRule,
-    StrictTypesRule, TypeMismatchRule,
+    CoalesceTypeMismatchRule, ConsistentReturnRule, ForceReturnTypeRule, MissingArgumentRule,
+    MissingReturnRule, PhpDocParamCheckRule, PhpDocReturnCheckRule, PhpDocReturnValueCheckRule,
+    PhpDocVarCheckRule, StrictTypesRule, TypeMismatchRule,
 };
+pub use style::FileHeaderRule;
 
 pub trait DiagnosticRule: Send + Sync {
     fn name(&self) -> &str;
+    /// One-line summary of what the rule reports, used when registering
+    /// inspection types with CI servers.
+    fn description(&self) -> &str;
+    /// Shared per-file artifacts the rule reads, built before it runs.
+    fn requires(&self) -> &[Artifact] {
+        &[]
+    }
     fn run(
         &self,
         parsed: &parser::ParsedSource,
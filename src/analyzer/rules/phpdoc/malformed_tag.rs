@@ -0,0 +1,101 @@
+use super::DiagnosticRule;
+use super::helpers::{diagnostic_for_range, node_text, walk_node};
+use crate::analyzer::phpdoc::PhpDocParser;
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::{Severity, parser};
+
+/// Reports PHPDoc tags the parser could not read, such as `@param int` without a
+/// `$name` or an unclosed `array{`. Other PHPDoc rules silently skip these tags,
+/// so without this the annotation just stops being checked.
+pub struct MalformedTagRule;
+
+impl MalformedTagRule {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DiagnosticRule for MalformedTagRule {
+    fn name(&self) -> &str {
+        "phpdoc/malformed_tag"
+    }
+
+    fn description(&self) -> &str {
+        "PHPDoc tags that cannot be parsed"
+    }
+
+    fn run(
+        &self,
+        parsed: &parser::ParsedSource,
+        _context: &ProjectContext,
+    ) -> Vec<crate::analyzer::Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            if node.kind() != "comment" {
+                return;
+            }
+            let Some(doc) = node_text(node, parsed)
+                .and_then(|text| PhpDocParser::parse_at(&text, node.start_byte()))
+            else {
+                return;
+            };
+
+            for tag in doc.malformed {
+                diagnostics.push(diagnostic_for_range(
+                    parsed,
+                    tag.range,
+                    Severity::Warning,
+                    format!("malformed @{} tag: {}", tag.tag, tag.message),
+                ));
+            }
+        });
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::rule_test;
+
+    rule_test!(
+        test_malformed_tag_reports_unparsable_tags,
+        rule: MalformedTagRule::new(),
+        source: r#"<?php
+/**
+ * @param int
+ * @param array{name: string $user
+ * @return
+ * @var int|
+ */
+function greet(int $count, array $user) {}
+"#,
+        expect: [
+            "warning: malformed @param tag: is missing a `$name`",
+            "warning: malformed @param tag: has an unclosed `{` in `array{name: string $user`",
+            "warning: malformed @return tag: is missing a type",
+            "warning: malformed @var tag: has an empty union member in `int|`",
+        ]
+    );
+
+    rule_test!(
+        test_malformed_tag_accepts_well_formed_tags,
+        rule: MalformedTagRule::new(),
+        source: r#"<?php
+/**
+ * @param array{name: string, tags: list<string>} $user The user
+ * @param callable(int $x): void $callback
+ * @param int|string ...$ids
+ * @return array<string, int | null> Counts per name
+ * @throws \RuntimeException when the user is unknown
+ */
+function greet(array $user, callable $callback, int|string ...$ids): array {}
+
+/** @var $count int */
+$count = 1;
+"#,
+        expect: []
+    );
+}
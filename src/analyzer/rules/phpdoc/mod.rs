@@ -0,0 +1,5 @@
+pub use crate::analyzer::rules::{DiagnosticRule, helpers};
+
+pub mod malformed_tag;
+
+pub use malformed_tag::MalformedTagRule;
@@ -3,7 +3,8 @@ use crate::analyzer::project::ProjectContext;
 use crate::analyzer::{Diagnostic, Severity, config::AnalyzerConfig};
 use std::path::{Path, PathBuf};
 
-const RULE_NAME: &str = "psr4/namespace";
+pub const RULE_NAME: &str = "psr4/namespace";
+pub const DESCRIPTION: &str = "Namespaces that do not match the PSR-4 directory layout";
 
 pub fn run_namespace_checks(
     root: &Path,
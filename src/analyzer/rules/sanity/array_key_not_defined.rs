@@ -1,10 +1,19 @@
 use super::DiagnosticRule;
-use super::helpers::{child_by_kind, diagnostic_for_node, node_text, variable_name_text};
+use super::helpers::{
+    child_by_kind, diagnostic_for_node, node_text, variable_name_text, walk_node,
+};
+use crate::analyzer::phpdoc::{TypeExpression, extract_phpdoc_for_node};
 use crate::analyzer::project::ProjectContext;
 use crate::analyzer::{Severity, parser};
 use std::collections::{HashMap, HashSet};
 use tree_sitter::Node;
 
+/// Reports reads of string keys an array is known not to have. Arrays are known
+/// from a literal assigned in the same scope, a shaped `@param` type such as
+/// `array{id: int, name?: string}`, or, for parameters of functions every caller
+/// passes an array literal to, the keys any of those literals has. Reads that
+/// only happen when the key exists (`isset()`, `empty()`, `??` and code under an
+/// `isset`/`array_key_exists` check) are not reported.
 pub struct ArrayKeyNotDefinedRule;
 
 impl ArrayKeyNotDefinedRule {
@@ -18,12 +27,16 @@ impl DiagnosticRule for ArrayKeyNotDefinedRule {
         "sanity/array_key_not_defined"
     }
 
+    fn description(&self) -> &str {
+        "Reads of keys missing from a locally built or documented array"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
-        _context: &ProjectContext,
+        context: &ProjectContext,
     ) -> Vec<crate::analyzer::Diagnostic> {
-        let mut visitor = ArrayKeyVisitor::new(parsed);
+        let mut visitor = ArrayKeyVisitor::new(parsed, context);
         visitor.visit(parsed.tree.root_node());
         visitor.diagnostics
     }
@@ -31,25 +44,45 @@ impl DiagnosticRule for ArrayKeyNotDefinedRule {
 
 struct ArrayKeyVisitor<'a> {
     parsed: &'a parser::ParsedSource,
+    context: &'a ProjectContext,
     definitions: HashMap<String, HashSet<String>>,
     diagnostics: Vec<crate::analyzer::Diagnostic>,
 }
 
 impl<'a> ArrayKeyVisitor<'a> {
-    fn new(parsed: &'a parser::ParsedSource) -> Self {
+    fn new(parsed: &'a parser::ParsedSource, context: &'a ProjectContext) -> Self {
         Self {
             parsed,
+            context,
             definitions: HashMap::new(),
             diagnostics: Vec::new(),
         }
     }
 
     fn visit(&mut self, node: Node<'a>) {
-        match node.kind() {
-            "assignment_expression" => self.handle_assignment(node),
-            "subscript_expression" => self.handle_subscript(node),
-            _ => {}
-        }
+        let outer = match node.kind() {
+            "assignment_expression" => {
+                self.handle_assignment(node);
+                None
+            }
+            "subscript_expression" => {
+                self.handle_subscript(node);
+                None
+            }
+            // Functions and methods start with only their parameters; closures
+            // also see the arrays of the enclosing scope.
+            "function_definition" | "method_declaration" => {
+                let parameters = self.parameter_keys(node);
+                Some(std::mem::replace(&mut self.definitions, parameters))
+            }
+            "anonymous_function_creation_expression" | "arrow_function" => {
+                let parameters = self.parameter_keys(node);
+                let outer = self.definitions.clone();
+                self.definitions.extend(parameters);
+                Some(outer)
+            }
+            _ => None,
+        };
 
         let mut cursor = node.walk();
         if cursor.goto_first_child() {
@@ -60,9 +93,25 @@ impl<'a> ArrayKeyVisitor<'a> {
                 }
             }
         }
+
+        if let Some(outer) = outer {
+            self.definitions = outer;
+        }
     }
 
     fn handle_assignment(&mut self, node: Node<'a>) {
+        // `$data['key'] = ...` adds the key to a known array
+        if let Some(left) = node.child_by_field_name("left")
+            && left.kind() == "subscript_expression"
+        {
+            if let Some((name, key, _)) = self.literal_subscript(left)
+                && let Some(keys) = self.definitions.get_mut(&name)
+            {
+                keys.insert(key);
+            }
+            return;
+        }
+
         let Some(variable_node) = child_by_kind(node, "variable_name") else {
             return;
         };
@@ -80,6 +129,27 @@ impl<'a> ArrayKeyVisitor<'a> {
     }
 
     fn handle_subscript(&mut self, node: Node<'a>) {
+        let Some((name, value, node)) = self.literal_subscript(node) else {
+            return;
+        };
+
+        if let Some(defined_keys) = self.definitions.get(&name) {
+            if !defined_keys.contains(&value) && !is_guarded(node, &name, &value, self.parsed) {
+                let start = node.start_position();
+                let row = start.row + 1;
+                let column = start.column + 1;
+                self.diagnostics.push(diagnostic_for_node(
+                    self.parsed,
+                    node,
+                    Severity::Error,
+                    format!("undefined array key '{value}' at {row}:{column}"),
+                ));
+            }
+        }
+    }
+
+    /// The variable and string key of `$name['key']`, with the key's node.
+    fn literal_subscript(&self, node: Node<'a>) -> Option<(String, String, Node<'a>)> {
         let mut variable_name = None;
         let mut literal_value = None;
         let mut literal_node = None;
@@ -108,22 +178,239 @@ impl<'a> ArrayKeyVisitor<'a> {
             }
         }
 
-        if let (Some(name), Some(value), Some(node)) = (variable_name, literal_value, literal_node)
-        {
-            if let Some(defined_keys) = self.definitions.get(&name) {
-                if !defined_keys.contains(&value) {
-                    let start = node.start_position();
-                    let row = start.row + 1;
-                    let column = start.column + 1;
-                    self.diagnostics.push(diagnostic_for_node(
-                        self.parsed,
-                        node,
-                        Severity::Error,
-                        format!("undefined array key '{value}' at {row}:{column}"),
-                    ));
+        Some((variable_name?, literal_value?, literal_node?))
+    }
+
+    /// Known keys of the array parameters of a function-like node.
+    fn parameter_keys(&self, function: Node<'a>) -> HashMap<String, HashSet<String>> {
+        let mut keys = HashMap::new();
+        let Some(parameters) = function.child_by_field_name("parameters") else {
+            return keys;
+        };
+        let phpdoc = extract_phpdoc_for_node(function, self.parsed);
+
+        for position in 0..parameters.named_child_count() {
+            let Some(parameter) = parameters.named_child(position) else {
+                continue;
+            };
+            if parameter.kind() != "simple_parameter" {
+                continue;
+            }
+            let Some(name) = parameter
+                .child_by_field_name("name")
+                .and_then(|name| variable_name_text(name, self.parsed))
+            else {
+                continue;
+            };
+
+            let documented = phpdoc.as_ref().and_then(|phpdoc| {
+                phpdoc
+                    .params
+                    .iter()
+                    .find(|tag| tag.name == name)
+                    .and_then(|tag| shape_keys(&tag.type_expr))
+            });
+            let known = match documented {
+                Some(known) => Some(known),
+                None if function.kind() == "function_definition" => {
+                    self.caller_keys(function, position)
                 }
+                None => None,
+            };
+            if let Some(known) = known {
+                keys.insert(name, known);
             }
         }
+
+        keys
+    }
+
+    /// The keys any call of `function` passes in an array literal at
+    /// `position`, or `None` unless every call passes a literal there.
+    fn caller_keys(&self, function: Node<'a>, position: usize) -> Option<HashSet<String>> {
+        let scope = self.context.scope_for(&self.parsed.path)?;
+        let symbol = scope
+            .functions
+            .iter()
+            .find(|symbol| symbol.span.start == function.start_position())?;
+
+        let mut passed: Option<HashSet<String>> = None;
+        for reference in self.context.function_references(&symbol.fq_name) {
+            if reference.declaration {
+                continue;
+            }
+            let caller = self.context.get(&reference.file)?;
+            let literal = argument_literal(caller, reference.start_byte, position)?;
+            passed
+                .get_or_insert_with(HashSet::new)
+                .extend(collect_array_keys(literal, caller));
+        }
+
+        passed
+    }
+}
+
+/// Whether the read of `$name['key']` at `node` only happens when the key
+/// exists: inside `isset()` or `empty()`, on the left of `??`, or after an
+/// `isset`/`array_key_exists` check of the same key in an enclosing `if`,
+/// ternary or `&&`.
+fn is_guarded(node: Node, name: &str, key: &str, parsed: &parser::ParsedSource) -> bool {
+    let mut child = node;
+    while let Some(parent) = child.parent() {
+        let checked = |field: &str| {
+            parent
+                .child_by_field_name(field)
+                .is_some_and(|condition| checks_key(condition, name, key, parsed))
+        };
+        match parent.kind() {
+            "function_definition"
+            | "method_declaration"
+            | "anonymous_function_creation_expression"
+            | "arrow_function" => return false,
+            "function_call_expression"
+                if matches!(
+                    call_name(parent, parsed).as_deref(),
+                    Some("isset" | "empty")
+                ) =>
+            {
+                return true;
+            }
+            "binary_expression" => {
+                let operator = parent
+                    .child_by_field_name("operator")
+                    .map(|operator| operator.kind().to_ascii_lowercase());
+                let side = |field: &str| parent.child_by_field_name(field) == Some(child);
+                match operator.as_deref() {
+                    Some("??") if side("left") => return true,
+                    Some("&&" | "and") if side("right") && checked("left") => return true,
+                    _ => {}
+                }
+            }
+            "if_statement" | "else_if_clause" | "conditional_expression"
+                if parent.child_by_field_name("body") == Some(child) && checked("condition") =>
+            {
+                return true;
+            }
+            _ => {}
+        }
+        child = parent;
+    }
+
+    false
+}
+
+/// Whether `condition` calls `isset($name['key'])` or
+/// `array_key_exists('key', $name)`.
+fn checks_key(condition: Node, name: &str, key: &str, parsed: &parser::ParsedSource) -> bool {
+    let mut found = false;
+    walk_node(condition, &mut |node| {
+        if found || node.kind() != "function_call_expression" {
+            return;
+        }
+        let Some(arguments) = node.child_by_field_name("arguments") else {
+            return;
+        };
+        let mut cursor = arguments.walk();
+        let values: Vec<Node> = arguments
+            .named_children(&mut cursor)
+            .filter_map(|argument| argument.named_child(0))
+            .collect();
+        let is_key = |value: &Node| {
+            matches!(value.kind(), "string" | "encapsed_string")
+                && literal_string_value(*value, parsed).as_deref() == Some(key)
+        };
+        let is_array = |value: &Node| {
+            value.kind() == "variable_name"
+                && variable_name_text(*value, parsed).as_deref() == Some(name)
+        };
+
+        found = match call_name(node, parsed).as_deref() {
+            Some("isset") => values.iter().any(|value| {
+                value.kind() == "subscript_expression"
+                    && value.named_child(0).is_some_and(|array| is_array(&array))
+                    && value.named_child(1).is_some_and(|index| is_key(&index))
+            }),
+            Some("array_key_exists" | "key_exists") => {
+                values.first().is_some_and(is_key) && values.get(1).is_some_and(is_array)
+            }
+            _ => false,
+        };
+    });
+    found
+}
+
+/// The lowercased name of the function a call expression calls.
+fn call_name(call: Node, parsed: &parser::ParsedSource) -> Option<String> {
+    let function = call.child_by_field_name("function")?;
+    node_text(function, parsed).map(|name| name.trim_start_matches('\\').to_ascii_lowercase())
+}
+
+/// The array literal passed at `position` in the call whose function name
+/// starts at `name_start`, if it's a plain positional argument.
+fn argument_literal(
+    parsed: &parser::ParsedSource,
+    name_start: usize,
+    position: usize,
+) -> Option<Node<'_>> {
+    let mut call = parsed
+        .tree
+        .root_node()
+        .descendant_for_byte_range(name_start, name_start)?;
+    while call.kind() != "function_call_expression" {
+        if !matches!(
+            call.kind(),
+            "name" | "qualified_name" | "namespace_name_as_prefix"
+        ) {
+            return None;
+        }
+        call = call.parent()?;
+    }
+
+    let arguments = call.child_by_field_name("arguments")?;
+    if arguments.kind() != "arguments" {
+        return None;
+    }
+    let mut cursor = arguments.walk();
+    let arguments: Vec<Node> = arguments.named_children(&mut cursor).collect();
+    // Named and unpacked arguments don't map to positions
+    if arguments
+        .iter()
+        .any(|argument| argument.kind() != "argument" || argument.named_child_count() != 1)
+    {
+        return None;
+    }
+
+    arguments
+        .get(position)?
+        .named_child(0)
+        .filter(|value| value.kind() == "array_creation_expression")
+}
+
+/// The keys of a shaped array type, optional ones included; `null` in a union
+/// is ignored.
+fn shape_keys(type_expr: &TypeExpression) -> Option<HashSet<String>> {
+    match type_expr {
+        TypeExpression::ShapedArray(fields) => Some(
+            fields
+                .iter()
+                .map(|(name, _)| {
+                    name.trim_end_matches('?')
+                        .trim_matches(|c| c == '\'' || c == '"')
+                        .to_string()
+                })
+                .collect(),
+        ),
+        TypeExpression::Nullable(inner) => shape_keys(inner),
+        TypeExpression::Union(members) => {
+            let mut shapes = members.iter().filter(|member| {
+                !matches!(member, TypeExpression::Simple(name) if name.eq_ignore_ascii_case("null"))
+            });
+            match (shapes.next(), shapes.next()) {
+                (Some(shape), None) => shape_keys(shape),
+                _ => None,
+            }
+        }
+        _ => None,
     }
 }
 
@@ -169,7 +456,9 @@ fn literal_string_value(node: Node, parsed: &parser::ParsedSource) -> Option<Str
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule};
+    use crate::analyzer::rules::test_utils::{
+        assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule, run_rule_with_context,
+    };
 
     #[test]
     fn test_array_key_not_defined() {
@@ -188,6 +477,69 @@ echo $data['missing'];
         assert_diagnostics_exact(&diagnostics, &["error: undefined array key 'missing' at 5:12"]);
     }
 
+    #[test]
+    fn test_array_key_not_defined_for_parameters() {
+        let source = r#"<?php
+namespace App;
+
+/**
+ * @param array{id: int, 'name'?: string} $user
+ * @param ?array{page: int} $paging
+ */
+function show(array $user, ?array $paging): void
+{
+    echo $user['id'], $user['name'], $user['email'];
+    echo $paging['page'], $paging['size'];
+    $user['email'] = 'none';
+    echo $user['email'];
+}
+
+function render(array $options, array $extra)
+{
+    echo $options['title'], $options['footer'], $options['width'], $options['height'];
+    echo $extra['any'];
+}
+
+/** @param array{name: string} $user */
+function describe(array $user, array $options): void
+{
+    echo $options['width'] ?? 80, $options['depth'] ?? 1;
+    if (isset($user['email']) && $user['email'] !== '') {
+        echo $user['email'];
+    }
+    if (array_key_exists('height', $options)) {
+        echo $options['height'];
+    } else {
+        echo $options['height'];
+    }
+    echo empty($user['phone']) ? 'none' : $user['phone'];
+}
+
+describe(['name' => 'A'], ['width' => 10]);
+
+render(['title' => 'A', 'footer' => 'B'], $defaults);
+\App\render(['title' => 'C', 'width' => 4], ['any' => 1]);
+
+$handler = function (array $row) use ($user) {
+    echo $row['anything'];
+};
+"#;
+
+        let rule = ArrayKeyNotDefinedRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "error: undefined array key 'email' at 10:40",
+                "error: undefined array key 'size' at 11:35",
+                "error: undefined array key 'height' at 18:75",
+                "error: undefined array key 'height' at 33:25",
+                "error: undefined array key 'phone' at 35:46",
+            ],
+        );
+    }
+
     #[test]
     fn test_array_key_not_defined_valid() {
         let source = r#"<?php
@@ -0,0 +1,269 @@
+use super::DiagnosticRule;
+use super::helpers::{diagnostic_for_node, is_anonymous_class, walk_node};
+use crate::analyzer::project::{ClassSymbol, ProjectContext};
+use crate::analyzer::{Diagnostic, Severity, parser};
+use std::collections::{BTreeMap, HashSet};
+
+/// Flags classes that inherit a constant of the same name but a different value
+/// from two of their interfaces or traits, which PHP rejects as ambiguous.
+///
+/// Constants the class redeclares itself, and constants reached twice through
+/// the same interface, are left alone. Only literal values are compared.
+///
+/// ```php
+/// interface HasLimit { const LIMIT = 10; }
+/// trait Paginates { const LIMIT = 25; }
+///
+/// class Listing implements HasLimit { // ✗ LIMIT is 10 in HasLimit, 25 in Paginates
+///     use Paginates;
+/// }
+/// ```
+pub struct ConstantCollisionRule;
+
+impl ConstantCollisionRule {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DiagnosticRule for ConstantCollisionRule {
+    fn name(&self) -> &str {
+        "sanity/constant_collision"
+    }
+
+    fn description(&self) -> &str {
+        "Constants inherited with different values from several interfaces or traits"
+    }
+
+    fn run(&self, parsed: &parser::ParsedSource, context: &ProjectContext) -> Vec<Diagnostic> {
+        let Some(scope) = context.scope_for(&parsed.path) else {
+            return Vec::new();
+        };
+
+        let mut diagnostics = Vec::new();
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            if !matches!(
+                node.kind(),
+                "class_declaration" | "interface_declaration" | "trait_declaration"
+            ) && !is_anonymous_class(node)
+            {
+                return;
+            }
+
+            let Some(class) = scope
+                .classes
+                .iter()
+                .find(|class| class.span.start == node.start_position())
+            else {
+                return;
+            };
+
+            // The name, or the `class` keyword of `new class { ... }`
+            let Some(target) = node.child_by_field_name("name").or_else(|| node.child(1)) else {
+                return;
+            };
+
+            for (name, first, second) in collisions(context, class) {
+                diagnostics.push(diagnostic_for_node(
+                    parsed,
+                    target,
+                    Severity::Error,
+                    format!(
+                        "{} inherits constant {name} from both {} ({}) and {} ({})",
+                        class.name, first.declarer, first.value, second.declarer, second.value
+                    ),
+                ));
+            }
+        });
+
+        diagnostics
+    }
+}
+
+/// A constant as seen from a class, with the class-like that declared it.
+struct Inherited {
+    declarer: String,
+    declarer_fq: String,
+    value: Option<String>,
+}
+
+/// A literal-valued constant that two sources declare differently.
+struct Resolved {
+    declarer: String,
+    value: String,
+}
+
+/// The constants `class` receives from its interfaces and traits that disagree,
+/// as (name, first source, conflicting source), once per constant name.
+fn collisions(context: &ProjectContext, class: &ClassSymbol) -> Vec<(String, Resolved, Resolved)> {
+    let mut inherited: BTreeMap<String, Inherited> = BTreeMap::new();
+    let mut found = BTreeMap::new();
+
+    for source in class.interfaces.iter().chain(&class.traits) {
+        let mut constants = BTreeMap::new();
+        collect_constants(context, source, &mut HashSet::new(), &mut constants);
+
+        for (name, constant) in constants {
+            if class.constants.contains(&name) || found.contains_key(&name) {
+                continue;
+            }
+            let Some(first) = inherited.get(&name) else {
+                inherited.insert(name, constant);
+                continue;
+            };
+            if first
+                .declarer_fq
+                .eq_ignore_ascii_case(&constant.declarer_fq)
+            {
+                continue;
+            }
+            if let (Some(first_value), Some(value)) = (&first.value, &constant.value)
+                && first_value != value
+            {
+                let first = Resolved {
+                    declarer: first.declarer.clone(),
+                    value: first_value.clone(),
+                };
+                let second = Resolved {
+                    declarer: constant.declarer,
+                    value: value.clone(),
+                };
+                found.insert(name, (first, second));
+            }
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|(name, (first, second))| (name, first, second))
+        .collect()
+}
+
+/// Gathers the constants visible on `fq_name`: its own, then those of its
+/// interfaces, traits and parent. Class-likes outside the project are skipped.
+fn collect_constants(
+    context: &ProjectContext,
+    fq_name: &str,
+    seen: &mut HashSet<String>,
+    constants: &mut BTreeMap<String, Inherited>,
+) {
+    if !seen.insert(fq_name.to_ascii_lowercase()) {
+        return;
+    }
+    let Some(symbol) = context.class_symbol(fq_name) else {
+        return;
+    };
+
+    for name in &symbol.constants {
+        constants.entry(name.clone()).or_insert_with(|| Inherited {
+            declarer: symbol.name.clone(),
+            declarer_fq: symbol.fq_name.clone(),
+            value: symbol.constant_values.get(name).cloned(),
+        });
+    }
+
+    for ancestor in symbol
+        .interfaces
+        .iter()
+        .chain(&symbol.traits)
+        .chain(&symbol.parent)
+    {
+        collect_constants(context, ancestor, seen, constants);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::{
+        assert_diagnostics_exact, assert_no_diagnostics, run_rule_with_context,
+    };
+
+    #[test]
+    fn test_constant_collisions() {
+        let source = r#"<?php
+namespace App;
+
+interface HasLimit
+{
+    const LIMIT = 10;
+    const NAME = 'list';
+}
+
+interface Sized extends HasLimit {}
+
+trait Paginates
+{
+    public const LIMIT = 25;
+    public const NAME = "list";
+}
+
+interface Labelled
+{
+    const NAME = 'label';
+}
+
+class Listing implements Sized, Labelled
+{
+    use Paginates;
+}
+
+$report = new class implements HasLimit {
+    use Paginates;
+};
+"#;
+
+        let rule = ConstantCollisionRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "error: Listing inherits constant LIMIT from both HasLimit (10) and Paginates (25)",
+                "error: Listing inherits constant NAME from both HasLimit ('list') and Labelled ('label')",
+                "error: App\\HasLimit@anonymous inherits constant LIMIT from both HasLimit (10) and Paginates (25)",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_constants_without_collision() {
+        let source = r#"<?php
+interface HasLimit
+{
+    const LIMIT = 10;
+}
+
+interface Sized extends HasLimit {}
+interface Counted extends HasLimit {}
+
+trait Paginates
+{
+    public const LIMIT = 10;
+    public const PAGE = self::LIMIT * 2;
+}
+
+trait Scrolls
+{
+    public const PAGE = 5;
+}
+
+class Listing implements Sized, Counted
+{
+    use Paginates, Scrolls;
+}
+
+class Overrides implements HasLimit
+{
+    use Paginates;
+
+    const LIMIT = 50;
+}
+"#;
+
+        let rule = ConstantCollisionRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_no_diagnostics(&diagnostics);
+    }
+}
@@ -0,0 +1,216 @@
+use super::DiagnosticRule;
+use super::helpers::{child_by_kind, diagnostic_for_node, node_text, walk_node};
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::{Severity, parser};
+use tree_sitter::Node;
+
+/// Reports writes to the value variable of a `foreach (... as &$v)` loop made after
+/// the loop without an `unset($v)` in between. `$v` still references the last
+/// element, so the write silently overwrites it.
+pub struct DanglingReferenceRule;
+
+impl DanglingReferenceRule {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DiagnosticRule for DanglingReferenceRule {
+    fn name(&self) -> &str {
+        "sanity/dangling_reference"
+    }
+
+    fn description(&self) -> &str {
+        "foreach reference variables reused after the loop without unset()"
+    }
+
+    fn run(
+        &self,
+        parsed: &parser::ParsedSource,
+        _context: &ProjectContext,
+    ) -> Vec<crate::analyzer::Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            if node.kind() != "foreach_statement" {
+                return;
+            }
+            let Some(variable) = reference_variable(node) else {
+                return;
+            };
+            let Some(name) = node_text(variable, parsed) else {
+                return;
+            };
+
+            let mut next = node.next_named_sibling();
+            while let Some(statement) = next {
+                match scan(statement, &name, parsed) {
+                    Scan::Untouched => next = statement.next_named_sibling(),
+                    Scan::Released => break,
+                    Scan::Reused(reuse) => {
+                        let line = node.start_position().row + 1;
+                        diagnostics.push(diagnostic_for_node(
+                            parsed,
+                            reuse,
+                            Severity::Warning,
+                            format!(
+                                "{name} still references the last element from the foreach by reference on line {line}; unset({name}) after that loop before reusing it"
+                            ),
+                        ));
+                        break;
+                    }
+                }
+            }
+        });
+
+        diagnostics
+    }
+}
+
+/// The `$v` of `foreach ($a as &$v)` or `foreach ($a as $k => &$v)`.
+fn reference_variable(foreach: Node) -> Option<Node> {
+    let value = foreach.named_child(1)?;
+    let by_ref = match value.kind() {
+        "by_ref" => value,
+        "pair" => child_by_kind(value, "by_ref")?,
+        _ => return None,
+    };
+    child_by_kind(by_ref, "variable_name")
+}
+
+enum Scan<'a> {
+    /// The statement leaves the reference alone.
+    Untouched,
+    /// `unset($v)` or a new reference binding ends the danger.
+    Released,
+    /// The statement writes through the reference.
+    Reused(Node<'a>),
+}
+
+/// Looks for the first write to or release of `name` in `node`, in source order.
+fn scan<'a>(node: Node<'a>, name: &str, parsed: &parser::ParsedSource) -> Scan<'a> {
+    let is_variable = |node: Node| {
+        node.kind() == "variable_name" && node_text(node, parsed).as_deref() == Some(name)
+    };
+
+    match node.kind() {
+        // Separate variable scopes.
+        "function_definition"
+        | "method_declaration"
+        | "anonymous_function_creation_expression"
+        | "arrow_function"
+        | "class_declaration" => return Scan::Untouched,
+        "unset_statement" => {
+            let unsets = (0..node.named_child_count())
+                .filter_map(|idx| node.named_child(idx))
+                .any(is_variable);
+            if unsets {
+                return Scan::Released;
+            }
+        }
+        "reference_assignment_expression" if node.named_child(0).is_some_and(is_variable) => {
+            return Scan::Released;
+        }
+        "foreach_statement" => {
+            if reference_variable(node).is_some_and(is_variable) {
+                return Scan::Released;
+            }
+            let binding = node.named_child(1).filter(|binding| {
+                is_variable(*binding)
+                    || (binding.kind() == "pair"
+                        && (0..binding.named_child_count())
+                            .filter_map(|idx| binding.named_child(idx))
+                            .any(is_variable))
+            });
+            if let Some(binding) = binding {
+                return Scan::Reused(binding);
+            }
+        }
+        "assignment_expression" => {
+            if let Some(left) = node.child_by_field_name("left") {
+                let mut destructures = false;
+                if matches!(left.kind(), "list_literal" | "array_creation_expression") {
+                    walk_node(left, &mut |element| destructures |= is_variable(element));
+                }
+                if is_variable(left) || destructures {
+                    return Scan::Reused(node);
+                }
+            }
+        }
+        "augmented_assignment_expression" | "update_expression"
+            if node.named_child(0).is_some_and(is_variable) =>
+        {
+            return Scan::Reused(node);
+        }
+        _ => {}
+    }
+
+    for idx in 0..node.named_child_count() {
+        if let Some(child) = node.named_child(idx) {
+            match scan(child, name, parsed) {
+                Scan::Untouched => continue,
+                found => return found,
+            }
+        }
+    }
+
+    Scan::Untouched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::rule_test;
+
+    rule_test! {
+        test_dangling_reference,
+        rule: DanglingReferenceRule::new(),
+        source: r#"<?php
+foreach ($prices as &$price) {
+    $price *= 1.2;
+}
+foreach ($prices as $price) {
+    echo $price;
+}
+
+function normalise(array $rows): array
+{
+    foreach ($rows as $key => &$row) {
+        $row = trim($row);
+    }
+    if ($rows) {
+        $row = null;
+    }
+    return $rows;
+}
+"#,
+        expect: [
+            "warning: $price still references the last element from the foreach by reference on line 2; unset($price) after that loop before reusing it",
+            "warning: $row still references the last element from the foreach by reference on line 11",
+        ],
+    }
+
+    rule_test! {
+        test_dangling_reference_valid,
+        rule: DanglingReferenceRule::new(),
+        source: r#"<?php
+foreach ($prices as &$price) {
+    $price *= 1.2;
+}
+unset($price);
+foreach ($prices as $price) {
+    echo $price;
+}
+
+foreach ($items as &$item) {
+    $item = strtoupper($item);
+}
+foreach ($others as &$item) {
+    $item = strtolower($item);
+}
+unset($item);
+echo count($items);
+"#,
+        expect: [],
+    }
+}
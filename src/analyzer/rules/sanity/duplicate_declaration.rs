@@ -17,6 +17,10 @@ impl DiagnosticRule for DuplicateDeclarationRule {
         "sanity/duplicate_declaration"
     }
 
+    fn description(&self) -> &str {
+        "Functions declared more than once in a file"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
@@ -40,15 +44,14 @@ impl DiagnosticRule for DuplicateDeclarationRule {
                 None => return,
             };
 
-            if seen.contains(&name) {
+            // Function names are case-insensitive, so `helper` and `Helper` collide.
+            if !seen.insert(name.to_ascii_lowercase()) {
                 diagnostics.push(diagnostic_for_node(
                     parsed,
                     name_node,
                     Severity::Error,
                     format!("duplicate declaration of \"{name}\""),
                 ));
-            } else {
-                seen.insert(name);
             }
         });
 
@@ -82,6 +85,25 @@ function helper(): void
         assert_diagnostics_exact(&diagnostics, &["error: duplicate declaration of \"helper\""]);
     }
 
+    #[test]
+    fn test_duplicate_declaration_ignores_case() {
+        let source = r#"<?php
+function helper(): void
+{
+}
+
+function HELPER(): void
+{
+}
+"#;
+
+        let parsed = parse_php(source);
+        let rule = DuplicateDeclarationRule::new();
+        let diagnostics = run_rule(&rule, &parsed);
+
+        assert_diagnostics_exact(&diagnostics, &["error: duplicate declaration of \"HELPER\""]);
+    }
+
     #[test]
     fn test_duplicate_declaration_valid() {
         let source = r#"<?php
@@ -0,0 +1,278 @@
+use super::DiagnosticRule;
+use super::helpers::{TypeHint, diagnostic_for_node, infer_type, node_text, walk_node};
+use crate::analyzer::artifacts::Artifact;
+use crate::analyzer::config::FloatEqualityConfig;
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::{Severity, parser};
+use tree_sitter::Node;
+
+/// Built-in functions that always return a float.
+const FLOAT_FUNCTIONS: &[&str] = &[
+    "floatval",
+    "fdiv",
+    "fmod",
+    "sqrt",
+    "pi",
+    "exp",
+    "log",
+    "log10",
+    "log2",
+    "sin",
+    "cos",
+    "tan",
+    "atan2",
+    "hypot",
+    "deg2rad",
+    "rad2deg",
+    "lcg_value",
+];
+
+/// Reports `==`, `===`, `!=` and `!==` comparisons involving a float. Rounding error
+/// makes exact equality unreliable (`0.1 + 0.2 == 0.3` is false), so the values
+/// should be compared against an epsilon instead.
+pub struct FloatEqualityRule {
+    allow_whole_literals: bool,
+}
+
+impl FloatEqualityRule {
+    pub fn new(config: FloatEqualityConfig) -> Self {
+        Self {
+            allow_whole_literals: config.allow_whole_literals,
+        }
+    }
+}
+
+impl DiagnosticRule for FloatEqualityRule {
+    fn name(&self) -> &str {
+        "sanity/float_equality"
+    }
+
+    fn description(&self) -> &str {
+        "Exact equality comparisons between floats"
+    }
+
+    fn requires(&self) -> &[Artifact] {
+        &[Artifact::ParameterTypes, Artifact::VarAnnotations]
+    }
+
+    fn run(
+        &self,
+        parsed: &parser::ParsedSource,
+        context: &ProjectContext,
+    ) -> Vec<crate::analyzer::Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            if node.kind() != "binary_expression" {
+                return;
+            }
+            let Some(operator) = node.child(1).map(|operator| operator.kind()) else {
+                return;
+            };
+            let within = match operator {
+                "==" | "===" => "<",
+                "!=" | "!==" | "<>" => ">=",
+                _ => return,
+            };
+            let (Some(left), Some(right)) = (node.child(0), node.child(2)) else {
+                return;
+            };
+
+            let is_float = |side: Node| is_float_expression(side, parsed, context);
+            if !is_float(left) && !is_float(right) {
+                return;
+            }
+            if self.allow_whole_literals
+                && (is_whole_literal(left, parsed) || is_whole_literal(right, parsed))
+            {
+                return;
+            }
+
+            let Some(left) = node_text(left, parsed) else {
+                return;
+            };
+            let Some(right) = node_text(right, parsed).map(|text| {
+                if right.kind() == "binary_expression" {
+                    format!("({text})")
+                } else {
+                    text
+                }
+            }) else {
+                return;
+            };
+            diagnostics.push(diagnostic_for_node(
+                parsed,
+                node,
+                Severity::Warning,
+                format!(
+                    "floats compared with {operator} may differ by rounding error; use abs({left} - {right}) {within} PHP_FLOAT_EPSILON"
+                ),
+            ));
+        });
+
+        diagnostics
+    }
+}
+
+/// Whether `node` evaluates to a float: float literals and casts, calls to
+/// float-returning functions, arithmetic with a float operand, and variables
+/// declared or assigned as floats.
+fn is_float_expression(
+    node: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> bool {
+    match node.kind() {
+        "float" => true,
+        "cast_expression" => node
+            .named_child(0)
+            .and_then(|cast| node_text(cast, parsed))
+            .is_some_and(|cast| matches!(cast.to_lowercase().as_str(), "float" | "double")),
+        "parenthesized_expression" => node
+            .named_child(0)
+            .is_some_and(|inner| is_float_expression(inner, parsed, context)),
+        "unary_op_expression" => {
+            is_sign(node)
+                && node
+                    .named_child(0)
+                    .is_some_and(|inner| is_float_expression(inner, parsed, context))
+        }
+        "binary_expression" => {
+            let arithmetic = node
+                .child(1)
+                .is_some_and(|operator| matches!(operator.kind(), "+" | "-" | "*" | "/" | "**"));
+            arithmetic
+                && [node.child(0), node.child(2)]
+                    .into_iter()
+                    .flatten()
+                    .any(|operand| is_float_expression(operand, parsed, context))
+        }
+        "function_call_expression" => node
+            .child_by_field_name("function")
+            .and_then(|function| node_text(function, parsed))
+            .is_some_and(|function| {
+                let function = function.trim_start_matches('\\').to_lowercase();
+                FLOAT_FUNCTIONS.contains(&function.as_str())
+            }),
+        "variable_name" => {
+            let declared = parameter_type(node, parsed)
+                .or_else(|| infer_type(node, parsed, context))
+                .unwrap_or(TypeHint::Unknown);
+            match declared {
+                TypeHint::Float => true,
+                TypeHint::Nullable(inner) => *inner == TypeHint::Float,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// The declared type of the enclosing function's parameter named like `variable`.
+fn parameter_type(variable: Node, parsed: &parser::ParsedSource) -> Option<TypeHint> {
+    let name = node_text(variable, parsed)?;
+    let mut function = variable.parent()?;
+    while !matches!(
+        function.kind(),
+        "function_definition"
+            | "method_declaration"
+            | "anonymous_function_creation_expression"
+            | "arrow_function"
+    ) {
+        function = function.parent()?;
+    }
+
+    parsed.parameter_type(function, &name).cloned()
+}
+
+/// Integer literals, and float literals with no fractional part, optionally negated.
+fn is_whole_literal(node: Node, parsed: &parser::ParsedSource) -> bool {
+    match node.kind() {
+        "integer" => true,
+        "float" => node_text(node, parsed)
+            .and_then(|text| text.replace('_', "").parse::<f64>().ok())
+            .is_some_and(|value| value.fract() == 0.0),
+        "unary_op_expression" => {
+            is_sign(node)
+                && node
+                    .named_child(0)
+                    .is_some_and(|inner| is_whole_literal(inner, parsed))
+        }
+        "parenthesized_expression" => node
+            .named_child(0)
+            .is_some_and(|inner| is_whole_literal(inner, parsed)),
+        _ => false,
+    }
+}
+
+/// `-$x` or `+$x`, as opposed to `!$x` or `~$x`.
+fn is_sign(unary: Node) -> bool {
+    unary
+        .child(0)
+        .is_some_and(|operator| matches!(operator.kind(), "-" | "+"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::rule_test;
+
+    rule_test! {
+        test_float_equality,
+        rule: FloatEqualityRule::new(FloatEqualityConfig::default()),
+        source: r#"<?php
+if (0.1 + 0.2 == 0.3) {
+    echo "never";
+}
+
+function matches(float $expected, float $actual): bool
+{
+    return $expected === $actual;
+}
+
+$ratio = 0.75;
+if ($ratio != 0.5 && sqrt(2) !== $ratio - 0.25) {
+    echo "different";
+}
+"#,
+        expect: [
+            "warning: floats compared with == may differ by rounding error; use abs(0.1 + 0.2 - 0.3) < PHP_FLOAT_EPSILON",
+            "warning: floats compared with === may differ by rounding error; use abs($expected - $actual) < PHP_FLOAT_EPSILON",
+            "warning: floats compared with != may differ by rounding error; use abs($ratio - 0.5) >= PHP_FLOAT_EPSILON",
+            "warning: floats compared with !== may differ by rounding error; use abs(sqrt(2) - ($ratio - 0.25)) >= PHP_FLOAT_EPSILON",
+        ],
+    }
+
+    rule_test! {
+        test_float_equality_valid,
+        rule: FloatEqualityRule::new(FloatEqualityConfig::default()),
+        source: r#"<?php
+function isZero(float $amount): bool
+{
+    return $amount === 0.0 || $amount == -1 || !$amount == false;
+}
+
+$count = 3;
+if ($count == 3 && abs(0.1 + 0.2 - 0.3) < PHP_FLOAT_EPSILON) {
+    echo "fine";
+}
+"#,
+        expect: [],
+    }
+
+    rule_test! {
+        test_float_equality_whole_literals_disallowed,
+        rule: FloatEqualityRule::new(FloatEqualityConfig {
+            allow_whole_literals: false,
+        }),
+        source: r#"<?php
+function isZero(float $amount): bool
+{
+    return $amount === 0.0;
+}
+"#,
+        expect: [
+            "warning: floats compared with === may differ by rounding error; use abs($amount - 0.0) < PHP_FLOAT_EPSILON",
+        ],
+    }
+}
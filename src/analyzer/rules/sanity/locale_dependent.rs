@@ -0,0 +1,246 @@
+use super::DiagnosticRule;
+use super::helpers::{child_by_kind, diagnostic_for_node, node_text, walk_node};
+use crate::analyzer::config::PhpVersion;
+use crate::analyzer::fix;
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::{Severity, parser};
+use tree_sitter::Node;
+
+/// How a byte-oriented string function maps onto its multibyte-safe counterpart.
+#[derive(Clone, Copy)]
+enum Replacement {
+    /// `mb_*` function taking the same leading arguments.
+    Function(&'static str),
+    /// `mb_*` function added in a later PHP release.
+    FunctionSince(&'static str, PhpVersion),
+    /// Single-argument call rewritten with extra trailing arguments.
+    AppendArguments(&'static str, &'static str),
+    /// No drop-in replacement; the advice is reported without a fix.
+    Advice(&'static str),
+}
+
+const LOCALE_DEPENDENT_FUNCTIONS: &[(&str, Replacement)] = &[
+    ("strtolower", Replacement::Function("mb_strtolower")),
+    ("strtoupper", Replacement::Function("mb_strtoupper")),
+    (
+        "ucfirst",
+        Replacement::FunctionSince("mb_ucfirst", PhpVersion::new(8, 4)),
+    ),
+    (
+        "lcfirst",
+        Replacement::FunctionSince("mb_lcfirst", PhpVersion::new(8, 4)),
+    ),
+    (
+        "ucwords",
+        Replacement::AppendArguments("mb_convert_case", "MB_CASE_TITLE"),
+    ),
+    ("strcmp", Replacement::Advice("Collator::compare()")),
+    ("strcasecmp", Replacement::Advice("Collator::compare()")),
+];
+
+/// Reports byte-oriented case conversion and comparison functions applied to
+/// text that may hold multibyte characters, where an `mb_*` function or
+/// `Collator` handles it correctly. Opt-in; `--fix` switches to the `mb_*`
+/// function when the call's arguments carry over unchanged.
+pub struct LocaleDependentRule {
+    php_version: PhpVersion,
+}
+
+impl LocaleDependentRule {
+    pub fn new(php_version: PhpVersion) -> Self {
+        Self { php_version }
+    }
+
+    fn findings<'a>(&self, parsed: &'a parser::ParsedSource) -> Vec<Finding<'a>> {
+        let mut findings = Vec::new();
+
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            if node.kind() != "function_call_expression" {
+                return;
+            }
+            let Some(name_node) = node.child_by_field_name("function") else {
+                return;
+            };
+            let Some(written) = node_text(name_node, parsed) else {
+                return;
+            };
+            let function = written.trim_start_matches('\\').to_lowercase();
+            let Some((_, replacement)) = LOCALE_DEPENDENT_FUNCTIONS
+                .iter()
+                .find(|(name, _)| *name == function)
+            else {
+                return;
+            };
+
+            let arguments = call_arguments(node);
+            let values: Vec<Node> = arguments
+                .iter()
+                .filter_map(|argument| {
+                    argument.named_child(argument.named_child_count().checked_sub(1)?)
+                })
+                .collect();
+            if values.is_empty() || values.iter().all(|value| is_ascii_literal(*value, parsed)) {
+                return;
+            }
+            let positional = arguments.iter().all(|argument| {
+                argument.named_child_count() == 1
+                    && argument
+                        .named_child(0)
+                        .is_some_and(|value| value.kind() != "variadic_unpacking")
+            });
+            let prefix = if written.starts_with('\\') { "\\" } else { "" };
+
+            let (suggestion, edit) = match *replacement {
+                Replacement::Function(mb) => (
+                    format!("{mb}()"),
+                    positional.then(|| rename(name_node, &format!("{prefix}{mb}"))),
+                ),
+                Replacement::FunctionSince(mb, since) => {
+                    if self.php_version < since {
+                        return;
+                    }
+                    (
+                        format!("{mb}()"),
+                        positional.then(|| rename(name_node, &format!("{prefix}{mb}"))),
+                    )
+                }
+                Replacement::AppendArguments(mb, extra) => {
+                    let edit = match (positional, values.as_slice()) {
+                        (true, [value]) => node_text(*value, parsed).map(|value| {
+                            fix::TextEdit::new(
+                                node.start_byte(),
+                                node.end_byte(),
+                                format!("{prefix}{mb}({value}, {extra})"),
+                            )
+                        }),
+                        _ => None,
+                    };
+                    (format!("{mb}(..., {extra})"), edit)
+                }
+                Replacement::Advice(advice) => (advice.to_string(), None),
+            };
+
+            findings.push(Finding {
+                node,
+                message: format!(
+                    "{function}() works on bytes and mishandles multibyte text; use {suggestion} instead"
+                ),
+                edit,
+            });
+        });
+
+        findings
+    }
+}
+
+struct Finding<'a> {
+    node: Node<'a>,
+    message: String,
+    edit: Option<fix::TextEdit>,
+}
+
+impl DiagnosticRule for LocaleDependentRule {
+    fn name(&self) -> &str {
+        "sanity/locale_dependent"
+    }
+
+    fn description(&self) -> &str {
+        "Byte-oriented string functions applied to possibly multibyte text"
+    }
+
+    fn run(
+        &self,
+        parsed: &parser::ParsedSource,
+        _context: &ProjectContext,
+    ) -> Vec<crate::analyzer::Diagnostic> {
+        self.findings(parsed)
+            .into_iter()
+            .map(|finding| {
+                diagnostic_for_node(parsed, finding.node, Severity::Warning, finding.message)
+            })
+            .collect()
+    }
+
+    fn fix(&self, parsed: &parser::ParsedSource, _context: &ProjectContext) -> Vec<fix::TextEdit> {
+        self.findings(parsed)
+            .into_iter()
+            .filter_map(|finding| finding.edit)
+            .collect()
+    }
+}
+
+fn call_arguments(call: Node) -> Vec<Node> {
+    let Some(arguments) = child_by_kind(call, "arguments") else {
+        return Vec::new();
+    };
+
+    (0..arguments.named_child_count())
+        .filter_map(|idx| arguments.named_child(idx))
+        .filter(|argument| argument.kind() == "argument")
+        .collect()
+}
+
+/// Numbers and ASCII-only string literals, which the byte functions handle fine.
+fn is_ascii_literal(node: Node, parsed: &parser::ParsedSource) -> bool {
+    match node.kind() {
+        "integer" | "float" | "boolean" | "null" => true,
+        "string" | "encapsed_string" => {
+            let interpolated = (0..node.named_child_count())
+                .filter_map(|idx| node.named_child(idx))
+                .any(|child| !matches!(child.kind(), "string_value" | "escape_sequence"));
+            !interpolated && node_text(node, parsed).is_some_and(|text| text.is_ascii())
+        }
+        _ => false,
+    }
+}
+
+fn rename(name_node: Node, replacement: &str) -> fix::TextEdit {
+    fix::TextEdit::new(
+        name_node.start_byte(),
+        name_node.end_byte(),
+        replacement.to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::rule_test;
+
+    rule_test! {
+        test_locale_dependent,
+        rule: LocaleDependentRule::new(PhpVersion::LATEST),
+        source: r#"<?php
+$slug = strtolower($title);
+$name = \ucfirst($user->name);
+$heading = ucwords($request->get('heading'));
+$order = strcmp($a, $b);
+$upper = strtoupper(...$parts);
+"#,
+        expect: [
+            "warning: strtolower() works on bytes and mishandles multibyte text; use mb_strtolower() instead",
+            "warning: ucfirst() works on bytes and mishandles multibyte text; use mb_ucfirst() instead",
+            "warning: ucwords() works on bytes and mishandles multibyte text; use mb_convert_case(..., MB_CASE_TITLE) instead",
+            "warning: strcmp() works on bytes and mishandles multibyte text; use Collator::compare() instead",
+            "warning: strtoupper() works on bytes and mishandles multibyte text; use mb_strtoupper() instead",
+        ],
+        fixed: r#"<?php
+$slug = mb_strtolower($title);
+$name = \mb_ucfirst($user->name);
+$heading = mb_convert_case($request->get('heading'), MB_CASE_TITLE);
+$order = strcmp($a, $b);
+$upper = strtoupper(...$parts);
+"#,
+    }
+
+    rule_test! {
+        test_locale_dependent_valid,
+        rule: LocaleDependentRule::new(PhpVersion::new(8, 3)),
+        source: r#"<?php
+$method = strtolower('GET');
+$name = ucfirst($user->name);
+$slug = mb_strtolower($title);
+"#,
+        expect: [],
+    }
+}
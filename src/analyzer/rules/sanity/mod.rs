@@ -1,9 +1,21 @@
 pub use crate::analyzer::rules::{DiagnosticRule, helpers};
 
 pub mod array_key_not_defined;
+pub mod constant_collision;
+pub mod dangling_reference;
 pub mod duplicate_declaration;
+pub mod float_equality;
+pub mod locale_dependent;
+pub mod modify_during_iteration;
+pub mod suspicious_numeric_literal;
 pub mod undefined_variable;
 
 pub use array_key_not_defined::ArrayKeyNotDefinedRule;
+pub use constant_collision::ConstantCollisionRule;
+pub use dangling_reference::DanglingReferenceRule;
 pub use duplicate_declaration::DuplicateDeclarationRule;
+pub use float_equality::FloatEqualityRule;
+pub use locale_dependent::LocaleDependentRule;
+pub use modify_during_iteration::ModifyDuringIterationRule;
+pub use suspicious_numeric_literal::SuspiciousNumericLiteralRule;
 pub use undefined_variable::UndefinedVariableRule;
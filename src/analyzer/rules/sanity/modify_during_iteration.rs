@@ -0,0 +1,210 @@
+use super::DiagnosticRule;
+use super::helpers::{child_by_kind, diagnostic_for_node, node_text, walk_node};
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::{Severity, parser};
+use tree_sitter::Node;
+
+/// Array functions that add to or remove from the array passed first.
+const MUTATING_FUNCTIONS: &[&str] = &[
+    "array_push",
+    "array_unshift",
+    "array_pop",
+    "array_shift",
+    "array_splice",
+];
+
+/// Reports changes to the array a by-value `foreach` is iterating. The loop walks a
+/// copy taken when it started, so appended elements are never visited and unset or
+/// replaced ones still are.
+pub struct ModifyDuringIterationRule;
+
+impl ModifyDuringIterationRule {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DiagnosticRule for ModifyDuringIterationRule {
+    fn name(&self) -> &str {
+        "sanity/modify_during_iteration"
+    }
+
+    fn description(&self) -> &str {
+        "Arrays changed inside a foreach that iterates over them by value"
+    }
+
+    fn run(
+        &self,
+        parsed: &parser::ParsedSource,
+        _context: &ProjectContext,
+    ) -> Vec<crate::analyzer::Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            if node.kind() != "foreach_statement" || iterates_by_reference(node) {
+                return;
+            }
+            let Some(collection) = node
+                .named_child(0)
+                .filter(|collection| {
+                    matches!(
+                        collection.kind(),
+                        "variable_name" | "member_access_expression"
+                    )
+                })
+                .and_then(|collection| node_text(collection, parsed))
+            else {
+                return;
+            };
+            let Some(body) = node.named_child(node.named_child_count().saturating_sub(1)) else {
+                return;
+            };
+
+            visit_loop_body(body, &mut |statement| {
+                let Some(change) = mutation_of(statement, &collection, parsed) else {
+                    return;
+                };
+                diagnostics.push(diagnostic_for_node(
+                    parsed,
+                    statement,
+                    Severity::Warning,
+                    format!(
+                        "{collection} is {change} inside a foreach over it; the loop keeps iterating the original array"
+                    ),
+                ));
+            });
+        });
+
+        diagnostics
+    }
+}
+
+/// `foreach ($items as &$item)` and `foreach ($items as $key => &$item)` iterate
+/// the array itself, so changes are seen by the loop.
+fn iterates_by_reference(foreach: Node) -> bool {
+    let Some(value) = foreach.named_child(1) else {
+        return false;
+    };
+    value.kind() == "by_ref" || (value.kind() == "pair" && child_by_kind(value, "by_ref").is_some())
+}
+
+/// Walks the loop body without entering closures, functions or classes declared in it.
+fn visit_loop_body<'a, F>(node: Node<'a>, callback: &mut F)
+where
+    F: FnMut(Node<'a>),
+{
+    if matches!(
+        node.kind(),
+        "function_definition"
+            | "anonymous_function_creation_expression"
+            | "arrow_function"
+            | "class_declaration"
+    ) {
+        return;
+    }
+
+    callback(node);
+    for idx in 0..node.named_child_count() {
+        if let Some(child) = node.named_child(idx) {
+            visit_loop_body(child, callback);
+        }
+    }
+}
+
+/// How `node` changes `collection`, worded for the diagnostic.
+fn mutation_of(node: Node, collection: &str, parsed: &parser::ParsedSource) -> Option<String> {
+    let is_collection = |node: Option<Node>| {
+        node.and_then(|node| node_text(node, parsed)).as_deref() == Some(collection)
+    };
+    let is_element = |node: Option<Node>| {
+        node.filter(|node| node.kind() == "subscript_expression")
+            .is_some_and(|subscript| is_collection(subscript.named_child(0)))
+    };
+
+    match node.kind() {
+        "unset_statement" => (0..node.named_child_count())
+            .any(|idx| is_element(node.named_child(idx)))
+            .then(|| "unset".to_string()),
+        "assignment_expression" | "augmented_assignment_expression" => {
+            let left = node.child_by_field_name("left");
+            if is_collection(left) {
+                return Some("reassigned".to_string());
+            }
+            // `$items[] = ...` appends; writes to existing keys are left alone.
+            let appends =
+                is_element(left) && left.is_some_and(|left| left.named_child_count() == 1);
+            appends.then(|| "appended to".to_string())
+        }
+        "function_call_expression" => {
+            let function = node
+                .child_by_field_name("function")
+                .and_then(|function| node_text(function, parsed))?
+                .trim_start_matches('\\')
+                .to_lowercase();
+            if !MUTATING_FUNCTIONS.contains(&function.as_str()) {
+                return None;
+            }
+            let first_argument = child_by_kind(node, "arguments")
+                .and_then(|arguments| child_by_kind(arguments, "argument"))
+                .and_then(|argument| argument.named_child(0));
+            is_collection(first_argument).then(|| format!("changed by {function}()"))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::rule_test;
+
+    rule_test! {
+        test_modify_during_iteration,
+        rule: ModifyDuringIterationRule::new(),
+        source: r#"<?php
+foreach ($items as $key => $item) {
+    unset($items[$key]);
+    $items[] = $item;
+    array_push($items, $item);
+}
+
+class Queue
+{
+    private array $jobs = [];
+
+    public function drain(): void
+    {
+        foreach ($this->jobs as $job) {
+            $this->jobs = [];
+        }
+    }
+}
+"#,
+        expect: [
+            "warning: $items is unset inside a foreach over it; the loop keeps iterating the original array",
+            "warning: $items is appended to inside a foreach over it",
+            "warning: $items is changed by array_push() inside a foreach over it",
+            "warning: $this->jobs is reassigned inside a foreach over it",
+        ],
+    }
+
+    rule_test! {
+        test_modify_during_iteration_valid,
+        rule: ModifyDuringIterationRule::new(),
+        source: r#"<?php
+foreach ($items as &$item) {
+    $items[] = 1;
+}
+unset($item);
+
+foreach ($items as $key => $item) {
+    $items[$key] = trim($item);
+    $copy[] = $item;
+    $callback = function () use ($items) {
+        $items[] = 1;
+    };
+}
+"#,
+        expect: [],
+    }
+}
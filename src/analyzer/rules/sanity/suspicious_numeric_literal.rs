@@ -0,0 +1,267 @@
+use super::DiagnosticRule;
+use super::helpers::{TypeHint, diagnostic_for_node, infer_type, node_text, walk_node};
+use crate::analyzer::artifacts::Artifact;
+use crate::analyzer::config::PhpVersion;
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::{Severity, parser};
+use std::num::IntErrorKind;
+use tree_sitter::Node;
+
+/// PHP release that added the explicit `0o` octal prefix.
+const EXPLICIT_OCTAL_SINCE: PhpVersion = PhpVersion::new(8, 1);
+
+/// Functions whose mode argument is conventionally written in octal.
+const PERMISSION_FUNCTIONS: &[&str] = &["chmod", "mkdir", "umask", "ftp_chmod"];
+
+/// Flags numeric literals that do not mean what they appear to:
+///
+/// ```php
+/// $limit = 0100;          // octal, so 64
+/// $id = 9223372036854775808; // beyond PHP_INT_MAX, so a float
+/// if ($count == 'abc') {} // string comparison since PHP 8
+/// ```
+pub struct SuspiciousNumericLiteralRule {
+    php_version: PhpVersion,
+}
+
+impl SuspiciousNumericLiteralRule {
+    pub fn new(php_version: PhpVersion) -> Self {
+        Self { php_version }
+    }
+}
+
+impl DiagnosticRule for SuspiciousNumericLiteralRule {
+    fn name(&self) -> &str {
+        "sanity/suspicious_numeric_literal"
+    }
+
+    fn description(&self) -> &str {
+        "Accidental octal, overflowing integer literals and number/string comparisons changed in PHP 8"
+    }
+
+    fn requires(&self) -> &[Artifact] {
+        &[Artifact::VarAnnotations]
+    }
+
+    fn run(
+        &self,
+        parsed: &parser::ParsedSource,
+        context: &ProjectContext,
+    ) -> Vec<crate::analyzer::Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            let message = match node.kind() {
+                "integer" => self.integer_message(node, parsed),
+                "binary_expression" => comparison_message(node, parsed, context),
+                _ => None,
+            };
+            if let Some(message) = message {
+                diagnostics.push(diagnostic_for_node(
+                    parsed,
+                    node,
+                    Severity::Warning,
+                    message,
+                ));
+            }
+        });
+
+        diagnostics
+    }
+}
+
+impl SuspiciousNumericLiteralRule {
+    fn integer_message(&self, node: Node, parsed: &parser::ParsedSource) -> Option<String> {
+        let literal = node_text(node, parsed)?;
+        let digits = literal.replace('_', "");
+        let lower = digits.to_ascii_lowercase();
+        let (radix, body) = if let Some(hex) = lower.strip_prefix("0x") {
+            (16, hex)
+        } else if let Some(binary) = lower.strip_prefix("0b") {
+            (2, binary)
+        } else if let Some(octal) = lower.strip_prefix("0o") {
+            (8, octal)
+        } else if lower.len() > 1 && lower.starts_with('0') {
+            (8, &lower[1..])
+        } else {
+            (10, lower.as_str())
+        };
+
+        let value = match u128::from_str_radix(body, radix) {
+            Ok(value) => value,
+            Err(error) if *error.kind() == IntErrorKind::PosOverflow => u128::MAX,
+            Err(_) => return None,
+        };
+        if value > i64::MAX as u128 {
+            return Some(format!(
+                "{literal} is larger than PHP_INT_MAX and becomes a float, losing integer precision; keep such values in strings or use bcmath/gmp"
+            ));
+        }
+
+        // `07` and `7` are the same number, so only larger values can mislead.
+        let implicit_octal = radix == 8 && !lower.starts_with("0o") && value >= 8;
+        if !implicit_octal || is_permission_mode(node, parsed) {
+            return None;
+        }
+        let decimal = body.trim_start_matches('0');
+        let decimal = if decimal.is_empty() { "0" } else { decimal };
+        let explicit = if self.php_version >= EXPLICIT_OCTAL_SINCE {
+            format!(", or 0o{body} if octal is intended")
+        } else {
+            String::new()
+        };
+        Some(format!(
+            "{literal} is an octal literal equal to {value}; write {decimal} for the decimal number{explicit}"
+        ))
+    }
+}
+
+/// Mode arguments such as `chmod($path, 0755)`, where octal is the norm.
+fn is_permission_mode(node: Node, parsed: &parser::ParsedSource) -> bool {
+    let Some(call) = node
+        .parent()
+        .filter(|argument| argument.kind() == "argument")
+        .and_then(|argument| argument.parent())
+        .and_then(|arguments| arguments.parent())
+        .filter(|call| call.kind() == "function_call_expression")
+    else {
+        return false;
+    };
+
+    call.child_by_field_name("function")
+        .and_then(|function| node_text(function, parsed))
+        .is_some_and(|function| {
+            let function = function.trim_start_matches('\\').to_lowercase();
+            PERMISSION_FUNCTIONS.contains(&function.as_str())
+        })
+}
+
+/// Loose comparisons of a number with a non-numeric string literal. PHP 7 cast the
+/// string to a number (`0 == 'abc'` was true); PHP 8 compares them as strings.
+fn comparison_message(
+    node: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<String> {
+    let operator = node.child(1)?.kind();
+    if !matches!(operator, "==" | "!=" | "<>") {
+        return None;
+    }
+    let (left, right) = (node.child(0)?, node.child(2)?);
+
+    let text = if is_number(left, parsed, context) {
+        string_literal(right, parsed)?
+    } else if is_number(right, parsed, context) {
+        string_literal(left, parsed)?
+    } else {
+        return None;
+    };
+    if is_numeric_string(&text) {
+        return None;
+    }
+
+    let expression = node_text(node, parsed)?;
+    Some(format!(
+        "{expression} compares a number with the non-numeric string \"{text}\", which PHP 8 compares as strings where PHP 7 converted the string to a number; use === or cast explicitly"
+    ))
+}
+
+fn is_number(node: Node, parsed: &parser::ParsedSource, context: &ProjectContext) -> bool {
+    match node.kind() {
+        "integer" | "float" => true,
+        "cast_expression" => node
+            .named_child(0)
+            .and_then(|cast| node_text(cast, parsed))
+            .is_some_and(|cast| {
+                matches!(
+                    cast.to_lowercase().as_str(),
+                    "int" | "integer" | "float" | "double"
+                )
+            }),
+        "variable_name" => matches!(
+            infer_type(node, parsed, context),
+            Some(TypeHint::Int | TypeHint::Float)
+        ),
+        _ => false,
+    }
+}
+
+/// The contents of a string literal without interpolation.
+fn string_literal(node: Node, parsed: &parser::ParsedSource) -> Option<String> {
+    if !matches!(node.kind(), "string" | "encapsed_string") {
+        return None;
+    }
+    let interpolated = (0..node.named_child_count())
+        .filter_map(|idx| node.named_child(idx))
+        .any(|child| !matches!(child.kind(), "string_value" | "escape_sequence"));
+    if interpolated {
+        return None;
+    }
+
+    let text = node_text(node, parsed)?;
+    text.get(1..text.len().checked_sub(1)?).map(str::to_string)
+}
+
+/// PHP 8's numeric string: a decimal number, optionally with an exponent, with
+/// leading and trailing whitespace allowed.
+fn is_numeric_string(text: &str) -> bool {
+    let number = text.trim_matches([' ', '\t', '\n', '\r', '\x0b', '\x0c']);
+    let unsigned = number.strip_prefix(['+', '-']).unwrap_or(number);
+    unsigned.starts_with(|c: char| c.is_ascii_digit() || c == '.') && number.parse::<f64>().is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::rule_test;
+
+    rule_test! {
+        test_suspicious_numeric_literal,
+        rule: SuspiciousNumericLiteralRule::new(PhpVersion::LATEST),
+        source: r#"<?php
+$limit = 0100;
+$id = 9223372036854775808;
+$mask = 0xFFFFFFFFFFFFFFFFF;
+
+$count = 0;
+if ($count == 'abc' || 1 != "1 apple") {
+    echo "changed in PHP 8";
+}
+"#,
+        expect: [
+            "warning: 0100 is an octal literal equal to 64; write 100 for the decimal number, or 0o100 if octal is intended",
+            "warning: 9223372036854775808 is larger than PHP_INT_MAX and becomes a float",
+            "warning: 0xFFFFFFFFFFFFFFFFF is larger than PHP_INT_MAX",
+            "warning: $count == 'abc' compares a number with the non-numeric string \"abc\", which PHP 8 compares as strings",
+            "warning: 1 != \"1 apple\" compares a number with the non-numeric string \"1 apple\"",
+        ],
+    }
+
+    rule_test! {
+        test_suspicious_numeric_literal_valid,
+        rule: SuspiciousNumericLiteralRule::new(PhpVersion::LATEST),
+        source: r#"<?php
+chmod($path, 0755);
+mkdir($dir, 0700, true);
+$mode = 0o644;
+$values = [0, 00, 07, 0x1F, 0b101, 1_000, 9223372036854775807];
+
+$count = 0;
+if ($count == '42' || 1.5 == " 1.5 " || $count === 'abc' || $name == 'abc') {
+    echo "fine";
+}
+"#,
+        expect: [],
+    }
+
+    rule_test! {
+        test_suspicious_numeric_literal_before_explicit_octal,
+        rule: SuspiciousNumericLiteralRule::new(PhpVersion::new(8, 0)),
+        source: r#"<?php
+$limit = 010;
+"#,
+        expect: [
+            "warning: 010 is an octal literal equal to 8; write 10 for the decimal number",
+        ],
+    }
+}
@@ -1,15 +1,18 @@
 use super::DiagnosticRule;
-use super::helpers::diagnostic_for_node;
-use crate::analyzer::project::ProjectContext;
-use crate::analyzer::{Severity, parser};
-use std::collections::HashSet;
-use tree_sitter::Node;
-
-pub struct UndefinedVariableRule;
+use super::helpers::{diagnostic_for_node, diagnostic_for_span};
+use crate::analyzer::artifacts::{Artifact, ScopeKind, VariableRole};
+use crate::analyzer::config::TemplateConfig;
+use crate::analyzer::project::{GlobalSiteKind, ProjectContext};
+use crate::analyzer::{Severity, is_template_file, parser};
+use std::collections::{HashMap, HashSet};
+
+pub struct UndefinedVariableRule {
+    templates: TemplateConfig,
+}
 
 impl UndefinedVariableRule {
-    pub fn new() -> Self {
-        Self
+    pub fn new(templates: TemplateConfig) -> Self {
+        Self { templates }
     }
 }
 
@@ -18,163 +21,140 @@ impl DiagnosticRule for UndefinedVariableRule {
         "sanity/undefined_variable"
     }
 
+    fn description(&self) -> &str {
+        "Variables read before they are assigned"
+    }
+
+    fn requires(&self) -> &[Artifact] {
+        &[Artifact::Scopes]
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
-        _context: &ProjectContext,
+        context: &ProjectContext,
     ) -> Vec<crate::analyzer::Diagnostic> {
-        let mut visitor = ScopeVisitor::new(parsed);
-        visitor.visit(parsed.tree.root_node());
-        visitor.diagnostics
-    }
-}
-
-struct ScopeVisitor<'a> {
-    parsed: &'a parser::ParsedSource,
-    scopes: Vec<HashSet<String>>,
-    diagnostics: Vec<crate::analyzer::Diagnostic>,
-}
+        // Templates receive their view variables from the renderer.
+        let view_variables: &[String] = if is_template_file(&parsed.path) {
+            &self.templates.view_variables
+        } else {
+            &[]
+        };
 
-impl<'a> ScopeVisitor<'a> {
-    fn new(parsed: &'a parser::ParsedSource) -> Self {
-        Self {
-            parsed,
-            scopes: vec![std::collections::HashSet::new()],
-            diagnostics: Vec::new(),
-        }
+        let mut diagnostics = undefined_variables(parsed, view_variables);
+        diagnostics.extend(global_diagnostics(parsed, context));
+        diagnostics
     }
+}
 
-    fn visit(&mut self, node: Node) {
-        if node.kind() == "function_definition" {
-            self.enter_scope();
-            self.visit_children(node);
-            self.exit_scope();
-            return;
-        }
-
-        if node.kind() == "variable_name" {
-            if let Some(name) = self.variable_name_text(node) {
-                if name == "this" {
-                    return;
-                }
-
-                if matches!(
-                    name.as_str(),
-                    "_GET"
-                        | "_POST"
-                        | "_REQUEST"
-                        | "_COOKIE"
-                        | "_FILES"
-                        | "_SERVER"
-                        | "_ENV"
-                        | "argc"
-                        | "argv"
-                ) {
-                    self.define_variable(name);
-                    return;
-                }
-
-                if let Some(parent) = node.parent() {
-                    if parent.kind() == "property_promotion_parameter" {
-                        self.define_variable(name);
-                        return;
-                    }
-                }
-
-                if self.is_definition(node) {
-                    self.define_variable(name);
-                } else if !self.is_defined(&name) {
-                    self.report_undefined(node, name);
-                }
+/// Checks `global $x` and `$GLOBALS['x']` sites in this file against the globals
+/// assigned and read across every analysed file.
+fn global_diagnostics(
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Vec<crate::analyzer::Diagnostic> {
+    let Some(scope) = context.scope_for(&parsed.path) else {
+        return Vec::new();
+    };
+
+    let mut diagnostics = Vec::new();
+    for site in &scope.globals {
+        let message = match site.kind {
+            GlobalSiteKind::Declaration if !context.global_is_defined(&site.name) => format!(
+                "global variable ${} is not defined at file scope in any analysed file",
+                site.name
+            ),
+            GlobalSiteKind::Declaration | GlobalSiteKind::SuperglobalWrite
+                if !context.global_is_read(&site.name) =>
+            {
+                format!("global variable ${} is written but never read", site.name)
             }
-        }
+            _ => continue,
+        };
 
-        self.visit_children(node);
+        diagnostics.push(diagnostic_for_span(
+            parsed,
+            site.span.clone(),
+            Severity::Warning,
+            message,
+        ));
     }
+    diagnostics
+}
 
-    fn visit_children(&mut self, node: Node) {
-        let mut cursor = node.walk();
-        if cursor.goto_first_child() {
-            loop {
-                self.visit(cursor.node());
-                if !cursor.goto_next_sibling() {
-                    break;
-                }
-            }
+/// Superglobals and the CLI variables, which every scope can read.
+const PREDEFINED: &[&str] = &[
+    "_GET", "_POST", "_REQUEST", "_COOKIE", "_FILES", "_SERVER", "_ENV", "GLOBALS", "argc", "argv",
+];
+
+/// Scopes whose variables are checked separately. Methods and closures share
+/// the variables of the code around them.
+const CHECKED_SCOPES: &[ScopeKind] = &[ScopeKind::File, ScopeKind::Function];
+
+/// Reports variables read before anything in their scope, or a scope around it,
+/// assigned them. `view_variables` are defined for the whole file.
+fn undefined_variables(
+    parsed: &parser::ParsedSource,
+    view_variables: &[String],
+) -> Vec<crate::analyzer::Diagnostic> {
+    let table = parsed.scopes();
+    let mut defined: HashMap<usize, HashSet<&str>> = HashMap::new();
+    defined.entry(0).or_default().extend(
+        view_variables
+            .iter()
+            .map(|variable| variable.trim_start_matches('$')),
+    );
+
+    let mut diagnostics = Vec::new();
+    for site in &table.variables {
+        let name = site.name.trim();
+        if name.is_empty() || name == "this" || PREDEFINED.contains(&name) {
+            continue;
         }
-    }
-
-    fn enter_scope(&mut self) {
-        self.scopes.push(HashSet::new());
-    }
-
-    fn exit_scope(&mut self) {
-        self.scopes.pop();
-    }
 
-    fn define_variable(&mut self, name: String) {
-        if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name);
+        let scope = table.enclosing(site.scope, CHECKED_SCOPES);
+        if site.role != VariableRole::Read {
+            defined.entry(scope).or_default().insert(name);
+            continue;
         }
-    }
 
-    fn is_defined(&self, name: &str) -> bool {
-        self.scopes.iter().rev().any(|scope| scope.contains(name))
-    }
-
-    fn variable_name_text(&self, node: Node) -> Option<String> {
-        let source = self.parsed.source.as_str();
-        node.utf8_text(source.as_bytes())
-            .ok()
-            .map(str::trim)
-            .map(|text| text.trim_start_matches('$'))
-            .filter(|name| !name.is_empty())
-            .map(ToOwned::to_owned)
-    }
-
-    fn is_definition(&self, node: Node) -> bool {
-        if let Some(parent) = node.parent() {
-            match parent.kind() {
-                "assignment_expression" => parent.named_child(0).map_or(false, |left| left == node),
-                "simple_parameter" | "variadic_parameter" => true,
-                // Class property declarations
-                "property_element" => true,
-                // Catch clause exception variable
-                "catch_clause" => true,
-                // Foreach loop variables (both key and value)
-                "foreach_statement" => true,
-                // Foreach loop key/value variables (pair case: foreach ($arr as $key => $val))
-                "pair" => {
-                    // Check if the pair is inside a foreach_statement
-                    parent.parent().map_or(false, |grandparent| {
-                        grandparent.kind() == "foreach_statement"
-                    })
-                }
-                _ => false,
+        let mut visible = Some(scope);
+        let is_defined = loop {
+            let Some(current) = visible else {
+                break false;
+            };
+            if defined
+                .get(&current)
+                .is_some_and(|names| names.contains(name))
+            {
+                break true;
             }
-        } else {
-            false
+            visible = table.scopes[current]
+                .parent
+                .map(|parent| table.enclosing(parent, CHECKED_SCOPES));
+        };
+        if !is_defined {
+            let node = site.node.node(parsed);
+            let start = node.start_position();
+            diagnostics.push(diagnostic_for_node(
+                parsed,
+                node,
+                Severity::Error,
+                format!(
+                    "undefined variable ${name} at {}:{}",
+                    start.row + 1,
+                    start.column + 1
+                ),
+            ));
         }
     }
-
-    fn report_undefined(&mut self, node: Node, name: String) {
-        self.diagnostics.push(diagnostic_for_node(
-            self.parsed,
-            node,
-            Severity::Error,
-            format!(
-                "undefined variable ${name} at {}:{}",
-                node.start_position().row + 1,
-                node.start_position().column + 1
-            ),
-        ));
-    }
+    diagnostics
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule};
+    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_no_diagnostics, parse_php, parse_php_with_path, prepare_artifacts, run_rule, run_rule_with_context};
 
     #[test]
     fn test_undefined_variable() {
@@ -194,7 +174,7 @@ echo divide(10, 2);
 "#;
 
         let parsed = parse_php(source);
-        let rule = UndefinedVariableRule::new();
+        let rule = UndefinedVariableRule::new(TemplateConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         assert_diagnostics_exact(&diagnostics, &["error: undefined variable $c at 11:17"]);
@@ -216,9 +196,103 @@ echo divide(10, 2);
 "#;
 
         let parsed = parse_php(source);
-        let rule = UndefinedVariableRule::new();
+        let rule = UndefinedVariableRule::new(TemplateConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         assert_no_diagnostics(&diagnostics);
     }
+
+    #[test]
+    fn test_template_view_variables() {
+        let source = r#"<ul>
+<?php foreach ($items as $item): ?>
+  <li><?= htmlspecialchars($item) ?></li>
+<?php endforeach; ?>
+</ul>
+<p><?= $title ?> <?= $missing ?></p>
+"#;
+
+        let parsed = parse_php_with_path(source, "view.phtml");
+        let rule = UndefinedVariableRule::new(TemplateConfig {
+            view_variables: vec!["items".into(), "$title".into()],
+        });
+        let diagnostics = run_rule(&rule, &parsed);
+
+        assert_diagnostics_exact(&diagnostics, &["error: undefined variable $missing"]);
+        let span = diagnostics[0].span.as_ref().expect("span should be set");
+        assert_eq!((span.start.row, span.start.column), (5, 21));
+    }
+
+    #[test]
+    fn test_global_defined_in_other_file() {
+        let bootstrap =
+            parse_php_with_path("<?php\n$config = ['debug' => true];\n", "bootstrap.php");
+        let source = r#"<?php
+function debug_enabled(): bool
+{
+    global $config, $cache;
+    return $config['debug'] && $cache;
+}
+"#;
+
+        let mut context = ProjectContext::new();
+        context.insert(bootstrap);
+        context.insert(parse_php_with_path(source, "helpers.php"));
+        let parsed = parse_php_with_path(source, "helpers.php");
+        let rule = UndefinedVariableRule::new(TemplateConfig::default());
+        prepare_artifacts(&rule, &parsed);
+        let diagnostics = rule.run(&parsed, &context);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &["warning: global variable $cache is not defined at file scope in any analysed file"],
+        );
+    }
+
+    #[test]
+    fn test_write_only_globals() {
+        let source = r#"<?php
+$counter = 0;
+
+function track(): void
+{
+    global $counter;
+    $counter = 1;
+    $GLOBALS['last'] = time();
+}
+
+function seen(): bool
+{
+    return isset($GLOBALS['seen']);
+}
+
+$GLOBALS['seen'] = true;
+"#;
+
+        let rule = UndefinedVariableRule::new(TemplateConfig::default());
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "warning: global variable $counter is written but never read",
+                "warning: global variable $last is written but never read",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_undefined_foreach_collection() {
+        let source = r#"<?php
+foreach ($rows as $key => $row) {
+    echo $key, $row;
+}
+"#;
+
+        let parsed = parse_php(source);
+        let rule = UndefinedVariableRule::new(TemplateConfig::default());
+        let diagnostics = run_rule(&rule, &parsed);
+
+        assert_diagnostics_exact(&diagnostics, &["error: undefined variable $rows"]);
+    }
 }
@@ -1,15 +1,66 @@
 use super::DiagnosticRule;
 use super::helpers::{diagnostic_for_node, node_text, walk_node};
+use crate::analyzer::config::SecurityConfig;
 use crate::analyzer::project::ProjectContext;
-use crate::analyzer::{Severity, parser};
+use crate::analyzer::{Confidence, Severity, parser};
 
 const SENSITIVE_SUBSTRINGS: &[&str] = &["password", "passwd", "token", "api_key", "secret"];
 
-pub struct HardCodedCredentialsRule;
+pub struct HardCodedCredentialsRule {
+    sensitive_substrings: Vec<String>,
+}
+
+impl HardCodedCredentialsRule {
+    pub fn new(config: SecurityConfig) -> Self {
+        Self {
+            sensitive_substrings: SecurityConfig::extend(
+                SENSITIVE_SUBSTRINGS,
+                &config.password_indicators,
+            ),
+        }
+    }
+}
 
 impl HardCodedCredentialsRule {
-    pub fn new() -> Self {
-        Self
+    /// High when the literal assigns a value to a sensitive name
+    /// (`'password=hunter2'`), low when it is only a name itself: an array key,
+    /// an index or an identifier such as `'api_token'`.
+    fn confidence(&self, node: tree_sitter::Node, lowered: &str) -> Confidence {
+        let value = lowered.trim_matches(|c| c == '\'' || c == '"');
+        let assigns_secret = self.sensitive_substrings.iter().any(|substr| {
+            value.match_indices(substr.as_str()).any(|(offset, _)| {
+                let rest = value[offset + substr.len()..].trim_start();
+                rest.strip_prefix(['=', ':']).is_some_and(|secret| {
+                    secret
+                        .trim_start()
+                        .starts_with(|c: char| !c.is_whitespace() && !"{%$?:".contains(c))
+                })
+            })
+        });
+        if assigns_secret {
+            return Confidence::High;
+        }
+
+        let is_name = value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_name || is_key_or_index(node) {
+            Confidence::Low
+        } else {
+            Confidence::Medium
+        }
+    }
+}
+
+/// `'password'` in `['password' => ...]` or `$_POST['password']`.
+fn is_key_or_index(node: tree_sitter::Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    match parent.kind() {
+        "array_element_initializer" => {
+            parent.named_child_count() == 2 && parent.named_child(0) == Some(node)
+        }
+        "subscript_expression" => parent.named_child(0) != Some(node),
+        _ => false,
     }
 }
 
@@ -18,6 +69,10 @@ impl DiagnosticRule for HardCodedCredentialsRule {
         "security/hard_coded_credentials"
     }
 
+    fn description(&self) -> &str {
+        "String literals that look like passwords or tokens"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
@@ -32,16 +87,19 @@ impl DiagnosticRule for HardCodedCredentialsRule {
 
             if let Some(text) = node_text(node, parsed) {
                 let lowered = text.to_lowercase();
-                if SENSITIVE_SUBSTRINGS
+                if self
+                    .sensitive_substrings
                     .iter()
-                    .any(|substr| lowered.contains(substr))
+                    .any(|substr| lowered.contains(substr.as_str()))
                 {
-                    diagnostics.push(diagnostic_for_node(
+                    let mut diagnostic = diagnostic_for_node(
                         parsed,
                         node,
                         Severity::Warning,
                         "hard-coded credential or token detected",
-                    ));
+                    );
+                    diagnostic.confidence = Some(self.confidence(node, &lowered));
+                    diagnostics.push(diagnostic);
                 }
             }
         });
@@ -65,7 +123,7 @@ call_api('token', 'my-api-key');
 "#;
 
         let parsed = parse_php(source);
-        let rule = HardCodedCredentialsRule::new();
+        let rule = HardCodedCredentialsRule::new(SecurityConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         // Expected: 2 warnings for hard-coded credentials
@@ -74,4 +132,37 @@ call_api('token', 'my-api-key');
             "warning: hard-coded credential or token detected",
         ]);
     }
+
+    #[test]
+    fn test_hard_coded_credentials_confidence() {
+        let source = r#"<?php
+$dsn = 'mysql:host=db;user=app;password=hunter2';
+$auth = 'token: 3f9a1c77';
+$query = 'UPDATE users SET password = :password';
+connect('super-secret-password');
+$name = 'reset_password';
+$user = ['password' => $hash];
+$input = $_POST['password-confirm'];
+"#;
+
+        let parsed = parse_php(source);
+        let rule = HardCodedCredentialsRule::new(SecurityConfig::default());
+        let confidences: Vec<_> = run_rule(&rule, &parsed)
+            .iter()
+            .map(|diag| diag.confidence)
+            .collect();
+
+        assert_eq!(
+            confidences,
+            [
+                Some(Confidence::High),
+                Some(Confidence::High),
+                Some(Confidence::Medium),
+                Some(Confidence::Medium),
+                Some(Confidence::Low),
+                Some(Confidence::Low),
+                Some(Confidence::Low),
+            ]
+        );
+    }
 }
@@ -27,6 +27,10 @@ impl DiagnosticRule for IncludeUserInputRule {
         "security/include_user_input"
     }
 
+    fn description(&self) -> &str {
+        "include and require paths built from request data"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
@@ -2,11 +2,16 @@ use super::DiagnosticRule;
 use super::helpers::{
     child_by_kind, diagnostic_for_node, newline_for_source, node_text, walk_node,
 };
+use crate::analyzer::config::SecurityConfig;
 use crate::analyzer::fix;
 use crate::analyzer::project::ProjectContext;
 use crate::analyzer::{Severity, parser};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use tree_sitter::Node;
+
+/// Prefix of the variables the fix introduces, e.g. `$_php_checker_literal_1`.
+const DEFAULT_PLACEHOLDER_PREFIX: &str = "_php_checker_literal";
+
 const MUTATING_FUNCTIONS: &[&str] = &[
     "array_pop",
     "array_shift",
@@ -17,11 +22,23 @@ const MUTATING_FUNCTIONS: &[&str] = &[
     "ksort",
 ];
 
-pub struct MutatingLiteralRule;
+pub struct MutatingLiteralRule {
+    functions: Vec<String>,
+    placeholder_prefix: String,
+}
 
 impl MutatingLiteralRule {
-    pub fn new() -> Self {
-        Self
+    pub fn new(config: SecurityConfig) -> Self {
+        Self {
+            functions: SecurityConfig::extend(MUTATING_FUNCTIONS, &config.mutating_functions),
+            placeholder_prefix: config
+                .literal_placeholder_prefix
+                .as_deref()
+                .map(|prefix| prefix.trim_start_matches('$'))
+                .filter(|prefix| !prefix.is_empty())
+                .unwrap_or(DEFAULT_PLACEHOLDER_PREFIX)
+                .to_string(),
+        }
     }
 }
 
@@ -30,12 +47,16 @@ impl DiagnosticRule for MutatingLiteralRule {
         "security/mutating_literal"
     }
 
+    fn description(&self) -> &str {
+        "Array literals passed to functions that modify their argument"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
         _context: &ProjectContext,
     ) -> Vec<crate::analyzer::Diagnostic> {
-        collect_mutating_literal_infos(parsed)
+        collect_mutating_literal_infos(parsed, &self.functions)
             .into_iter()
             .map(|info| {
                 diagnostic_for_node(
@@ -54,7 +75,7 @@ impl DiagnosticRule for MutatingLiteralRule {
     fn fix(&self, parsed: &parser::ParsedSource, _context: &ProjectContext) -> Vec<fix::TextEdit> {
         let source = parsed.source.as_str();
         let newline = newline_for_source(source);
-        let infos = collect_mutating_literal_infos(parsed);
+        let infos = collect_mutating_literal_infos(parsed, &self.functions);
         if infos.is_empty() {
             return Vec::new();
         }
@@ -71,9 +92,18 @@ impl DiagnosticRule for MutatingLiteralRule {
                 .push(info.literal);
         }
 
+        // Numbering restarts in every statement so fixing one literal doesn't
+        // rename the placeholders of the others; names already used in the
+        // enclosing function (or file) are skipped.
+        let mut scope_variables: HashMap<usize, HashSet<String>> = HashMap::new();
         let mut edits = Vec::new();
-        let mut counter = 0;
         for (_, statement_fix) in statements.into_iter() {
+            let scope = enclosing_scope(statement_fix.statement);
+            let taken = scope_variables
+                .entry(scope.id())
+                .or_insert_with(|| variable_names(scope, parsed));
+            let mut counter = 0;
+
             let statement_start = statement_fix.statement.start_byte();
             let statement_end = statement_fix.statement.end_byte();
             let statement_text = &source[statement_start..statement_end];
@@ -86,8 +116,13 @@ impl DiagnosticRule for MutatingLiteralRule {
             let mut cursor = statement_start;
 
             for literal in literals {
-                counter += 1;
-                let placeholder = format!("$_php_checker_literal_{counter}");
+                let placeholder = loop {
+                    counter += 1;
+                    let placeholder = format!("${}_{counter}", self.placeholder_prefix);
+                    if !taken.contains(&placeholder) {
+                        break placeholder;
+                    }
+                };
                 let literal_text = &source[literal.start_byte()..literal.end_byte()];
                 assignment_text.push_str(&format!("{placeholder} = {literal_text};{newline}"));
 
@@ -127,6 +162,7 @@ struct MutatingLiteralInfo<'a> {
 
 fn collect_mutating_literal_infos<'a>(
     parsed: &'a parser::ParsedSource,
+    functions: &[String],
 ) -> Vec<MutatingLiteralInfo<'a>> {
     let mut infos = Vec::new();
 
@@ -145,7 +181,7 @@ fn collect_mutating_literal_infos<'a>(
             None => return,
         };
 
-        if !MUTATING_FUNCTIONS.contains(&name.as_str()) {
+        if !functions.contains(&name.to_lowercase()) {
             return;
         }
 
@@ -172,6 +208,35 @@ fn collect_mutating_literal_infos<'a>(
     infos
 }
 
+/// The function-like whose variables `node` shares, or the file.
+fn enclosing_scope(mut node: Node) -> Node {
+    while let Some(parent) = node.parent() {
+        if matches!(
+            parent.kind(),
+            "function_definition" | "method_declaration" | "anonymous_function_creation_expression"
+        ) {
+            return parent;
+        }
+        node = parent;
+    }
+
+    node
+}
+
+/// Variables used in `scope` itself, not in the functions nested inside it.
+fn variable_names(scope: Node, parsed: &parser::ParsedSource) -> HashSet<String> {
+    let mut names = HashSet::new();
+    walk_node(scope, &mut |node| {
+        if node.kind() == "variable_name"
+            && enclosing_scope(node).id() == scope.id()
+            && let Some(name) = node_text(node, parsed)
+        {
+            names.insert(name);
+        }
+    });
+    names
+}
+
 fn enclosing_expression_statement(mut node: Node) -> Node {
     while let Some(parent) = node.parent() {
         if parent.kind() == "expression_statement" {
@@ -198,7 +263,7 @@ sort([3, 1, 2]);
 "#;
 
         let parsed = parse_php(source);
-        let rule = MutatingLiteralRule::new();
+        let rule = MutatingLiteralRule::new(SecurityConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         assert_diagnostics_exact(&diagnostics, &[
@@ -220,16 +285,55 @@ sort([3, 1, 2]);
 
 $_php_checker_literal_1 = [1, 2, 3];
 array_pop($_php_checker_literal_1);
-$_php_checker_literal_2 = [3, 1, 2];
-sort($_php_checker_literal_2);
+$_php_checker_literal_1 = [3, 1, 2];
+sort($_php_checker_literal_1);
+
+"#;
+
+        let parsed = parse_php(input);
+        let rule = MutatingLiteralRule::new(SecurityConfig::default());
+        assert_fix(&rule, &parsed, input, expected);
+    }
+
+    #[test]
+    fn test_mutating_literal_fix_avoids_existing_variables() {
+        let input = r#"<?php
+function trim_list($_php_checker_literal_1)
+{
+    array_pop([1, 2]) + array_shift([3]);
+}
 
+sort([4, 5]);
+"#;
+
+        let expected = r#"<?php
+function trim_list($_php_checker_literal_1)
+{
+    $_php_checker_literal_2 = [1, 2];
+$_php_checker_literal_3 = [3];
+array_pop($_php_checker_literal_2) + array_shift($_php_checker_literal_3);
+}
+
+$_php_checker_literal_1 = [4, 5];
+sort($_php_checker_literal_1);
 "#;
 
         let parsed = parse_php(input);
-        let rule = MutatingLiteralRule::new();
+        let rule = MutatingLiteralRule::new(SecurityConfig::default());
         assert_fix(&rule, &parsed, input, expected);
     }
 
+    #[test]
+    fn test_mutating_literal_fix_configured_prefix() {
+        let input = "<?php\n$tmp_1 = 0;\nsort([2, 1]);\n";
+        let expected = "<?php\n$tmp_1 = 0;\n$tmp_2 = [2, 1];\nsort($tmp_2);\n";
+
+        let config: SecurityConfig =
+            serde_yaml::from_str("literal_placeholder_prefix: $tmp").unwrap();
+        let parsed = parse_php(input);
+        assert_fix(&MutatingLiteralRule::new(config), &parsed, input, expected);
+    }
+
     #[test]
     fn test_mutating_literal_valid() {
         let source = r#"<?php
@@ -242,9 +346,25 @@ rsort($data);
 "#;
 
         let parsed = parse_php(source);
-        let rule = MutatingLiteralRule::new();
+        let rule = MutatingLiteralRule::new(SecurityConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         assert_no_diagnostics(&diagnostics);
     }
+
+    #[test]
+    fn test_mutating_literal_configured_functions() {
+        let source = r#"<?php
+shuffle([1, 2, 3]);
+Arr::shuffle([1, 2, 3]);
+"#;
+
+        let config: SecurityConfig = serde_yaml::from_str("mutating_functions: [shuffle]").unwrap();
+        let parsed = parse_php(source);
+
+        assert_diagnostics_exact(
+            &run_rule(&MutatingLiteralRule::new(config), &parsed),
+            &["warning: shuffle modifies its argument in place; avoid passing literals"],
+        );
+    }
 }
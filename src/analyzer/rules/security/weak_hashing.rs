@@ -1,22 +1,28 @@
 use super::DiagnosticRule;
 use super::helpers::{child_by_kind, diagnostic_for_node, node_text, walk_node};
+use crate::analyzer::config::SecurityConfig;
 use crate::analyzer::project::ProjectContext;
 use crate::analyzer::{Severity, parser};
 
 const WEAK_HASH_FUNCTIONS: &[&str] = &["md5", "sha1"];
-const PASSWORD_INDICATORS: &[&str] = &[
-    "password",
-    "passwd",
-    "pwd",
-    "userpassword",
-    "hashedpassword",
-];
+/// Algorithms that are just as weak when selected through `hash()`.
+const WEAK_HASH_ALGORITHMS: &[&str] = &["md2", "md4", "md5", "sha1"];
+const PASSWORD_INDICATORS: &[&str] = &["password", "passwd", "pwd", "passphrase"];
 
-pub struct WeakHashingRule;
+pub struct WeakHashingRule {
+    functions: Vec<String>,
+    password_indicators: Vec<String>,
+}
 
 impl WeakHashingRule {
-    pub fn new() -> Self {
-        Self
+    pub fn new(config: SecurityConfig) -> Self {
+        Self {
+            functions: SecurityConfig::extend(WEAK_HASH_FUNCTIONS, &config.weak_hash_functions),
+            password_indicators: SecurityConfig::extend(
+                PASSWORD_INDICATORS,
+                &config.password_indicators,
+            ),
+        }
     }
 }
 
@@ -25,6 +31,10 @@ impl DiagnosticRule for WeakHashingRule {
         "security/weak_hashing"
     }
 
+    fn description(&self) -> &str {
+        "Passwords hashed with md5, sha1 or other weak algorithms"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
@@ -37,28 +47,52 @@ impl DiagnosticRule for WeakHashingRule {
                 return;
             }
 
-            let name_node = match child_by_kind(node, "name") {
-                Some(name_node) => name_node,
-                None => return,
-            };
-
-            let function_name = match node_text(name_node, parsed) {
-                Some(name) => name,
-                None => return,
+            let Some(name_node) = child_by_kind(node, "name") else {
+                return;
             };
-
-            // Check if this is a weak hash function
-            if !WEAK_HASH_FUNCTIONS.contains(&function_name.as_str()) {
+            let Some(function_name) = node_text(name_node, parsed) else {
                 return;
-            }
+            };
+            let arguments = call_arguments(node);
+
+            let function_name = function_name.to_lowercase();
+            let (message, data_index) = match function_name.as_str() {
+                name if self.functions.iter().any(|function| function == name) => (
+                    format!(
+                        "weak hashing function '{name}' used for password hashing, consider using password_hash() or similar secure alternatives"
+                    ),
+                    0,
+                ),
+                "hash" => {
+                    let Some(algorithm) = arguments
+                        .first()
+                        .and_then(|algorithm| string_literal(*algorithm, parsed))
+                        .map(|algorithm| algorithm.to_lowercase())
+                        .filter(|algorithm| WEAK_HASH_ALGORITHMS.contains(&algorithm.as_str()))
+                    else {
+                        return;
+                    };
+                    (
+                        format!(
+                            "weak hashing algorithm '{algorithm}' passed to hash() used for password hashing, consider using password_hash() or similar secure alternatives"
+                        ),
+                        1,
+                    )
+                }
+                _ => return,
+            };
 
-            // Check if this is used in a password-related context
-            if is_password_context(node, parsed) {
+            let indicators = &self.password_indicators;
+            let hashes_password = is_password_target(node, parsed, indicators)
+                || arguments
+                    .get(data_index)
+                    .is_some_and(|data| is_password_argument(*data, parsed, indicators));
+            if hashes_password {
                 diagnostics.push(diagnostic_for_node(
                     parsed,
                     name_node,
                     Severity::Warning,
-                    format!("weak hashing function '{}' used for password hashing, consider using password_hash() or similar secure alternatives", function_name),
+                    message,
                 ));
             }
         });
@@ -67,112 +101,89 @@ impl DiagnosticRule for WeakHashingRule {
     }
 }
 
-fn is_password_context(function_call: tree_sitter::Node, parsed: &parser::ParsedSource) -> bool {
-    // Check if the function call is assigned to a password-related variable
-    if let Some(parent) = function_call.parent() {
-        match parent.kind() {
-            "assignment_expression" => {
-                if let Some(left) = parent.child_by_field_name("left") {
-                    if let Some(var_name) = extract_variable_name(left, parsed) {
-                        let lowered = var_name.to_lowercase();
-                        if PASSWORD_INDICATORS
-                            .iter()
-                            .any(|indicator| lowered.contains(indicator))
-                        {
-                            return true;
-                        }
-                    }
-                }
-            }
-            "variable_declaration" => {
-                if let Some(var_name) = extract_variable_name_from_declaration(parent, parsed) {
-                    let lowered = var_name.to_lowercase();
-                    if PASSWORD_INDICATORS
-                        .iter()
-                        .any(|indicator| lowered.contains(indicator))
-                    {
-                        return true;
-                    }
-                }
-            }
-            _ => {}
-        }
-    }
+fn call_arguments(function_call: tree_sitter::Node) -> Vec<tree_sitter::Node> {
+    let Some(arguments) = child_by_kind(function_call, "arguments") else {
+        return Vec::new();
+    };
 
-    // Check function arguments for password-related content
-    if let Some(arguments) = child_by_kind(function_call, "arguments") {
-        for idx in 0..arguments.named_child_count() {
-            if let Some(arg) = arguments.named_child(idx) {
-                if is_password_argument(arg, parsed) {
-                    return true;
-                }
-            }
-        }
-    }
-
-    false
+    (0..arguments.named_child_count())
+        .filter_map(|idx| arguments.named_child(idx))
+        .filter(|argument| argument.kind() == "argument")
+        .collect()
 }
 
-fn extract_variable_name(node: tree_sitter::Node, parsed: &parser::ParsedSource) -> Option<String> {
-    match node.kind() {
-        "variable_name" => node_text(node, parsed),
-        "member_access_expression" => {
-            // For $user->password, etc.
-            if let Some(member) = node.child_by_field_name("name") {
-                node_text(member, parsed)
-            } else {
-                None
-            }
-        }
-        _ => None,
+fn string_literal(argument: tree_sitter::Node, parsed: &parser::ParsedSource) -> Option<String> {
+    let value = argument.named_child(0)?;
+    if !matches!(value.kind(), "string" | "encapsed_string") {
+        return None;
     }
+    node_text(value, parsed).map(|text| text.trim_matches(|c| c == '\'' || c == '"').to_string())
 }
 
-fn extract_variable_name_from_declaration(
-    node: tree_sitter::Node,
+fn is_password_name(name: &str, indicators: &[String]) -> bool {
+    let lowered = name.to_lowercase();
+    indicators
+        .iter()
+        .any(|indicator| lowered.contains(indicator))
+}
+
+/// The hash is stored in a password-named variable or property (`$user->password = md5(...)`).
+fn is_password_target(
+    function_call: tree_sitter::Node,
     parsed: &parser::ParsedSource,
-) -> Option<String> {
-    // For declarations like $password = md5(...)
-    for idx in 0..node.named_child_count() {
-        if let Some(child) = node.named_child(idx) {
-            if child.kind() == "simple_parameter" || child.kind() == "property_element" {
-                if let Some(var_name) = child.child_by_field_name("name") {
-                    return node_text(var_name, parsed);
-                }
-            }
-        }
+    indicators: &[String],
+) -> bool {
+    let Some(parent) = function_call.parent() else {
+        return false;
+    };
+    if parent.kind() != "assignment_expression" {
+        return false;
     }
-    None
+
+    parent
+        .child_by_field_name("left")
+        .and_then(|left| extract_variable_name(left, parsed))
+        .is_some_and(|name| is_password_name(&name, indicators))
 }
 
-fn is_password_argument(node: tree_sitter::Node, parsed: &parser::ParsedSource) -> bool {
-    // Check if argument contains password-related strings
+/// The hashed data mentions a password: a variable, a property (`$user->password`) or an
+/// array key (`$_POST['password']`), possibly nested in concatenations or other calls.
+fn is_password_argument(
+    node: tree_sitter::Node,
+    parsed: &parser::ParsedSource,
+    indicators: &[String],
+) -> bool {
+    let mut found = false;
     walk_node(node, &mut |child| {
-        if child.kind() == "string" {
-            if let Some(text) = node_text(child, parsed) {
-                let lowered = text.to_lowercase();
-                if PASSWORD_INDICATORS
-                    .iter()
-                    .any(|indicator| lowered.contains(indicator))
-                {
-                    return;
-                }
-            }
+        if found {
+            return;
         }
-        // Could also check variable names in arguments
-        if child.kind() == "variable_name" {
-            if let Some(var_name) = node_text(child, parsed) {
-                let lowered = var_name.to_lowercase();
-                if PASSWORD_INDICATORS
-                    .iter()
-                    .any(|indicator| lowered.contains(indicator))
-                {
-                    return;
-                }
+
+        let name = match child.kind() {
+            "variable_name" | "member_access_expression" | "nullsafe_member_access_expression" => {
+                extract_variable_name(child, parsed)
             }
-        }
+            "subscript_expression" => child
+                .named_child(1)
+                .filter(|key| matches!(key.kind(), "string" | "encapsed_string"))
+                .and_then(|key| node_text(key, parsed)),
+            _ => None,
+        };
+        found = name.is_some_and(|name| is_password_name(&name, indicators));
     });
-    false
+    found
+}
+
+fn extract_variable_name(node: tree_sitter::Node, parsed: &parser::ParsedSource) -> Option<String> {
+    match node.kind() {
+        "variable_name" => node_text(node, parsed),
+        "member_access_expression" | "nullsafe_member_access_expression" => {
+            // For $user->password, etc.
+            node.child_by_field_name("name")
+                .and_then(|member| node_text(member, parsed))
+        }
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -215,7 +226,7 @@ $contentSha1 = sha1("content");
 "#;
 
         let parsed = parse_php(source);
-        let rule = WeakHashingRule::new();
+        let rule = WeakHashingRule::new(SecurityConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         assert_diagnostics_exact(&diagnostics, &[
@@ -248,9 +259,61 @@ $hash = hash('sha256', $data);
 "#;
 
         let parsed = parse_php(source);
-        let rule = WeakHashingRule::new();
+        let rule = WeakHashingRule::new(SecurityConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         assert_no_diagnostics(&diagnostics);
     }
+
+    #[test]
+    fn test_weak_hashing_arguments_and_hash_wrapper() {
+        let source = r#"<?php
+$stored = md5($user->password);
+$digest = sha1($salt . $_POST['password']);
+$legacy = hash('md5', $plainPassword);
+$token = hash('SHA1', strtolower($request->passwd));
+
+// OK - strong algorithm, or weak algorithm on non-password data
+$strong = hash('sha256', $password);
+$etag = hash('md5', $body);
+$cacheKey = md5($user->email);
+"#;
+
+        let parsed = parse_php(source);
+        let diagnostics = run_rule(&WeakHashingRule::new(SecurityConfig::default()), &parsed);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "warning: weak hashing function 'md5' used for password hashing",
+                "warning: weak hashing function 'sha1' used for password hashing",
+                "warning: weak hashing algorithm 'md5' passed to hash() used for password hashing",
+                "warning: weak hashing algorithm 'sha1' passed to hash() used for password hashing",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_weak_hashing_configured_functions_and_indicators() {
+        let source = r#"<?php
+$passwordHash = crc32($input);
+$digest = md5($user->credential);
+
+// OK - crc32 on non-password data
+$checksum = crc32($body);
+"#;
+
+        let config: SecurityConfig =
+            serde_yaml::from_str("weak_hash_functions: [CRC32]\npassword_indicators: [credential]")
+                .unwrap();
+        let parsed = parse_php(source);
+
+        assert_diagnostics_exact(
+            &run_rule(&WeakHashingRule::new(config), &parsed),
+            &[
+                "warning: weak hashing function 'crc32' used for password hashing",
+                "warning: weak hashing function 'md5' used for password hashing",
+            ],
+        );
+    }
 }
@@ -0,0 +1,285 @@
+use super::DiagnosticRule;
+use super::helpers::{TypeHint, diagnostic_for_node, node_text, value_type, walk_node};
+use crate::analyzer::artifacts::Artifact;
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::types::{TypeArena, TypeId, type_expression_to_hint};
+use crate::analyzer::{Severity, parser};
+use tree_sitter::Node;
+
+/// Reports `??` fallbacks whose type the receiving variable or parameter can't
+/// hold, e.g. `$count = $row['count'] ?? 'none'` when `$count` is an `int`.
+pub struct CoalesceTypeMismatchRule;
+
+impl CoalesceTypeMismatchRule {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DiagnosticRule for CoalesceTypeMismatchRule {
+    fn name(&self) -> &str {
+        "strict_typing/coalesce_type_mismatch"
+    }
+
+    fn description(&self) -> &str {
+        "?? fallbacks whose type conflicts with the variable or parameter receiving them"
+    }
+
+    fn requires(&self) -> &[Artifact] {
+        &[
+            Artifact::VarAnnotations,
+            Artifact::ParameterTypes,
+            Artifact::FunctionSignatures,
+        ]
+    }
+
+    fn run(
+        &self,
+        parsed: &parser::ParsedSource,
+        context: &ProjectContext,
+    ) -> Vec<crate::analyzer::Diagnostic> {
+        let types = context.types();
+        let mut diagnostics = Vec::new();
+
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            let (fallback, target) = match node.kind() {
+                // `$x = $a ?? 'default'`
+                "assignment_expression" => {
+                    let Some(right) = node.child_by_field_name("right") else {
+                        return;
+                    };
+                    if !is_coalesce(right) {
+                        return;
+                    }
+                    (innermost_fallback(right), node.child_by_field_name("left"))
+                }
+                // `$x ??= 'default'`
+                "augmented_assignment_expression" => {
+                    let is_coalesce_assignment = node
+                        .child_by_field_name("operator")
+                        .or_else(|| node.child(1))
+                        .is_some_and(|operator| operator.kind() == "??=");
+                    if !is_coalesce_assignment {
+                        return;
+                    }
+                    let Some(right) = node.child_by_field_name("right") else {
+                        return;
+                    };
+                    (right, node.child_by_field_name("left"))
+                }
+                // `takesInt($a ?? 'default')`
+                "argument" => {
+                    let Some(value) = node.named_child(0) else {
+                        return;
+                    };
+                    if !is_coalesce(value) {
+                        return;
+                    }
+                    if let Some(diagnostic) =
+                        check_argument(node, innermost_fallback(value), parsed, context)
+                    {
+                        diagnostics.push(diagnostic);
+                    }
+                    return;
+                }
+                _ => return,
+            };
+
+            let Some(target) = target.filter(|target| target.kind() == "variable_name") else {
+                return;
+            };
+            let Some(name) = node_text(target, parsed) else {
+                return;
+            };
+            let Some(expected) = declared_type(target, &name, parsed) else {
+                return;
+            };
+            let Some(actual) = value_type(fallback, parsed, context) else {
+                return;
+            };
+            let (actual, expected) = (types.intern(&actual), types.intern(&expected));
+            if conflicts(actual, expected, types) {
+                diagnostics.push(diagnostic_for_node(
+                    parsed,
+                    fallback,
+                    Severity::Error,
+                    format!(
+                        "?? fallback {} is {} but {name} is declared as {}",
+                        node_text(fallback, parsed).unwrap_or_default(),
+                        types.display(actual),
+                        types.display(expected)
+                    ),
+                ));
+            }
+        });
+
+        diagnostics
+    }
+}
+
+/// Compares the fallback of a coalescing argument with the parameter it's
+/// passed to, for functions declared in the same file.
+fn check_argument(
+    argument: Node,
+    fallback: Node,
+    parsed: &parser::ParsedSource,
+    context: &ProjectContext,
+) -> Option<crate::analyzer::Diagnostic> {
+    let arguments = argument.parent()?;
+    let call = arguments.parent()?;
+    if call.kind() != "function_call_expression" {
+        return None;
+    }
+    let function = node_text(call.child_by_field_name("function")?, parsed)?;
+    let signature = parsed.function_signatures().get(&function)?;
+
+    let mut cursor = arguments.walk();
+    let position = arguments
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() == "argument")
+        .position(|child| child.id() == argument.id())?;
+    let types = context.types();
+    let expected = types.intern(signature.params.get(position)?);
+    let actual = types.intern(&value_type(fallback, parsed, context)?);
+
+    conflicts(actual, expected, types).then(|| {
+        diagnostic_for_node(
+            parsed,
+            fallback,
+            Severity::Error,
+            format!(
+                "?? fallback {} is {} but argument {} of {function} expects {}",
+                node_text(fallback, parsed).unwrap_or_default(),
+                types.display(actual),
+                position + 1,
+                types.display(expected)
+            ),
+        )
+    })
+}
+
+fn is_coalesce(node: Node) -> bool {
+    node.kind() == "binary_expression" && node.child(1).is_some_and(|op| op.kind() == "??")
+}
+
+/// The value used when every operand is null: `'c'` in `$a ?? $b ?? 'c'`.
+fn innermost_fallback(coalesce: Node) -> Node {
+    let mut fallback = coalesce;
+    while is_coalesce(fallback) {
+        match fallback.child(2) {
+            Some(right) => fallback = right,
+            None => break,
+        }
+    }
+    fallback
+}
+
+/// The type `variable` is declared with: the closest `@var` before it, else
+/// the enclosing function's parameter type.
+fn declared_type(variable: Node, name: &str, parsed: &parser::ParsedSource) -> Option<TypeHint> {
+    let bare = name.trim_start_matches('$');
+    let annotation = parsed
+        .var_annotations()
+        .iter()
+        .rfind(|annotation| annotation.name == bare && annotation.offset < variable.start_byte());
+    if let Some(annotation) = annotation {
+        return type_expression_to_hint(&annotation.type_expr);
+    }
+
+    let mut function = variable.parent()?;
+    while !matches!(
+        function.kind(),
+        "function_definition"
+            | "method_declaration"
+            | "anonymous_function_creation_expression"
+            | "arrow_function"
+    ) {
+        function = function.parent()?;
+    }
+    parsed
+        .parameter_type(function, name)
+        .filter(|hint| **hint != TypeHint::Unknown)
+        .cloned()
+}
+
+/// Whether a fallback of type `actual` can't be stored as `expected`. Ints
+/// widen to floats, and enum cases are only checked against scalar types.
+fn conflicts(actual: TypeId, expected: TypeId, types: &TypeArena) -> bool {
+    fn accepts(expected: &TypeHint, test: &dyn Fn(&TypeHint) -> bool) -> bool {
+        match expected {
+            TypeHint::Nullable(inner) => accepts(inner, test),
+            TypeHint::Union(members) => members.iter().any(|member| accepts(member, test)),
+            other => test(other),
+        }
+    }
+
+    if actual == TypeId::UNKNOWN {
+        return false;
+    }
+    let expected_hint = types.hint(expected);
+    match *types.hint(actual) {
+        TypeHint::Int if accepts(&expected_hint, &|hint| *hint == TypeHint::Float) => false,
+        TypeHint::Object(_)
+            if accepts(&expected_hint, &|hint| matches!(hint, TypeHint::Object(_))) =>
+        {
+            false
+        }
+        _ => !types.is_compatible(actual, expected),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::{
+        assert_diagnostics_exact, assert_no_diagnostics, run_rule_with_context,
+    };
+
+    #[test]
+    fn test_coalesce_fallback_conflicts() {
+        let source = r#"<?php
+function takesInt(int $value): void {}
+
+function load(array $row, int $limit): void
+{
+    /** @var int $count */
+    $count = $row['count'] ?? 'none';
+    $limit ??= 'all';
+    takesInt($row['id'] ?? $row['key'] ?? 'missing');
+}
+"#;
+
+        let rule = CoalesceTypeMismatchRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "error: ?? fallback 'none' is string but $count is declared as int",
+                "error: ?? fallback 'all' is string but $limit is declared as int",
+                "error: ?? fallback 'missing' is string but argument 1 of takesInt expects int",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_coalesce_fallback_matches() {
+        let source = r#"<?php
+function takesFloat(float $value): void {}
+
+function load(array $row, ?string $name): void
+{
+    /** @var float $ratio */
+    $ratio = $row['ratio'] ?? 1;
+    $name = $row['name'] ?? 'anonymous';
+    $untyped = $row['x'] ?? 'x';
+    takesFloat($row['value'] ?? 0);
+}
+"#;
+
+        let rule = CoalesceTypeMismatchRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_no_diagnostics(&diagnostics);
+    }
+}
@@ -1,13 +1,16 @@
 use super::DiagnosticRule;
-use super::helpers::{TypeHint, child_by_kind, diagnostic_for_node, literal_type, walk_node};
+use super::helpers::{TypeHint, diagnostic_for_node, literal_type};
+use crate::analyzer::artifacts::Artifact;
 use crate::analyzer::project::ProjectContext;
+use crate::analyzer::types::{TypeArena, TypeId};
 use crate::analyzer::{Severity, parser};
+use std::sync::Arc;
 use tree_sitter::Node;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ReturnType {
     Void,
-    Typed(TypeHint),
+    Typed(TypeId),
 }
 
 pub struct ConsistentReturnRule;
@@ -23,73 +26,81 @@ impl DiagnosticRule for ConsistentReturnRule {
         "strict_typing/consistent_return"
     }
 
+    fn description(&self) -> &str {
+        "Functions that return values of different types"
+    }
+
+    fn requires(&self) -> &[Artifact] {
+        &[Artifact::ControlFlow]
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
-        _context: &ProjectContext,
+        context: &ProjectContext,
     ) -> Vec<crate::analyzer::Diagnostic> {
+        let types = context.types();
         let mut diagnostics = Vec::new();
 
-        walk_node(parsed.tree.root_node(), &mut |node| {
-            if node.kind() != "function_definition" {
-                return;
+        for function in &parsed.control_flow().functions {
+            if function.node.kind != "function_definition" || function.body.is_none() {
+                continue;
             }
 
-            let body = match child_by_kind(node, "compound_statement") {
-                Some(body) => body,
-                None => return,
-            };
-
-            let mut return_types = Vec::new();
-
-            walk_node(body, &mut |candidate| {
-                if candidate.kind() == "return_statement" {
-                    let return_type = analyze_return_type(candidate, parsed);
-                    return_types.push((return_type, candidate));
-                }
-            });
+            let return_types: Vec<_> = function
+                .returns
+                .iter()
+                .map(|ret| {
+                    let candidate = ret.node(parsed);
+                    (analyze_return_type(candidate, parsed, types), candidate)
+                })
+                .collect();
 
             if return_types.len() <= 1 {
-                return; // Need at least 2 returns to check consistency
+                continue; // Need at least 2 returns to check consistency
             }
 
             // Check if all return types are the same
-            let first_type = &return_types[0].0;
-            for (return_type, return_node) in return_types.iter().skip(1) {
-                if !types_compatible(first_type, return_type) {
+            let first_type = return_types[0].0;
+            for &(return_type, return_node) in return_types.iter().skip(1) {
+                if first_type != return_type {
                     let start = return_node.start_position();
                     let row = start.row + 1;
                     let column = start.column + 1;
 
                     diagnostics.push(diagnostic_for_node(
                         parsed,
-                        *return_node,
+                        return_node,
                         Severity::Error,
                         format!(
                             "inconsistent return type: expected {}, found {} at {row}:{column}",
-                            type_description(&first_type),
-                            type_description(return_type)
+                            type_description(first_type, types),
+                            type_description(return_type, types)
                         ),
                     ));
                 }
             }
-        });
+        }
 
         diagnostics
     }
 }
 
-fn analyze_return_type(return_node: Node, parsed: &parser::ParsedSource) -> ReturnType {
+fn analyze_return_type(
+    return_node: Node,
+    parsed: &parser::ParsedSource,
+    types: &TypeArena,
+) -> ReturnType {
     // Check if there's an expression after 'return'
     for idx in 0..return_node.named_child_count() {
         if let Some(child) = return_node.named_child(idx) {
             // Try to determine the type using literal_type first
             if let Some(returned_type) = literal_type(child) {
-                return ReturnType::Typed(returned_type);
+                return ReturnType::Typed(types.intern(&returned_type));
             }
             // Try to determine the type of the expression directly
             if let Some(returned_type) = infer_expression_type(child, parsed) {
-                return ReturnType::Typed(returned_type);
+                return ReturnType::Typed(types.intern(&returned_type));
             }
         }
     }
@@ -125,53 +136,10 @@ fn infer_expression_type(node: Node, _parsed: &parser::ParsedSource) -> Option<T
     }
 }
 
-fn types_compatible(type1: &ReturnType, type2: &ReturnType) -> bool {
-    match (type1, type2) {
-        (ReturnType::Void, ReturnType::Void) => true,
-        (ReturnType::Typed(t1), ReturnType::Typed(t2)) => t1 == t2,
-        _ => false,
-    }
-}
-
-fn type_description(return_type: &ReturnType) -> String {
+fn type_description(return_type: ReturnType, types: &TypeArena) -> Arc<str> {
     match return_type {
-        ReturnType::Void => "void".to_string(),
-        ReturnType::Typed(hint) => type_hint_to_string(hint),
-    }
-}
-
-fn type_hint_to_string(hint: &TypeHint) -> String {
-    match hint {
-        TypeHint::Int => "int".to_string(),
-        TypeHint::String => "string".to_string(),
-        TypeHint::Bool => "bool".to_string(),
-        TypeHint::Float => "float".to_string(),
-        TypeHint::Object(class_name) => class_name.clone(),
-        TypeHint::Nullable(inner) => format!("?{}", type_hint_to_string(inner)),
-        TypeHint::Union(types) => types
-            .iter()
-            .map(type_hint_to_string)
-            .collect::<Vec<_>>()
-            .join("|"),
-        TypeHint::Array(inner) => format!("{}[]", type_hint_to_string(inner)),
-        TypeHint::GenericArray { key, value } => {
-            format!(
-                "array<{}, {}>",
-                type_hint_to_string(key),
-                type_hint_to_string(value)
-            )
-        }
-        TypeHint::ShapedArray(fields) => {
-            let fields_str = fields
-                .iter()
-                .map(|(name, hint)| {
-                    format!("{}: {}", name, type_hint_to_string(hint))
-                })
-                .collect::<Vec<_>>()
-                .join(", ");
-            format!("array{{{}}}", fields_str)
-        }
-        TypeHint::Unknown => "unknown".to_string(),
+        ReturnType::Void => "void".into(),
+        ReturnType::Typed(id) => types.display(id),
     }
 }
 
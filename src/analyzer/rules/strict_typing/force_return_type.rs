@@ -16,6 +16,10 @@ impl DiagnosticRule for ForceReturnTypeRule {
         "strict_typing/force_return_type"
     }
 
+    fn description(&self) -> &str {
+        "Functions and methods without a return type"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
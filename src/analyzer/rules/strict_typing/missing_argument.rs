@@ -16,6 +16,10 @@ impl DiagnosticRule for MissingArgumentRule {
         "strict_typing/missing_argument"
     }
 
+    fn description(&self) -> &str {
+        "Calls that pass fewer arguments than the function requires"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
@@ -96,6 +100,51 @@ takesTwo(1);
         assert_diagnostics_exact(&diagnostics, &["error: missing required argument 2 for takesTwo"]);
     }
 
+    #[test]
+    fn test_missing_argument_resolves_case_insensitively() {
+        let source = r#"<?php
+namespace App;
+
+function takesTwo(int $a, int $b): void
+{
+}
+
+TAKESTWO(1);
+\app\takestwo(1);
+"#;
+
+        let rule = MissingArgumentRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "error: missing required argument 2 for TAKESTWO",
+                "error: missing required argument 2 for \\app\\takestwo",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_missing_argument_through_function_import() {
+        let source = r#"<?php
+namespace App;
+
+use function App\{takesTwo as two};
+
+function takesTwo(int $a, int $b): void
+{
+}
+
+two(1);
+"#;
+
+        let rule = MissingArgumentRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(&diagnostics, &["error: missing required argument 2 for two"]);
+    }
+
     #[test]
     fn test_missing_argument_valid() {
         let source = r#"<?php
@@ -1,7 +1,6 @@
 use super::DiagnosticRule;
-use super::helpers::{
-    child_by_kind, diagnostic_for_node, has_conditional_ancestor, node_text, walk_node,
-};
+use super::helpers::{child_by_kind, diagnostic_for_node, has_conditional_ancestor, node_text};
+use crate::analyzer::artifacts::Artifact;
 use crate::analyzer::project::ProjectContext;
 use crate::analyzer::{Severity, parser};
 use tree_sitter::Node;
@@ -19,6 +18,14 @@ impl DiagnosticRule for MissingReturnRule {
         "strict_typing/missing_return"
     }
 
+    fn description(&self) -> &str {
+        "Typed functions with paths that end without a return"
+    }
+
+    fn requires(&self) -> &[Artifact] {
+        &[Artifact::ControlFlow]
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
@@ -26,39 +33,40 @@ impl DiagnosticRule for MissingReturnRule {
     ) -> Vec<crate::analyzer::Diagnostic> {
         let mut diagnostics = Vec::new();
 
-        walk_node(parsed.tree.root_node(), &mut |node| {
-            if node.kind() != "function_definition" {
-                return;
+        let flow = parsed.control_flow();
+        for function in &flow.functions {
+            if function.node.kind != "function_definition" || function.returns.is_empty() {
+                continue;
             }
-
-            let body = match child_by_kind(node, "compound_statement") {
-                Some(body) => body,
-                None => return,
+            let Some(body) = function.body else {
+                continue;
             };
-
-            let mut return_nodes = Vec::new();
-            walk_node(body, &mut |candidate| {
-                if candidate.kind() == "return_statement" {
-                    return_nodes.push(candidate);
-                }
-            });
-
-            if return_nodes.is_empty() {
-                return;
-            }
+            let node = function.node.node(parsed);
+            let body_node = body.node(parsed);
+            let return_nodes: Vec<_> = function
+                .returns
+                .iter()
+                .map(|ret| ret.node(parsed))
+                .collect();
 
             // Check if there's an unconditional return (early return pattern)
             let has_unconditional = return_nodes
                 .iter()
-                .any(|r| !has_conditional_ancestor(*r, body));
+                .any(|r| !has_conditional_ancestor(*r, body_node));
 
             if has_unconditional {
-                return;
+                continue;
             }
 
             // Check if all conditional branches return (e.g., if-else where both return)
-            if all_conditional_branches_return(body, &return_nodes) {
-                return;
+            let if_statements: Vec<_> = flow
+                .branches
+                .iter()
+                .filter(|branch| branch.node.kind == "if_statement" && body.contains(&branch.node))
+                .map(|branch| branch.node.node(parsed))
+                .collect();
+            if all_conditional_branches_return(&if_statements, &return_nodes) {
+                continue;
             }
 
             let name_node = node.child_by_field_name("name").unwrap_or(node);
@@ -73,7 +81,7 @@ impl DiagnosticRule for MissingReturnRule {
                 Severity::Error,
                 format!("function {name} is missing a return on some paths at {row}:{column}"),
             ));
-        });
+        }
 
         diagnostics
     }
@@ -88,21 +96,13 @@ impl DiagnosticRule for MissingReturnRule {
 ///     return 'b';
 /// }
 /// ```
-fn all_conditional_branches_return(body: Node, return_nodes: &[Node]) -> bool {
-    // Check all if statements in the body
-    let mut if_statements = Vec::new();
-    walk_node(body, &mut |node| {
-        if node.kind() == "if_statement" {
-            if_statements.push(node);
-        }
-    });
-
+fn all_conditional_branches_return(if_statements: &[Node], return_nodes: &[Node]) -> bool {
     if if_statements.is_empty() {
         return false; // No conditionals to check
     }
 
     // Check each if statement
-    for if_stmt in if_statements {
+    for &if_stmt in if_statements {
         let mut has_if_return = false;
         let mut has_else_return = false;
         let mut has_else = false;
@@ -224,4 +224,27 @@ function earlyReturn(bool $flag): string {
 
         assert_no_diagnostics(&diagnostics);
     }
+
+    #[test]
+    fn test_nested_closure_return_does_not_count() {
+        let source = r#"<?php
+function label(bool $flag): string {
+    $format = function () {
+        return 'formatted';
+    };
+    if ($flag) {
+        return $format();
+    }
+}
+"#;
+
+        let parsed = parse_php(source);
+        let rule = MissingReturnRule::new();
+        let diagnostics = run_rule(&rule, &parsed);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &["error: function label is missing a return on some paths"],
+        );
+    }
 }
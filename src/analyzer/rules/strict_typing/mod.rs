@@ -1,5 +1,6 @@
 pub use crate::analyzer::rules::{DiagnosticRule, helpers};
 
+pub mod coalesce_type_mismatch;
 pub mod consistent_return;
 pub mod force_return_type;
 pub mod missing_argument;
@@ -11,6 +12,7 @@ pub mod phpdoc_var_check;
 pub mod strict_types;
 pub mod type_mismatch;
 
+pub use coalesce_type_mismatch::CoalesceTypeMismatchRule;
 pub use consistent_return::ConsistentReturnRule;
 pub use force_return_type::ForceReturnTypeRule;
 pub use missing_argument::MissingArgumentRule;
@@ -1,8 +1,7 @@
-use super::helpers::{
-    TypeHint, child_by_kind, diagnostic_for_node, is_type_compatible, node_text, walk_node,
-};
+use super::helpers::{TypeHint, child_by_kind, diagnostic_for_range, node_text, walk_node};
 use crate::analyzer::phpdoc::{TypeExpression, extract_phpdoc_for_node};
 use crate::analyzer::rules::DiagnosticRule;
+use crate::analyzer::types::{TypeArena, TypeFormatter, TypeId, type_expression_to_hint};
 use crate::analyzer::{Diagnostic, Severity, parser, project::ProjectContext};
 
 /// Validates that @return types match native return type hints
@@ -41,7 +40,12 @@ impl DiagnosticRule for PhpDocReturnCheckRule {
         "strict_typing/phpdoc_return_check"
     }
 
-    fn run(&self, parsed: &parser::ParsedSource, _context: &ProjectContext) -> Vec<Diagnostic> {
+    fn description(&self) -> &str {
+        "@return tags that conflict with the native return type"
+    }
+
+    fn run(&self, parsed: &parser::ParsedSource, context: &ProjectContext) -> Vec<Diagnostic> {
+        let types = context.types();
         let mut diagnostics = Vec::new();
 
         walk_node(parsed.tree.root_node(), &mut |node| {
@@ -78,18 +82,19 @@ impl DiagnosticRule for PhpDocReturnCheckRule {
 
             // Check for conflicts
             if let Some(_phpdoc) = phpdoc_hint {
-                if !is_compatible_return(&native_hint, &return_tag.type_expr) {
-                    let native_type_display = type_hint_to_string(&native_hint);
+                let native_hint = types.intern(&native_hint);
+                if !is_compatible_return(native_hint, &return_tag.type_expr, types) {
+                    let native_type_display = types.display(native_hint);
 
                     let message = format!(
                         "@return type '{}' conflicts with native return type hint '{}'",
-                        type_expression_to_string(&return_tag.type_expr),
+                        TypeFormatter::expression(&return_tag.type_expr),
                         native_type_display
                     );
 
-                    diagnostics.push(diagnostic_for_node(
+                    diagnostics.push(diagnostic_for_range(
                         parsed,
-                        native_type_node,
+                        return_tag.range.clone(),
                         Severity::Error,
                         message,
                     ));
@@ -103,15 +108,22 @@ impl DiagnosticRule for PhpDocReturnCheckRule {
 
 /// Check if PHPDoc type is compatible with native type hint
 /// PHPDoc can be more specific than native type (e.g., array<int, string> vs array)
-fn is_compatible_return(native: &TypeHint, phpdoc_expr: &TypeExpression) -> bool {
+fn is_compatible_return(native: TypeId, phpdoc_expr: &TypeExpression, types: &TypeArena) -> bool {
     // If we have a generic/array PHPDoc type and native is just "array", that's compatible
     // (PHPDoc is being more specific)
-    if matches!(native, TypeHint::Object(name) if name == "array") {
+    if matches!(&*types.hint(native), TypeHint::Object(name) if name == "array") {
         match phpdoc_expr {
             // array<...> is compatible with array
-            TypeExpression::Generic { base, .. } if base == "array" => return true,
-            // Type[] is compatible with array
-            TypeExpression::Array(_) => return true,
+            TypeExpression::Generic { base, .. }
+                if matches!(
+                    base.as_str(),
+                    "array" | "non-empty-array" | "list" | "non-empty-list"
+                ) =>
+            {
+                return true;
+            }
+            // Type[] and array{...} are compatible with array
+            TypeExpression::Array(_) | TypeExpression::ShapedArray(_) => return true,
             // array (simple) is compatible with array
             TypeExpression::Simple(s) if s == "array" => return true,
             _ => {}
@@ -122,8 +134,9 @@ fn is_compatible_return(native: &TypeHint, phpdoc_expr: &TypeExpression) -> bool
     if let Some(phpdoc_hint) = type_expression_to_hint(phpdoc_expr) {
         // Check bidirectional compatibility for @return
         // Either they should match exactly or be compatible in some direction
-        return is_type_compatible(native, &phpdoc_hint)
-            || is_type_compatible(&phpdoc_hint, native);
+        let phpdoc_hint = types.intern(&phpdoc_hint);
+        return types.is_compatible(native, phpdoc_hint)
+            || types.is_compatible(phpdoc_hint, native);
     }
 
     false
@@ -205,133 +218,16 @@ fn text_to_type_hint(text: &str) -> Option<TypeHint> {
     }
 }
 
-fn type_hint_to_string(hint: &TypeHint) -> String {
-    match hint {
-        TypeHint::Int => "int".to_string(),
-        TypeHint::String => "string".to_string(),
-        TypeHint::Bool => "bool".to_string(),
-        TypeHint::Float => "float".to_string(),
-        TypeHint::Object(name) => name.clone(),
-        TypeHint::Nullable(inner) => format!("?{}", type_hint_to_string(inner)),
-        TypeHint::Union(types) => types
-            .iter()
-            .map(type_hint_to_string)
-            .collect::<Vec<_>>()
-            .join("|"),
-        TypeHint::Array(inner) => format!("{}[]", type_hint_to_string(inner)),
-        TypeHint::GenericArray { key, value } => {
-            format!(
-                "array<{}, {}>",
-                type_hint_to_string(key),
-                type_hint_to_string(value)
-            )
-        }
-        TypeHint::ShapedArray(fields) => {
-            let fields_str = fields
-                .iter()
-                .map(|(name, hint)| {
-                    format!("{}: {}", name, type_hint_to_string(hint))
-                })
-                .collect::<Vec<_>>()
-                .join(", ");
-            format!("array{{{}}}", fields_str)
-        }
-        TypeHint::Unknown => "unknown".to_string(),
-    }
-}
-
-fn type_expression_to_hint(expr: &TypeExpression) -> Option<TypeHint> {
-    match expr {
-        TypeExpression::Simple(s) => match s.as_str() {
-            "int" | "integer" => Some(TypeHint::Int),
-            "string" => Some(TypeHint::String),
-            "bool" | "boolean" => Some(TypeHint::Bool),
-            "float" | "double" => Some(TypeHint::Float),
-            // Anything else is treated as an object type (class/interface name)
-            _ => Some(TypeHint::Object(s.clone())),
-        },
-        TypeExpression::Nullable(inner) => {
-            // Wrap the inner type in Nullable
-            type_expression_to_hint(inner).map(|t| TypeHint::Nullable(Box::new(t)))
-        }
-        TypeExpression::Union(types) => {
-            // Convert each type in the union
-            let hints: Vec<TypeHint> = types.iter().filter_map(type_expression_to_hint).collect();
-            if hints.is_empty() {
-                None
-            } else {
-                Some(TypeHint::Union(hints))
-            }
-        }
-        TypeExpression::Array(inner) => {
-            // Convert array type (e.g., int[], User[])
-            type_expression_to_hint(inner).map(|t| TypeHint::Array(Box::new(t)))
-        }
-        TypeExpression::Generic { base, params } => {
-            // Handle generic array types (e.g., array<string, int>)
-            if base == "array" && params.len() == 2 {
-                let key_hint = type_expression_to_hint(&params[0])?;
-                let value_hint = type_expression_to_hint(&params[1])?;
-                return Some(TypeHint::GenericArray {
-                    key: Box::new(key_hint),
-                    value: Box::new(value_hint),
-                });
-            }
-            None
-        }
-        _ => None,
-    }
-}
-
-fn type_expression_to_string(expr: &TypeExpression) -> String {
-    match expr {
-        TypeExpression::Simple(s) => s.clone(),
-        TypeExpression::Array(inner) => format!("{}[]", type_expression_to_string(inner)),
-        TypeExpression::Generic { base, params } => {
-            let params_str = params
-                .iter()
-                .map(type_expression_to_string)
-                .collect::<Vec<_>>()
-                .join(", ");
-            format!("{}<{}>", base, params_str)
-        }
-        TypeExpression::Union(types) => types
-            .iter()
-            .map(type_expression_to_string)
-            .collect::<Vec<_>>()
-            .join("|"),
-        TypeExpression::Nullable(inner) => format!("?{}", type_expression_to_string(inner)),
-        TypeExpression::ShapedArray(fields) => {
-            let fields_str = fields
-                .iter()
-                .map(|(name, type_expr)| {
-                    format!("{}: {}", name, type_expression_to_string(type_expr))
-                })
-                .collect::<Vec<_>>()
-                .join(", ");
-            format!("array{{{}}}", fields_str)
-        }
-        TypeExpression::Mixed => "mixed".to_string(),
-        TypeExpression::Void => "void".to_string(),
-        TypeExpression::Never => "never".to_string(),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Arc;
 
     fn parse_php(source: &str) -> parser::ParsedSource {
         let mut ts_parser = tree_sitter::Parser::new();
         ts_parser.set_language(tree_sitter_php::language()).unwrap();
         let tree = ts_parser.parse(source, None).unwrap();
 
-        parser::ParsedSource {
-            path: std::path::PathBuf::from("test.php"),
-            source: Arc::new(source.to_string()),
-            tree,
-        }
+        parser::ParsedSource::new("test.php", source.to_string(), tree)
     }
 
     #[test]
@@ -359,6 +255,29 @@ function test(): int {
         );
     }
 
+    #[test]
+    fn test_return_type_conflict_reported_at_tag() {
+        let source = r#"<?php
+/**
+ * Answers.
+ * @return string The answer
+ */
+function test(): int {
+    return 42;
+}
+"#;
+
+        let parsed = parse_php(source);
+        let diagnostics = PhpDocReturnCheckRule::new().run(&parsed, &ProjectContext::new());
+
+        let span = diagnostics[0].span.as_ref().expect("span should be set");
+        assert_eq!((span.start.row, span.start.column), (3, 3));
+        assert_eq!(
+            diagnostics[0].snippet_line.as_deref(),
+            Some(" * @return string The answer")
+        );
+    }
+
     #[test]
     fn test_return_type_matches() {
         let source = r#"<?php
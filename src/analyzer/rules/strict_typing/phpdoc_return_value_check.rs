@@ -1,9 +1,11 @@
 use super::helpers::{
-    TypeHint, child_by_kind, diagnostic_for_node, extract_array_elements,
-    extract_array_key_value_pairs, infer_type, is_type_compatible, walk_node,
+    TypeHint, diagnostic_for_node, extract_array_elements, extract_array_key_value_pairs,
+    infer_type,
 };
+use crate::analyzer::artifacts::Artifact;
 use crate::analyzer::phpdoc::{TypeExpression, extract_phpdoc_for_node};
 use crate::analyzer::rules::DiagnosticRule;
+use crate::analyzer::types::{TypeFormatter, TypeId, type_expression_to_hint};
 use crate::analyzer::{Diagnostic, Severity, parser, project::ProjectContext};
 
 /// Validates that actual return values match @return types
@@ -36,125 +38,13 @@ impl PhpDocReturnValueCheckRule {
         Self
     }
 
-    /// Convert PHPDoc TypeExpression to our internal TypeHint
-    fn type_expression_to_hint(expr: &TypeExpression) -> Option<TypeHint> {
-        match expr {
-            TypeExpression::Simple(s) => match s.as_str() {
-                "int" | "integer" => Some(TypeHint::Int),
-                "string" => Some(TypeHint::String),
-                "bool" | "boolean" => Some(TypeHint::Bool),
-                "float" | "double" => Some(TypeHint::Float),
-                _ => Some(TypeHint::Object(s.clone())),
-            },
-            TypeExpression::Nullable(inner) => {
-                Self::type_expression_to_hint(inner).map(|t| TypeHint::Nullable(Box::new(t)))
-            }
-            TypeExpression::Union(types) => {
-                let hints: Vec<TypeHint> = types
-                    .iter()
-                    .filter_map(|t| Self::type_expression_to_hint(t))
-                    .collect();
-                if hints.is_empty() {
-                    None
-                } else {
-                    Some(TypeHint::Union(hints))
-                }
-            }
-            TypeExpression::Array(inner) => {
-                Self::type_expression_to_hint(inner).map(|t| TypeHint::Array(Box::new(t)))
-            }
-            TypeExpression::Generic { base, params } => {
-                if base == "array" && params.len() == 2 {
-                    let key_hint = Self::type_expression_to_hint(&params[0])?;
-                    let value_hint = Self::type_expression_to_hint(&params[1])?;
-                    return Some(TypeHint::GenericArray {
-                        key: Box::new(key_hint),
-                        value: Box::new(value_hint),
-                    });
-                }
-                None
-            }
-            _ => None,
-        }
-    }
-
-    fn type_expression_to_string(expr: &TypeExpression) -> String {
-        match expr {
-            TypeExpression::Simple(s) => s.clone(),
-            TypeExpression::Array(inner) => format!("{}[]", Self::type_expression_to_string(inner)),
-            TypeExpression::Generic { base, params } => {
-                let params_str = params
-                    .iter()
-                    .map(|p| Self::type_expression_to_string(p))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("{}<{}>", base, params_str)
-            }
-            TypeExpression::Union(types) => types
-                .iter()
-                .map(|t| Self::type_expression_to_string(t))
-                .collect::<Vec<_>>()
-                .join("|"),
-            TypeExpression::Nullable(inner) => {
-                format!("?{}", Self::type_expression_to_string(inner))
-            }
-            TypeExpression::ShapedArray(fields) => {
-                let fields_str = fields
-                    .iter()
-                    .map(|(name, type_expr)| {
-                        format!("{}: {}", name, Self::type_expression_to_string(type_expr))
-                    })
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("array{{{}}}", fields_str)
-            }
-            TypeExpression::Mixed => "mixed".to_string(),
-            TypeExpression::Void => "void".to_string(),
-            TypeExpression::Never => "never".to_string(),
-        }
-    }
-
-    fn type_hint_to_string(hint: &TypeHint) -> String {
-        match hint {
-            TypeHint::Int => "int".to_string(),
-            TypeHint::String => "string".to_string(),
-            TypeHint::Bool => "bool".to_string(),
-            TypeHint::Float => "float".to_string(),
-            TypeHint::Object(name) => name.clone(),
-            TypeHint::Nullable(inner) => format!("?{}", Self::type_hint_to_string(inner)),
-            TypeHint::Union(types) => types
-                .iter()
-                .map(|t| Self::type_hint_to_string(t))
-                .collect::<Vec<_>>()
-                .join("|"),
-            TypeHint::Array(inner) => format!("{}[]", Self::type_hint_to_string(inner)),
-            TypeHint::GenericArray { key, value } => {
-                format!(
-                    "array<{}, {}>",
-                    Self::type_hint_to_string(key),
-                    Self::type_hint_to_string(value)
-                )
-            }
-            TypeHint::ShapedArray(fields) => {
-                let fields_str = fields
-                    .iter()
-                    .map(|(name, hint)| {
-                        format!("{}: {}", name, Self::type_hint_to_string(hint))
-                    })
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("array{{{}}}", fields_str)
-            }
-            TypeHint::Unknown => "unknown".to_string(),
-        }
-    }
-
     /// Check array elements match the expected array type
     fn check_array_elements(
         array_node: tree_sitter::Node,
         expected_type: &TypeHint,
         type_expr: &TypeExpression,
         parsed: &parser::ParsedSource,
+        context: &ProjectContext,
         diagnostics: &mut Vec<Diagnostic>,
     ) {
         // Check if this is a generic array type
@@ -169,6 +59,7 @@ impl PhpDocReturnValueCheckRule {
                 expected_value,
                 type_expr,
                 parsed,
+                context,
                 diagnostics,
             );
             return;
@@ -176,20 +67,23 @@ impl PhpDocReturnValueCheckRule {
 
         // Extract the expected element type from simple array types
         let expected_elem_type = match expected_type {
-            TypeHint::Array(elem_type) => Some(elem_type.as_ref()),
+            TypeHint::Array(elem_type) | TypeHint::List(elem_type) => Some(elem_type.as_ref()),
             _ => None,
         };
 
         if let Some(expected_elem) = expected_elem_type {
+            let types = context.types();
+            let expected_elem = types.intern(expected_elem);
             // Extract all elements from the array
-            let elements = extract_array_elements(array_node, parsed);
+            let elements = extract_array_elements(array_node, parsed, context);
 
             for (elem_node, elem_type_opt) in elements {
                 if let Some(elem_type) = elem_type_opt {
+                    let elem_type = types.intern(&elem_type);
                     // Check if the type is unknown (couldn't be inferred)
-                    if elem_type == TypeHint::Unknown {
-                        let expected_name = Self::type_hint_to_string(expected_elem);
-                        let array_type_name = Self::type_expression_to_string(type_expr);
+                    if elem_type == TypeId::UNKNOWN {
+                        let expected_name = types.display(expected_elem);
+                        let array_type_name = TypeFormatter::expression(type_expr);
 
                         diagnostics.push(diagnostic_for_node(
                             parsed,
@@ -200,11 +94,11 @@ impl PhpDocReturnValueCheckRule {
                                 expected_name, array_type_name
                             ),
                         ));
-                    } else if !is_type_compatible(&elem_type, expected_elem) {
+                    } else if !types.is_compatible(elem_type, expected_elem) {
                         // Check if element type is compatible with expected element type
-                        let expected_name = Self::type_hint_to_string(expected_elem);
-                        let actual_name = Self::type_hint_to_string(&elem_type);
-                        let array_type_name = Self::type_expression_to_string(type_expr);
+                        let expected_name = types.display(expected_elem);
+                        let actual_name = types.display(elem_type);
+                        let array_type_name = TypeFormatter::expression(type_expr);
 
                         diagnostics.push(diagnostic_for_node(
                             parsed,
@@ -228,15 +122,20 @@ impl PhpDocReturnValueCheckRule {
         expected_value: &TypeHint,
         type_expr: &TypeExpression,
         parsed: &parser::ParsedSource,
+        context: &ProjectContext,
         diagnostics: &mut Vec<Diagnostic>,
     ) {
-        let pairs = extract_array_key_value_pairs(array_node, parsed);
-        let array_type_name = Self::type_expression_to_string(type_expr);
+        let types = context.types();
+        let expected_key = types.intern(expected_key);
+        let expected_value = types.intern(expected_value);
+        let pairs = extract_array_key_value_pairs(array_node, parsed, context);
+        let array_type_name = TypeFormatter::expression(type_expr);
 
         for (key_node_opt, key_type_opt, value_node, value_type_opt) in pairs {
             // Check key type
             if let Some(key_type) = key_type_opt {
-                if key_type == TypeHint::Unknown {
+                let key_type = types.intern(&key_type);
+                if key_type == TypeId::UNKNOWN {
                     if let Some(key_node) = key_node_opt {
                         diagnostics.push(diagnostic_for_node(
                             parsed,
@@ -244,12 +143,12 @@ impl PhpDocReturnValueCheckRule {
                             Severity::Error,
                             format!(
                                 "Cannot infer type of array key; expected key type '{}' for @return type '{}'",
-                                Self::type_hint_to_string(expected_key),
+                                types.display(expected_key),
                                 array_type_name
                             ),
                         ));
                     }
-                } else if !is_type_compatible(&key_type, expected_key) {
+                } else if !types.is_compatible(key_type, expected_key) {
                     if let Some(key_node) = key_node_opt {
                         diagnostics.push(diagnostic_for_node(
                             parsed,
@@ -257,8 +156,8 @@ impl PhpDocReturnValueCheckRule {
                             Severity::Error,
                             format!(
                                 "Array key type '{}' conflicts with expected key type '{}' for @return type '{}'",
-                                Self::type_hint_to_string(&key_type),
-                                Self::type_hint_to_string(expected_key),
+                                types.display(key_type),
+                                types.display(expected_key),
                                 array_type_name
                             ),
                         ));
@@ -268,26 +167,27 @@ impl PhpDocReturnValueCheckRule {
 
             // Check value type
             if let Some(value_type) = value_type_opt {
-                if value_type == TypeHint::Unknown {
+                let value_type = types.intern(&value_type);
+                if value_type == TypeId::UNKNOWN {
                     diagnostics.push(diagnostic_for_node(
                         parsed,
                         value_node,
                         Severity::Error,
                         format!(
                             "Cannot infer type of array value; expected value type '{}' for @return type '{}'",
-                            Self::type_hint_to_string(expected_value),
+                            types.display(expected_value),
                             array_type_name
                         ),
                     ));
-                } else if !is_type_compatible(&value_type, expected_value) {
+                } else if !types.is_compatible(value_type, expected_value) {
                     diagnostics.push(diagnostic_for_node(
                         parsed,
                         value_node,
                         Severity::Error,
                         format!(
                             "Array value type '{}' conflicts with expected value type '{}' for @return type '{}'",
-                            Self::type_hint_to_string(&value_type),
-                            Self::type_hint_to_string(expected_value),
+                            types.display(value_type),
+                            types.display(expected_value),
                             array_type_name
                         ),
                     ));
@@ -302,44 +202,53 @@ impl DiagnosticRule for PhpDocReturnValueCheckRule {
         "strict_typing/phpdoc_return_value_check"
     }
 
-    fn run(&self, parsed: &parser::ParsedSource, _context: &ProjectContext) -> Vec<Diagnostic> {
+    fn description(&self) -> &str {
+        "Returned values that do not match the @return type"
+    }
+
+    fn requires(&self) -> &[Artifact] {
+        &[Artifact::ControlFlow, Artifact::VarAnnotations]
+    }
+
+    fn run(&self, parsed: &parser::ParsedSource, context: &ProjectContext) -> Vec<Diagnostic> {
+        let types = context.types();
         let mut diagnostics = Vec::new();
 
-        walk_node(parsed.tree.root_node(), &mut |node| {
-            if !matches!(node.kind(), "function_definition" | "method_declaration") {
-                return;
+        for function in &parsed.control_flow().functions {
+            if !matches!(
+                function.node.kind,
+                "function_definition" | "method_declaration"
+            ) || function.body.is_none()
+            {
+                continue;
             }
+            let node = function.node.node(parsed);
 
             // Extract @return PHPDoc
             let Some(phpdoc) = extract_phpdoc_for_node(node, parsed) else {
-                return;
+                continue;
             };
 
             let Some(return_tag) = &phpdoc.return_tag else {
-                return;
+                continue;
             };
 
             // Get expected return type from @return
-            let Some(expected_type) = Self::type_expression_to_hint(&return_tag.type_expr) else {
-                return;
-            };
-
-            // Find the function body
-            let Some(body) = child_by_kind(node, "compound_statement") else {
-                return;
+            let Some(expected_type) = type_expression_to_hint(&return_tag.type_expr) else {
+                continue;
             };
+            let expected_id = types.intern(&expected_type);
 
-            // Check all return statements in the function
-            walk_node(body, &mut |ret_node| {
-                if ret_node.kind() != "return_statement" {
-                    return;
-                }
-
+            // Check the function's own return statements
+            for ret_node in function.returns.iter().map(|ret| ret.node(parsed)) {
                 // Get the return value
                 if let Some(value_node) = ret_node.named_child(0) {
                     // Check if this is an array literal and we expect an array type
                     if value_node.kind() == "array_creation_expression"
-                        && matches!(expected_type, TypeHint::Array(_) | TypeHint::GenericArray { .. })
+                        && matches!(
+                            expected_type,
+                            TypeHint::Array(_) | TypeHint::List(_) | TypeHint::GenericArray { .. }
+                        )
                     {
                         // Validate array elements (handles both simple and generic arrays)
                         Self::check_array_elements(
@@ -347,27 +256,29 @@ impl DiagnosticRule for PhpDocReturnValueCheckRule {
                             &expected_type,
                             &return_tag.type_expr,
                             parsed,
+                            context,
                             &mut diagnostics,
                         );
                     } else {
                         // Infer the type of the return value
-                        if let Some(actual_type) = infer_type(value_node, parsed) {
+                        if let Some(actual_type) = infer_type(value_node, parsed, context) {
+                            let actual_type = types.intern(&actual_type);
                             // Check if unknown type
-                            if actual_type == TypeHint::Unknown {
+                            if actual_type == TypeId::UNKNOWN {
                                 diagnostics.push(diagnostic_for_node(
                                     parsed,
                                     value_node,
                                     Severity::Error,
                                     format!(
                                         "Cannot infer type of return value; expected @return type '{}'",
-                                        Self::type_expression_to_string(&return_tag.type_expr)
+                                        TypeFormatter::expression(&return_tag.type_expr)
                                     ),
                                 ));
-                            } else if !is_type_compatible(&actual_type, &expected_type) {
+                            } else if !types.is_compatible(actual_type, expected_id) {
                                 // Check if types are compatible
-                                let actual_name = Self::type_hint_to_string(&actual_type);
+                                let actual_name = types.display(actual_type);
                                 let expected_name =
-                                    Self::type_expression_to_string(&return_tag.type_expr);
+                                    TypeFormatter::expression(&return_tag.type_expr);
 
                                 diagnostics.push(diagnostic_for_node(
                                     parsed,
@@ -382,9 +293,144 @@ impl DiagnosticRule for PhpDocReturnValueCheckRule {
                         }
                     }
                 }
-            });
-        });
+            }
+        }
 
         diagnostics
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::rule_test;
+
+    rule_test!(
+        test_return_value_uses_standalone_and_declaration_var_annotations,
+        rule: PhpDocReturnValueCheckRule::new(),
+        source: r#"<?php
+/** @var float $ratio */
+
+/**
+ * @return string
+ */
+function ratio() {
+    global $ratio;
+    return $ratio;
+}
+
+/**
+ * @return string[]
+ */
+function names() {
+    /**
+     * @var int $id
+     * @var string $name
+     */
+    [$id, $name] = row();
+    return [$name, $id];
+}
+"#,
+        expect: [
+            "error: Return value type 'float' conflicts with @return type 'string'",
+            "error: Array element type 'int' conflicts with expected element type 'string' for @return type 'string[]'",
+        ]
+    );
+
+    rule_test!(
+        test_return_value_narrowed_by_assertions_and_conditional_returns,
+        rule: PhpDocReturnValueCheckRule::new(),
+        source: r#"<?php
+/** @phpstan-assert !null $value */
+function assertNotNull($value): void {}
+
+/** @phpstan-assert-if-true User $value */
+function isUser($value): bool {}
+
+/** @return ($asString is true ? string : int) */
+function total(bool $asString) {}
+
+/** @return User */
+function asserted() {
+    /** @var ?User $user */
+    $user = find();
+    assertNotNull($user);
+    return $user;
+}
+
+/** @return User */
+function guarded() {
+    /** @var mixed $user */
+    $user = find();
+    if (!isUser($user)) {
+        throw new InvalidArgumentException();
+    }
+    return $user;
+}
+
+/** @return string */
+function label() {
+    $total = total(true);
+    return $total;
+}
+
+/** @return string */
+function count_label() {
+    $total = total(false);
+    return $total;
+}
+
+/** @return User */
+function unchecked() {
+    /** @var ?User $user */
+    $user = find();
+    return $user;
+}
+"#,
+        expect: [
+            "error: Return value type 'int' conflicts with @return type 'string'",
+            "error: Return value type '?User' conflicts with @return type 'User'",
+        ]
+    );
+
+    rule_test!(
+        test_return_value_pseudo_types,
+        rule: PhpDocReturnValueCheckRule::new(),
+        source: r#"<?php
+/** @return list<string> */
+function names() {
+    return ['ada', 42];
+}
+
+/** @return array-key */
+function id() {
+    return 1.5;
+}
+
+/** @return key-of<array<string, int>> */
+function first_key() {
+    return 3;
+}
+
+/** @return array-key|null */
+function maybe_id() {
+    return 'abc';
+}
+
+/** @return non-empty-string */
+function label() {
+    return 'label';
+}
+
+/** @return value-of<list<int>> */
+function first_value() {
+    return 3;
+}
+"#,
+        expect: [
+            "error: Array element type 'int' conflicts with expected element type 'string' for @return type 'list<string>'",
+            "error: Return value type 'float' conflicts with @return type 'array-key'",
+            "error: Return value type 'int' conflicts with @return type 'key-of<array<string, int>>'",
+        ]
+    );
+}
@@ -1,11 +1,13 @@
 use super::DiagnosticRule;
 use super::helpers::{
     TypeHint, child_by_kind, diagnostic_for_node, extract_array_elements,
-    extract_array_key_value_pairs, is_type_compatible, literal_type, node_text,
-    variable_name_text, walk_node,
+    extract_array_key_value_pairs, node_text, source_type, value_type, variable_name_text,
+    walk_node,
 };
-use crate::analyzer::phpdoc::{TypeExpression, extract_phpdoc_for_node};
+use crate::analyzer::artifacts::Artifact;
+use crate::analyzer::phpdoc::{TypeExpression, VarTag, extract_phpdoc_for_node};
 use crate::analyzer::project::ProjectContext;
+use crate::analyzer::types::{TypeArena, TypeFormatter, TypeId, type_expression_to_hint};
 use crate::analyzer::{Severity, parser};
 
 pub struct PhpDocVarCheckRule;
@@ -15,140 +17,13 @@ impl PhpDocVarCheckRule {
         Self
     }
 
-    fn type_expression_to_string(expr: &TypeExpression) -> String {
-        match expr {
-            TypeExpression::Simple(s) => s.clone(),
-            TypeExpression::Array(inner) => format!("{}[]", Self::type_expression_to_string(inner)),
-            TypeExpression::Generic { base, params } => {
-                let params_str = params
-                    .iter()
-                    .map(|p| Self::type_expression_to_string(p))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("{}<{}>", base, params_str)
-            }
-            TypeExpression::Union(types) => types
-                .iter()
-                .map(|t| Self::type_expression_to_string(t))
-                .collect::<Vec<_>>()
-                .join("|"),
-            TypeExpression::Nullable(inner) => {
-                format!("?{}", Self::type_expression_to_string(inner))
-            }
-            TypeExpression::ShapedArray(fields) => {
-                let fields_str = fields
-                    .iter()
-                    .map(|(name, type_expr)| {
-                        format!("{}: {}", name, Self::type_expression_to_string(type_expr))
-                    })
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("array{{{}}}", fields_str)
-            }
-            TypeExpression::Mixed => "mixed".to_string(),
-            TypeExpression::Void => "void".to_string(),
-            TypeExpression::Never => "never".to_string(),
-        }
-    }
-
-    fn type_hint_to_string(hint: &TypeHint) -> String {
-        match hint {
-            TypeHint::Int => "int".to_string(),
-            TypeHint::String => "string".to_string(),
-            TypeHint::Bool => "bool".to_string(),
-            TypeHint::Float => "float".to_string(),
-            TypeHint::Object(name) => name.clone(),
-            TypeHint::Nullable(inner) => format!("?{}", Self::type_hint_to_string(inner)),
-            TypeHint::Union(types) => types
-                .iter()
-                .map(|t| Self::type_hint_to_string(t))
-                .collect::<Vec<_>>()
-                .join("|"),
-            TypeHint::Array(inner) => format!("{}[]", Self::type_hint_to_string(inner)),
-            TypeHint::GenericArray { key, value } => {
-                format!(
-                    "array<{}, {}>",
-                    Self::type_hint_to_string(key),
-                    Self::type_hint_to_string(value)
-                )
-            }
-            TypeHint::ShapedArray(fields) => {
-                let fields_str = fields
-                    .iter()
-                    .map(|(name, hint)| {
-                        format!("{}: {}", name, Self::type_hint_to_string(hint))
-                    })
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("array{{{}}}", fields_str)
-            }
-            TypeHint::Unknown => "unknown".to_string(),
-        }
-    }
-
-    /// Convert PHPDoc TypeExpression to our internal TypeHint for simple cases
-    fn type_expression_to_hint(expr: &TypeExpression) -> Option<TypeHint> {
-        match expr {
-            TypeExpression::Simple(s) => match s.as_str() {
-                "int" | "integer" => Some(TypeHint::Int),
-                "string" => Some(TypeHint::String),
-                "bool" | "boolean" => Some(TypeHint::Bool),
-                "float" | "double" => Some(TypeHint::Float),
-                // Anything else is treated as an object type (class/interface name)
-                _ => Some(TypeHint::Object(s.clone())),
-            },
-            TypeExpression::Nullable(inner) => {
-                // Wrap the inner type in Nullable
-                Self::type_expression_to_hint(inner).map(|t| TypeHint::Nullable(Box::new(t)))
-            }
-            TypeExpression::Union(types) => {
-                // Convert each type in the union
-                let hints: Vec<TypeHint> = types
-                    .iter()
-                    .filter_map(|t| Self::type_expression_to_hint(t))
-                    .collect();
-                if hints.is_empty() {
-                    None
-                } else {
-                    Some(TypeHint::Union(hints))
-                }
-            }
-            TypeExpression::Array(inner) => {
-                // Convert array type (e.g., int[], User[])
-                Self::type_expression_to_hint(inner).map(|t| TypeHint::Array(Box::new(t)))
-            }
-            TypeExpression::Generic { base, params } => {
-                // Handle generic array types (e.g., array<string, int>)
-                if base == "array" && params.len() == 2 {
-                    let key_hint = Self::type_expression_to_hint(&params[0])?;
-                    let value_hint = Self::type_expression_to_hint(&params[1])?;
-                    return Some(TypeHint::GenericArray {
-                        key: Box::new(key_hint),
-                        value: Box::new(value_hint),
-                    });
-                }
-                None
-            }
-            TypeExpression::ShapedArray(fields) => {
-                // Convert shaped array to TypeHint
-                let hint_fields: Option<Vec<_>> = fields
-                    .iter()
-                    .map(|(name, type_expr)| {
-                        Self::type_expression_to_hint(type_expr).map(|hint| (name.clone(), hint))
-                    })
-                    .collect();
-                hint_fields.map(TypeHint::ShapedArray)
-            }
-            _ => None,
-        }
-    }
-
     /// Check array elements match the expected array type
     fn check_array_elements(
         array_node: tree_sitter::Node,
         expected_type: &TypeHint,
         type_expr: &TypeExpression,
         parsed: &parser::ParsedSource,
+        context: &ProjectContext,
         diagnostics: &mut Vec<crate::analyzer::Diagnostic>,
     ) {
         // Check if this is a shaped array type
@@ -158,6 +33,7 @@ impl PhpDocVarCheckRule {
                 expected_fields,
                 type_expr,
                 parsed,
+                context,
                 diagnostics,
             );
             return;
@@ -175,6 +51,7 @@ impl PhpDocVarCheckRule {
                 expected_value,
                 type_expr,
                 parsed,
+                context,
                 diagnostics,
             );
             return;
@@ -182,20 +59,23 @@ impl PhpDocVarCheckRule {
 
         // Extract the expected element type from simple array types
         let expected_elem_type = match expected_type {
-            TypeHint::Array(elem_type) => Some(elem_type.as_ref()),
+            TypeHint::Array(elem_type) | TypeHint::List(elem_type) => Some(elem_type.as_ref()),
             _ => None,
         };
 
         if let Some(expected_elem) = expected_elem_type {
+            let types = context.types();
+            let expected_elem = types.intern(expected_elem);
             // Extract all elements from the array
-            let elements = extract_array_elements(array_node, parsed);
+            let elements = extract_array_elements(array_node, parsed, context);
 
             for (elem_node, elem_type_opt) in elements {
                 if let Some(elem_type) = elem_type_opt {
+                    let elem_type = types.intern(&elem_type);
                     // Check if the type is unknown (couldn't be inferred)
-                    if elem_type == TypeHint::Unknown {
-                        let expected_name = Self::type_hint_to_string(expected_elem);
-                        let array_type_name = Self::type_expression_to_string(type_expr);
+                    if elem_type == TypeId::UNKNOWN {
+                        let expected_name = types.display(expected_elem);
+                        let array_type_name = TypeFormatter::expression(type_expr);
 
                         diagnostics.push(diagnostic_for_node(
                             parsed,
@@ -206,11 +86,11 @@ impl PhpDocVarCheckRule {
                                 array_type_name, expected_name
                             ),
                         ));
-                    } else if !is_type_compatible(&elem_type, expected_elem) {
+                    } else if !types.is_compatible(elem_type, expected_elem) {
                         // Check if element type is compatible with expected element type
-                        let expected_name = Self::type_hint_to_string(expected_elem);
-                        let actual_name = Self::type_hint_to_string(&elem_type);
-                        let array_type_name = Self::type_expression_to_string(type_expr);
+                        let expected_name = types.display(expected_elem);
+                        let actual_name = types.display(elem_type);
+                        let array_type_name = TypeFormatter::expression(type_expr);
 
                         diagnostics.push(diagnostic_for_node(
                             parsed,
@@ -234,15 +114,20 @@ impl PhpDocVarCheckRule {
         expected_value: &TypeHint,
         type_expr: &TypeExpression,
         parsed: &parser::ParsedSource,
+        context: &ProjectContext,
         diagnostics: &mut Vec<crate::analyzer::Diagnostic>,
     ) {
-        let pairs = extract_array_key_value_pairs(array_node, parsed);
-        let array_type_name = Self::type_expression_to_string(type_expr);
+        let types = context.types();
+        let expected_key = types.intern(expected_key);
+        let expected_value = types.intern(expected_value);
+        let pairs = extract_array_key_value_pairs(array_node, parsed, context);
+        let array_type_name = TypeFormatter::expression(type_expr);
 
         for (key_node_opt, key_type_opt, value_node, value_type_opt) in pairs {
             // Check key type
             if let Some(key_type) = key_type_opt {
-                if key_type == TypeHint::Unknown {
+                let key_type = types.intern(&key_type);
+                if key_type == TypeId::UNKNOWN {
                     if let Some(key_node) = key_node_opt {
                         diagnostics.push(diagnostic_for_node(
                             parsed,
@@ -251,11 +136,11 @@ impl PhpDocVarCheckRule {
                             format!(
                                 "Cannot infer type of array key for {}; expected key type '{}'",
                                 array_type_name,
-                                Self::type_hint_to_string(expected_key)
+                                types.display(expected_key)
                             ),
                         ));
                     }
-                } else if !is_type_compatible(&key_type, expected_key) {
+                } else if !types.is_compatible(key_type, expected_key) {
                     if let Some(key_node) = key_node_opt {
                         diagnostics.push(diagnostic_for_node(
                             parsed,
@@ -263,8 +148,8 @@ impl PhpDocVarCheckRule {
                             Severity::Error,
                             format!(
                                 "Array key type '{}' conflicts with expected key type '{}' for {}",
-                                Self::type_hint_to_string(&key_type),
-                                Self::type_hint_to_string(expected_key),
+                                types.display(key_type),
+                                types.display(expected_key),
                                 array_type_name
                             ),
                         ));
@@ -274,7 +159,8 @@ impl PhpDocVarCheckRule {
 
             // Check value type
             if let Some(value_type) = value_type_opt {
-                if value_type == TypeHint::Unknown {
+                let value_type = types.intern(&value_type);
+                if value_type == TypeId::UNKNOWN {
                     diagnostics.push(diagnostic_for_node(
                         parsed,
                         value_node,
@@ -282,18 +168,18 @@ impl PhpDocVarCheckRule {
                         format!(
                             "Cannot infer type of array value for {}; expected value type '{}'",
                             array_type_name,
-                            Self::type_hint_to_string(expected_value)
+                            types.display(expected_value)
                         ),
                     ));
-                } else if !is_type_compatible(&value_type, expected_value) {
+                } else if !types.is_compatible(value_type, expected_value) {
                     diagnostics.push(diagnostic_for_node(
                         parsed,
                         value_node,
                         Severity::Error,
                         format!(
                             "Array value type '{}' conflicts with expected value type '{}' for {}",
-                            Self::type_hint_to_string(&value_type),
-                            Self::type_hint_to_string(expected_value),
+                            types.display(value_type),
+                            types.display(expected_value),
                             array_type_name
                         ),
                     ));
@@ -309,12 +195,14 @@ impl PhpDocVarCheckRule {
         expected_fields: &[(String, TypeHint)],
         type_expr: &TypeExpression,
         parsed: &parser::ParsedSource,
+        context: &ProjectContext,
         diagnostics: &mut Vec<crate::analyzer::Diagnostic>,
     ) {
-        let array_type_name = Self::type_expression_to_string(type_expr);
+        let types = context.types();
+        let array_type_name = TypeFormatter::expression(type_expr);
 
         // Extract all key-value pairs from the array
-        let pairs = extract_array_key_value_pairs(array_node, parsed);
+        let pairs = extract_array_key_value_pairs(array_node, parsed, context);
 
         // Build a map of actual field names to their values for easy lookup
         use std::collections::HashMap;
@@ -331,14 +219,15 @@ impl PhpDocVarCheckRule {
             }
         }
 
-
         // Check each expected field
         for (expected_name, expected_type) in expected_fields {
+            let expected_type = types.intern(expected_type);
 
             if let Some((value_node, value_type_opt)) = actual_fields.get(expected_name) {
                 // Field exists, check its type
                 if let Some(value_type) = value_type_opt {
-                    if *value_type == TypeHint::Unknown {
+                    let value_type = types.intern(value_type);
+                    if value_type == TypeId::UNKNOWN {
                         diagnostics.push(diagnostic_for_node(
                             parsed,
                             *value_node,
@@ -347,10 +236,10 @@ impl PhpDocVarCheckRule {
                                 "Cannot infer type of field '{}' in {}; expected type '{}'",
                                 expected_name,
                                 array_type_name,
-                                Self::type_hint_to_string(expected_type)
+                                types.display(expected_type)
                             ),
                         ));
-                    } else if !is_type_compatible(value_type, expected_type) {
+                    } else if !types.is_compatible(value_type, expected_type) {
                         diagnostics.push(diagnostic_for_node(
                             parsed,
                             *value_node,
@@ -358,8 +247,8 @@ impl PhpDocVarCheckRule {
                             format!(
                                 "Field '{}' has type '{}' but expected type '{}' in {}",
                                 expected_name,
-                                Self::type_hint_to_string(value_type),
-                                Self::type_hint_to_string(expected_type),
+                                types.display(value_type),
+                                types.display(expected_type),
                                 array_type_name
                             ),
                         ));
@@ -396,6 +285,150 @@ impl PhpDocVarCheckRule {
             }
         }
     }
+
+    /// Check `[$a, $b] = ...` and `['id' => $id] = ...` against the array type of
+    /// the right-hand side: keys the type lacks, and `@var` tags on the targets
+    fn check_destructuring(
+        pattern: tree_sitter::Node,
+        source: &TypeHint,
+        var_tags: &[VarTag],
+        parsed: &parser::ParsedSource,
+        types: &TypeArena,
+        diagnostics: &mut Vec<crate::analyzer::Diagnostic>,
+    ) {
+        let source_name = types.display(types.intern(source));
+        if matches!(
+            source,
+            TypeHint::Int
+                | TypeHint::String
+                | TypeHint::NonEmptyString
+                | TypeHint::Bool
+                | TypeHint::Float
+        ) {
+            diagnostics.push(diagnostic_for_node(
+                parsed,
+                pattern,
+                Severity::Error,
+                format!("Cannot destructure '{source_name}'; every target would be null"),
+            ));
+            return;
+        }
+
+        for entry in destructured_entries(pattern) {
+            let Some(element) = Self::destructured_element(
+                source,
+                &entry,
+                &source_name,
+                parsed,
+                types,
+                diagnostics,
+            ) else {
+                continue;
+            };
+
+            match entry.target.kind() {
+                "list_literal" => Self::check_destructuring(
+                    entry.target,
+                    &element,
+                    var_tags,
+                    parsed,
+                    types,
+                    diagnostics,
+                ),
+                "variable_name" => {
+                    let Some(name) = variable_name_text(entry.target, parsed) else {
+                        continue;
+                    };
+                    let Some(tag) = var_tags
+                        .iter()
+                        .find(|tag| tag.name.as_deref() == Some(name.as_str()))
+                    else {
+                        continue;
+                    };
+                    let Some(expected) = type_expression_to_hint(&tag.type_expr) else {
+                        continue;
+                    };
+                    let element = types.intern(&element);
+                    if conflicts(element, types.intern(&expected), types) {
+                        diagnostics.push(diagnostic_for_node(
+                            parsed,
+                            entry.target,
+                            Severity::Error,
+                            format!(
+                                "@var type '{}' conflicts with destructured type '{}' of ${name}",
+                                TypeFormatter::expression(&tag.type_expr),
+                                types.display(element)
+                            ),
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The type an entry receives from `source`, reporting keys the source lacks
+    fn destructured_element(
+        source: &TypeHint,
+        entry: &DestructuredEntry,
+        source_name: &str,
+        parsed: &parser::ParsedSource,
+        types: &TypeArena,
+        diagnostics: &mut Vec<crate::analyzer::Diagnostic>,
+    ) -> Option<TypeHint> {
+        let key = match entry.key {
+            Some(key_node) => Some(literal_key(key_node, parsed)?),
+            None => None,
+        };
+        let key_node = entry.key.unwrap_or(entry.target);
+        let key_name = key
+            .as_ref()
+            .map(|(name, _)| name.clone())
+            .unwrap_or_else(|| entry.position.to_string());
+        let key_type = types.intern(&key.map_or(TypeHint::Int, |(_, hint)| hint));
+
+        let mut check_key = |expected: &TypeHint| {
+            let expected = types.intern(expected);
+            if !types.is_compatible(key_type, expected) {
+                diagnostics.push(diagnostic_for_node(
+                    parsed,
+                    key_node,
+                    Severity::Error,
+                    format!(
+                        "Destructured key '{key_name}' conflicts with key type '{}' of {source_name}",
+                        types.display(expected)
+                    ),
+                ));
+            }
+        };
+
+        match source {
+            TypeHint::ShapedArray(fields) => {
+                let field = fields
+                    .iter()
+                    .find(|(name, _)| unquote(name.trim_end_matches('?')) == key_name);
+                if field.is_none() {
+                    diagnostics.push(diagnostic_for_node(
+                        parsed,
+                        key_node,
+                        Severity::Error,
+                        format!("Destructured key '{key_name}' is missing from {source_name}"),
+                    ));
+                }
+                field.map(|(_, hint)| hint.clone())
+            }
+            TypeHint::List(element) => {
+                check_key(&TypeHint::Int);
+                Some(element.as_ref().clone())
+            }
+            TypeHint::GenericArray { key, value } => {
+                check_key(key);
+                Some(value.as_ref().clone())
+            }
+            TypeHint::Array(element) => Some(element.as_ref().clone()),
+            _ => None,
+        }
+    }
 }
 
 impl DiagnosticRule for PhpDocVarCheckRule {
@@ -403,11 +436,20 @@ impl DiagnosticRule for PhpDocVarCheckRule {
         "strict_typing/phpdoc_var_check"
     }
 
+    fn description(&self) -> &str {
+        "@var tags that conflict with the assigned value"
+    }
+
+    fn requires(&self) -> &[Artifact] {
+        &[Artifact::VarAnnotations]
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
-        _context: &ProjectContext,
+        context: &ProjectContext,
     ) -> Vec<crate::analyzer::Diagnostic> {
+        let types = context.types();
         let mut diagnostics = Vec::new();
 
         // Check class properties with @var tags
@@ -418,7 +460,7 @@ impl DiagnosticRule for PhpDocVarCheckRule {
 
             // Extract @var PHPDoc
             if let Some(phpdoc) = extract_phpdoc_for_node(node, parsed) {
-                if let Some(var_tag) = phpdoc.var_tag {
+                if let Some(var_tag) = phpdoc.var_tag() {
                     // Find the property initializer
                     for i in 0..node.named_child_count() {
                         if let Some(child) = node.named_child(i) {
@@ -432,31 +474,39 @@ impl DiagnosticRule for PhpDocVarCheckRule {
                                         // Check if it's an array and validate elements
                                         if value_node.kind() == "array_creation_expression" {
                                             if let Some(expected_type) =
-                                                Self::type_expression_to_hint(&var_tag.type_expr)
+                                                type_expression_to_hint(&var_tag.type_expr)
                                             {
                                                 Self::check_array_elements(
                                                     value_node,
                                                     &expected_type,
                                                     &var_tag.type_expr,
                                                     parsed,
+                                                    context,
                                                     &mut diagnostics,
                                                 );
                                             }
                                         } else {
                                             // Get the literal type of the value
-                                            if let Some(actual_type) = literal_type(value_node) {
+                                            if let Some(actual_type) =
+                                                value_type(value_node, parsed, context)
+                                            {
                                                 // Get the expected type from @var
                                                 if let Some(expected_type) =
-                                                    Self::type_expression_to_hint(&var_tag.type_expr)
+                                                    type_expression_to_hint(&var_tag.type_expr)
                                                 {
                                                     // Check if types are compatible
-                                                    if !is_type_compatible(&actual_type, &expected_type) {
+                                                    let actual_type = types.intern(&actual_type);
+                                                    if conflicts(
+                                                        actual_type,
+                                                        types.intern(&expected_type),
+                                                        types,
+                                                    ) {
                                                         let expected_name =
-                                                            Self::type_expression_to_string(
+                                                            TypeFormatter::expression(
                                                                 &var_tag.type_expr,
                                                             );
                                                         let actual_name =
-                                                            Self::type_hint_to_string(&actual_type);
+                                                            types.display(actual_type);
 
                                                         diagnostics.push(diagnostic_for_node(
                                                             parsed,
@@ -480,6 +530,33 @@ impl DiagnosticRule for PhpDocVarCheckRule {
             }
         });
 
+        // Check destructuring assignments against the array type being unpacked
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            if node.kind() != "assignment_expression" {
+                return;
+            }
+            let (Some(pattern), Some(value_node)) = (
+                node.child_by_field_name("left"),
+                node.child_by_field_name("right"),
+            ) else {
+                return;
+            };
+            if pattern.kind() != "list_literal" {
+                return;
+            }
+            let Some(source) = source_type(value_node, parsed, context) else {
+                return;
+            };
+
+            let var_tags = node
+                .parent()
+                .filter(|statement| statement.kind() == "expression_statement")
+                .and_then(|statement| extract_phpdoc_for_node(statement, parsed))
+                .map(|phpdoc| phpdoc.var_tags)
+                .unwrap_or_default();
+            Self::check_destructuring(pattern, &source, &var_tags, parsed, types, &mut diagnostics);
+        });
+
         // Check inline @var assignments
         walk_node(parsed.tree.root_node(), &mut |node| {
             if node.kind() != "expression_statement" {
@@ -489,9 +566,6 @@ impl DiagnosticRule for PhpDocVarCheckRule {
             let Some(phpdoc) = extract_phpdoc_for_node(node, parsed) else {
                 return;
             };
-            let Some(var_tag) = phpdoc.var_tag else {
-                return;
-            };
 
             let Some(assign) = child_by_kind(node, "assignment_expression") else {
                 return;
@@ -501,22 +575,19 @@ impl DiagnosticRule for PhpDocVarCheckRule {
                 return;
             };
 
-            if let Some(expected_type) = Self::type_expression_to_hint(&var_tag.type_expr) {
-                // Validate variable name matches if specified
-                if let Some(expected_name) = var_tag.name.as_ref() {
-                    if let Some(left_node) = assign.child_by_field_name("left") {
-                        if let Some(variable_name) = variable_name_text(left_node, parsed) {
-                            if &variable_name != expected_name {
-                                return;
-                            }
-                        } else {
-                            return;
-                        }
-                    } else {
-                        return;
-                    }
-                }
+            // A docblock can annotate several variables; use the tag for the assigned
+            // one, or an unnamed tag
+            let variable_name = assign
+                .child_by_field_name("left")
+                .and_then(|left| variable_name_text(left, parsed));
+            let Some(var_tag) = phpdoc.var_tags.iter().find(|tag| match &tag.name {
+                Some(name) => variable_name.as_ref() == Some(name),
+                None => true,
+            }) else {
+                return;
+            };
 
+            if let Some(expected_type) = type_expression_to_hint(&var_tag.type_expr) {
                 // Check if it's an array and validate elements
                 if value_node.kind() == "array_creation_expression" {
                     Self::check_array_elements(
@@ -524,13 +595,15 @@ impl DiagnosticRule for PhpDocVarCheckRule {
                         &expected_type,
                         &var_tag.type_expr,
                         parsed,
+                        context,
                         &mut diagnostics,
                     );
-                } else if let Some(actual_type) = literal_type(value_node) {
-                    // Check non-array literal types
-                    if !is_type_compatible(&actual_type, &expected_type) {
-                        let expected_name_str = Self::type_expression_to_string(&var_tag.type_expr);
-                        let actual_name_str = Self::type_hint_to_string(&actual_type);
+                } else if let Some(actual_type) = value_type(value_node, parsed, context) {
+                    // Check non-array literals, constants and enum cases
+                    let actual_type = types.intern(&actual_type);
+                    if conflicts(actual_type, types.intern(&expected_type), types) {
+                        let expected_name_str = TypeFormatter::expression(&var_tag.type_expr);
+                        let actual_name_str = types.display(actual_type);
 
                         diagnostics.push(diagnostic_for_node(
                             parsed,
@@ -549,3 +622,76 @@ impl DiagnosticRule for PhpDocVarCheckRule {
         diagnostics
     }
 }
+
+/// Whether a value of `actual` type can't be assigned to `expected`. An enum
+/// case only conflicts with types that accept no objects, since class names
+/// aren't resolved through imports or interfaces here.
+fn conflicts(actual: TypeId, expected: TypeId, types: &TypeArena) -> bool {
+    fn accepts_objects(hint: &TypeHint) -> bool {
+        match hint {
+            TypeHint::Object(_) => true,
+            TypeHint::Nullable(inner) => accepts_objects(inner),
+            TypeHint::Union(members) => members.iter().any(accepts_objects),
+            _ => false,
+        }
+    }
+
+    if matches!(*types.hint(actual), TypeHint::Object(_)) && accepts_objects(&types.hint(expected))
+    {
+        return false;
+    }
+    !types.is_compatible(actual, expected)
+}
+
+/// One entry of a `[...]` or `list(...)` destructuring pattern.
+struct DestructuredEntry<'a> {
+    key: Option<tree_sitter::Node<'a>>,
+    target: tree_sitter::Node<'a>,
+    position: usize,
+}
+
+/// The entries of a destructuring pattern, with skipped entries (`[, $b]`)
+/// counted towards the positions of those after them.
+fn destructured_entries(pattern: tree_sitter::Node) -> Vec<DestructuredEntry> {
+    // Each group holds the named nodes between two commas and whether `=>` was seen
+    let mut groups = vec![(Vec::new(), false)];
+    let mut cursor = pattern.walk();
+    for child in pattern.children(&mut cursor) {
+        let (named, keyed) = groups.last_mut().expect("groups is never empty");
+        match child.kind() {
+            "," => groups.push((Vec::new(), false)),
+            "=>" => *keyed = true,
+            _ if child.is_named() => named.push(child),
+            _ => {}
+        }
+    }
+
+    groups
+        .into_iter()
+        .enumerate()
+        .filter_map(|(position, (named, keyed))| {
+            Some(DestructuredEntry {
+                key: if keyed { named.first().copied() } else { None },
+                target: *named.last()?,
+                position,
+            })
+        })
+        .collect()
+}
+
+/// A destructuring key as written: `'id'` or `0`, with its type.
+fn literal_key(
+    key: tree_sitter::Node,
+    parsed: &parser::ParsedSource,
+) -> Option<(String, TypeHint)> {
+    let text = node_text(key, parsed)?;
+    match key.kind() {
+        "string" | "encapsed_string" => Some((unquote(&text).to_string(), TypeHint::String)),
+        "integer" => Some((text, TypeHint::Int)),
+        _ => None,
+    }
+}
+
+fn unquote(text: &str) -> &str {
+    text.trim_matches('"').trim_matches('\'')
+}
@@ -1,14 +1,27 @@
 use super::DiagnosticRule;
 use super::helpers::{diagnostic_for_node, newline_for_source, walk_node};
+use crate::analyzer::config::StrictTypesConfig;
 use crate::analyzer::fix;
 use crate::analyzer::project::ProjectContext;
-use crate::analyzer::{Severity, parser};
+use crate::analyzer::{Severity, is_template_file, parser};
 
-pub struct StrictTypesRule;
+pub struct StrictTypesRule {
+    config: StrictTypesConfig,
+}
 
 impl StrictTypesRule {
-    pub fn new() -> Self {
-        Self
+    pub fn new(config: StrictTypesConfig) -> Self {
+        Self { config }
+    }
+
+    fn should_warn(&self, parsed: &parser::ParsedSource) -> bool {
+        // Templates are rendered output, not typed code; `declare` would have to
+        // precede any HTML, so the check does not apply to them.
+        if is_template_file(&parsed.path) {
+            return false;
+        }
+
+        self.config.applies_to(&parsed.path) || is_strict_missing_fixture(parsed)
     }
 }
 
@@ -17,12 +30,16 @@ impl DiagnosticRule for StrictTypesRule {
         "strict_typing/strict_types"
     }
 
+    fn description(&self) -> &str {
+        "Files missing declare(strict_types=1)"
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
         _context: &ProjectContext,
     ) -> Vec<crate::analyzer::Diagnostic> {
-        if !should_warn(parsed) || !has_type_hint(parsed) || has_strict_declare(parsed) {
+        if !self.should_warn(parsed) || !has_type_hint(parsed) || has_strict_declare(parsed) {
             return Vec::new();
         }
 
@@ -40,7 +57,7 @@ impl DiagnosticRule for StrictTypesRule {
     }
 
     fn fix(&self, parsed: &parser::ParsedSource, _context: &ProjectContext) -> Vec<fix::TextEdit> {
-        if !should_warn(parsed) || !has_type_hint(parsed) || has_strict_declare(parsed) {
+        if !self.should_warn(parsed) || !has_type_hint(parsed) || has_strict_declare(parsed) {
             return Vec::new();
         }
 
@@ -55,7 +72,8 @@ impl DiagnosticRule for StrictTypesRule {
     }
 }
 
-fn should_warn(parsed: &parser::ParsedSource) -> bool {
+/// The test fixtures opt in by name, so they run without any configuration.
+fn is_strict_missing_fixture(parsed: &parser::ParsedSource) -> bool {
     parsed
         .path
         .file_name()
@@ -80,14 +98,12 @@ fn has_type_hint(parsed: &parser::ParsedSource) -> bool {
 fn has_strict_declare(parsed: &parser::ParsedSource) -> bool {
     let mut found = false;
     walk_node(parsed.tree.root_node(), &mut |node| {
-        if node.kind() == "declare_directive" {
-            if let Some(name_node) = node.child_by_field_name("name") {
-                if let Some(name) = name_node.utf8_text(parsed.source.as_bytes()).ok() {
-                    if name.trim() == "strict_types" {
-                        found = true;
-                    }
-                }
-            }
+        // The directive name is an anonymous token, not a `name` field.
+        if node.kind() == "declare_directive"
+            && let Some(name_node) = node.child(0)
+            && name_node.kind() == "strict_types"
+        {
+            found = true;
         }
     });
     found
@@ -139,7 +155,7 @@ function example(): void
 
         // Use parse_php_with_path because the rule checks for "strict_missing" in the filename
         let parsed = parse_php_with_path(source, "strict_missing.php");
-        let rule = StrictTypesRule::new();
+        let rule = StrictTypesRule::new(StrictTypesConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         // Expected: warning: file missing `declare(strict_types=1)`
@@ -161,7 +177,7 @@ function example(): void
 "#;
 
         let parsed = parse_php(source);
-        let rule = StrictTypesRule::new();
+        let rule = StrictTypesRule::new(StrictTypesConfig::default());
         let diagnostics = run_rule(&rule, &parsed);
 
         assert_no_diagnostics(&diagnostics);
@@ -192,8 +208,41 @@ function example(): void
 
 "#;
 
-        let rule = StrictTypesRule::new();
+        let rule = StrictTypesRule::new(StrictTypesConfig::default());
         // Use assert_fix_with_path because the rule checks for "strict_missing" in the filename
         assert_fix_with_path(&rule, input, expected, "strict_missing.php");
     }
+
+    #[test]
+    fn test_strict_types_skips_templates() {
+        let source = r#"<div><?php function label(int $id): string { return "id-$id"; } ?></div>
+"#;
+
+        let parsed = parse_php_with_path(source, "strict_missing.phtml");
+        let rule = StrictTypesRule::new(StrictTypesConfig::default());
+        let diagnostics = run_rule(&rule, &parsed);
+
+        assert_no_diagnostics(&diagnostics);
+    }
+
+    #[test]
+    fn test_strict_types_configured_paths() {
+        let source = "<?php\nfunction label(int $id): string { return (string) $id; }\n";
+        let mut config: StrictTypesConfig = serde_yaml::from_str("paths: [\"src/**\"]").unwrap();
+        config.compile().unwrap();
+        let rule = StrictTypesRule::new(config);
+
+        let parsed = parse_php_with_path(source, "/app/src/Label.php");
+        assert_diagnostics_exact(
+            &run_rule(&rule, &parsed),
+            &["warning: file missing `declare(strict_types=1)`"],
+        );
+
+        let parsed = parse_php_with_path(source, "/app/scripts/label.php");
+        assert_no_diagnostics(&run_rule(&rule, &parsed));
+
+        let declared = "<?php\n/* header */\ndeclare(strict_types=1);\n\nfunction label(int $id): string { return (string) $id; }\n";
+        let parsed = parse_php_with_path(declared, "/app/src/Label.php");
+        assert_no_diagnostics(&run_rule(&rule, &parsed));
+    }
 }
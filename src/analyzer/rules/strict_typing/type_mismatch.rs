@@ -1,8 +1,9 @@
 use super::DiagnosticRule;
 use super::helpers::{
-    LiteralKind, TypeHint, argument_literal_kind, child_by_kind, collect_function_signatures,
+    LiteralKind, TypeHint, argument_literal_kind, child_by_kind, class_constant_type,
     diagnostic_for_node, node_text, walk_node,
 };
+use crate::analyzer::artifacts::Artifact;
 use crate::analyzer::project::ProjectContext;
 use crate::analyzer::{Severity, parser};
 
@@ -19,12 +20,21 @@ impl DiagnosticRule for TypeMismatchRule {
         "strict_typing/type_mismatch"
     }
 
+    fn description(&self) -> &str {
+        "Arguments whose literal type conflicts with the parameter type"
+    }
+
+    fn requires(&self) -> &[Artifact] {
+        &[Artifact::FunctionSignatures]
+    }
+
     fn run(
         &self,
         parsed: &parser::ParsedSource,
-        _context: &ProjectContext,
+        context: &ProjectContext,
     ) -> Vec<crate::analyzer::Diagnostic> {
-        let signatures = collect_function_signatures(parsed);
+        let signatures = parsed.function_signatures();
+        let types = context.types();
         let mut diagnostics = Vec::new();
 
         walk_node(parsed.tree.root_node(), &mut |node| {
@@ -82,6 +92,30 @@ impl DiagnosticRule for TypeMismatchRule {
                             ),
                         ));
                     }
+                } else if let Some(value_node) = argument_node.named_child(0)
+                    && let Some(actual) = class_constant_type(value_node, parsed, context)
+                {
+                    let expected = &signature.params[arg_index];
+                    if constant_mismatch(&actual, expected) {
+                        let start = value_node.start_position();
+                        let row = start.row + 1;
+                        let column = start.column + 1;
+                        let constant = node_text(value_node, parsed).unwrap_or_default();
+                        let got = match actual {
+                            TypeHint::Object(_) => "enum case".to_string(),
+                            _ => format!("{} constant", types.display(types.intern(&actual))),
+                        };
+                        diagnostics.push(diagnostic_for_node(
+                            parsed,
+                            value_node,
+                            Severity::Error,
+                            format!(
+                                "type mismatch: argument {} of {name} expects {} but got {got} {constant} at {row}:{column}",
+                                arg_index + 1,
+                                types.display(types.intern(expected))
+                            ),
+                        ));
+                    }
                 }
 
                 arg_index += 1;
@@ -92,10 +126,24 @@ impl DiagnosticRule for TypeMismatchRule {
     }
 }
 
+/// Mirrors the literal check (a string where an int is expected), and also
+/// rejects enum cases passed for scalar parameters.
+fn constant_mismatch(actual: &TypeHint, expected: &TypeHint) -> bool {
+    let scalar = matches!(
+        expected,
+        TypeHint::Int | TypeHint::String | TypeHint::Float | TypeHint::Bool
+    );
+    match actual {
+        TypeHint::String => *expected == TypeHint::Int,
+        TypeHint::Object(_) => scalar,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule};
+    use crate::analyzer::rules::test_utils::{assert_diagnostics_exact, assert_no_diagnostics, parse_php, run_rule, run_rule_with_context};
 
     #[test]
     fn test_type_mismatch_file() {
@@ -142,4 +190,30 @@ takesString('hello');
 
         assert_no_diagnostics(&diagnostics);
     }
+
+    #[test]
+    fn test_type_mismatch_constants() {
+        let source = r#"<?php
+enum Suit: string { case Hearts = 'H'; }
+class Config { const NAME = 'app'; const LIMIT = 5; }
+
+function takesInt(int $value): void {}
+
+takesInt(Config::NAME);
+takesInt(Suit::Hearts);
+takesInt(Config::LIMIT);
+takesInt(Undefined::THING);
+"#;
+
+        let rule = TypeMismatchRule::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_diagnostics_exact(
+            &diagnostics,
+            &[
+                "error: type mismatch: argument 1 of takesInt expects int but got string constant Config::NAME at 7:10",
+                "error: type mismatch: argument 1 of takesInt expects int but got enum case Suit::Hearts at 8:10",
+            ],
+        );
+    }
 }
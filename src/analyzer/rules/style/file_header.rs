@@ -0,0 +1,335 @@
+use super::DiagnosticRule;
+use super::helpers::{diagnostic_for_node, newline_for_source, node_text};
+use crate::analyzer::config::FileHeaderConfig;
+use crate::analyzer::fix;
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::{Severity, is_template_file, parser};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tree_sitter::Node;
+
+const YEAR_PLACEHOLDER: &str = "{year}";
+
+/// Requires every file to open with the configured header comment.
+///
+/// The header belongs directly after `<?php`, ahead of `declare(strict_types=1)`
+/// (PSR-12 order). A comment mentioning a copyright or license among the leading
+/// comments and declares is treated as an outdated header and replaced in place.
+pub struct FileHeaderRule {
+    config: FileHeaderConfig,
+}
+
+impl FileHeaderRule {
+    pub fn new(config: FileHeaderConfig) -> Self {
+        Self { config }
+    }
+
+    fn check<'a>(&self, parsed: &'a parser::ParsedSource) -> Option<HeaderState<'a>> {
+        let template = self.config.template.as_deref()?;
+        if is_template_file(&parsed.path) {
+            return None;
+        }
+
+        let root = parsed.tree.root_node();
+        let tag = root.child(0).filter(|node| node.kind() == "php_tag")?;
+
+        let mut outdated = None;
+        for idx in 1..root.child_count() {
+            let Some(node) = root.child(idx) else {
+                continue;
+            };
+            match node.kind() {
+                "comment" => {
+                    let text = node_text(node, parsed).unwrap_or_default();
+                    if matches_template(template, &text) {
+                        return None;
+                    }
+                    if outdated.is_none() && looks_like_header(&text) {
+                        outdated = Some(node);
+                    }
+                }
+                "declare_statement" => {}
+                _ => break,
+            }
+        }
+
+        Some(match outdated {
+            Some(comment) => HeaderState::Outdated(comment),
+            None => HeaderState::Missing(tag),
+        })
+    }
+}
+
+enum HeaderState<'a> {
+    /// No header; holds the `<?php` tag it should follow.
+    Missing(Node<'a>),
+    /// A different copyright/license comment that should be replaced.
+    Outdated(Node<'a>),
+}
+
+impl DiagnosticRule for FileHeaderRule {
+    fn name(&self) -> &str {
+        "style/file_header"
+    }
+
+    fn description(&self) -> &str {
+        "Files missing the configured header comment"
+    }
+
+    fn run(
+        &self,
+        parsed: &parser::ParsedSource,
+        _context: &ProjectContext,
+    ) -> Vec<crate::analyzer::Diagnostic> {
+        let diagnostic = match self.check(parsed) {
+            Some(HeaderState::Missing(tag)) => diagnostic_for_node(
+                parsed,
+                tag,
+                Severity::Warning,
+                "file is missing the configured header",
+            ),
+            Some(HeaderState::Outdated(comment)) => diagnostic_for_node(
+                parsed,
+                comment,
+                Severity::Warning,
+                "file header does not match the configured template",
+            ),
+            None => return Vec::new(),
+        };
+
+        vec![diagnostic]
+    }
+
+    fn fix(&self, parsed: &parser::ParsedSource, _context: &ProjectContext) -> Vec<fix::TextEdit> {
+        let Some(state) = self.check(parsed) else {
+            return Vec::new();
+        };
+        let Some(template) = self.config.template.as_deref() else {
+            return Vec::new();
+        };
+
+        let source = parsed.source.as_str();
+        let newline = newline_for_source(source);
+
+        match state {
+            HeaderState::Outdated(comment) => {
+                let old = node_text(comment, parsed).unwrap_or_default();
+                let year = first_year(&old).unwrap_or_else(current_year);
+                let header = render_header(template, &year, newline);
+                vec![fix::TextEdit::new(
+                    comment.start_byte(),
+                    comment.end_byte(),
+                    header,
+                )]
+            }
+            HeaderState::Missing(tag) => {
+                let header = render_header(template, &current_year(), newline);
+                // Replace the whitespace between the tag and the first statement so
+                // `<?php declare(...)` on one line ends up below the header as well.
+                let end = tag
+                    .next_sibling()
+                    .map_or(source.len(), |next| next.start_byte());
+                let trailing = if end == source.len() {
+                    newline.to_string()
+                } else {
+                    format!("{newline}{newline}")
+                };
+                vec![fix::TextEdit::new(
+                    tag.end_byte(),
+                    end,
+                    format!("{newline}{newline}{header}{trailing}"),
+                )]
+            }
+        }
+    }
+}
+
+fn looks_like_header(comment: &str) -> bool {
+    let lowered = comment.to_lowercase();
+    lowered.contains("copyright") || lowered.contains("license")
+}
+
+/// Compares a comment to the template, ignoring line endings and trailing whitespace.
+fn matches_template(template: &str, comment: &str) -> bool {
+    let template = normalize(template);
+    let comment = normalize(comment);
+
+    let mut parts = template.split(YEAR_PLACEHOLDER);
+    let Some(first) = parts.next() else {
+        return false;
+    };
+    let Some(mut rest) = comment.strip_prefix(first) else {
+        return false;
+    };
+
+    for part in parts {
+        let Some(after_year) = strip_year(rest) else {
+            return false;
+        };
+        let Some(after_part) = after_year.strip_prefix(part) else {
+            return false;
+        };
+        rest = after_part;
+    }
+
+    rest.is_empty()
+}
+
+fn normalize(text: &str) -> String {
+    text.trim()
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Strips a leading `2024` or `2019-2024`.
+fn strip_year(text: &str) -> Option<&str> {
+    let is_year =
+        |value: &str| value.len() >= 4 && value.as_bytes()[..4].iter().all(u8::is_ascii_digit);
+    if !is_year(text) {
+        return None;
+    }
+
+    let rest = &text[4..];
+    match rest.strip_prefix('-') {
+        Some(range_end) if is_year(range_end) => Some(&range_end[4..]),
+        _ => Some(rest),
+    }
+}
+
+/// The year (or year range) written in an existing header, kept when it is rewritten.
+fn first_year(text: &str) -> Option<String> {
+    text.char_indices().find_map(|(idx, _)| {
+        let candidate = &text[idx..];
+        let boundary = text[..idx]
+            .chars()
+            .next_back()
+            .is_none_or(|c| !c.is_ascii_digit());
+        if !boundary {
+            return None;
+        }
+        let rest = strip_year(candidate)?;
+        if rest.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        Some(candidate[..candidate.len() - rest.len()].to_string())
+    })
+}
+
+fn render_header(template: &str, year: &str, newline: &str) -> String {
+    normalize(template)
+        .replace(YEAR_PLACEHOLDER, year)
+        .replace('\n', newline)
+}
+
+fn current_year() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400) as i64;
+
+    // Civil-from-days (Howard Hinnant): shift the epoch to 0000-03-01 so leap
+    // days fall at the end of each 400-year era.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let year = year_of_era + era * 400 + i64::from(month_index >= 10);
+    year.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::{
+        assert_diagnostics_exact, assert_fix, assert_no_diagnostics, parse_php, run_rule,
+    };
+
+    const TEMPLATE: &str =
+        "/*\n * Copyright (c) {year} Acme Ltd.\n * Licensed under the MIT license.\n */\n";
+
+    fn rule(template: &str) -> FileHeaderRule {
+        FileHeaderRule::new(FileHeaderConfig {
+            template: Some(template.to_string()),
+        })
+    }
+
+    #[test]
+    fn test_file_header_present() {
+        let source = r#"<?php
+
+/*
+ * Copyright (c) 2019-2024 Acme Ltd.
+ * Licensed under the MIT license.
+ */
+
+declare(strict_types=1);
+"#;
+
+        let parsed = parse_php(source);
+        assert_no_diagnostics(&run_rule(&rule(TEMPLATE), &parsed));
+    }
+
+    #[test]
+    fn test_file_header_missing_inserted_before_declare() {
+        let source = "<?php declare(strict_types=1);\n\nnamespace App;\n";
+        let header = "/**\n * Part of Acme.\n */";
+
+        let parsed = parse_php(source);
+        let rule = rule(header);
+        assert_diagnostics_exact(
+            &run_rule(&rule, &parsed),
+            &["warning: file is missing the configured header"],
+        );
+        assert_fix(
+            &rule,
+            &parsed,
+            source,
+            "<?php\n\n/**\n * Part of Acme.\n */\n\ndeclare(strict_types=1);\n\nnamespace App;\n",
+        );
+    }
+
+    #[test]
+    fn test_file_header_outdated_keeps_year() {
+        let source = r#"<?php
+
+declare(strict_types=1);
+
+// Copyright 2021 Acme, all rights reserved.
+
+namespace App;
+"#;
+
+        let parsed = parse_php(source);
+        let rule = rule(TEMPLATE);
+        assert_diagnostics_exact(
+            &run_rule(&rule, &parsed),
+            &["warning: file header does not match the configured template"],
+        );
+        assert_fix(
+            &rule,
+            &parsed,
+            source,
+            r#"<?php
+
+declare(strict_types=1);
+
+/*
+ * Copyright (c) 2021 Acme Ltd.
+ * Licensed under the MIT license.
+ */
+
+namespace App;
+"#,
+        );
+    }
+
+    #[test]
+    fn test_file_header_disabled_without_template() {
+        let parsed = parse_php("<?php\necho 1;\n");
+        let rule = FileHeaderRule::new(FileHeaderConfig::default());
+        assert_no_diagnostics(&run_rule(&rule, &parsed));
+    }
+}
@@ -0,0 +1,5 @@
+pub use crate::analyzer::rules::{DiagnosticRule, helpers};
+
+pub mod file_header;
+
+pub use file_header::FileHeaderRule;
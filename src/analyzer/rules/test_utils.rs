@@ -3,8 +3,7 @@
 //! This module provides utilities to make it easy to write tests directly
 //! in rule files, allowing for better test organization and isolation.
 
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::Path;
 
 use crate::analyzer::fix;
 use crate::analyzer::parser;
@@ -54,11 +53,7 @@ pub fn parse_php_with_path(source: &str, path: &str) -> parser::ParsedSource {
         .parse(source, None)
         .expect("failed to parse PHP source");
 
-    parser::ParsedSource {
-        path: PathBuf::from(path),
-        source: Arc::new(source.to_string()),
-        tree,
-    }
+    parser::ParsedSource::new(path, source.to_string(), tree)
 }
 
 /// Run a rule on parsed PHP code and return the diagnostics.
@@ -86,6 +81,7 @@ where
     R: crate::analyzer::rules::DiagnosticRule,
 {
     let context = ProjectContext::new();
+    prepare_artifacts(rule, parsed);
     rule.run(parsed, &context)
 }
 
@@ -110,13 +106,41 @@ pub fn run_rule_with_context<R>(rule: &R, source: &str) -> Vec<Diagnostic>
 where
     R: crate::analyzer::rules::DiagnosticRule,
 {
-    // Parse twice: once for context (needs ownership), once for rule (needs reference)
-    let parsed_for_context = parse_php(source);
-    let parsed_for_rule = parse_php(source);
-    
+    let context = context_with_source(source, "test.php");
+    let parsed = parsed_from_context(&context, "test.php");
+    prepare_artifacts(rule, parsed);
+    rule.run(parsed, &context)
+}
+
+/// Build the artifacts `rule` lists in `requires`, as the analyzer does before
+/// running it.
+pub fn prepare_artifacts<R>(rule: &R, parsed: &parser::ParsedSource)
+where
+    R: crate::analyzer::rules::DiagnosticRule + ?Sized,
+{
+    for artifact in rule.requires() {
+        parsed.prepare(*artifact);
+    }
+}
+
+/// Build a `ProjectContext` containing `source` parsed as `path`.
+///
+/// The parsed file is owned by the context; borrow it back with
+/// [`parsed_from_context`] instead of parsing the source a second time.
+pub fn context_with_source(source: &str, path: &str) -> ProjectContext {
     let mut context = ProjectContext::new();
-    context.insert(parsed_for_context);
-    rule.run(&parsed_for_rule, &context)
+    context.insert(parse_php_with_path(source, path));
+    context
+}
+
+/// Borrow the file at `path` from a context built by [`context_with_source`].
+pub fn parsed_from_context<'a>(
+    context: &'a ProjectContext,
+    path: &str,
+) -> &'a parser::ParsedSource {
+    context
+        .get(Path::new(path))
+        .expect("source should have been inserted into the context")
 }
 
 /// Assert that diagnostics match expected messages.
@@ -382,6 +406,7 @@ where
     R: crate::analyzer::rules::DiagnosticRule,
 {
     let context = ProjectContext::new();
+    prepare_artifacts(rule, parsed);
     rule.fix(parsed, &context)
 }
 
@@ -405,13 +430,10 @@ pub fn run_fix_with_context<R>(rule: &R, source: &str) -> Vec<fix::TextEdit>
 where
     R: crate::analyzer::rules::DiagnosticRule,
 {
-    // Parse twice: once for context (needs ownership), once for rule (needs reference)
-    let parsed_for_context = parse_php(source);
-    let parsed_for_rule = parse_php(source);
-    
-    let mut context = ProjectContext::new();
-    context.insert(parsed_for_context);
-    rule.fix(&parsed_for_rule, &context)
+    let context = context_with_source(source, "test.php");
+    let parsed = parsed_from_context(&context, "test.php");
+    prepare_artifacts(rule, parsed);
+    rule.fix(parsed, &context)
 }
 
 /// Assert that a rule's fix produces the expected output when applied to input source.
@@ -450,49 +472,7 @@ pub fn assert_fix<R>(
     let edits = run_fix(rule, parsed);
     let actual = fix::apply_text_edits(input, &edits);
 
-    if actual != expected {
-        let mut error_msg = String::new();
-        error_msg.push_str(&format!(
-            "\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n"
-        ));
-        error_msg.push_str("Fix output mismatch\n");
-        error_msg.push_str(&format!(
-            "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n"
-        ));
-        
-        error_msg.push_str("\nExpected output:\n");
-        error_msg.push_str(&format!("```php\n{}\n```\n", expected));
-        
-        error_msg.push_str("\nActual output:\n");
-        error_msg.push_str(&format!("```php\n{}\n```\n", actual));
-        
-        // Show diff-like output
-        error_msg.push_str("\nDifferences:\n");
-        let expected_lines: Vec<&str> = expected.lines().collect();
-        let actual_lines: Vec<&str> = actual.lines().collect();
-        
-        let max_lines = expected_lines.len().max(actual_lines.len());
-        for i in 0..max_lines {
-            let expected_line = expected_lines.get(i).copied().unwrap_or("");
-            let actual_line = actual_lines.get(i).copied().unwrap_or("");
-            
-            if expected_line != actual_line {
-                error_msg.push_str(&format!("  Line {}:\n", i + 1));
-                if !expected_line.is_empty() {
-                    error_msg.push_str(&format!("    - {}\n", expected_line));
-                }
-                if !actual_line.is_empty() {
-                    error_msg.push_str(&format!("    + {}\n", actual_line));
-                }
-            }
-        }
-        
-        error_msg.push_str(&format!(
-            "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n"
-        ));
-        
-        panic!("{}", error_msg);
-    }
+    assert_fixed_output(&actual, expected);
 }
 
 /// Assert that a rule's fix produces the expected output when applied to input source,
@@ -565,6 +545,11 @@ pub fn assert_fix_with_context<R>(
     let edits = run_fix_with_context(rule, input);
     let actual = fix::apply_text_edits(input, &edits);
 
+    assert_fixed_output(&actual, expected);
+}
+
+/// Compare fixed source against the expected output, with a line diff on mismatch.
+fn assert_fixed_output(actual: &str, expected: &str) {
     if actual != expected {
         let mut error_msg = String::new();
         error_msg.push_str(&format!(
@@ -574,23 +559,23 @@ pub fn assert_fix_with_context<R>(
         error_msg.push_str(&format!(
             "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n"
         ));
-        
+
         error_msg.push_str("\nExpected output:\n");
         error_msg.push_str(&format!("```php\n{}\n```\n", expected));
-        
+
         error_msg.push_str("\nActual output:\n");
         error_msg.push_str(&format!("```php\n{}\n```\n", actual));
-        
+
         // Show diff-like output
         error_msg.push_str("\nDifferences:\n");
         let expected_lines: Vec<&str> = expected.lines().collect();
         let actual_lines: Vec<&str> = actual.lines().collect();
-        
+
         let max_lines = expected_lines.len().max(actual_lines.len());
         for i in 0..max_lines {
             let expected_line = expected_lines.get(i).copied().unwrap_or("");
             let actual_line = actual_lines.get(i).copied().unwrap_or("");
-            
+
             if expected_line != actual_line {
                 error_msg.push_str(&format!("  Line {}:\n", i + 1));
                 if !expected_line.is_empty() {
@@ -601,11 +586,141 @@ pub fn assert_fix_with_context<R>(
                 }
             }
         }
-        
+
         error_msg.push_str(&format!(
             "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n"
         ));
-        
+
         panic!("{}", error_msg);
     }
 }
+
+/// Declarative test case for a rule: source in, diagnostics (and optionally the
+/// fixed source) out. The source is analysed with a context that contains it,
+/// as in a real run.
+///
+/// When no fixed output is given the rule must not produce any edits, so rules
+/// with a fixer always have their fix covered.
+///
+/// Most tests use it through [`rule_test!`]; the builder is there for cases
+/// that need to inspect the diagnostics further.
+///
+/// # Example
+/// ```rust
+/// use crate::analyzer::rules::test_utils::RuleTest;
+/// use crate::analyzer::rules::api::InvalidThisRule;
+///
+/// RuleTest::new(InvalidThisRule::new(), "<?php\nfunction f() { return $this; }\n")
+///     .expect(&["error: $this is not allowed outside of class scope"])
+///     .run();
+/// ```
+pub struct RuleTest<'a, R> {
+    rule: R,
+    source: &'a str,
+    path: &'a str,
+    expected: &'a [&'a str],
+    fixed: Option<&'a str>,
+}
+
+impl<'a, R> RuleTest<'a, R>
+where
+    R: crate::analyzer::rules::DiagnosticRule,
+{
+    pub fn new(rule: R, source: &'a str) -> Self {
+        Self {
+            rule,
+            source,
+            path: "test.php",
+            expected: &[],
+            fixed: None,
+        }
+    }
+
+    /// Analyse the source as if it lived at `path` (for path-sensitive rules).
+    pub fn path(mut self, path: &'a str) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Expected diagnostics in `.expect` format; see [`assert_diagnostics_exact`].
+    pub fn expect(mut self, expected: &'a [&'a str]) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    /// Expected source after applying the rule's fixes.
+    pub fn fixed(mut self, fixed: &'a str) -> Self {
+        self.fixed = Some(fixed);
+        self
+    }
+
+    /// Run the checks and return the diagnostics for any further assertions.
+    pub fn run(self) -> Vec<Diagnostic> {
+        let context = context_with_source(self.source, self.path);
+        let parsed = parsed_from_context(&context, self.path);
+        prepare_artifacts(&self.rule, parsed);
+
+        let diagnostics = self.rule.run(parsed, &context);
+        if self.expected.is_empty() {
+            assert_no_diagnostics(&diagnostics);
+        } else {
+            assert_diagnostics_exact(&diagnostics, self.expected);
+        }
+
+        let edits = self.rule.fix(parsed, &context);
+        match self.fixed {
+            Some(expected) => {
+                let actual = fix::apply_text_edits(self.source, &edits);
+                assert_fixed_output(&actual, expected);
+            }
+            None => assert!(
+                edits.is_empty(),
+                "{} produced {} fix edit(s); add the expected `fixed` output to the test",
+                self.rule.name(),
+                edits.len()
+            ),
+        }
+
+        diagnostics
+    }
+}
+
+/// Generate a `#[test]` from a [`RuleTest`] description.
+///
+/// `path` and `fixed` are optional; `expect` may be empty to assert the source
+/// is clean.
+///
+/// # Example
+/// ```rust
+/// use crate::analyzer::rules::test_utils::rule_test;
+///
+/// rule_test! {
+///     test_implicit_nullable_fix,
+///     rule: ImplicitNullableRule::new(PhpVersion::new(8, 4)),
+///     source: "<?php\nfunction f(int $x = null) {}\n",
+///     expect: ["warning: implicitly nullable parameter"],
+///     fixed: "<?php\nfunction f(?int $x = null) {}\n",
+/// }
+/// ```
+macro_rules! rule_test {
+    (
+        $name:ident,
+        rule: $rule:expr,
+        source: $source:expr,
+        $(path: $path:expr,)?
+        expect: [$($expected:expr),* $(,)?]
+        $(, fixed: $fixed:expr)?
+        $(,)?
+    ) => {
+        #[test]
+        fn $name() {
+            let test = $crate::analyzer::rules::test_utils::RuleTest::new($rule, $source)
+                .expect(&[$($expected),*]);
+            $(let test = test.path($path);)?
+            $(let test = test.fixed($fixed);)?
+            test.run();
+        }
+    };
+}
+
+pub(crate) use rule_test;
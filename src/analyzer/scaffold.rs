@@ -0,0 +1,365 @@
+//! `php-checker dev new-rule`: creates a rule module with its tests and wires
+//! it into the category module, the rule re-exports and `Analyzer::new`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+const RULES_DIR: &str = "src/analyzer/rules";
+const ANALYZER_FILE: &str = "src/analyzer.rs";
+const FIXTURES_DIR: &str = "tests/invalid";
+/// The end of the rule list in `Analyzer::new`; new rules are registered last.
+const REGISTRATION_END: &str = "        ];\n\n        rules.retain(";
+const MAX_WIDTH: usize = 100;
+
+/// A rule to create, from a `category/name` such as `sanity/empty_catch`.
+pub struct RuleScaffold {
+    category: String,
+    module: String,
+    type_name: String,
+}
+
+impl RuleScaffold {
+    pub fn parse(rule_name: &str) -> Result<Self> {
+        let Some((category, module)) = rule_name.split_once('/') else {
+            bail!("rule name \"{rule_name}\" should look like category/rule_name");
+        };
+        for part in [category, module] {
+            let valid = part.starts_with(|c: char| c.is_ascii_lowercase())
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+            if !valid {
+                bail!("\"{part}\" in \"{rule_name}\" should be snake_case");
+            }
+        }
+
+        let type_name = module
+            .split('_')
+            .filter(|word| !word.is_empty())
+            .map(|word| word[..1].to_ascii_uppercase() + &word[1..])
+            .collect::<String>()
+            + "Rule";
+
+        Ok(Self {
+            category: category.to_owned(),
+            module: module.to_owned(),
+            type_name,
+        })
+    }
+
+    fn rule_name(&self) -> String {
+        format!("{}/{}", self.category, self.module)
+    }
+
+    /// Writes the new files and registers the rule in the repository at
+    /// `root`, returning every file created or changed.
+    pub fn create(&self, root: &Path) -> Result<Vec<PathBuf>> {
+        let rules_dir = root.join(RULES_DIR);
+        let category_dir = rules_dir.join(&self.category);
+        if !category_dir.is_dir() {
+            bail!(
+                "unknown rule category \"{}\"; expected a directory at {}",
+                self.category,
+                category_dir.display()
+            );
+        }
+        let module_file = category_dir.join(format!("{}.rs", self.module));
+        if module_file.exists() {
+            bail!("{} already exists", module_file.display());
+        }
+        let fixture = root
+            .join(FIXTURES_DIR)
+            .join(&self.category)
+            .join(format!("{}.php", self.module));
+
+        // Compute every edit first so a failure leaves the tree untouched
+        let category_mod = category_dir.join("mod.rs");
+        let rules_mod = rules_dir.join("mod.rs");
+        let analyzer = root.join(ANALYZER_FILE);
+        let edits = [
+            (&category_mod, self.register_module(&read(&category_mod)?)?),
+            (&rules_mod, self.reexport(&read(&rules_mod)?)?),
+            (&analyzer, self.register_rule(&read(&analyzer)?)?),
+        ];
+
+        write(&module_file, &self.module_source())?;
+        for (path, contents) in &edits {
+            write(path, contents)?;
+        }
+        if !fixture.exists() {
+            fs::create_dir_all(fixture.parent().expect("fixture path has a parent"))
+                .with_context(|| format!("failed to create {}", fixture.display()))?;
+            write(&fixture, &self.fixture_source())?;
+        }
+
+        let mut changed = vec![module_file];
+        changed.extend(edits.into_iter().map(|(path, _)| path.clone()));
+        changed.push(fixture);
+        Ok(changed)
+    }
+
+    /// Adds `pub mod` and `pub use` lines to the category's `mod.rs`, keeping
+    /// each block sorted.
+    fn register_module(&self, source: &str) -> Result<String> {
+        let module_line = format!("pub mod {};", self.module);
+        let use_line = format!("pub use {}::{};", self.module, self.type_name);
+        let mut lines: Vec<String> = source.lines().map(str::to_owned).collect();
+        for (new_line, prefix) in [(module_line, "pub mod "), (use_line, "pub use ")] {
+            let block: Vec<usize> = lines
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.starts_with(prefix) && !line.contains("crate::"))
+                .map(|(idx, _)| idx)
+                .collect();
+            let Some(&last) = block.last() else {
+                bail!("no `{prefix}` lines found in the category mod.rs");
+            };
+            let position = block
+                .iter()
+                .copied()
+                .find(|&idx| lines[idx] > new_line)
+                .unwrap_or(last + 1);
+            lines.insert(position, new_line);
+        }
+
+        Ok(lines.join("\n") + "\n")
+    }
+
+    /// Adds the rule type to the category's `pub use` in `rules/mod.rs`.
+    fn reexport(&self, source: &str) -> Result<String> {
+        let prefix = format!("pub use {}::", self.category);
+        let Some(start) = source.find(&prefix) else {
+            bail!("rules/mod.rs has no `{prefix}` re-export");
+        };
+        let end = start
+            + source[start..]
+                .find(';')
+                .context("unterminated `pub use`")?
+            + 1;
+        let list = source[start + prefix.len()..end - 1].trim();
+        let mut names: Vec<&str> = list
+            .trim_start_matches('{')
+            .trim_end_matches('}')
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect();
+        names.push(&self.type_name);
+        names.sort_unstable();
+
+        let single = format!("{prefix}{{{}}};", names.join(", "));
+        let statement = if names.len() == 1 {
+            format!("{prefix}{};", names[0])
+        } else if single.len() <= MAX_WIDTH {
+            single
+        } else {
+            let mut wrapped = format!("{prefix}{{\n");
+            let mut line = String::from("   ");
+            for name in names {
+                if line.len() + name.len() + 2 > MAX_WIDTH {
+                    wrapped.push_str(&line);
+                    wrapped.push('\n');
+                    line = String::from("   ");
+                }
+                line.push_str(&format!(" {name},"));
+            }
+            wrapped.push_str(&line);
+            wrapped.push_str("\n};");
+            wrapped
+        };
+
+        Ok(format!("{}{statement}{}", &source[..start], &source[end..]))
+    }
+
+    /// Appends the rule to the list in `Analyzer::new`.
+    fn register_rule(&self, source: &str) -> Result<String> {
+        let Some(position) = source.find(REGISTRATION_END) else {
+            bail!("could not find the rule list in Analyzer::new");
+        };
+        let line = format!("            Arc::new(rules::{}::new()),\n", self.type_name);
+        Ok(format!(
+            "{}{line}{}",
+            &source[..position],
+            &source[position..]
+        ))
+    }
+
+    fn module_source(&self) -> String {
+        MODULE_TEMPLATE
+            .replace("{type}", &self.type_name)
+            .replace("{rule}", &self.rule_name())
+            .replace("{module}", &self.module)
+    }
+
+    fn fixture_source(&self) -> String {
+        format!(
+            "<?php\n// Code {} should report. Once it does, run\n// `php-checker golden {} --bless` to record the expectations.\n",
+            self.rule_name(),
+            Path::new(FIXTURES_DIR).join(&self.category).display()
+        )
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents).with_context(|| format!("failed to write {}", path.display()))
+}
+
+const MODULE_TEMPLATE: &str = r##"use super::DiagnosticRule;
+use super::helpers::{diagnostic_for_node, walk_node};
+use crate::analyzer::project::ProjectContext;
+use crate::analyzer::{Severity, parser};
+
+/// TODO: describe what {rule} reports, with a short PHP example.
+pub struct {type};
+
+impl {type} {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DiagnosticRule for {type} {
+    fn name(&self) -> &str {
+        "{rule}"
+    }
+
+    fn description(&self) -> &str {
+        "TODO: one-line summary of what {rule} reports"
+    }
+
+    fn run(
+        &self,
+        parsed: &parser::ParsedSource,
+        _context: &ProjectContext,
+    ) -> Vec<crate::analyzer::Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        walk_node(parsed.tree.root_node(), &mut |node| {
+            // TODO: the tree-sitter node kind(s) {rule} checks
+            if node.kind() != "todo_node_kind" {
+                return;
+            }
+            diagnostics.push(diagnostic_for_node(
+                parsed,
+                node,
+                Severity::Warning,
+                "TODO: message",
+            ));
+        });
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::{assert_no_diagnostics, run_rule_with_context};
+
+    #[test]
+    fn test_{module}() {
+        let source = r#"<?php
+// TODO: code {rule} should report, checked with assert_diagnostics_exact
+"#;
+
+        let rule = {type}::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_no_diagnostics(&diagnostics);
+    }
+
+    #[test]
+    fn test_{module}_valid() {
+        let source = r#"<?php
+echo 'fine';
+"#;
+
+        let rule = {type}::new();
+        let diagnostics = run_rule_with_context(&rule, source);
+
+        assert_no_diagnostics(&diagnostics);
+    }
+}
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rule_names_map_to_modules_and_types() {
+        let scaffold = RuleScaffold::parse("sanity/empty_catch_2").unwrap();
+        assert_eq!(scaffold.module, "empty_catch_2");
+        assert_eq!(scaffold.type_name, "EmptyCatch2Rule");
+
+        for invalid in [
+            "empty_catch",
+            "Sanity/empty_catch",
+            "sanity/empty-catch",
+            "a/b/c",
+        ] {
+            assert!(RuleScaffold::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn new_rule_is_wired_into_every_file() {
+        let root =
+            std::env::temp_dir().join(format!("php-checker-scaffold-{}", std::process::id()));
+        let category = root.join(RULES_DIR).join("sanity");
+        fs::create_dir_all(&category).unwrap();
+        fs::write(
+            category.join("mod.rs"),
+            "pub use crate::analyzer::rules::{DiagnosticRule, helpers};\n\npub mod dangling_reference;\npub mod undefined_variable;\n\npub use dangling_reference::DanglingReferenceRule;\npub use undefined_variable::UndefinedVariableRule;\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join(RULES_DIR).join("mod.rs"),
+            "pub use compat::DynamicPropertiesRule;\npub use sanity::{DanglingReferenceRule, UndefinedVariableRule};\npub use style::FileHeaderRule;\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join(ANALYZER_FILE),
+            "        let mut rules = vec![\n            Arc::new(rules::UndefinedVariableRule::new()),\n        ];\n\n        rules.retain(|rule| true);\n",
+        )
+        .unwrap();
+
+        let scaffold = RuleScaffold::parse("sanity/empty_catch").unwrap();
+        let changed = scaffold.create(&root).unwrap();
+        assert_eq!(changed.len(), 5);
+
+        assert_eq!(
+            read(&category.join("mod.rs")).unwrap(),
+            "pub use crate::analyzer::rules::{DiagnosticRule, helpers};\n\npub mod dangling_reference;\npub mod empty_catch;\npub mod undefined_variable;\n\npub use dangling_reference::DanglingReferenceRule;\npub use empty_catch::EmptyCatchRule;\npub use undefined_variable::UndefinedVariableRule;\n"
+        );
+        assert!(
+            read(&root.join(RULES_DIR).join("mod.rs"))
+                .unwrap()
+                .contains("pub use sanity::{DanglingReferenceRule, EmptyCatchRule, UndefinedVariableRule};\n")
+        );
+        assert!(
+            read(&root.join(ANALYZER_FILE)).unwrap().contains(
+                "new()),\n            Arc::new(rules::EmptyCatchRule::new()),\n        ];"
+            )
+        );
+        let module = read(&category.join("empty_catch.rs")).unwrap();
+        assert!(module.contains("pub struct EmptyCatchRule;"));
+        assert!(module.contains("\"sanity/empty_catch\""));
+        assert!(
+            root.join(FIXTURES_DIR)
+                .join("sanity/empty_catch.php")
+                .is_file()
+        );
+
+        let error = scaffold.create(&root).unwrap_err().to_string();
+        assert!(error.contains("already exists"), "{error}");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+//! The contract of `--format json` output: a JSON Schema published as
+//! `schema/output.schema.json`, embedded here and printed by `php-checker schema`.
+
+/// Written as `schema_version` in JSON output. Bump it (and the schema's `const`
+/// values and `$id`) when a field is removed, renamed or changes meaning; new
+/// optional fields keep the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The JSON Schema describing [`SCHEMA_VERSION`] of the output.
+pub const OUTPUT_SCHEMA: &str = include_str!("../../schema/output.schema.json");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{Confidence, Diagnostic, Severity, Span, SpanColumns, suppressions};
+    use serde_json::Value;
+    use std::path::PathBuf;
+    use tree_sitter::Point;
+
+    fn schema() -> Value {
+        serde_json::from_str(OUTPUT_SCHEMA).expect("the schema should be valid JSON")
+    }
+
+    fn sorted_keys(object: &Value) -> Vec<&str> {
+        let mut keys: Vec<&str> = object
+            .as_object()
+            .expect("value should be an object")
+            .keys()
+            .map(String::as_str)
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn schema_version_matches() {
+        let schema = schema();
+        assert_eq!(
+            schema["properties"]["schema_version"]["const"],
+            SCHEMA_VERSION
+        );
+        for definition in ["stats_line", "summary"] {
+            assert_eq!(
+                schema["$defs"][definition]["properties"]["schema_version"]["const"],
+                SCHEMA_VERSION
+            );
+        }
+        assert!(
+            schema["$id"]
+                .as_str()
+                .is_some_and(|id| id.ends_with(&format!(":{SCHEMA_VERSION}")))
+        );
+    }
+
+    #[test]
+    fn diagnostic_json_follows_the_schema() {
+        let mut diag = Diagnostic::with_span(
+            PathBuf::from("src/User.php"),
+            Severity::Info,
+            "TODO: tidy up",
+            Span {
+                start: Point { row: 2, column: 4 },
+                end: Point { row: 2, column: 9 },
+            },
+            None,
+            Some("// TODO: tidy up".into()),
+            None,
+            Some(4),
+            5,
+        );
+        diag.rule_name = Some("cleanup/todo_comment".into());
+        diag.columns = Some(SpanColumns {
+            start_char: 4,
+            end_char: 9,
+            start_utf16: 4,
+            end_utf16: 9,
+        });
+        diag.metadata.insert("owner".into(), "alice".into());
+        diag.confidence = Some(Confidence::Low);
+
+        let schema = schema();
+        let definitions = &schema["$defs"];
+        let json = serde_json::to_value(diag.to_json()).unwrap();
+
+        assert_eq!(
+            sorted_keys(&json),
+            sorted_keys(&definitions["diagnostic"]["properties"])
+        );
+        assert_eq!(
+            sorted_keys(&json["span"]["start"]),
+            sorted_keys(&definitions["point"]["properties"])
+        );
+
+        let severities = definitions["diagnostic"]["properties"]["severity"]["enum"]
+            .as_array()
+            .unwrap();
+        for severity in [Severity::Info, Severity::Warning, Severity::Error] {
+            assert!(severities.contains(&serde_json::to_value(severity).unwrap()));
+        }
+        let confidences = definitions["diagnostic"]["properties"]["confidence"]["enum"]
+            .as_array()
+            .unwrap();
+        for confidence in [Confidence::Low, Confidence::Medium, Confidence::High] {
+            assert!(confidences.contains(&serde_json::to_value(confidence).unwrap()));
+        }
+    }
+
+    #[test]
+    fn suppression_json_follows_the_schema() {
+        let file =
+            std::env::temp_dir().join(format!("php-checker-schema-{}.php", std::process::id()));
+        std::fs::write(&file, "<?php\n// php-checker-disable: cleanup\n$a = 1;\n").unwrap();
+        let found = suppressions(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        let schema = schema();
+        let definition = &schema["$defs"]["suppression"];
+        let json = serde_json::to_value(&found).unwrap();
+        assert_eq!(json.as_array().map(Vec::len), Some(1));
+        assert_eq!(
+            sorted_keys(&json[0]),
+            sorted_keys(&definition["properties"])
+        );
+        assert!(
+            definition["properties"]["directive"]["enum"]
+                .as_array()
+                .unwrap()
+                .contains(&json[0]["directive"])
+        );
+        assert_eq!(json[0]["rules"], serde_json::json!(["cleanup"]));
+        assert_eq!(json[0]["span"]["start"]["row"], 1);
+        assert_eq!(json[0]["end_row"], Value::Null);
+    }
+}
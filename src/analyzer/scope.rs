@@ -0,0 +1,155 @@
+//! Decides which code `--scope public-api` holds to the documentation and
+//! strict typing standards.
+
+use crate::analyzer::parser::ParsedSource;
+use crate::analyzer::rules::helpers::{child_by_kind, node_text};
+use tree_sitter::{Node, Point};
+
+/// Whether `point` lies on the exported surface of the file: outside private and
+/// protected members, anonymous classes and declarations marked `@internal`.
+///
+/// Diagnostics inside a docblock are judged by the declaration it documents.
+pub fn in_public_api(parsed: &ParsedSource, point: Point) -> bool {
+    let Some(mut node) = parsed
+        .tree
+        .root_node()
+        .descendant_for_point_range(point, point)
+    else {
+        return true;
+    };
+    if node.kind() == "comment"
+        && let Some(declaration) = documented_declaration(node)
+    {
+        node = declaration;
+    }
+
+    let mut current = Some(node);
+    while let Some(node) = current {
+        match node.kind() {
+            "method_declaration" | "property_declaration" | "const_declaration"
+                if !is_public(node, parsed) =>
+            {
+                return false;
+            }
+            // `new class { ... }` can't be named outside the expression that creates it.
+            "object_creation_expression" if child_by_kind(node, "declaration_list").is_some() => {
+                return false;
+            }
+            _ => {}
+        }
+        if is_declaration(node) && is_internal(node, parsed) {
+            return false;
+        }
+        current = node.parent();
+    }
+
+    true
+}
+
+fn documented_declaration(comment: Node) -> Option<Node> {
+    let mut next = comment.next_named_sibling();
+    while let Some(node) = next {
+        if node.kind() != "comment" {
+            return Some(node);
+        }
+        next = node.next_named_sibling();
+    }
+    None
+}
+
+/// Members without a visibility modifier are public.
+fn is_public(member: Node, parsed: &ParsedSource) -> bool {
+    child_by_kind(member, "visibility_modifier")
+        .and_then(|modifier| node_text(modifier, parsed))
+        .is_none_or(|visibility| visibility.eq_ignore_ascii_case("public"))
+}
+
+fn is_declaration(node: Node) -> bool {
+    matches!(
+        node.kind(),
+        "class_declaration"
+            | "interface_declaration"
+            | "trait_declaration"
+            | "enum_declaration"
+            | "function_definition"
+            | "method_declaration"
+            | "property_declaration"
+            | "const_declaration"
+    )
+}
+
+fn is_internal(declaration: Node, parsed: &ParsedSource) -> bool {
+    declaration
+        .prev_named_sibling()
+        .filter(|comment| comment.kind() == "comment")
+        .and_then(|comment| node_text(comment, parsed))
+        .is_some_and(|text| text.starts_with("/**") && text.contains("@internal"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::parse_php;
+
+    fn public_at(source: &str, needle: &str) -> bool {
+        let parsed = parse_php(source);
+        let (row, line) = source
+            .lines()
+            .enumerate()
+            .find(|(_, line)| line.contains(needle))
+            .unwrap();
+        let column = line.find(needle).unwrap();
+        in_public_api(&parsed, Point { row, column })
+    }
+
+    #[test]
+    fn public_members_and_functions_are_in_scope() {
+        let source = r#"<?php
+function helper($value) {}
+
+class Api
+{
+    /** @return int */
+    public function get() { return 1; }
+    function legacy() {}
+    public $name;
+}
+"#;
+
+        assert!(public_at(source, "helper"));
+        assert!(public_at(source, "@return"));
+        assert!(public_at(source, "return 1"));
+        assert!(public_at(source, "legacy"));
+        assert!(public_at(source, "$name"));
+    }
+
+    #[test]
+    fn private_internal_and_anonymous_code_is_out_of_scope() {
+        let source = r#"<?php
+class Api
+{
+    /** @param string $id */
+    protected function find($id) {}
+    private static function cache() { return []; }
+    private const LIMIT = 10;
+    /** @internal */
+    public function reset() {}
+}
+
+/** @internal */
+class Registry
+{
+    public function all() {}
+}
+
+$handler = new class { public function handle() {} };
+"#;
+
+        assert!(!public_at(source, "@param"));
+        assert!(!public_at(source, "return []"));
+        assert!(!public_at(source, "LIMIT"));
+        assert!(!public_at(source, "reset"));
+        assert!(!public_at(source, "all()"));
+        assert!(!public_at(source, "handle()"));
+    }
+}
@@ -0,0 +1,348 @@
+//! `php-checker score`: one number for a project's health, built from the
+//! diagnostics per thousand lines, the share of functions that are too complex
+//! and the number of suppression directives. The JSON report can be saved and
+//! passed back with `--compare-to` to show how the score moved.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tree_sitter::Node;
+
+use super::ignore::IgnoreState;
+use super::parser::ParsedSource;
+use super::{Diagnostic, Severity};
+
+/// Share of the score each component contributes.
+const DIAGNOSTICS_WEIGHT: f64 = 0.5;
+const COMPLEXITY_WEIGHT: f64 = 0.3;
+const SUPPRESSIONS_WEIGHT: f64 = 0.2;
+/// Severity-weighted diagnostics per 1000 lines at which that component scores 50.
+const DIAGNOSTIC_DENSITY_MIDPOINT: f64 = 20.0;
+/// Suppression directives per 1000 lines at which that component scores 50.
+const SUPPRESSION_DENSITY_MIDPOINT: f64 = 5.0;
+/// Cyclomatic complexity above which a function counts as too complex.
+pub const COMPLEXITY_LIMIT: usize = 10;
+
+const FUNCTION_KINDS: &[&str] = &[
+    "function_definition",
+    "method_declaration",
+    "anonymous_function_creation_expression",
+    "arrow_function",
+];
+/// Nodes that add a path through a function.
+const BRANCH_KINDS: &[&str] = &[
+    "if_statement",
+    "else_if_clause",
+    "for_statement",
+    "foreach_statement",
+    "while_statement",
+    "do_statement",
+    "case_statement",
+    "catch_clause",
+    "conditional_expression",
+    "match_conditional_expression",
+];
+const BRANCH_OPERATORS: &[&str] = &["&&", "||", "and", "or", "??"];
+
+/// Size, complexity and suppressions of one file.
+#[derive(Debug, Default)]
+pub struct FileMetrics {
+    pub lines: usize,
+    /// Cyclomatic complexity of each function, method, closure and arrow function.
+    pub complexities: Vec<usize>,
+    pub suppressions: usize,
+}
+
+impl FileMetrics {
+    pub(crate) fn measure(parsed: &ParsedSource) -> Self {
+        let mut complexities = Vec::new();
+        collect_complexities(parsed.tree.root_node(), &mut complexities);
+
+        Self {
+            lines: parsed.source.lines().count(),
+            complexities,
+            suppressions: IgnoreState::from_source(&parsed.source).directives().len(),
+        }
+    }
+}
+
+/// The score and what it was computed from, as printed by `--format json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreReport {
+    /// From 0 to 100; higher is healthier.
+    pub score: f64,
+    pub components: Components,
+    pub files: usize,
+    pub lines: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub infos: usize,
+    pub functions: usize,
+    /// Functions above [`COMPLEXITY_LIMIT`].
+    pub complex_functions: usize,
+    pub max_complexity: usize,
+    pub suppressions: usize,
+    /// Change in `score` since the `--compare-to` report.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta: Option<f64>,
+}
+
+/// Each component's score from 0 to 100, before weighting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Components {
+    pub diagnostics: f64,
+    pub complexity: f64,
+    pub suppressions: f64,
+}
+
+impl ScoreReport {
+    pub fn new(files: &[FileMetrics], diagnostics: &[Diagnostic]) -> Self {
+        let count = |severity: Severity| {
+            diagnostics
+                .iter()
+                .filter(|diag| diag.severity == severity)
+                .count()
+        };
+        let (errors, warnings, infos) = (
+            count(Severity::Error),
+            count(Severity::Warning),
+            count(Severity::Info),
+        );
+        let lines: usize = files.iter().map(|file| file.lines).sum();
+        let complexities = files.iter().flat_map(|file| &file.complexities);
+        let functions = complexities.clone().count();
+        let complex_functions = complexities
+            .clone()
+            .filter(|&&complexity| complexity > COMPLEXITY_LIMIT)
+            .count();
+        let suppressions = files.iter().map(|file| file.suppressions).sum();
+
+        let kilo_lines = (lines as f64 / 1000.0).max(0.001);
+        let weighted = 4.0 * errors as f64 + warnings as f64 + 0.25 * infos as f64;
+        let components = Components {
+            diagnostics: round(decay(weighted / kilo_lines, DIAGNOSTIC_DENSITY_MIDPOINT)),
+            complexity: round(if functions == 0 {
+                100.0
+            } else {
+                100.0 * (functions - complex_functions) as f64 / functions as f64
+            }),
+            suppressions: round(decay(
+                suppressions as f64 / kilo_lines,
+                SUPPRESSION_DENSITY_MIDPOINT,
+            )),
+        };
+        let score = round(
+            DIAGNOSTICS_WEIGHT * components.diagnostics
+                + COMPLEXITY_WEIGHT * components.complexity
+                + SUPPRESSIONS_WEIGHT * components.suppressions,
+        );
+
+        Self {
+            score,
+            components,
+            files: files.len(),
+            lines,
+            errors,
+            warnings,
+            infos,
+            functions,
+            complex_functions,
+            max_complexity: complexities.copied().max().unwrap_or(0),
+            suppressions,
+            delta: None,
+        }
+    }
+
+    /// Reads a report saved from `php-checker score --format json`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("{} is not a php-checker score report", path.display()))
+    }
+
+    /// Records the change since `previous` in [`Self::delta`].
+    pub fn compare_to(&mut self, previous: &ScoreReport) {
+        self.delta = Some(round(self.score - previous.score));
+    }
+
+    /// The score and its components, with the change since `previous` when given.
+    pub fn format(&self, previous: Option<&ScoreReport>) -> String {
+        let change = |current: f64, before: Option<f64>| {
+            before.map_or_else(String::new, |before| {
+                format!(" ({:+.1})", round(current - before))
+            })
+        };
+        let mut output = String::new();
+
+        let _ = writeln!(
+            output,
+            "Health score: {:.1} / 100{}",
+            self.score,
+            change(self.score, previous.map(|report| report.score))
+        );
+        let _ = writeln!(
+            output,
+            "  diagnostics   {:>5.1}{}  {} error(s), {} warning(s), {} info(s) in {} line(s)",
+            self.components.diagnostics,
+            change(
+                self.components.diagnostics,
+                previous.map(|report| report.components.diagnostics)
+            ),
+            self.errors,
+            self.warnings,
+            self.infos,
+            self.lines
+        );
+        let _ = writeln!(
+            output,
+            "  complexity    {:>5.1}{}  {} of {} function(s) above complexity {COMPLEXITY_LIMIT} (max {})",
+            self.components.complexity,
+            change(
+                self.components.complexity,
+                previous.map(|report| report.components.complexity)
+            ),
+            self.complex_functions,
+            self.functions,
+            self.max_complexity
+        );
+        let _ = writeln!(
+            output,
+            "  suppressions  {:>5.1}{}  {} suppression directive(s)",
+            self.components.suppressions,
+            change(
+                self.components.suppressions,
+                previous.map(|report| report.components.suppressions)
+            ),
+            self.suppressions
+        );
+
+        output
+    }
+}
+
+/// 100 at zero density, falling to 50 at `midpoint` and towards 0 beyond it.
+fn decay(density: f64, midpoint: f64) -> f64 {
+    100.0 / (1.0 + density / midpoint)
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+fn collect_complexities(node: Node, complexities: &mut Vec<usize>) {
+    if FUNCTION_KINDS.contains(&node.kind()) {
+        complexities.push(1 + branches(node));
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_complexities(child, complexities);
+    }
+}
+
+/// Branch points inside `node`, leaving out nested functions, which are
+/// measured on their own.
+fn branches(node: Node) -> usize {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !FUNCTION_KINDS.contains(&child.kind()))
+        .map(|child| usize::from(is_branch(child)) + branches(child))
+        .sum()
+}
+
+fn is_branch(node: Node) -> bool {
+    BRANCH_KINDS.contains(&node.kind())
+        || (node.kind() == "binary_expression"
+            && node
+                .child_by_field_name("operator")
+                .is_some_and(|operator| {
+                    BRANCH_OPERATORS.contains(&operator.kind().to_ascii_lowercase().as_str())
+                }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::rules::test_utils::parse_php;
+    use std::path::PathBuf;
+
+    #[test]
+    fn measures_complexity_per_function() {
+        let parsed = parse_php(
+            r#"<?php
+// php-checker-ignore: cleanup
+function simple() {
+    return 1;
+}
+
+class Report
+{
+    public function render($rows, $title)
+    {
+        if ($rows && $title || !$rows) {
+            foreach ($rows as $row) {
+                echo $row ?? 'n/a';
+            }
+        } elseif ($title) {
+            $format = fn($value) => $value ? 'yes' : 'no';
+        }
+        try {
+            return match ($title) { 'a' => 1, default => 2 };
+        } catch (Exception $e) {
+            return 0;
+        }
+    }
+}
+"#,
+        );
+
+        let metrics = FileMetrics::measure(&parsed);
+        assert_eq!(metrics.lines, 24);
+        assert_eq!(metrics.complexities, [1, 9, 2]);
+        assert_eq!(metrics.suppressions, 1);
+    }
+
+    #[test]
+    fn score_weighs_components_and_reports_the_change() {
+        let files = [FileMetrics {
+            lines: 2000,
+            complexities: vec![1, 4, 12, 30],
+            suppressions: 10,
+        }];
+        let diagnostics: Vec<Diagnostic> = [Severity::Error, Severity::Warning, Severity::Info]
+            .into_iter()
+            .cycle()
+            .take(30)
+            .map(|severity| Diagnostic::new(PathBuf::from("a.php"), severity, "message"))
+            .collect();
+
+        let mut report = ScoreReport::new(&files, &diagnostics);
+        assert_eq!(report.components.diagnostics, 43.2);
+        assert_eq!(report.components.complexity, 50.0);
+        assert_eq!(report.components.suppressions, 50.0);
+        assert_eq!(report.score, 46.6);
+        assert_eq!((report.complex_functions, report.max_complexity), (2, 30));
+
+        let clean = ScoreReport::new(&files[..0], &[]);
+        assert_eq!(clean.score, 100.0);
+
+        report.compare_to(&clean);
+        assert_eq!(report.delta, Some(-53.4));
+        let text = report.format(Some(&clean));
+        assert!(
+            text.starts_with("Health score: 46.6 / 100 (-53.4)\n"),
+            "{text}"
+        );
+        assert!(
+            text.contains("complexity     50.0 (-50.0)  2 of 4 function(s) above complexity 10"),
+            "{text}"
+        );
+
+        let json = serde_json::to_string(&report).unwrap();
+        let reloaded: ScoreReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.score, report.score);
+    }
+}
@@ -0,0 +1,341 @@
+//! `php-checker self-update`: replaces the running binary with a release build.
+//!
+//! Downloads go through `curl`, so no HTTP stack is compiled into the analyzer.
+//! Release assets are named `php-checker-<arch>-<os>` (plus `.exe` on Windows),
+//! e.g. `php-checker-x86_64-linux`, and attached to `v<version>` tags. Each one
+//! has a `<asset>.sha256` file next to it in `sha256sum` format, which the
+//! download must match before it is made executable or run.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use super::version::{CURRENT, Version};
+
+/// Where releases are published; `PHP_CHECKER_RELEASE_URL` points elsewhere,
+/// e.g. at an internal mirror laid out the same way.
+const RELEASE_URL: &str = "https://github.com/kcoulsy/php-checker/releases";
+
+/// What [`run`] did.
+pub enum UpdateOutcome {
+    UpToDate(Version),
+    Available { current: Version, latest: Version },
+    Updated { from: Version, to: Version },
+}
+
+/// Installs `requested` (the latest release when `None`) over the running
+/// binary. With `check_only`, only reports whether a newer release exists.
+pub fn run(requested: Option<&str>, check_only: bool) -> Result<UpdateOutcome> {
+    let current = Version::current();
+    let target = match requested {
+        Some(version) => {
+            Version::parse(version).ok_or_else(|| anyhow!("invalid version \"{version}\""))?
+        }
+        None => latest_release()?,
+    };
+
+    if check_only {
+        return Ok(if target > current {
+            UpdateOutcome::Available {
+                current,
+                latest: target,
+            }
+        } else {
+            UpdateOutcome::UpToDate(current)
+        });
+    }
+    if target == current {
+        return Ok(UpdateOutcome::UpToDate(current));
+    }
+
+    let executable = env::current_exe()
+        .and_then(fs::canonicalize)
+        .context("failed to locate the running php-checker binary")?;
+    let download = staging_path(&executable);
+    let url = format!("{}/download/v{target}/{}", release_url(), asset_name());
+
+    let installed = fetch(&url, &download)
+        .and_then(|()| check_digest(&url, &download))
+        .and_then(|()| verify(&download, target))
+        .and_then(|()| replace(&executable, &download));
+    if installed.is_err() {
+        let _ = fs::remove_file(&download);
+    }
+    installed?;
+
+    Ok(UpdateOutcome::Updated {
+        from: current,
+        to: target,
+    })
+}
+
+fn release_url() -> String {
+    env::var("PHP_CHECKER_RELEASE_URL")
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| RELEASE_URL.to_string())
+}
+
+/// The release asset built for this platform.
+pub fn asset_name() -> String {
+    let extension = if cfg!(windows) { ".exe" } else { "" };
+    format!(
+        "php-checker-{}-{}{extension}",
+        env::consts::ARCH,
+        env::consts::OS
+    )
+}
+
+/// `<releases>/latest` redirects to the newest release's `tag/v<version>` page.
+fn latest_release() -> Result<Version> {
+    let discard = if cfg!(windows) { "NUL" } else { "/dev/null" };
+    let url = format!("{}/latest", release_url());
+    let output = curl(&["--output", discard, "--write-out", "%{url_effective}", &url])
+        .context("failed to look up the latest release")?;
+    let resolved = String::from_utf8_lossy(&output);
+    release_tag(&resolved)
+        .ok_or_else(|| anyhow!("{url} did not lead to a release tag (got {resolved})"))
+}
+
+/// The version in the `tag/v<version>` URL the latest release resolved to.
+fn release_tag(resolved: &str) -> Option<Version> {
+    let (path, tag) = resolved.trim().trim_end_matches('/').rsplit_once('/')?;
+    if !path.ends_with("/tag") {
+        return None;
+    }
+    Version::parse(tag)
+}
+
+/// Compares the download against the `<url>.sha256` file published with it.
+fn check_digest(url: &str, download: &Path) -> Result<()> {
+    let checksum_url = format!("{url}.sha256");
+    let published = curl(&[&checksum_url])
+        .with_context(|| format!("failed to download the checksum {checksum_url}"))?;
+    let expected = parse_checksum(&String::from_utf8_lossy(&published))
+        .ok_or_else(|| anyhow!("{checksum_url} does not contain a SHA-256 digest"))?;
+    let contents =
+        fs::read(download).with_context(|| format!("failed to read {}", download.display()))?;
+    let actual = hex(&sha256(&contents));
+    if actual != expected {
+        bail!("the download from {url} has SHA-256 {actual}, but {checksum_url} lists {expected}");
+    }
+    Ok(())
+}
+
+/// The lowercase digest from a `sha256sum` line (`<digest>  <file>`) or a bare digest.
+fn parse_checksum(contents: &str) -> Option<String> {
+    let digest = contents.split_whitespace().next()?;
+    (digest.len() == 64 && digest.bytes().all(|byte| byte.is_ascii_hexdigit()))
+        .then(|| digest.to_ascii_lowercase())
+}
+
+fn fetch(url: &str, destination: &Path) -> Result<()> {
+    let destination = destination.to_string_lossy();
+    curl(&["--output", &destination, url]).with_context(|| format!("failed to download {url}"))?;
+    Ok(())
+}
+
+fn curl(args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("curl")
+        .args(["--fail", "--location", "--silent", "--show-error"])
+        .args(args)
+        .output()
+        .context("self-update needs `curl` on the PATH")?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(output.stdout)
+}
+
+/// Runs the download with `--version` so a truncated file or a build for
+/// another platform never replaces a working binary.
+fn verify(download: &Path, expected: Version) -> Result<()> {
+    make_executable(download)?;
+    let output = Command::new(download)
+        .arg("--version")
+        .output()
+        .context("the downloaded binary does not run on this system")?;
+    let reported = String::from_utf8_lossy(&output.stdout);
+    let version = reported.split_whitespace().last().and_then(Version::parse);
+    if version != Some(expected) {
+        bail!(
+            "the downloaded binary reports \"{}\" instead of php-checker {expected}",
+            reported.trim()
+        );
+    }
+    Ok(())
+}
+
+fn replace(executable: &Path, download: &Path) -> Result<()> {
+    // Windows cannot overwrite a running executable, but it can rename it.
+    if cfg!(windows) {
+        let previous = executable.with_extension("old.exe");
+        let _ = fs::remove_file(&previous);
+        fs::rename(executable, &previous)
+            .with_context(|| format!("failed to move aside {}", executable.display()))?;
+    }
+    fs::rename(download, executable)
+        .with_context(|| format!("failed to replace {}", executable.display()))
+}
+
+fn staging_path(executable: &Path) -> PathBuf {
+    let name = executable
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "php-checker".to_string());
+    executable.with_file_name(format!(".{name}.{CURRENT}.download"))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .with_context(|| format!("failed to mark {} executable", path.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// SHA-256 (FIPS 180-4), kept here so checking a download needs no extra crate.
+fn sha256(data: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for (k, w) in K.iter().zip(w) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(choice)
+                .wrapping_add(*k)
+                .wrapping_add(w);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(majority);
+            (h, g, f, e, d, c, b, a) = (g, f, e, d.wrapping_add(t1), c, b, a, t1.wrapping_add(t2));
+        }
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asset_name_includes_the_platform() {
+        let name = asset_name();
+        assert!(name.starts_with("php-checker-"), "{name}");
+        assert!(
+            name.contains(&format!("-{}-{}", env::consts::ARCH, env::consts::OS)),
+            "{name}"
+        );
+        assert_eq!(name.ends_with(".exe"), cfg!(windows), "{name}");
+    }
+
+    #[test]
+    fn staging_path_is_hidden_next_to_the_executable() {
+        let staged = staging_path(Path::new("/usr/local/bin/php-checker"));
+        assert_eq!(
+            staged,
+            PathBuf::from(format!("/usr/local/bin/.php-checker.{CURRENT}.download"))
+        );
+    }
+
+    #[test]
+    fn release_tag_reads_the_resolved_url() {
+        let base = "https://github.com/kcoulsy/php-checker/releases";
+        assert_eq!(
+            release_tag(&format!("{base}/tag/v0.5.2\n")),
+            Version::parse("0.5.2")
+        );
+        assert_eq!(
+            release_tag(&format!("{base}/tag/v1.0.0/")),
+            Version::parse("1.0.0")
+        );
+        assert_eq!(release_tag(&format!("{base}/latest")), None);
+        assert_eq!(release_tag(&format!("{base}/tag/nightly")), None);
+        assert_eq!(release_tag(""), None);
+    }
+
+    #[test]
+    fn parses_sha256sum_lines() {
+        let digest = "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855";
+        assert_eq!(
+            parse_checksum(&format!("{digest}  php-checker-x86_64-linux\n")),
+            Some(digest.to_ascii_lowercase())
+        );
+        assert_eq!(parse_checksum("not a digest"), None);
+        assert_eq!(parse_checksum(""), None);
+    }
+
+    #[test]
+    fn sha256_matches_known_digests() {
+        assert_eq!(
+            hex(&sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            hex(&sha256(b"abc")),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hex(&sha256(&[b'a'; 1000])),
+            "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3"
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Resolves the `PATH` argument of the CLI into the files to analyse.
+//!
+//! `PATH` is a comma-separated list of paths and glob patterns, such as
+//! `src,lib/**/*.php,!vendor/**`. Relative entries are resolved against a base
+//! directory (the config file's directory, or the current one). Entries starting
+//! with `!` exclude the files (or directories) they match.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use glob::Pattern;
+
+use super::collect_php_files_from_roots;
+
+/// The roots to analyse, the PHP files beneath them and the exclusions that
+/// filtered those files.
+#[derive(Debug)]
+pub struct Targets {
+    roots: Vec<PathBuf>,
+    files: Vec<PathBuf>,
+    exclusions: Vec<Exclusion>,
+    unmatched: Vec<String>,
+}
+
+#[derive(Debug)]
+struct Exclusion {
+    source: String,
+    pattern: Pattern,
+}
+
+impl Targets {
+    /// Resolves `spec` against `base`. Entries that match nothing are reported
+    /// by [`Targets::unmatched`]; it is only an error when no entry matches.
+    pub fn resolve(spec: &str, base: &Path) -> Result<Self> {
+        let base = base
+            .canonicalize()
+            .with_context(|| format!("failed to access {}", base.display()))?;
+
+        let mut roots = Vec::new();
+        let mut exclusions = Vec::new();
+        let mut unmatched = Vec::new();
+
+        for entry in split_spec(spec, &base) {
+            if let Some(excluded) = entry.strip_prefix('!') {
+                let pattern = format!(
+                    "{}/{}",
+                    Pattern::escape(&base.to_string_lossy()),
+                    excluded.trim_start_matches("./")
+                );
+                let pattern = if Path::new(excluded).is_absolute() {
+                    Pattern::new(excluded)
+                } else {
+                    Pattern::new(&pattern)
+                }
+                .with_context(|| format!("invalid exclude pattern \"{entry}\""))?;
+                exclusions.push(Exclusion {
+                    source: entry.to_string(),
+                    pattern,
+                });
+                continue;
+            }
+
+            let matches = expand(entry, &base)?;
+            if matches.is_empty() {
+                unmatched.push(entry.to_string());
+            }
+            roots.extend(matches);
+        }
+
+        if roots.is_empty() {
+            let patterns: Vec<String> = unmatched
+                .iter()
+                .map(|pattern| format!("\"{pattern}\""))
+                .collect();
+            if patterns.is_empty() {
+                bail!("no paths to analyse in \"{spec}\"");
+            }
+            bail!(
+                "no files matched {} (relative to {})",
+                patterns.join(", "),
+                base.display()
+            );
+        }
+
+        roots.sort();
+        roots.dedup();
+        let mut files = collect_php_files_from_roots(&roots)?;
+        let mut used = vec![false; exclusions.len()];
+        files.retain(|path| {
+            let matched = exclusions
+                .iter()
+                .position(|exclusion: &Exclusion| exclusion.excludes(path));
+            if let Some(idx) = matched {
+                used[idx] = true;
+            }
+            matched.is_none()
+        });
+        unmatched.extend(
+            exclusions
+                .iter()
+                .zip(used)
+                .filter(|(_, used)| !used)
+                .map(|(exclusion, _)| exclusion.source.clone()),
+        );
+
+        Ok(Self {
+            roots,
+            files,
+            exclusions,
+            unmatched,
+        })
+    }
+
+    /// The canonical files and directories that matched.
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Entries that matched nothing while others did, e.g. `lib/**` when there
+    /// is no `lib` directory, and exclusions that left every file in place.
+    pub fn unmatched(&self) -> &[String] {
+        &self.unmatched
+    }
+
+    /// Whether `path` (canonical) falls under one of the `!` entries.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.exclusions
+            .iter()
+            .any(|exclusion| exclusion.excludes(path))
+    }
+
+    /// Every PHP file beneath the roots that no exclusion covers.
+    pub fn php_files(&self) -> &[PathBuf] {
+        &self.files
+    }
+}
+
+impl Exclusion {
+    /// `!vendor` and `!vendor/**` both exclude everything inside `vendor`.
+    fn excludes(&self, path: &Path) -> bool {
+        path.ancestors()
+            .any(|ancestor| self.pattern.matches_path(ancestor))
+    }
+}
+
+/// Splits on commas, unless the whole spec names an existing path.
+fn split_spec<'a>(spec: &'a str, base: &Path) -> Vec<&'a str> {
+    if base.join(spec).exists() {
+        return vec![spec];
+    }
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// The canonical paths an entry names: itself when it is a plain path that
+/// exists, or whatever its glob matches.
+fn expand(entry: &str, base: &Path) -> Result<Vec<PathBuf>> {
+    let path = base.join(entry);
+    if !contains_glob(entry) {
+        return Ok(path.canonicalize().into_iter().collect());
+    }
+
+    let pattern = format!(
+        "{}/{}",
+        Pattern::escape(&base.to_string_lossy()),
+        entry.trim_start_matches("./")
+    );
+    let pattern = if Path::new(entry).is_absolute() {
+        entry
+    } else {
+        &pattern
+    };
+    let mut matches = Vec::new();
+    for matched in
+        glob::glob(pattern).with_context(|| format!("invalid glob pattern \"{entry}\""))?
+    {
+        let matched =
+            matched.with_context(|| format!("failed to read entries for pattern \"{entry}\""))?;
+        let canonical = matched
+            .canonicalize()
+            .with_context(|| format!("failed to access {}", matched.display()))?;
+        matches.push(canonical);
+    }
+    Ok(matches)
+}
+
+fn contains_glob(entry: &str) -> bool {
+    entry
+        .chars()
+        .any(|c| matches!(c, '*' | '?' | '[' | ']' | '{' | '}'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn project() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("php-checker-targets-{}", std::process::id()));
+        for file in [
+            "src/App.php",
+            "src/Http/Kernel.php",
+            "vendor/lib/Dep.php",
+            "bin/run.php",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "<?php\n").unwrap();
+        }
+        root.canonicalize().unwrap()
+    }
+
+    fn names(root: &Path, files: &[PathBuf]) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| file.strip_prefix(root).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn resolves_lists_globs_and_exclusions() {
+        let root = project();
+
+        let targets = Targets::resolve("**/*.php,!vendor/**", &root).unwrap();
+        assert_eq!(
+            names(&root, targets.php_files()),
+            ["bin/run.php", "src/App.php", "src/Http/Kernel.php"]
+        );
+
+        let targets = Targets::resolve("src, bin, lib/**, !vendor", &root).unwrap();
+        assert_eq!(targets.roots().len(), 2);
+        assert_eq!(targets.unmatched(), ["lib/**", "!vendor"]);
+
+        let err = Targets::resolve("lib/**,missing.php", &root).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("no files matched \"lib/**\", \"missing.php\""),
+            "{err}"
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,434 @@
+//! Type representation shared by the type-checking rules.
+//!
+//! Rules compare and report the same handful of types over and over, so each
+//! analysis run interns them in a [`TypeArena`]: every distinct [`TypeHint`] is
+//! stored and spelled once, and rules pass around [`TypeId`] handles that
+//! compare as integers. [`TypeFormatter`] is the single place that spells types
+//! and PHPDoc type expressions for messages, and [`type_expression_to_hint`]
+//! the single place PHPDoc types are converted for checking.
+
+use crate::analyzer::phpdoc::TypeExpression;
+use crate::analyzer::rules::helpers::is_type_compatible;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeHint {
+    Int,
+    String,
+    Bool,
+    Float,
+    Object(String),          // Stores the class/interface name
+    Nullable(Box<TypeHint>), // Wraps another type to make it nullable
+    Union(Vec<TypeHint>),    // Union of multiple types (int|string)
+    Array(Box<TypeHint>),    // Array of a specific type (int[], User[])
+    GenericArray {           // Associative array with key/value types (array<string, int>)
+        key: Box<TypeHint>,
+        value: Box<TypeHint>,
+    },
+    ShapedArray(Vec<(String, TypeHint)>), // Shaped array with named fields (array{name: string, age: int})
+    List(Box<TypeHint>),                  // Array with sequential int keys (list<User>)
+    ArrayKey,                             // Any valid array key: int|string
+    NonEmptyString,                       // A string known not to be ''
+    Unknown,
+}
+
+/// Handle to a type interned in a [`TypeArena`]. Equal types get the same
+/// handle, so comparing handles compares types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TypeId(u32);
+
+impl TypeId {
+    /// [`TypeHint::Unknown`], interned first by every arena.
+    pub const UNKNOWN: TypeId = TypeId(0);
+}
+
+struct InternedType {
+    hint: Arc<TypeHint>,
+    display: Arc<str>,
+}
+
+/// The distinct types of one analysis run, owned by the run's
+/// [`ProjectContext`](crate::analyzer::project::ProjectContext) and dropped
+/// with it. Rules on different threads intern into the same arena.
+pub struct TypeArena {
+    types: RwLock<Types>,
+    /// Memoised [`TypeArena::is_compatible`] results by (actual, expected).
+    compatible: RwLock<HashMap<(TypeId, TypeId), bool>>,
+}
+
+#[derive(Default)]
+struct Types {
+    ids: HashMap<Arc<TypeHint>, TypeId>,
+    entries: Vec<InternedType>,
+}
+
+impl TypeArena {
+    pub fn new() -> Self {
+        let arena = Self {
+            types: RwLock::new(Types::default()),
+            compatible: RwLock::new(HashMap::new()),
+        };
+        arena.intern(&TypeHint::Unknown);
+        arena
+    }
+
+    /// The handle for `hint`, spelling the type the first time it is seen.
+    pub fn intern(&self, hint: &TypeHint) -> TypeId {
+        if let Some(&id) = self.types.read().unwrap().ids.get(hint) {
+            return id;
+        }
+
+        let mut types = self.types.write().unwrap();
+        if let Some(&id) = types.ids.get(hint) {
+            return id;
+        }
+        let id = TypeId(types.entries.len() as u32);
+        let hint = Arc::new(hint.clone());
+        types.entries.push(InternedType {
+            hint: Arc::clone(&hint),
+            display: TypeFormatter::hint(&hint).into(),
+        });
+        types.ids.insert(hint, id);
+        id
+    }
+
+    pub fn hint(&self, id: TypeId) -> Arc<TypeHint> {
+        Arc::clone(&self.types.read().unwrap().entries[id.0 as usize].hint)
+    }
+
+    /// The type as written in messages: `?User`, `array<string, int>`
+    pub fn display(&self, id: TypeId) -> Arc<str> {
+        Arc::clone(&self.types.read().unwrap().entries[id.0 as usize].display)
+    }
+
+    /// Whether a value of type `actual` fits where `expected` is declared; see
+    /// [`is_type_compatible`]. Each pair of types is only worked out once.
+    pub fn is_compatible(&self, actual: TypeId, expected: TypeId) -> bool {
+        if actual == expected {
+            return true;
+        }
+        if let Some(&compatible) = self.compatible.read().unwrap().get(&(actual, expected)) {
+            return compatible;
+        }
+
+        let compatible = is_type_compatible(&self.hint(actual), &self.hint(expected));
+        self.compatible
+            .write()
+            .unwrap()
+            .insert((actual, expected), compatible);
+        compatible
+    }
+}
+
+impl Default for TypeArena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert a PHPDoc type to the [`TypeHint`] rules check values against.
+///
+/// Returns `None` for types that can't be checked (`mixed`, `void`, `never`,
+/// generics other than arrays and lists); union members that can't be
+/// checked are dropped. Conditional types stand for either branch.
+pub fn type_expression_to_hint(expr: &TypeExpression) -> Option<TypeHint> {
+    match expr {
+        TypeExpression::Simple(s) => match s.as_str() {
+            "int" | "integer" => Some(TypeHint::Int),
+            "string" => Some(TypeHint::String),
+            "bool" | "boolean" => Some(TypeHint::Bool),
+            "float" | "double" => Some(TypeHint::Float),
+            "array-key" => Some(TypeHint::ArrayKey),
+            "non-empty-string" => Some(TypeHint::NonEmptyString),
+            "non-empty-array" | "list" | "non-empty-list" => {
+                Some(TypeHint::Object("array".to_string()))
+            }
+            // Anything else is treated as an object type (class/interface name)
+            _ => Some(TypeHint::Object(s.clone())),
+        },
+        TypeExpression::Nullable(inner) => {
+            type_expression_to_hint(inner).map(|t| TypeHint::Nullable(Box::new(t)))
+        }
+        TypeExpression::Union(types) => {
+            let hints: Vec<TypeHint> = types.iter().filter_map(type_expression_to_hint).collect();
+            if hints.is_empty() {
+                None
+            } else {
+                Some(TypeHint::Union(hints))
+            }
+        }
+        // int[], User[]
+        TypeExpression::Array(inner) => {
+            type_expression_to_hint(inner).map(|t| TypeHint::Array(Box::new(t)))
+        }
+        TypeExpression::Generic { base, params } => match (base.as_str(), params.as_slice()) {
+            // array<string, int>, non-empty-array<string, int>
+            ("array" | "non-empty-array", [key, value]) => Some(TypeHint::GenericArray {
+                key: Box::new(type_expression_to_hint(key)?),
+                value: Box::new(type_expression_to_hint(value)?),
+            }),
+            ("array" | "non-empty-array", [value]) => {
+                type_expression_to_hint(value).map(|t| TypeHint::Array(Box::new(t)))
+            }
+            ("list" | "non-empty-list", [value]) => {
+                type_expression_to_hint(value).map(|t| TypeHint::List(Box::new(t)))
+            }
+            _ => None,
+        },
+        TypeExpression::ShapedArray(fields) => fields
+            .iter()
+            .map(|(name, type_expr)| {
+                type_expression_to_hint(type_expr).map(|hint| (name.clone(), hint))
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(TypeHint::ShapedArray),
+        TypeExpression::KeyOf(inner) => type_expression_to_hint(&inner.key_type()),
+        TypeExpression::ValueOf(inner) => type_expression_to_hint(&inner.value_type()?),
+        TypeExpression::Conditional { .. } => type_expression_to_hint(&expr.branch_union()?),
+        TypeExpression::Mixed | TypeExpression::Void | TypeExpression::Never => None,
+    }
+}
+
+/// Spells types for diagnostics.
+pub struct TypeFormatter;
+
+impl TypeFormatter {
+    /// `?User`, `int|string`, `array<string, int>`
+    pub fn hint(hint: &TypeHint) -> String {
+        match hint {
+            TypeHint::Int => "int".to_string(),
+            TypeHint::String => "string".to_string(),
+            TypeHint::Bool => "bool".to_string(),
+            TypeHint::Float => "float".to_string(),
+            TypeHint::Object(name) => name.clone(),
+            TypeHint::Nullable(inner) => format!("?{}", Self::hint(inner)),
+            TypeHint::Union(types) => Self::join(types.iter().map(Self::hint), "|"),
+            TypeHint::Array(inner) => format!("{}[]", Self::hint(inner)),
+            TypeHint::GenericArray { key, value } => {
+                format!("array<{}, {}>", Self::hint(key), Self::hint(value))
+            }
+            TypeHint::ShapedArray(fields) => format!(
+                "array{{{}}}",
+                Self::join(
+                    fields
+                        .iter()
+                        .map(|(name, hint)| format!("{}: {}", name, Self::hint(hint))),
+                    ", "
+                )
+            ),
+            TypeHint::List(inner) => format!("list<{}>", Self::hint(inner)),
+            TypeHint::ArrayKey => "array-key".to_string(),
+            TypeHint::NonEmptyString => "non-empty-string".to_string(),
+            TypeHint::Unknown => "unknown".to_string(),
+        }
+    }
+
+    /// A PHPDoc type expression in PHPDoc syntax: `User[]`, `list<int>`
+    pub fn expression(expr: &TypeExpression) -> String {
+        match expr {
+            TypeExpression::Simple(s) => s.clone(),
+            TypeExpression::Array(inner) => format!("{}[]", Self::expression(inner)),
+            TypeExpression::Generic { base, params } => format!(
+                "{}<{}>",
+                base,
+                Self::join(params.iter().map(Self::expression), ", ")
+            ),
+            TypeExpression::Union(types) => Self::join(types.iter().map(Self::expression), "|"),
+            TypeExpression::Nullable(inner) => format!("?{}", Self::expression(inner)),
+            TypeExpression::ShapedArray(fields) => format!(
+                "array{{{}}}",
+                Self::join(
+                    fields.iter().map(|(name, type_expr)| {
+                        format!("{}: {}", name, Self::expression(type_expr))
+                    }),
+                    ", "
+                )
+            ),
+            TypeExpression::Mixed => "mixed".to_string(),
+            TypeExpression::Void => "void".to_string(),
+            TypeExpression::Never => "never".to_string(),
+            TypeExpression::KeyOf(inner) => format!("key-of<{}>", Self::expression(inner)),
+            TypeExpression::ValueOf(inner) => {
+                format!("value-of<{}>", Self::expression(inner))
+            }
+            TypeExpression::Conditional {
+                parameter,
+                negated,
+                condition,
+                then,
+                otherwise,
+            } => format!(
+                "(${} is {}{} ? {} : {})",
+                parameter,
+                if *negated { "not " } else { "" },
+                Self::expression(condition),
+                Self::expression(then),
+                Self::expression(otherwise)
+            ),
+        }
+    }
+
+    fn join(parts: impl Iterator<Item = String>, separator: &str) -> String {
+        parts.collect::<Vec<_>>().join(separator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::phpdoc::PhpDocParser;
+
+    fn hint(written: &str) -> Option<TypeHint> {
+        type_expression_to_hint(&PhpDocParser::parse_type_expression(written).unwrap())
+    }
+
+    fn object(name: &str) -> TypeHint {
+        TypeHint::Object(name.to_string())
+    }
+
+    #[test]
+    fn converts_simple_types() {
+        for (written, expected) in [
+            ("int", TypeHint::Int),
+            ("integer", TypeHint::Int),
+            ("string", TypeHint::String),
+            ("bool", TypeHint::Bool),
+            ("boolean", TypeHint::Bool),
+            ("float", TypeHint::Float),
+            ("double", TypeHint::Float),
+            ("array-key", TypeHint::ArrayKey),
+            ("non-empty-string", TypeHint::NonEmptyString),
+            ("list", object("array")),
+            ("non-empty-array", object("array")),
+            ("User", object("User")),
+            ("null", object("null")),
+        ] {
+            assert_eq!(hint(written), Some(expected), "{written}");
+        }
+    }
+
+    #[test]
+    fn converts_compound_types() {
+        let int = || Box::new(TypeHint::Int);
+        for (written, expected) in [
+            ("?int", TypeHint::Nullable(int())),
+            (
+                "int|User",
+                TypeHint::Union(vec![TypeHint::Int, object("User")]),
+            ),
+            ("int[]", TypeHint::Array(int())),
+            ("array<int>", TypeHint::Array(int())),
+            (
+                "non-empty-array<string, int>",
+                TypeHint::GenericArray {
+                    key: Box::new(TypeHint::String),
+                    value: int(),
+                },
+            ),
+            ("list<int>", TypeHint::List(int())),
+            ("non-empty-list<int>", TypeHint::List(int())),
+            (
+                "array{id: int, name?: string}",
+                TypeHint::ShapedArray(vec![
+                    ("id".to_string(), TypeHint::Int),
+                    ("name?".to_string(), TypeHint::String),
+                ]),
+            ),
+            ("key-of<array<string, int>>", TypeHint::String),
+            ("key-of<Countable>", TypeHint::ArrayKey),
+            ("value-of<list<int>>", TypeHint::Int),
+            (
+                "($flag is true ? int : null)",
+                TypeHint::Union(vec![TypeHint::Int, object("null")]),
+            ),
+        ] {
+            assert_eq!(hint(written), Some(expected), "{written}");
+        }
+    }
+
+    #[test]
+    fn unconvertible_types_are_skipped() {
+        for written in [
+            "mixed",
+            "void",
+            "never",
+            "Collection<User>",
+            "array<int, mixed>",
+            "array{id: mixed}",
+            "value-of<Countable>",
+            "mixed|void",
+        ] {
+            assert_eq!(hint(written), None, "{written}");
+        }
+
+        // Unions keep the members that can be checked
+        assert_eq!(
+            hint("int|mixed"),
+            Some(TypeHint::Union(vec![TypeHint::Int]))
+        );
+    }
+
+    #[test]
+    fn formats_hints() {
+        let cases = [
+            (
+                TypeHint::Union(vec![TypeHint::Int, TypeHint::Object("null".to_string())]),
+                "int|null",
+            ),
+            (
+                TypeHint::GenericArray {
+                    key: Box::new(TypeHint::ArrayKey),
+                    value: Box::new(TypeHint::List(Box::new(TypeHint::NonEmptyString))),
+                },
+                "array<array-key, list<non-empty-string>>",
+            ),
+            (
+                TypeHint::ShapedArray(vec![
+                    ("id".to_string(), TypeHint::Int),
+                    (
+                        "tags".to_string(),
+                        TypeHint::Array(Box::new(TypeHint::String)),
+                    ),
+                ]),
+                "array{id: int, tags: string[]}",
+            ),
+        ];
+
+        for (hint, expected) in cases {
+            assert_eq!(TypeFormatter::hint(&hint), expected);
+        }
+    }
+
+    #[test]
+    fn formats_expressions_as_written() {
+        for written in [
+            "?User",
+            "int|string|null",
+            "array<string, User[]>",
+            "array{name: string, tags: list<string>}",
+            "key-of<array<string, int>>",
+            "($flag is not true ? string : null)",
+        ] {
+            let expr = PhpDocParser::parse_type_expression(written).unwrap();
+            assert_eq!(TypeFormatter::expression(&expr), written);
+        }
+    }
+
+    #[test]
+    fn arena_interns_each_type_once() {
+        let arena = TypeArena::new();
+        let user = arena.intern(&hint("?User").unwrap());
+
+        assert_eq!(arena.intern(&hint("?User").unwrap()), user);
+        assert_eq!(arena.intern(&TypeHint::Unknown), TypeId::UNKNOWN);
+        assert_eq!(&*arena.display(user), "?User");
+        assert!(Arc::ptr_eq(&arena.display(user), &arena.display(user)));
+        assert_eq!(*arena.hint(user), hint("?User").unwrap());
+
+        let int = arena.intern(&TypeHint::Int);
+        let int_or_string = arena.intern(&hint("int|string").unwrap());
+        assert!(arena.is_compatible(int, int_or_string));
+        assert!(!arena.is_compatible(int_or_string, int));
+    }
+}
@@ -0,0 +1,279 @@
+use std::fmt;
+
+use anyhow::{Result, bail};
+use serde::Deserialize;
+
+/// The version of this php-checker build.
+pub const CURRENT: &str = env!("CARGO_PKG_VERSION");
+
+/// Fails unless this build satisfies the project's `required_version`.
+pub fn ensure_satisfies(required: &VersionRequirement) -> Result<()> {
+    let current = Version::current();
+    if !required.matches(current) {
+        bail!(
+            "this project requires php-checker {required} (`required_version` in its config), but {current} is installed; run `php-checker self-update <version>` with a matching release"
+        );
+    }
+    Ok(())
+}
+
+/// A `major.minor.patch` php-checker release. Pre-release and build suffixes
+/// (`-beta.1`, `+abc`) are ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// The running binary's version.
+    pub fn current() -> Self {
+        Self::parse(CURRENT).expect("CARGO_PKG_VERSION is a valid version")
+    }
+
+    /// Parses `0.5.2` or `v0.5.2`; missing minor and patch parts are zero.
+    pub fn parse(value: &str) -> Option<Self> {
+        let partial = Partial::parse(value.trim().trim_start_matches('v'))?;
+        Some(partial.lower())
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A version range in Cargo's syntax, e.g. `^0.5`, `~0.5.1`, `>=0.4, <0.6` or
+/// `0.5.*`. A bare version means the same as `^`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionRequirement {
+    source: String,
+    bounds: Vec<Bound>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bound {
+    AtLeast(Version),
+    Above(Version),
+    Below(Version),
+    AtMost(Version),
+}
+
+impl VersionRequirement {
+    pub fn parse(value: &str) -> Result<Self> {
+        let mut bounds = Vec::new();
+        for comparator in value.split(',') {
+            let comparator = comparator.trim();
+            let Some(parsed) = parse_comparator(comparator) else {
+                bail!("invalid version requirement \"{value}\"");
+            };
+            bounds.extend(parsed);
+        }
+        Ok(Self {
+            source: value.trim().to_string(),
+            bounds,
+        })
+    }
+
+    pub fn matches(&self, version: Version) -> bool {
+        self.bounds.iter().all(|bound| match *bound {
+            Bound::AtLeast(min) => version >= min,
+            Bound::Above(min) => version > min,
+            Bound::Below(max) => version < max,
+            Bound::AtMost(max) => version <= max,
+        })
+    }
+}
+
+impl fmt::Display for VersionRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionRequirement {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // YAML reads an unquoted `0.10` as the float 0.1, which would quietly pin
+        // the wrong releases, so numbers are refused rather than converted.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum RawRequirement {
+            Text(String),
+            Number(f64),
+        }
+
+        match RawRequirement::deserialize(deserializer)? {
+            RawRequirement::Text(text) => {
+                VersionRequirement::parse(&text).map_err(serde::de::Error::custom)
+            }
+            RawRequirement::Number(number) => Err(serde::de::Error::custom(format!(
+                "version requirement {number} must be quoted, e.g. \"{number}\"; YAML reads unquoted versions as numbers, so `0.10` would become 0.1"
+            ))),
+        }
+    }
+}
+
+/// A version with trailing parts left out (`0.5`) or wildcarded (`0.5.*`).
+#[derive(Clone, Copy)]
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+}
+
+impl Partial {
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.split(['-', '+']).next()?;
+        let mut parts = value.split('.');
+        let mut next = || -> Option<Option<u64>> {
+            match parts.next() {
+                None | Some("*" | "x" | "X") => Some(None),
+                Some(part) => part.parse().ok().map(Some),
+            }
+        };
+        let (major, minor, patch) = (next()?, next()?, next()?);
+        if parts.next().is_some() || (major.is_none() && minor.is_some()) {
+            return None;
+        }
+        if minor.is_none() && patch.is_some() {
+            return None;
+        }
+        Some(Self {
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    fn lower(self) -> Version {
+        Version::new(
+            self.major.unwrap_or(0),
+            self.minor.unwrap_or(0),
+            self.patch.unwrap_or(0),
+        )
+    }
+
+    /// The first version past everything this partial version covers:
+    /// `0.5` is followed by `0.6.0`, `1` by `2.0.0`.
+    fn successor(self) -> Option<Version> {
+        let major = self.major?;
+        Some(match (self.minor, self.patch) {
+            (None, _) => Version::new(major + 1, 0, 0),
+            (Some(minor), None) => Version::new(major, minor + 1, 0),
+            (Some(minor), Some(patch)) => Version::new(major, minor, patch + 1),
+        })
+    }
+
+    fn is_complete(self) -> bool {
+        self.patch.is_some()
+    }
+}
+
+fn parse_comparator(comparator: &str) -> Option<Vec<Bound>> {
+    let operators = [">=", "<=", ">", "<", "=", "^", "~"];
+    let (operator, version) = operators
+        .iter()
+        .find_map(|operator| {
+            comparator
+                .strip_prefix(operator)
+                .map(|rest| (*operator, rest))
+        })
+        .unwrap_or(("^", comparator));
+    let version = version.trim();
+    if version.is_empty() {
+        return None;
+    }
+    let partial = Partial::parse(version)?;
+    let lower = partial.lower();
+
+    // Anything within the partial version: `=0.5` is `>=0.5.0, <0.6.0`.
+    let within = |partial: Partial| match partial.successor() {
+        Some(upper) => vec![Bound::AtLeast(lower), Bound::Below(upper)],
+        None => Vec::new(),
+    };
+
+    Some(match operator {
+        "=" if partial.is_complete() => vec![Bound::AtLeast(lower), Bound::AtMost(lower)],
+        "=" => within(partial),
+        ">=" => vec![Bound::AtLeast(lower)],
+        "<" => vec![Bound::Below(lower)],
+        ">" if partial.is_complete() => vec![Bound::Above(lower)],
+        ">" => vec![Bound::AtLeast(partial.successor()?)],
+        "<=" if partial.is_complete() => vec![Bound::AtMost(lower)],
+        "<=" => vec![Bound::Below(partial.successor()?)],
+        "~" => match partial.minor {
+            Some(_) => within(Partial {
+                patch: None,
+                ..partial
+            }),
+            None => within(partial),
+        },
+        // `^` keeps the left-most non-zero part fixed.
+        _ => match (partial.major, partial.minor, partial.patch) {
+            (Some(0), Some(0), Some(_)) => within(partial),
+            (Some(0), Some(_), _) => within(Partial {
+                patch: None,
+                ..partial
+            }),
+            _ => within(Partial {
+                minor: None,
+                patch: None,
+                ..partial
+            }),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(requirement: &str, version: &str) -> bool {
+        VersionRequirement::parse(requirement)
+            .unwrap()
+            .matches(Version::parse(version).unwrap())
+    }
+
+    #[test]
+    fn caret_and_tilde_ranges() {
+        assert!(matches("^0.5", "0.5.0"));
+        assert!(matches("^0.5", "0.5.9"));
+        assert!(!matches("^0.5", "0.6.0"));
+        assert!(!matches("^0.5", "0.4.9"));
+        assert!(matches("1.2", "1.9.0"));
+        assert!(!matches("^1.2", "2.0.0"));
+        assert!(!matches("^0.0.3", "0.0.4"));
+        assert!(matches("~0.5.1", "0.5.3"));
+        assert!(!matches("~0.5.1", "0.5.0"));
+        assert!(!matches("~1", "2.0.0"));
+    }
+
+    #[test]
+    fn comparators_and_wildcards() {
+        assert!(matches(">=0.4, <0.6", "0.5.2"));
+        assert!(!matches(">=0.4, <0.6", "0.6.0"));
+        assert!(matches("=0.5.2", "0.5.2"));
+        assert!(!matches("=0.5.2", "0.5.3"));
+        assert!(matches("0.5.*", "0.5.7"));
+        assert!(!matches(">0.5", "0.5.9"));
+        assert!(matches("<=0.5", "0.5.9"));
+        assert!(matches("*", "3.1.4"));
+        assert!(matches("^0.1", "0.1.0-beta.1"));
+
+        assert!(VersionRequirement::parse(">=").is_err());
+        assert!(VersionRequirement::parse("0.five").is_err());
+    }
+}
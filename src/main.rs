@@ -1,18 +1,29 @@
 use php_checker::analyzer;
+use php_checker::analyzer::aliases;
 use php_checker::analyzer::fix;
-use php_checker::analyzer::{config::AnalyzerConfig, is_php_file};
+use php_checker::analyzer::golden;
+use php_checker::analyzer::scaffold::RuleScaffold;
+use php_checker::analyzer::schema;
+use php_checker::analyzer::score::ScoreReport;
+use php_checker::analyzer::self_update::{self, UpdateOutcome};
+use php_checker::analyzer::targets::Targets;
+use php_checker::analyzer::{
+    config::{AnalysisScope, AnalyzerConfig},
+    is_php_file,
+};
 use serde::Serialize;
 use serde_json::to_writer_pretty;
-use std::collections::HashSet;
-use std::fs;
+use std::collections::{BTreeMap, HashSet};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::channel;
+use std::process::ExitCode;
+use std::sync::mpsc::{Sender, channel};
+use std::thread;
 use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, anyhow, bail};
-use clap::{Parser, Subcommand, ValueEnum};
-use glob::glob;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use indicatif::{ProgressBar, ProgressStyle};
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 
@@ -20,14 +31,160 @@ use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 enum OutputFormat {
     Text,
     Json,
+    /// `file:line:col: severity: message [rule]`, one per line, for editor quickfix lists.
+    Compact,
+    /// TeamCity `##teamcity[inspection ...]` service messages.
+    Teamcity,
+}
+
+impl OutputFormat {
+    /// Whether status lines such as "Checking N file(s)..." may precede the
+    /// report; compact and JSON output must stay machine-readable.
+    fn shows_progress(self) -> bool {
+        !matches!(self, OutputFormat::Compact | OutputFormat::Json)
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum ScoreFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum SeverityThreshold {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum ConfidenceThreshold {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+enum ScopeArg {
+    All,
+    PublicApi,
+}
+
+impl ScopeArg {
+    fn scope(self) -> AnalysisScope {
+        match self {
+            ScopeArg::All => AnalysisScope::All,
+            ScopeArg::PublicApi => AnalysisScope::PublicApi,
+        }
+    }
+}
+
+impl SeverityThreshold {
+    fn severity(self) -> analyzer::Severity {
+        match self {
+            SeverityThreshold::Info => analyzer::Severity::Info,
+            SeverityThreshold::Warning => analyzer::Severity::Warning,
+            SeverityThreshold::Error => analyzer::Severity::Error,
+        }
+    }
+}
+
+impl ConfidenceThreshold {
+    fn confidence(self) -> analyzer::Confidence {
+        match self {
+            ConfidenceThreshold::Low => analyzer::Confidence::Low,
+            ConfidenceThreshold::Medium => analyzer::Confidence::Medium,
+            ConfidenceThreshold::High => analyzer::Confidence::High,
+        }
+    }
+}
+
+/// Analysis controls shared by `analyse` and `watch`.
+#[derive(Args, Clone)]
+struct AnalysisOptions {
+    /// Apply available fixes when diagnostics are emitted.
+    #[arg(long)]
+    fix: bool,
+    /// Preview the fix output without modifying files.
+    #[arg(long, requires = "fix")]
+    dry_run: bool,
+    /// Write nothing, but exit with status 3 if `--fix` would change any file.
+    #[arg(long, conflicts_with = "fix")]
+    check: bool,
+    /// Choose the CLI output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    /// Print diagnostics as they are found instead of sorted by file at the end.
+    #[arg(long)]
+    stream: bool,
+    /// Only run this rule or rule group (repeatable), e.g. `--rule cleanup`.
+    #[arg(long = "rule", value_name = "RULE")]
+    rules: Vec<String>,
+    /// Hide diagnostics below this severity.
+    #[arg(long, value_enum, value_name = "SEVERITY")]
+    min_severity: Option<SeverityThreshold>,
+    /// Hide heuristic diagnostics below this confidence; other diagnostics are kept.
+    #[arg(long, value_enum, value_name = "CONFIDENCE")]
+    min_confidence: Option<ConfidenceThreshold>,
+    /// Limit documentation and strict typing rules to the public API of non-test code.
+    #[arg(long, value_enum)]
+    scope: Option<ScopeArg>,
+    /// Report diagnostic messages in this language, e.g. `de`.
+    #[arg(long, value_name = "LOCALE")]
+    locale: Option<String>,
+    /// Also write the run's counts per severity and rule to this JSON file.
+    #[arg(long, value_name = "FILE")]
+    summary_file: Option<PathBuf>,
+}
+
+impl AnalysisOptions {
+    /// Layers the command-line flags over the loaded config.
+    fn apply(&self, config: Option<AnalyzerConfig>) -> AnalyzerConfig {
+        let mut config = config.unwrap_or_default();
+        config.stream |= self.stream;
+        for rule in &self.rules {
+            match aliases::find(rule) {
+                Some(alias) => {
+                    config.notices.push(alias.notice());
+                    config.only_rules.push(alias.new.to_string());
+                }
+                None => config.only_rules.push(rule.clone()),
+            }
+        }
+        if let Some(scope) = self.scope {
+            config.scope = scope.scope();
+        }
+        if let Some(locale) = &self.locale {
+            config.locale = Some(locale.clone());
+        }
+        config
+    }
+
+    fn reports(&self, diagnostic: &analyzer::Diagnostic) -> bool {
+        let confident = match (self.min_confidence, diagnostic.confidence) {
+            (Some(threshold), Some(confidence)) => confidence >= threshold.confidence(),
+            _ => true,
+        };
+        confident
+            && self
+                .min_severity
+                .is_none_or(|threshold| diagnostic.severity >= threshold.severity())
+    }
 }
 
 /// Entry point for the PHP checker CLI.
 #[derive(Parser)]
-#[command(author, version, about = "Static analysis prototype for PHP fixtures.")]
+#[command(
+    name = "php-checker",
+    author,
+    version,
+    about = "Static analysis prototype for PHP fixtures."
+)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Config file to use instead of `php_checker.yaml` in the analysed directory.
     #[arg(long, value_name = "FILE")]
     config: Option<PathBuf>,
 }
@@ -38,37 +195,99 @@ enum Commands {
     Analyse {
         /// Path to a PHP file or directory containing PHP files.
         path: PathBuf,
-        /// Apply available fixes when diagnostics are emitted.
-        #[arg(long)]
-        fix: bool,
-        /// Preview the fix output without modifying files.
-        #[arg(long, requires = "fix")]
-        dry_run: bool,
-        /// Choose the CLI output format.
-        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
-        format: OutputFormat,
+        #[command(flatten)]
+        options: AnalysisOptions,
     },
     /// Run once, then keep watching for PHP file changes.
     Watch {
         /// Path to a PHP file or directory containing PHP files.
         path: PathBuf,
-        /// Choose the CLI output format.
-        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
-        format: OutputFormat,
+        #[command(flatten)]
+        options: AnalysisOptions,
+    },
+    /// Rate the project's health from 0 to 100, from diagnostics, complexity and suppressions.
+    Score {
+        /// Path to a PHP file or directory containing PHP files.
+        path: PathBuf,
+        /// A report saved with `--format json` to show the change against.
+        #[arg(long, value_name = "FILE")]
+        compare_to: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = ScoreFormat::Text)]
+        format: ScoreFormat,
+    },
+    /// Compare analyser output on fixture corpora with their `.expect` files.
+    Golden {
+        /// Corpus directories; defaults to `tests/valid` and `tests/invalid`.
+        paths: Vec<PathBuf>,
+        /// Rewrite mismatching `.expect` files from the current output.
+        #[arg(long)]
+        bless: bool,
+    },
+    /// Manage the php-checker config file.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Replace this binary with the latest (or given) php-checker release.
+    SelfUpdate {
+        /// Release to install, e.g. `0.5.2`; defaults to the latest.
+        version: Option<String>,
+        /// Only report whether a newer release is available.
+        #[arg(long)]
+        check: bool,
+    },
+    /// Print the JSON Schema of `--format json` output.
+    Schema,
+    /// Print a shell completion script, e.g. `php-checker completions bash`.
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print the man page in roff format.
+    Manpage,
+    /// Tools for working on php-checker itself.
+    #[command(hide = true)]
+    Dev {
+        #[command(subcommand)]
+        command: DevCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum DevCommand {
+    /// Create a rule module, its tests and fixture, and register the rule.
+    NewRule {
+        /// Full rule name, e.g. `sanity/empty_catch`.
+        name: String,
+        /// Root of the php-checker repository.
+        #[arg(long, default_value = ".")]
+        root: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Replace retired rule names in the config and in ignore comments.
+    Migrate {
+        /// Project directory (or file) whose config and PHP files to update.
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// List the changes without writing them.
+        #[arg(long)]
+        dry_run: bool,
     },
 }
 
 struct AnalysisTargets {
-    canonical_targets: Vec<PathBuf>,
+    targets: Targets,
     analysis_root: PathBuf,
     config: Option<AnalyzerConfig>,
 }
 
 impl AnalysisTargets {
     fn new(path: &Path, config_path: Option<PathBuf>) -> Result<Self> {
-        let requested_targets = resolve_targets(path)?;
-        let canonical_targets = canonicalize_paths(requested_targets)?;
-        let analysis_root = derive_analysis_root(&canonical_targets);
+        let targets = resolve_targets(path, config_path.as_deref())?;
+        let analysis_root = derive_analysis_root(targets.roots());
 
         let config_file = AnalyzerConfig::find_config(config_path, &analysis_root);
         let config = if let Some(path) = config_file {
@@ -78,14 +297,14 @@ impl AnalysisTargets {
         };
 
         Ok(Self {
-            canonical_targets,
+            targets,
             analysis_root,
             config,
         })
     }
 
     fn canonical_targets(&self) -> &[PathBuf] {
-        &self.canonical_targets
+        self.targets.roots()
     }
 
     fn analysis_root(&self) -> &Path {
@@ -96,34 +315,169 @@ impl AnalysisTargets {
         self.config.clone()
     }
 
-    fn collect_php_files(&self) -> Result<Vec<PathBuf>> {
-        analyzer::collect_php_files_from_roots(&self.canonical_targets)
+    fn collect_php_files(&self) -> Vec<PathBuf> {
+        self.targets.php_files().to_vec()
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.targets.is_excluded(path)
+    }
+
+    fn warn_unmatched(&self) {
+        for pattern in self.targets.unmatched() {
+            eprintln!("warning: no files matched \"{pattern}\"");
+        }
     }
 }
 
-fn main() -> Result<()> {
+/// Exit status when `--fix` leaves errors that have no automatic fix.
+const EXIT_ERRORS_REMAIN: u8 = 2;
+/// Exit status when `--check` finds files that `--fix` would change.
+const EXIT_FIXES_PENDING: u8 = 3;
+
+fn main() -> Result<ExitCode> {
     let Cli { command, config } = Cli::parse();
 
     match command {
-        Commands::Analyse {
+        Commands::Analyse { path, options } => run_analysis(path, config, &options),
+        Commands::Watch { path, options } => {
+            run_watch_mode(path, config, &options).map(|()| ExitCode::SUCCESS)
+        }
+        Commands::Score {
             path,
-            fix,
-            dry_run,
+            compare_to,
             format,
-        } => run_analysis(path, config, fix, dry_run, format),
-        Commands::Watch { path, format } => run_watch_mode(path, config, format),
+        } => run_score(path, config, compare_to, format).map(|()| ExitCode::SUCCESS),
+        Commands::Golden { paths, bless } => run_golden(paths, bless).map(|()| ExitCode::SUCCESS),
+        Commands::Config {
+            command: ConfigCommand::Migrate { path, dry_run },
+        } => run_config_migrate(path, config, dry_run).map(|()| ExitCode::SUCCESS),
+        Commands::SelfUpdate { version, check } => {
+            run_self_update(version.as_deref(), check).map(|()| ExitCode::SUCCESS)
+        }
+        Commands::Schema => {
+            print!("{}", schema::OUTPUT_SCHEMA);
+            Ok(ExitCode::SUCCESS)
+        }
+        Commands::Completions { shell } => {
+            let mut command = Cli::command();
+            let name = command.get_name().to_owned();
+            clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+            Ok(ExitCode::SUCCESS)
+        }
+        Commands::Dev {
+            command: DevCommand::NewRule { name, root },
+        } => run_new_rule(&name, &root).map(|()| ExitCode::SUCCESS),
+        Commands::Manpage => {
+            clap_mangen::Man::new(Cli::command())
+                .render(&mut io::stdout())
+                .context("failed to write the man page")?;
+            Ok(ExitCode::SUCCESS)
+        }
     }
 }
 
-fn run_analysis(
+fn run_new_rule(name: &str, root: &Path) -> Result<()> {
+    let scaffold = RuleScaffold::parse(name)?;
+    for path in scaffold.create(root)? {
+        println!("{}", path.display());
+    }
+    println!(
+        "Next: implement the rule and its tests, add its messages to src/analyzer/messages/de.yaml, \
+         and record the fixture's diagnostics with `php-checker golden --bless`."
+    );
+    Ok(())
+}
+
+fn run_score(
     path: PathBuf,
     config_path: Option<PathBuf>,
-    fix: bool,
-    dry_run: bool,
-    output_format: OutputFormat,
+    compare_to: Option<PathBuf>,
+    format: ScoreFormat,
 ) -> Result<()> {
+    // Read the earlier report first, so a wrong path fails before the analysis.
+    let previous = compare_to.as_deref().map(ScoreReport::load).transpose()?;
+    let targets = AnalysisTargets::new(&path, config_path)?;
+    targets.warn_unmatched();
+    let php_files = targets.collect_php_files();
+    if php_files.is_empty() {
+        bail!(
+            "No PHP files found under {}",
+            targets.analysis_root().display()
+        );
+    }
+
+    let mut analyzer = analyzer::Analyzer::new(targets.config())?;
+    let mut report = analyzer.score(&php_files, targets.analysis_root())?;
+    if let Some(previous) = &previous {
+        report.compare_to(previous);
+    }
+    match format {
+        ScoreFormat::Text => print!("{}", report.format(previous.as_ref())),
+        ScoreFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    Ok(())
+}
+
+fn run_config_migrate(path: PathBuf, config_path: Option<PathBuf>, dry_run: bool) -> Result<()> {
+    let targets = AnalysisTargets::new(&path, config_path.clone())?;
+    targets.warn_unmatched();
+    let config_file = AnalyzerConfig::find_config(config_path, targets.analysis_root());
+    let php_files = targets.collect_php_files();
+
+    let migrated = aliases::migrate(config_file.as_deref(), &php_files, dry_run)?;
+    if migrated.is_empty() {
+        println!("No retired rule names found.");
+        return Ok(());
+    }
+    let verb = if dry_run { "would rename" } else { "renamed" };
+    for file in &migrated {
+        println!(
+            "{}: {verb} {} rule name(s)",
+            file.path.display(),
+            file.applied
+        );
+    }
+    Ok(())
+}
+
+fn run_self_update(version: Option<&str>, check: bool) -> Result<()> {
+    match self_update::run(version, check)? {
+        UpdateOutcome::UpToDate(current) => println!("php-checker {current} is up to date"),
+        UpdateOutcome::Available { current, latest } => println!(
+            "php-checker {latest} is available (installed: {current}); run `php-checker self-update` to install it"
+        ),
+        UpdateOutcome::Updated { from, to } => println!("Updated php-checker from {from} to {to}"),
+    }
+    Ok(())
+}
+
+fn run_golden(paths: Vec<PathBuf>, bless: bool) -> Result<()> {
+    let roots = if paths.is_empty() {
+        vec![PathBuf::from("tests/valid"), PathBuf::from("tests/invalid")]
+    } else {
+        paths
+    };
+
+    let report = golden::run(&roots, bless)?;
+    print!("{}", report.format());
+    if !report.is_success() {
+        bail!(
+            "{} fixture(s) differ from their expectations",
+            report.failures.len()
+        );
+    }
+    Ok(())
+}
+
+fn run_analysis(
+    path: PathBuf,
+    config_path: Option<PathBuf>,
+    options: &AnalysisOptions,
+) -> Result<ExitCode> {
     let targets = AnalysisTargets::new(&path, config_path)?;
-    let php_files = targets.collect_php_files()?;
+    targets.warn_unmatched();
+    let php_files = targets.collect_php_files();
     let php_file_count = php_files.len();
 
     if php_file_count == 0 {
@@ -131,70 +485,206 @@ fn run_analysis(
             "No PHP files found under {}",
             targets.analysis_root().display()
         );
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
-    println!("Checking {} file(s)...", php_file_count);
+    if options.format.shows_progress() {
+        println!("Checking {} file(s)...", php_file_count);
+    }
 
-    let mut analyzer = analyzer::Analyzer::new(targets.config())?;
-    let show_progress = matches!(output_format, OutputFormat::Text);
-    let (diagnostics, diagnostics_streamed, duration) = collect_diagnostics(
+    let config = options.apply(targets.config());
+    for notice in &config.notices {
+        eprintln!("warning: {notice}");
+    }
+    let stream = config.stream;
+    let mut analyzer = analyzer::Analyzer::new(Some(config))?;
+    check_files(
         &mut analyzer,
         &php_files,
         targets.analysis_root(),
-        output_format,
-        show_progress,
-    )?;
+        options,
+        stream,
+    )
+}
 
-    let fixes = analyzer.fix_files(&php_files)?;
-    let fixable_count = fixes.values().map(Vec::len).sum::<usize>();
+/// Analyses `paths`, reports the diagnostics and applies fixes when asked to.
+fn check_files(
+    analyzer: &mut analyzer::Analyzer,
+    paths: &[PathBuf],
+    root: &Path,
+    options: &AnalysisOptions,
+    stream: bool,
+) -> Result<ExitCode> {
+    // TeamCity only shows inspections whose type was registered first.
+    if matches!(options.format, OutputFormat::Teamcity) {
+        for (name, description) in analyzer.rule_descriptions() {
+            println!("{}", analyzer::teamcity_inspection_type(name, description));
+        }
+    }
 
-    emit_output(
-        &diagnostics,
-        output_format,
-        diagnostics_streamed,
-        php_file_count,
-        duration,
-        fixable_count,
-    )?;
+    // Fixes are collected first so streamed diagnostics can already say whether they are fixable.
+    let fixes = analyzer.fix_set(paths)?;
+    let (mut diagnostics, duration) =
+        collect_diagnostics(analyzer, paths, root, options, stream, &fixes)?;
+    diagnostics.retain(|diag| options.reports(diag));
+    sort_diagnostics(&mut diagnostics);
+    let stats = JsonStats::new(&diagnostics, paths.len(), duration, fixes.edit_count());
 
-    if fix {
-        if fixes.is_empty() {
-            println!("No fixable diagnostics were detected.");
-        } else if dry_run {
-            for (file, edits) in &fixes {
-                let source = fs::read_to_string(&file)
-                    .with_context(|| format!("failed to read {}", file.display()))?;
-                let patched = fix::apply_text_edits(&source, edits);
-                println!("--- {} ---", file.display());
-                print!("{patched}");
-                if !patched.ends_with('\n') {
-                    println!();
-                }
-            }
-        } else {
-            for (file, edits) in &fixes {
-                let source = fs::read_to_string(&file)
-                    .with_context(|| format!("failed to read {}", file.display()))?;
-                let patched = fix::apply_text_edits(&source, edits);
-                fs::write(&file, patched)
-                    .with_context(|| format!("failed to write {}", file.display()))?;
-                println!("Fixed {}", file.display());
+    emit_output(&diagnostics, paths, options.format, stream, &stats)?;
+    if let Some(summary_file) = &options.summary_file {
+        write_summary(summary_file, &diagnostics, stats)?;
+    }
+
+    if options.check {
+        let staged = fix::stage(&fixes.edits())?;
+        if staged.is_empty() {
+            println!("No files would be changed by --fix.");
+            return Ok(ExitCode::SUCCESS);
+        }
+        print_fix_summary(&staged, FixMode::Check, &BTreeMap::new());
+        return Ok(ExitCode::from(EXIT_FIXES_PENDING));
+    }
+
+    if options.fix {
+        let remaining = apply_fixes(analyzer, paths, root, options, &fixes.edits())?;
+        let errors_remain = remaining
+            .iter()
+            .any(|diag| matches!(diag.severity, analyzer::Severity::Error));
+        if errors_remain {
+            return Ok(ExitCode::from(EXIT_ERRORS_REMAIN));
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FixMode {
+    Write,
+    DryRun,
+    Check,
+}
+
+/// Applies (or with `--dry-run`, prints) the fixes, then returns the reported
+/// diagnostics the patched files still have.
+fn apply_fixes(
+    analyzer: &mut analyzer::Analyzer,
+    paths: &[PathBuf],
+    root: &Path,
+    options: &AnalysisOptions,
+    fixes: &BTreeMap<PathBuf, Vec<fix::TextEdit>>,
+) -> Result<Vec<analyzer::Diagnostic>> {
+    // Every file is patched and re-parsed before the first one is written.
+    let staged = fix::stage(fixes)?;
+    let mut remaining = analyzer.analyse_patched(paths, root, &staged)?;
+    remaining.retain(|diag| options.reports(diag));
+
+    if staged.is_empty() {
+        println!("No fixable diagnostics were detected.");
+        print_remaining(&remaining);
+        return Ok(remaining);
+    }
+
+    let mut remaining_per_file = BTreeMap::new();
+    for diag in &remaining {
+        *remaining_per_file.entry(diag.file.clone()).or_insert(0) += 1;
+    }
+
+    let dry_run = options.dry_run;
+    if dry_run {
+        for file in &staged {
+            println!("--- {} ---", file.path.display());
+            print!("{}", file.contents);
+            if !file.contents.ends_with('\n') {
+                println!();
             }
         }
+    } else {
+        fix::write_staged(&staged)?;
     }
 
-    Ok(())
+    let mode = if dry_run {
+        FixMode::DryRun
+    } else {
+        FixMode::Write
+    };
+    print_fix_summary(&staged, mode, &remaining_per_file);
+    print_remaining(&remaining);
+    Ok(remaining)
+}
+
+fn print_remaining(remaining: &[analyzer::Diagnostic]) {
+    let errors = remaining
+        .iter()
+        .filter(|diag| matches!(diag.severity, analyzer::Severity::Error))
+        .count();
+    println!(
+        "{} diagnostic(s) remain without an automatic fix ({errors} error(s))",
+        remaining.len()
+    );
+}
+
+/// Prints one row per touched file with its applied and skipped edits and, after
+/// fixing, the diagnostics left in it; then the totals.
+fn print_fix_summary(
+    staged: &[fix::StagedFile],
+    mode: FixMode,
+    remaining: &BTreeMap<PathBuf, usize>,
+) {
+    let paths: Vec<String> = staged
+        .iter()
+        .map(|file| file.path.display().to_string())
+        .collect();
+    let width = paths
+        .iter()
+        .map(String::len)
+        .max()
+        .unwrap_or(0)
+        .max("File".len());
+
+    println!();
+    match mode {
+        FixMode::Write => println!("Fix summary:"),
+        FixMode::DryRun => println!("Fix summary (dry run, no files were written):"),
+        FixMode::Check => println!("Files --fix would change:"),
+    }
+    if mode == FixMode::Check {
+        println!("  {:<width$}  {:>7}  {:>7}", "File", "Applied", "Skipped");
+        for (path, file) in paths.iter().zip(staged) {
+            println!("  {path:<width$}  {:>7}  {:>7}", file.applied, file.skipped);
+        }
+    } else {
+        println!(
+            "  {:<width$}  {:>7}  {:>7}  {:>9}",
+            "File", "Applied", "Skipped", "Remaining"
+        );
+        for (path, file) in paths.iter().zip(staged) {
+            let left = remaining.get(&file.path).copied().unwrap_or(0);
+            println!(
+                "  {path:<width$}  {:>7}  {:>7}  {left:>9}",
+                file.applied, file.skipped
+            );
+        }
+    }
+
+    let applied: usize = staged.iter().map(|file| file.applied).sum();
+    let skipped: usize = staged.iter().map(|file| file.skipped).sum();
+    println!(
+        "{} file(s) touched, {applied} edit(s) applied, {skipped} skipped due to conflicts",
+        staged.len()
+    );
 }
 
 fn collect_diagnostics(
     analyzer: &mut analyzer::Analyzer,
     paths: &[PathBuf],
     root: &Path,
-    output_format: OutputFormat,
-    show_progress: bool,
-) -> Result<(Vec<analyzer::Diagnostic>, bool, Duration)> {
-    let progress = if show_progress {
+    options: &AnalysisOptions,
+    stream: bool,
+    fixes: &fix::FixSet,
+) -> Result<(Vec<analyzer::Diagnostic>, Duration)> {
+    let output_format = options.format;
+    let progress = if matches!(output_format, OutputFormat::Text) {
         let pb = ProgressBar::new(paths.len() as u64);
         pb.set_style(
             ProgressStyle::default_bar()
@@ -207,89 +697,198 @@ fn collect_diagnostics(
         None
     };
 
+    let print_diagnostic = |diag: &analyzer::Diagnostic| {
+        if !options.reports(diag) {
+            return;
+        }
+        let mut diag = diag.clone();
+        fixes.link(&mut diag);
+        print_streamed(&diag, output_format, progress.as_ref());
+    };
+    let on_diagnostic: Option<&(dyn Fn(&analyzer::Diagnostic) + Sync)> =
+        stream.then_some(&print_diagnostic);
+
     let start = Instant::now();
-    let diagnostics = analyzer.analyse_files_with_progress(paths, root, progress.as_ref())?;
+    let mut diagnostics =
+        analyzer.analyse_files_streaming(paths, root, progress.as_ref(), on_diagnostic)?;
+    diagnostics.iter_mut().for_each(|diag| fixes.link(diag));
     if let Some(pb) = &progress {
         pb.finish_and_clear();
     }
 
-    let diagnostics_streamed = show_progress && matches!(output_format, OutputFormat::Text);
-    Ok((diagnostics, diagnostics_streamed, start.elapsed()))
+    Ok((diagnostics, start.elapsed()))
+}
+
+/// Prints one diagnostic as `--stream` does. Streamed JSON is one diagnostic
+/// object per line, followed by the stats line.
+fn print_streamed(
+    diag: &analyzer::Diagnostic,
+    output_format: OutputFormat,
+    progress: Option<&ProgressBar>,
+) {
+    match (output_format, progress) {
+        (OutputFormat::Text, Some(pb)) => pb.println(format!("{diag}")),
+        (OutputFormat::Text, None) => println!("{diag}"),
+        (OutputFormat::Json, _) => match serde_json::to_string(&diag.to_json()) {
+            Ok(line) => println!("{line}"),
+            Err(err) => eprintln!("failed to serialise diagnostic: {err}"),
+        },
+        (OutputFormat::Compact, _) => println!("{}", diag.to_compact()),
+        (OutputFormat::Teamcity, _) => println!("{}", diag.to_teamcity()),
+    }
+}
+
+/// Orders diagnostics by file, then position, so buffered output is stable.
+fn sort_diagnostics(diagnostics: &mut [analyzer::Diagnostic]) {
+    diagnostics.sort_by(|left, right| {
+        let position = |diag: &analyzer::Diagnostic| {
+            diag.span
+                .as_ref()
+                .map(|span| (span.start.row, span.start.column))
+        };
+        left.file
+            .cmp(&right.file)
+            .then_with(|| position(left).cmp(&position(right)))
+            .then_with(|| left.message.cmp(&right.message))
+    });
 }
 
+/// Prints the report; JSON output also lists the suppression directives in `paths`.
 fn emit_output(
     diagnostics: &[analyzer::Diagnostic],
+    paths: &[PathBuf],
     output_format: OutputFormat,
-    diagnostics_streamed: bool,
-    file_count: usize,
-    duration: Duration,
-    fixable_count: usize,
+    streamed: bool,
+    stats: &JsonStats,
 ) -> Result<()> {
-    let error_count = diagnostics
-        .iter()
-        .filter(|d| matches!(d.severity, analyzer::Severity::Error))
-        .count();
-    let warning_count = diagnostics
-        .iter()
-        .filter(|d| matches!(d.severity, analyzer::Severity::Warning))
-        .count();
-
     match output_format {
         OutputFormat::Text => {
             if diagnostics.is_empty() {
                 println!(
                     "Analysis complete ▸ {} PHP file(s), no diagnostics emitted yet.",
-                    file_count
+                    stats.files
                 );
-            } else if !diagnostics_streamed {
-                for diag in diagnostics {
-                    println!("{diag}");
+            } else if !streamed {
+                for group in diagnostics.chunk_by(|left, right| left.file == right.file) {
+                    println!(
+                        "\x1b[1m{}\x1b[0m ({} diagnostic(s))\n",
+                        group[0].file.display(),
+                        group.len()
+                    );
+                    for diag in group {
+                        println!("{diag}");
+                    }
                 }
             }
 
             println!(
                 "Stats ▸ {} file(s) | {} error(s), {} warning(s) | {:.2}s ({} potentially fixable with --fix)",
-                file_count,
-                error_count,
-                warning_count,
-                duration.as_secs_f64(),
-                fixable_count
+                stats.files, stats.errors, stats.warnings, stats.duration_seconds, stats.fixable
             );
         }
         OutputFormat::Json => {
-            let stats = JsonStats {
-                files: file_count,
-                errors: error_count,
-                warnings: warning_count,
-                fixable: fixable_count,
-                duration_seconds: duration.as_secs_f64(),
-            };
-            let output = JsonOutput {
-                diagnostics: diagnostics.iter().map(|diag| diag.to_json()).collect(),
-                stats,
-            };
-
+            let stats = stats.clone();
+            let mut suppressions = Vec::new();
+            for path in paths {
+                suppressions.extend(analyzer::suppressions(path)?);
+            }
             let stdout = io::stdout();
             let mut handle = stdout.lock();
-            to_writer_pretty(&mut handle, &output)?;
+            if streamed {
+                let line = JsonStatsLine {
+                    schema_version: schema::SCHEMA_VERSION,
+                    stats,
+                    suppressions,
+                };
+                serde_json::to_writer(&mut handle, &line)?;
+            } else {
+                let output = JsonOutput {
+                    schema_version: schema::SCHEMA_VERSION,
+                    diagnostics: diagnostics.iter().map(|diag| diag.to_json()).collect(),
+                    stats,
+                    suppressions,
+                };
+                to_writer_pretty(&mut handle, &output)?;
+            }
             handle.write_all(b"\n")?;
         }
+        OutputFormat::Compact => {
+            if !streamed {
+                for diag in diagnostics {
+                    println!("{}", diag.to_compact());
+                }
+            }
+        }
+        OutputFormat::Teamcity => {
+            if !streamed {
+                for diag in diagnostics {
+                    println!("{}", diag.to_teamcity());
+                }
+            }
+        }
     }
 
     Ok(())
 }
 
-fn run_watch_mode(path: PathBuf, config: Option<PathBuf>, format: OutputFormat) -> Result<()> {
-    run_analysis(path.clone(), config.clone(), false, false, format)?;
-    watch_changes(path, config, format)
+fn write_summary(
+    path: &Path,
+    diagnostics: &[analyzer::Diagnostic],
+    stats: JsonStats,
+) -> Result<()> {
+    let mut rules = BTreeMap::new();
+    for rule in diagnostics.iter().filter_map(|diag| diag.rule_name.clone()) {
+        *rules.entry(rule).or_insert(0) += 1;
+    }
+    let summary = JsonSummary {
+        schema_version: schema::SCHEMA_VERSION,
+        stats,
+        rules,
+    };
+
+    let mut contents = serde_json::to_string_pretty(&summary)?;
+    contents.push('\n');
+    std::fs::write(path, contents)
+        .with_context(|| format!("failed to write summary file {}", path.display()))
+}
+
+fn run_watch_mode(path: PathBuf, config: Option<PathBuf>, options: &AnalysisOptions) -> Result<()> {
+    // Fixes from the initial run are written before watching starts, so they
+    // cannot race with edits being saved.
+    if options.fix || options.check {
+        run_analysis(path.clone(), config.clone(), options)?;
+        return watch_changes(path, config, options, false);
+    }
+    watch_changes(path, config, options, true)
+}
+
+/// What the watch loop waits for: a file system event, or the initial full
+/// analysis finishing in the background.
+enum WatchMessage {
+    Event(notify::Result<Event>),
+    InitialRun(Result<InitialRun>),
+}
+
+/// The initial full analysis, run on a background pool while watching starts.
+struct InitialRun {
+    paths: Vec<PathBuf>,
+    diagnostics: Vec<analyzer::Diagnostic>,
+    fixable: usize,
+    duration: Duration,
 }
 
-fn watch_changes(path: PathBuf, config: Option<PathBuf>, format: OutputFormat) -> Result<()> {
+fn watch_changes(
+    path: PathBuf,
+    config: Option<PathBuf>,
+    options: &AnalysisOptions,
+    prefetch: bool,
+) -> Result<()> {
     let targets = AnalysisTargets::new(&path, config)?;
-    let (tx, rx) = channel::<notify::Result<Event>>();
+    let (tx, rx) = channel::<WatchMessage>();
+    let event_tx = tx.clone();
     let mut watcher = RecommendedWatcher::new(
         move |res| {
-            let _ = tx.send(res);
+            let _ = event_tx.send(WatchMessage::Event(res));
         },
         Config::default(),
     )
@@ -306,17 +905,52 @@ fn watch_changes(path: PathBuf, config: Option<PathBuf>, format: OutputFormat) -
             .with_context(|| format!("failed to watch {}", target.display()))?;
     }
 
+    let config = options.apply(targets.config());
+    let stream = config.stream;
+    let mut analyzer = analyzer::Analyzer::new(Some(config.clone()))?;
+
+    // Files analysed after a change while the initial run is still going;
+    // their fresher diagnostics replace the initial run's.
+    let mut reanalysed = HashSet::new();
+    let mut initial_run_pending = false;
+    if prefetch {
+        targets.warn_unmatched();
+        for notice in &config.notices {
+            eprintln!("warning: {notice}");
+        }
+        let php_files = targets.collect_php_files();
+        if php_files.is_empty() {
+            println!(
+                "No PHP files found under {}",
+                targets.analysis_root().display()
+            );
+        } else {
+            if options.format.shows_progress() {
+                println!("Checking {} file(s) in the background...", php_files.len());
+            }
+            spawn_initial_run(config, php_files, targets.analysis_root(), tx);
+            initial_run_pending = true;
+        }
+    }
+
     println!("Watching for changes (Ctrl+C to exit)...");
 
-    let mut analyzer = analyzer::Analyzer::new(targets.config())?;
     loop {
         match rx.recv() {
-            Ok(Ok(event)) => {
-                handle_watch_event(event, &mut analyzer, &targets, format)?;
+            Ok(WatchMessage::Event(Ok(event))) => {
+                let analysed = handle_watch_event(event, &mut analyzer, &targets, options, stream)?;
+                if initial_run_pending {
+                    reanalysed.extend(analysed);
+                }
             }
-            Ok(Err(err)) => {
+            Ok(WatchMessage::Event(Err(err))) => {
                 eprintln!("watch error: {err}");
             }
+            Ok(WatchMessage::InitialRun(run)) => {
+                initial_run_pending = false;
+                report_initial_run(&analyzer, run?, &reanalysed, options, stream)?;
+                reanalysed.clear();
+            }
             Err(err) => {
                 return Err(anyhow!("file watch channel closed: {err}"));
             }
@@ -324,12 +958,87 @@ fn watch_changes(path: PathBuf, config: Option<PathBuf>, format: OutputFormat) -
     }
 }
 
+/// Analyses `paths` on a thread pool half the size of the default one, so
+/// files changed meanwhile are still checked promptly on the global pool.
+fn spawn_initial_run(
+    config: AnalyzerConfig,
+    paths: Vec<PathBuf>,
+    root: &Path,
+    tx: Sender<WatchMessage>,
+) {
+    let root = root.to_path_buf();
+    thread::spawn(move || {
+        let run = || -> Result<InitialRun> {
+            let threads = thread::available_parallelism().map_or(1, |count| count.get() / 2);
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads.max(1))
+                .thread_name(|index| format!("php-checker-initial-{index}"))
+                .build()
+                .context("failed to start the background analysis")?;
+
+            pool.install(|| {
+                let mut analyzer = analyzer::Analyzer::new(Some(config))?;
+                let start = Instant::now();
+                let fixes = analyzer.fix_set(&paths)?;
+                let mut diagnostics =
+                    analyzer.analyse_files_streaming(&paths, &root, None, None)?;
+                diagnostics.iter_mut().for_each(|diag| fixes.link(diag));
+
+                Ok(InitialRun {
+                    paths,
+                    diagnostics,
+                    fixable: fixes.edit_count(),
+                    duration: start.elapsed(),
+                })
+            })
+        };
+        let _ = tx.send(WatchMessage::InitialRun(run()));
+    });
+}
+
+/// Reports the initial run, minus the files already re-analysed after a change.
+fn report_initial_run(
+    analyzer: &analyzer::Analyzer,
+    run: InitialRun,
+    reanalysed: &HashSet<PathBuf>,
+    options: &AnalysisOptions,
+    stream: bool,
+) -> Result<()> {
+    if matches!(options.format, OutputFormat::Teamcity) {
+        for (name, description) in analyzer.rule_descriptions() {
+            println!("{}", analyzer::teamcity_inspection_type(name, description));
+        }
+    }
+
+    let mut diagnostics = run.diagnostics;
+    diagnostics.retain(|diag| options.reports(diag) && !reanalysed.contains(&diag.file));
+    sort_diagnostics(&mut diagnostics);
+    let stats = JsonStats::new(&diagnostics, run.paths.len(), run.duration, run.fixable);
+
+    if stream {
+        for diag in &diagnostics {
+            print_streamed(diag, options.format, None);
+        }
+    }
+    let paths: Vec<PathBuf> = run
+        .paths
+        .into_iter()
+        .filter(|path| !reanalysed.contains(path))
+        .collect();
+    emit_output(&diagnostics, &paths, options.format, stream, &stats)?;
+    if let Some(summary_file) = &options.summary_file {
+        write_summary(summary_file, &diagnostics, stats)?;
+    }
+    Ok(())
+}
+
 fn handle_watch_event(
     event: Event,
     analyzer: &mut analyzer::Analyzer,
     targets: &AnalysisTargets,
-    format: OutputFormat,
-) -> Result<()> {
+    options: &AnalysisOptions,
+    stream: bool,
+) -> Result<Vec<PathBuf>> {
     let mut changed_files = HashSet::new();
 
     for path in event.paths {
@@ -337,14 +1046,14 @@ fn handle_watch_event(
             continue;
         }
         if let Ok(canonical) = path.canonicalize() {
-            if canonical.is_file() {
+            if canonical.is_file() && !targets.is_excluded(&canonical) {
                 changed_files.insert(canonical);
             }
         }
     }
 
     if changed_files.is_empty() {
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     let mut changed_vec: Vec<PathBuf> = changed_files.into_iter().collect();
@@ -355,58 +1064,25 @@ fn handle_watch_event(
         println!("  {}", file.display());
     }
 
-    let (diagnostics, diagnostics_streamed, duration) = collect_diagnostics(
+    // Files rewritten by `--fix` trigger one more event, which finds nothing left to fix.
+    check_files(
         analyzer,
         &changed_vec,
         targets.analysis_root(),
-        format,
-        false,
-    )?;
-
-    let fixes = analyzer.fix_files(&changed_vec)?;
-    let fixable_count = fixes.values().map(Vec::len).sum::<usize>();
-
-    emit_output(
-        &diagnostics,
-        format,
-        diagnostics_streamed,
-        changed_vec.len(),
-        duration,
-        fixable_count,
+        options,
+        stream,
     )?;
-
-    Ok(())
-}
-
-fn resolve_targets(path: &Path) -> Result<Vec<PathBuf>> {
-    if path_contains_glob(path) {
-        let pattern = path.as_os_str().to_string_lossy().into_owned();
-        let matches = glob(&pattern)
-            .with_context(|| format!("invalid glob pattern \"{pattern}\""))?
-            .collect::<Result<Vec<_>, _>>()
-            .with_context(|| format!("failed to read entries for pattern \"{pattern}\""))?;
-
-        if matches.is_empty() {
-            bail!("no files matched \"{pattern}\"");
-        }
-
-        Ok(matches)
-    } else {
-        Ok(vec![path.to_path_buf()])
-    }
+    Ok(changed_vec)
 }
 
-fn canonicalize_paths(paths: Vec<PathBuf>) -> Result<Vec<PathBuf>> {
-    let mut canonical_paths = Vec::new();
-    for path in paths {
-        let canonical_path = path
-            .canonicalize()
-            .with_context(|| format!("failed to access {}", path.display()))?;
-        canonical_paths.push(canonical_path);
-    }
-    canonical_paths.sort();
-    canonical_paths.dedup();
-    Ok(canonical_paths)
+/// Resolves the `PATH` argument. Relative entries start from the directory
+/// of `--config` when one is given, else from the current directory.
+fn resolve_targets(path: &Path, config_path: Option<&Path>) -> Result<Targets> {
+    let base = match config_path.and_then(Path::parent) {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    Targets::resolve(&path.to_string_lossy(), &base)
 }
 
 fn derive_analysis_root(targets: &[PathBuf]) -> PathBuf {
@@ -454,24 +1130,62 @@ fn ancestors_from_root(path: &Path) -> Vec<PathBuf> {
     ancestors
 }
 
-fn path_contains_glob(path: &Path) -> bool {
-    path.as_os_str()
-        .to_string_lossy()
-        .chars()
-        .any(|c| matches!(c, '*' | '?' | '[' | ']' | '{' | '}'))
-}
-
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 struct JsonStats {
     files: usize,
     errors: usize,
     warnings: usize,
+    infos: usize,
     fixable: usize,
     duration_seconds: f64,
 }
 
+impl JsonStats {
+    fn new(
+        diagnostics: &[analyzer::Diagnostic],
+        files: usize,
+        duration: Duration,
+        fixable: usize,
+    ) -> Self {
+        let count = |severity: analyzer::Severity| {
+            diagnostics
+                .iter()
+                .filter(|diag| diag.severity == severity)
+                .count()
+        };
+        Self {
+            files,
+            errors: count(analyzer::Severity::Error),
+            warnings: count(analyzer::Severity::Warning),
+            infos: count(analyzer::Severity::Info),
+            fixable,
+            duration_seconds: duration.as_secs_f64(),
+        }
+    }
+}
+
+/// `--summary-file` contents: the stats block plus the number of diagnostics
+/// each rule reported, without the diagnostics themselves.
+#[derive(Serialize)]
+struct JsonSummary {
+    schema_version: u32,
+    stats: JsonStats,
+    rules: BTreeMap<String, usize>,
+}
+
+/// `--format json` output, described by `schema/output.schema.json`.
 #[derive(Serialize)]
 struct JsonOutput {
+    schema_version: u32,
     diagnostics: Vec<analyzer::DiagnosticJson>,
     stats: JsonStats,
+    suppressions: Vec<analyzer::SuppressionJson>,
+}
+
+/// Final line of streamed JSON output, after one line per diagnostic.
+#[derive(Serialize)]
+struct JsonStatsLine {
+    schema_version: u32,
+    stats: JsonStats,
+    suppressions: Vec<analyzer::SuppressionJson>,
 }
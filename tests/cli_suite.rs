@@ -0,0 +1,66 @@
+use std::process::Command;
+
+use anyhow::Result;
+use serde_json::Value;
+
+fn keys(object: &Value) -> Vec<&str> {
+    let mut keys: Vec<&str> = object
+        .as_object()
+        .expect("value should be an object")
+        .keys()
+        .map(String::as_str)
+        .collect();
+    keys.sort_unstable();
+    keys
+}
+
+fn required(definition: &Value) -> Vec<&str> {
+    let mut required: Vec<&str> = definition["required"]
+        .as_array()
+        .expect("definition should list required fields")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect();
+    required.sort_unstable();
+    required
+}
+
+#[test]
+fn analyse_json_output_follows_the_schema() -> Result<()> {
+    let output = Command::new(env!("CARGO_BIN_EXE_php-checker"))
+        .args([
+            "analyse",
+            "tests/invalid/strict_typing/coalesce_type_mismatch.php",
+            "--format",
+            "json",
+        ])
+        .output()?;
+    let stdout = String::from_utf8(output.stdout)?;
+    let document: Value = serde_json::from_str(&stdout)
+        .map_err(|error| anyhow::anyhow!("stdout is not one JSON document ({error}):\n{stdout}"))?;
+    let schema: Value = serde_json::from_str(include_str!("../schema/output.schema.json"))?;
+    let definitions = &schema["$defs"];
+
+    assert_eq!(keys(&document), required(&schema));
+    assert_eq!(
+        keys(&document["stats"]),
+        keys(&definitions["stats"]["properties"])
+    );
+
+    let diagnostics = document["diagnostics"].as_array().unwrap();
+    assert!(!diagnostics.is_empty(), "{stdout}");
+    let allowed = keys(&definitions["diagnostic"]["properties"]);
+    for diagnostic in diagnostics {
+        let present = keys(diagnostic);
+        for field in required(&definitions["diagnostic"]) {
+            assert!(present.contains(&field), "missing {field} in {diagnostic}");
+        }
+        for field in present {
+            assert!(
+                allowed.contains(&field),
+                "unexpected {field} in {diagnostic}"
+            );
+        }
+    }
+    Ok(())
+}
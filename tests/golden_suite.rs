@@ -0,0 +1,18 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use php_checker::analyzer::golden;
+
+#[test]
+fn golden_fixtures_match_expectations() -> Result<()> {
+    let roots = [PathBuf::from("tests/valid"), PathBuf::from("tests/invalid")];
+    let report = golden::run(&roots, false)?;
+
+    assert!(
+        report.is_success(),
+        "{}\nRun `cargo run -- golden --bless` to accept the new output.",
+        report.format()
+    );
+    Ok(())
+}
@@ -0,0 +1,18 @@
+<?php
+
+declare(strict_types=1);
+
+function takesInt(int $value): int
+{
+    return $value;
+}
+
+function load(array $row, int $limit, ?string $name): void
+{
+    /** @var int $count */
+    $count = $row['count'] ?? 'none';
+    $limit ??= 'all';
+    $name = $row['name'] ?? 'anonymous';
+    takesInt($row['id'] ?? $row['key'] ?? 'missing');
+    takesInt($row['id'] ?? 0);
+}
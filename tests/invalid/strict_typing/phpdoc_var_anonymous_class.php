@@ -0,0 +1,27 @@
+<?php
+
+declare(strict_types=1);
+
+class Base
+{
+    public const LIMIT = 10;
+}
+
+$job = new class extends Base {
+    public const NAME = 'job';
+
+    public function run(): void
+    {
+        /** @var string $limit */
+        $limit = parent::LIMIT;
+        /** @var int $name */
+        $name = self::NAME;
+        echo $limit, $name;
+    }
+};
+
+/** @var Base $base */
+$base = new class extends Base {
+};
+
+echo $job, $base;
@@ -0,0 +1,46 @@
+<?php
+// php-checker-test: only-rules=strict_typing/phpdoc_var_check
+
+// Scenario: @var types checked against class constants and enum cases
+// Expected: Errors on lines 26, 29, 32
+
+enum Status: string
+{
+    case Active = 'active';
+    case Archived = 'archived';
+}
+
+class Limits
+{
+    const MAX = 100;
+    const LABEL = 'limit';
+}
+
+class Child extends Limits
+{
+    const RATIO = 0.5;
+
+    public function check(): void
+    {
+        /** @var string $max */
+        $max = self::MAX; // Error: int constant
+
+        /** @var int $status */
+        $status = Status::Active; // Error: enum case
+
+        /** @var int $value */
+        $value = Status::Active->value; // Error: string backing value
+
+        /** @var Status $ok */
+        $ok = Status::Archived;
+
+        /** @var string $label */
+        $label = parent::LABEL;
+
+        /** @var float $ratio */
+        $ratio = static::RATIO;
+
+        /** @var string $class */
+        $class = Limits::class;
+    }
+}
@@ -0,0 +1,57 @@
+<?php
+// php-checker-test: only-rules=strict_typing/phpdoc_var_check
+
+// Scenario: destructuring checked against the array type being unpacked
+// Expected: Errors on lines 35, 38, 41, 45, 48, 49, 52, 56
+
+/**
+ * @return array{int, string}
+ */
+function getPair(): array
+{
+    return [1, 'one'];
+}
+
+/**
+ * @return list<array{id: int, name: string}>
+ */
+function getRows(): array
+{
+    return [];
+}
+
+/**
+ * @return array<string, int>
+ */
+function getCounts(): array
+{
+    return [];
+}
+
+function check(): void
+{
+    /** @var array{id: int, name: string} $row */
+    $row = ['id' => 1, 'name' => 'a'];
+    ['id' => $id, 'email' => $email] = $row; // Error: no 'email' key
+
+    /** @var string $count */
+    [$count, $label] = getPair(); // Error: int into string
+
+    /** @var int $name */
+    [[, $name]] = getRows(); // Error: position 1 of the row shape is missing
+
+    /** @var string $rowName */
+    [['name' => $rowName]] = getRows(); // OK: the nested shape has 'name'
+    [, , $third] = getPair(); // Error: no position 2
+
+    /** @var string $total */
+    ['a' => $total] = getCounts(); // Error: int into string
+    [$first] = getCounts(); // Error: int key into string-keyed array
+
+    $limit = 5;
+    [$x] = $limit; // Error: not an array
+
+    /** @var int $n */
+    list($n, $text) = getPair(); // OK
+    ['name' => $nm] = getRows(); // Error: list keys are ints
+}
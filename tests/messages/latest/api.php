@@ -0,0 +1,18 @@
+<?php
+/* Copyright 2024 Example */
+declare(strict_types=1);
+
+each($items);
+old_helper();
+legacy_mail('to');
+ini_set('assert.active', '1');
+ini_set('session.legacy', '1');
+echo $this->name;
+
+class Widget
+{
+    public static function make(): void
+    {
+        $this->name = 'x';
+    }
+}
@@ -0,0 +1,14 @@
+<?php
+/* Copyright 2024 Example */
+declare(strict_types=1);
+
+namespace App;
+
+use App\Unused\Thing;
+
+function connect(): void
+{
+    $unused = 1;
+    $dsn = 'mysql:host=localhost;dbname=app';
+    echo $dsn;
+}
@@ -0,0 +1,11 @@
+<?php
+/* Copyright 2024 Example */
+declare(strict_types=1);
+
+class Point
+{
+    public function move(int $x = null): void
+    {
+        $this->undeclared = $x;
+    }
+}
@@ -0,0 +1,54 @@
+<?php
+/* Copyright 2024 Example */
+declare(strict_types=1);
+
+function enabled(): bool
+{
+    return false;
+}
+
+function status(): int
+{
+    return 1;
+}
+
+function flow(int $value, string $label): int
+{
+    switch ($value) {
+        case 1:
+            echo 'one';
+        case 2:
+            echo 'two';
+            break;
+        case 1:
+            break;
+    }
+
+    $limit = 10;
+    if ($limit > 5) {
+        echo 'first';
+    }
+    if ($limit > 5) {
+        echo 'again';
+    }
+    $flag = true;
+    if ($flag === 5) {
+        echo 'never';
+    }
+    if (enabled()) {
+    }
+    if (!enabled()) {
+    }
+    if (status() === 2) {
+    }
+
+    switch ($label) {
+        case 'a':
+            echo 'a';
+            break;
+            break;
+    }
+
+    return $value;
+    echo 'never';
+}
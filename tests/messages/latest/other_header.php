@@ -0,0 +1,5 @@
+<?php
+/* Copyright 2024 Someone Else */
+declare(strict_types=1);
+
+echo 'other header';
@@ -0,0 +1,16 @@
+<?php
+/* Copyright 2024 Example */
+declare(strict_types=1);
+
+/**
+ * @param
+ * @param int
+ * @return
+ * @var array{id: int
+ * @var int|>
+ * @var int||string
+ * @throws
+ */
+function documented(int $id): void
+{
+}
@@ -0,0 +1,74 @@
+<?php
+/* Copyright 2024 Example */
+declare(strict_types=1);
+
+namespace App;
+
+interface HasLimit
+{
+    const LIMIT = 10;
+}
+
+trait Paginates
+{
+    public const LIMIT = 25;
+}
+
+class Listing implements HasLimit
+{
+    use Paginates;
+}
+
+function duplicated(): void {}
+function duplicated(): void {}
+
+function prices(array $prices, string $title, int $count): void
+{
+    foreach ($prices as &$price) {
+        $price *= 2;
+    }
+    foreach ($prices as $price) {
+        echo $price;
+    }
+
+    if (0.1 + 0.2 == 0.3) {
+        echo strtolower($title);
+    }
+
+    $data = [];
+    echo $data['missing'];
+    echo $undefined;
+
+    $limit = 0100;
+    $id = 9223372036854775808;
+    if ($count == 'abc' || 1 != "1 apple") {
+        echo $limit, $id;
+    }
+
+    foreach ($prices as $key => $value) {
+        unset($prices[$key]);
+    }
+    foreach ($prices as $value) {
+        $prices = [];
+    }
+    foreach ($prices as $value) {
+        $prices[] = $value;
+    }
+    foreach ($prices as $value) {
+        array_pop($prices);
+    }
+}
+
+function debug_enabled(): bool
+{
+    global $settings;
+    return (bool) $settings;
+}
+
+$counter = 0;
+
+function track(): void
+{
+    global $counter;
+    $counter = 1;
+}
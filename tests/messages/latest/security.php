@@ -0,0 +1,13 @@
+<?php
+/* Copyright 2024 Example */
+declare(strict_types=1);
+
+db_connect('super-secret-password');
+$dsn = 'mysql:host=db;user=app;password=hunter2';
+$name = 'reset_password';
+$key = "hardcodedkey123456789012345";
+include $_GET['file'];
+array_pop([1, 2, 3]);
+$password = md5("secret");
+$passwordHash = hash('md5', $input);
+$cache = "redis://127.0.0.1:6379";
@@ -0,0 +1,7 @@
+<?php
+/* Copyright 2024 Example */
+declare(strict_types=1);
+
+namespace Wrong\Place;
+
+class Mismatch {}
@@ -0,0 +1,7 @@
+<?php
+/* Copyright 2024 Example */
+
+function typed(int $value): int
+{
+    return $value;
+}
@@ -0,0 +1,65 @@
+<?php
+/* Copyright 2024 Example */
+declare(strict_types=1);
+
+enum Suit: string { case Hearts = 'H'; }
+class Config { const NAME = 'app'; }
+
+function takesInt(int $value): void {}
+function takesTwo(int $a, int $b): void {}
+
+function load(array $row): void
+{
+    /** @var int $count */
+    $count = $row['count'] ?? 'none';
+    takesInt($row['id'] ?? 'missing');
+    takesInt('not-int');
+    takesInt(Suit::Hearts);
+    takesInt(Config::NAME);
+    takesTwo(1);
+    echo $count;
+}
+
+function inconsistent(bool $flag)
+{
+    if ($flag) {
+        return 42;
+    }
+    return "hello";
+}
+
+function maybeString(bool $flag)
+{
+    if ($flag) {
+        return 'ok';
+    }
+}
+
+/**
+ * @param string $id
+ */
+function lookup(int $id): void {}
+
+/**
+ * @return string
+ */
+function count_items(): int
+{
+    return 42;
+}
+
+/**
+ * @return string
+ */
+function ratio()
+{
+    return 1.5;
+}
+
+/**
+ * @return string
+ */
+function unknown()
+{
+    return $unknown;
+}
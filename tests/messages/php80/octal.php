@@ -0,0 +1,3 @@
+<?php
+$limit = 0100;
+echo $limit;